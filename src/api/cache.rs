@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use http::{HeaderValue, Method, StatusCode, header};
+use log::debug;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+lazy_static::lazy_static! {
+    static ref ENTRIES: Mutex<HashMap<String, CachedResponse>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn cache_path(universe_id: u64) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| {
+        dir.join("rbx-products")
+            .join(format!("{}.json", universe_id))
+    })
+}
+
+fn universe_entries(
+    entries: &HashMap<String, CachedResponse>,
+    universe_id: u64,
+) -> HashMap<String, CachedResponse> {
+    let needle = format!("/universes/{}/", universe_id);
+
+    entries
+        .iter()
+        .filter(|(url, _)| url.contains(&needle))
+        .map(|(url, entry)| (url.clone(), entry.clone()))
+        .collect()
+}
+
+/// Loads the cached responses for a universe from disk. The cache is only an
+/// optimisation, so a missing or unreadable file just means a full fetch.
+pub async fn load(universe_id: u64) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let Some(path) = cache_path(universe_id) else {
+        return;
+    };
+
+    let entries = match fs::read(&path).await {
+        Ok(data) => serde_json::from_slice(&data).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    };
+
+    ENTRIES.lock().unwrap().extend(entries);
+}
+
+pub async fn save(universe_id: u64) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let Some(path) = cache_path(universe_id) else {
+        return;
+    };
+
+    let entries = universe_entries(&ENTRIES.lock().unwrap(), universe_id);
+
+    let result = async {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(&path, serde_json::to_vec(&entries)?).await?;
+        crate::Result::Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        debug!("failed to write http cache {}: {}", path.display(), e);
+    }
+}
+
+/// Revalidates GET requests with the ETag of the last response for the same
+/// URL, serving the cached body when the API answers 304 Not Modified.
+#[derive(Clone, Debug)]
+pub struct CacheMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for CacheMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if req.method() != Method::GET || !ENABLED.load(Ordering::Relaxed) {
+            return next.run(req, extensions).await;
+        }
+
+        let url = req.url().to_string();
+        let cached = ENTRIES.lock().unwrap().get(&url).cloned();
+
+        if let Some(entry) = &cached
+            && let Ok(etag) = HeaderValue::from_str(&entry.etag)
+        {
+            req.headers_mut().insert(header::IF_NONE_MATCH, etag);
+        }
+
+        let resp = next.run(req, extensions).await?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED
+            && let Some(entry) = cached
+        {
+            debug!("not modified, using cached response for {}", url);
+
+            let resp = http::Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(entry.body)
+                .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+
+            return Ok(Response::from(resp));
+        }
+
+        let Some(etag) = resp
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+        else {
+            return Ok(resp);
+        };
+
+        if !resp.status().is_success() {
+            return Ok(resp);
+        }
+
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.text().await?;
+
+        ENTRIES.lock().unwrap().insert(
+            url,
+            CachedResponse {
+                etag,
+                body: body.clone(),
+            },
+        );
+
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in &headers {
+            // The body has already been decoded, so its encoding and length no longer apply.
+            if name == header::CONTENT_ENCODING || name == header::CONTENT_LENGTH {
+                continue;
+            }
+            builder = builder.header(name, value);
+        }
+
+        let resp = builder
+            .body(body)
+            .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+
+        Ok(Response::from(resp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_persists_entries_for_the_universe() {
+        let entry = CachedResponse {
+            etag: "\"abc\"".to_string(),
+            body: "{}".to_string(),
+        };
+
+        let entries = HashMap::from([
+            (
+                "https://apis.roblox.com/game-passes/v1/universes/1/game-passes/creator"
+                    .to_string(),
+                entry.clone(),
+            ),
+            (
+                "https://apis.roblox.com/game-passes/v1/universes/12/game-passes/creator"
+                    .to_string(),
+                entry,
+            ),
+        ]);
+
+        let filtered = universe_entries(&entries, 1);
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered.keys().all(|url| url.contains("/universes/1/")));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::check_status;
+use super::{ECONOMY_CLIENT, send_with_csrf, website_url};
+use crate::Result;
+
+/// The catalog's item details endpoint takes at most this many items per request.
+const DETAILS_BATCH_SIZE: usize = 120;
+
+/// The sale details of an avatar item, as the catalog shows them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct AssetDetails {
+    pub name: String,
+    pub price_in_robux: Option<u64>,
+    pub is_for_sale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceConfiguration {
+    pub price_in_robux: u64,
+}
+
+/// Puts an item on or off sale at a price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseRequest {
+    pub price_configuration: PriceConfiguration,
+    /// `OnSale` or `OffSale`
+    pub sale_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemReference {
+    pub item_type: String,
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemDetailsRequest {
+    pub items: Vec<ItemReference>,
+}
+
+/// An item in a batch details response. Items that aren't on sale have no
+/// `price`, and say so in `priceStatus`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ItemDetails {
+    pub id: u64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_status: Option<String>,
+}
+
+impl From<ItemDetails> for AssetDetails {
+    fn from(item: ItemDetails) -> Self {
+        Self {
+            is_for_sale: item.price.is_some() && item.price_status.as_deref() != Some("Off Sale"),
+            name: item.name,
+            price_in_robux: item.price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemDetailsPage {
+    pub data: Vec<ItemDetails>,
+}
+
+/// Looks up many items at once, in chunks the catalog accepts. Items the
+/// batch endpoint leaves out, like ones that aren't public yet, are fetched
+/// one by one.
+pub async fn fetch_assets(asset_ids: &[u64]) -> Result<BTreeMap<u64, AssetDetails>> {
+    let mut details = BTreeMap::new();
+
+    for chunk in asset_ids.chunks(DETAILS_BATCH_SIZE) {
+        let body = ItemDetailsRequest {
+            items: chunk
+                .iter()
+                .map(|id| ItemReference {
+                    item_type: "Asset".to_string(),
+                    id: *id,
+                })
+                .collect(),
+        };
+
+        let page: ItemDetailsPage = send_with_csrf(|| {
+            ECONOMY_CLIENT
+                .post(format!(
+                    "{}/v1/catalog/items/details",
+                    website_url("catalog")
+                ))
+                .json(&body)
+        })
+        .await?
+        .json()
+        .await?;
+
+        details.extend(
+            page.data
+                .into_iter()
+                .map(|item| (item.id, AssetDetails::from(item))),
+        );
+    }
+
+    for id in asset_ids {
+        if !details.contains_key(id) {
+            details.insert(*id, fetch_asset(*id).await?);
+        }
+    }
+
+    Ok(details)
+}
+
+pub async fn fetch_asset(asset_id: u64) -> Result<AssetDetails> {
+    let resp = ECONOMY_CLIENT
+        .get(format!(
+            "{}/v2/assets/{}/details",
+            website_url("economy"),
+            asset_id
+        ))
+        .send()
+        .await?;
+
+    Ok(check_status(resp).await?.json().await?)
+}
+
+/// Sets an item's price and sale state. Item configuration only accepts a
+/// `.ROBLOSECURITY` cookie with access to the owning group.
+pub async fn release_asset(asset_id: u64, cookie: &str, price: u64, on_sale: bool) -> Result<()> {
+    let body = ReleaseRequest {
+        price_configuration: PriceConfiguration {
+            price_in_robux: price,
+        },
+        sale_status: match on_sale {
+            true => "OnSale",
+            false => "OffSale",
+        }
+        .to_string(),
+    };
+
+    send_with_csrf(|| {
+        ECONOMY_CLIENT
+            .post(format!(
+                "{}/v1/assets/{}/release",
+                website_url("itemconfiguration"),
+                asset_id
+            ))
+            .header("Cookie", format!(".ROBLOSECURITY={}", cookie))
+            .json(&body)
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sale_state_from_batch_details() {
+        let page: ItemDetailsPage = serde_json::from_str(
+            r#"{"data": [
+                {"id": 1, "itemType": "Asset", "name": "Shirt", "price": 10},
+                {"id": 2, "itemType": "Asset", "name": "Hat", "priceStatus": "Off Sale"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let details = page
+            .data
+            .into_iter()
+            .map(AssetDetails::from)
+            .map(|details| (details.price_in_robux, details.is_for_sale))
+            .collect::<Vec<_>>();
+        assert_eq!(details, [(Some(10), true), (None, false)]);
+    }
+}
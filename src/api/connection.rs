@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+/// Timeouts and connection pooling for every HTTP client. Set under
+/// `[metadata.connection]` in the products file, or with `--timeout` and
+/// friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ConnectionPolicy {
+    /// Most seconds a whole request may take, including the response body. 0 disables it
+    pub timeout_secs: u64,
+    /// Most seconds to spend connecting. 0 disables it
+    pub connect_timeout_secs: u64,
+    /// Idle connections kept open per host
+    pub pool_size: usize,
+    /// Seconds an idle connection is kept open
+    pub pool_idle_secs: u64,
+}
+
+impl Default for ConnectionPolicy {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 60,
+            connect_timeout_secs: 10,
+            pool_size: 16,
+            pool_idle_secs: 90,
+        }
+    }
+}
+
+fn seconds(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+impl ConnectionPolicy {
+    pub fn timeout(&self) -> Option<Duration> {
+        seconds(self.timeout_secs)
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        seconds(self.connect_timeout_secs)
+    }
+
+    pub fn pool_idle_timeout(&self) -> Option<Duration> {
+        seconds(self.pool_idle_secs)
+    }
+
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout() {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        builder
+            .pool_max_idle_per_host(self.pool_size)
+            .pool_idle_timeout(self.pool_idle_timeout())
+    }
+}
+
+/// Command line overrides for the products file's connection settings.
+#[derive(Args, Debug, Clone, Default)]
+pub struct ConnectionOptions {
+    /// Seconds before a request is abandoned, 0 for no limit (default 60)
+    #[arg(long, global = true)]
+    pub timeout: Option<u64>,
+    /// Seconds before connecting is abandoned, 0 for no limit (default 10)
+    #[arg(long, global = true)]
+    pub connect_timeout: Option<u64>,
+    /// Idle connections kept open per host (default 16)
+    #[arg(long, global = true)]
+    pub pool_size: Option<usize>,
+}
+
+impl ConnectionOptions {
+    pub fn apply(&self, mut policy: ConnectionPolicy) -> ConnectionPolicy {
+        if let Some(timeout) = self.timeout {
+            policy.timeout_secs = timeout;
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            policy.connect_timeout_secs = connect_timeout;
+        }
+        if let Some(pool_size) = self.pool_size {
+            policy.pool_size = pool_size;
+        }
+        policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_override_the_file() {
+        let file: ConnectionPolicy = toml::from_str("timeout-secs = 30\npool-size = 4").unwrap();
+        assert_eq!(file.connect_timeout_secs, 10);
+
+        let policy = ConnectionOptions {
+            timeout: Some(0),
+            ..Default::default()
+        }
+        .apply(file);
+
+        assert_eq!(policy.timeout(), None);
+        assert_eq!(policy.connect_timeout(), Some(Duration::from_secs(10)));
+        assert_eq!(policy.pool_size, 4);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::Result;
+
+/// A named API key and the universes it should be used for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Profile {
+    pub api_key: String,
+    #[serde(default)]
+    pub universes: Vec<u64>,
+}
+
+/// `~/.config/rbx-products/credentials.toml`, for keeping one key per
+/// experience (or client) outside of each project's `.env`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Credentials {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+pub fn credentials_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| {
+        home.join(".config")
+            .join("rbx-products")
+            .join("credentials.toml")
+    })
+}
+
+impl Credentials {
+    /// Loads the credentials file, or an empty one if it doesn't exist.
+    pub async fn load() -> Result<Self> {
+        let Some(path) = credentials_path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+
+        let data = tokio::fs::read_to_string(&path).await?;
+        toml::from_str(&data).map_err(|e| format!("invalid {}: {}", path.display(), e).into())
+    }
+
+    /// Picks the profile named on the command line, or else the one listing
+    /// the universe. Returns the profile's name and key.
+    pub fn resolve(
+        &self,
+        profile: Option<&str>,
+        universe_id: Option<u64>,
+    ) -> Result<Option<(&str, &str)>> {
+        if let Some(name) = profile {
+            let (name, profile) = self
+                .profiles
+                .get_key_value(name)
+                .ok_or_else(|| format!("no profile named '{}' in the credentials file", name))?;
+            return Ok(Some((name, &profile.api_key)));
+        }
+
+        let Some(universe_id) = universe_id else {
+            return Ok(None);
+        };
+
+        let mut matches = self
+            .profiles
+            .iter()
+            .filter(|(_, profile)| profile.universes.contains(&universe_id));
+
+        match (matches.next(), matches.next()) {
+            (Some((name, profile)), None) => Ok(Some((name, &profile.api_key))),
+            (Some((first, _)), Some((second, _))) => Err(format!(
+                "universe {} is listed by more than one profile ('{}' and '{}'), pick one with --profile",
+                universe_id, first, second
+            )
+            .into()),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_profiles_by_name_or_universe() {
+        let credentials: Credentials = toml::from_str(
+            r#"
+            [profiles.studio-a]
+            api-key = "key-a"
+            universes = [1, 2]
+
+            [profiles.studio-b]
+            api-key = "key-b"
+            universes = [3]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            credentials.resolve(None, Some(3)).unwrap(),
+            Some(("studio-b", "key-b"))
+        );
+        assert_eq!(
+            credentials.resolve(Some("studio-a"), Some(3)).unwrap(),
+            Some(("studio-a", "key-a"))
+        );
+        assert_eq!(credentials.resolve(None, Some(9)).unwrap(), None);
+        assert!(credentials.resolve(Some("missing"), None).is_err());
+    }
+}
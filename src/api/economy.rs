@@ -0,0 +1,93 @@
+use serde::Deserialize;
+
+use super::ECONOMY_CLIENT;
+use super::error::check_status;
+use crate::Result;
+
+/// Whose sales to fetch. Sales are recorded against the experience owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SalesOwner {
+    User(u64),
+    Group(u64),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SalePlace {
+    pub universe_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SaleDetails {
+    pub id: Option<u64>,
+    pub name: String,
+    /// e.g. `GamePass` or `DeveloperProduct`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub place: Option<SalePlace>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SaleCurrency {
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Sale {
+    pub created: String,
+    pub details: SaleDetails,
+    pub currency: SaleCurrency,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SalePage {
+    data: Vec<Sale>,
+    next_page_cursor: Option<String>,
+}
+
+/// Fetches sale transactions newer than `since` (an ISO 8601 date or
+/// timestamp). The economy API only accepts a `.ROBLOSECURITY` cookie, not
+/// Open Cloud keys.
+pub async fn fetch_sales(owner: SalesOwner, cookie: &str, since: &str) -> Result<Vec<Sale>> {
+    let url = match owner {
+        SalesOwner::User(id) => format!("https://economy.roblox.com/v2/users/{}/transactions", id),
+        SalesOwner::Group(id) => {
+            format!("https://economy.roblox.com/v2/groups/{}/transactions", id)
+        }
+    };
+
+    let mut sales = vec![];
+    let mut cursor = String::default();
+
+    loop {
+        let mut req = ECONOMY_CLIENT
+            .get(&url)
+            .header("Cookie", format!(".ROBLOSECURITY={}", cookie))
+            .query(&[("transactionType", "Sale"), ("limit", "100")]);
+
+        if !cursor.is_empty() {
+            req = req.query(&[("cursor", cursor.clone())]);
+        }
+
+        let page: SalePage = check_status(req.send().await?).await?.json().await?;
+
+        // Transactions come newest first, so stop at the first one that's too old.
+        let reached_end = page.data.iter().any(|sale| sale.created.as_str() < since);
+        sales.extend(
+            page.data
+                .into_iter()
+                .filter(|sale| sale.created.as_str() >= since),
+        );
+
+        match page.next_page_cursor {
+            Some(next) if !reached_end => cursor = next,
+            _ => break,
+        }
+    }
+
+    Ok(sales)
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::fmt;
+
+use reqwest::{Response, StatusCode};
+use serde::Serialize;
+
+/// A non-success response from the Roblox API, keeping the body so failures
+/// can be told apart (e.g. moderation vs. other bad requests).
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.body.is_empty() {
+            write!(f, "request failed with status {}", self.status)
+        } else {
+            write!(
+                f,
+                "request failed with status {}: {}",
+                self.status, self.body
+            )
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+/// Like `error_for_status`, but keeps the response body in the error.
+pub async fn check_status(resp: Response) -> crate::Result<Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+
+    let body = resp.text().await.unwrap_or_default();
+    Err(Box::new(ApiError { status, body }))
+}
+
+/// Stable failure categories, included in webhook payloads so alerting can
+/// treat e.g. auth failures differently from moderation rejections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCode {
+    Auth,
+    Moderation,
+    RateLimit,
+    Validation,
+    Network,
+    /// The change was accepted, but Roblox doesn't reflect it afterwards.
+    Diverged,
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Auth => "auth",
+            ErrorCode::Moderation => "moderation",
+            ErrorCode::RateLimit => "rate-limit",
+            ErrorCode::Validation => "validation",
+            ErrorCode::Network => "network",
+            ErrorCode::Diverged => "diverged",
+            ErrorCode::Unknown => "unknown",
+        }
+    }
+
+    fn from_status(status: StatusCode, body: &str) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCode::Auth,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimit,
+            _ if body.to_lowercase().contains("moderat") => ErrorCode::Moderation,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::Validation,
+            _ => ErrorCode::Unknown,
+        }
+    }
+
+    fn from_reqwest(e: &reqwest::Error) -> Self {
+        if let Some(status) = e.status() {
+            ErrorCode::from_status(status, "")
+        } else if e.is_connect() || e.is_timeout() || e.is_request() {
+            ErrorCode::Network
+        } else {
+            ErrorCode::Unknown
+        }
+    }
+
+    pub fn classify(e: &(dyn Error + 'static)) -> Self {
+        if let Some(e) = e.downcast_ref::<ApiError>() {
+            ErrorCode::from_status(e.status, &e.body)
+        } else if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+            ErrorCode::from_reqwest(e)
+        } else if let Some(e) = e.downcast_ref::<reqwest_middleware::Error>() {
+            match e {
+                reqwest_middleware::Error::Reqwest(e) => ErrorCode::from_reqwest(e),
+                reqwest_middleware::Error::Middleware(_) => ErrorCode::Unknown,
+            }
+        } else {
+            ErrorCode::Unknown
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(status: StatusCode, body: &str) -> ErrorCode {
+        let e: Box<dyn Error> = Box::new(ApiError {
+            status,
+            body: body.to_string(),
+        });
+        ErrorCode::classify(e.as_ref())
+    }
+
+    #[test]
+    fn classifies_api_errors() {
+        assert_eq!(classify(StatusCode::FORBIDDEN, ""), ErrorCode::Auth);
+        assert_eq!(
+            classify(StatusCode::TOO_MANY_REQUESTS, ""),
+            ErrorCode::RateLimit
+        );
+        assert_eq!(
+            classify(StatusCode::BAD_REQUEST, "Name was moderated"),
+            ErrorCode::Moderation
+        );
+        assert_eq!(
+            classify(StatusCode::BAD_REQUEST, "Price is invalid"),
+            ErrorCode::Validation
+        );
+        assert_eq!(
+            classify(StatusCode::INTERNAL_SERVER_ERROR, ""),
+            ErrorCode::Unknown
+        );
+    }
+}
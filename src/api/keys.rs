@@ -0,0 +1,62 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use super::error::check_status;
+use super::{API_CLIENT, base_url};
+use crate::Result;
+use crate::sync::products::ProductType;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyScope {
+    /// e.g. `game-pass` or `developer-product`
+    pub name: String,
+    pub operations: Vec<String>,
+}
+
+/// What Open Cloud reports about an API key.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyInfo {
+    pub name: String,
+    pub authorized_user_id: Option<u64>,
+    pub scopes: Vec<KeyScope>,
+    pub enabled: bool,
+    pub expired: bool,
+    pub expiration_time_utc: Option<String>,
+}
+
+pub async fn introspect(api_key: &str) -> Result<KeyInfo> {
+    let resp = API_CLIENT
+        .post(format!("{}/api-keys/v1/introspect", base_url()))
+        .json(&json!({ "apiKey": api_key }))
+        .send()
+        .await?;
+
+    Ok(check_status(resp).await?.json().await?)
+}
+
+/// Lists a single product in the universe, to check the key can reach it.
+pub async fn check_universe_access(universe_id: u64, product_type: ProductType) -> Result<()> {
+    let url = match product_type {
+        ProductType::GamePass => format!(
+            "{}/game-passes/v1/universes/{}/game-passes/creator",
+            base_url(),
+            universe_id
+        ),
+        ProductType::DevProduct => format!(
+            "{}/developer-products/v2/universes/{}/developer-products/creator",
+            base_url(),
+            universe_id
+        ),
+    };
+
+    let resp = API_CLIENT
+        .get(url)
+        .query(&[("pageSize", "1")])
+        .send()
+        .await?;
+
+    check_status(resp).await?;
+    Ok(())
+}
@@ -5,11 +5,12 @@ use reqwest_middleware::{Middleware, Next, Result};
 use std::{sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
-#[derive(Clone, Debug)]
-pub struct RobloxRateLimitMiddleware {
-    max_429_retries: usize,
-    cushion_ms: u64,
-}
+use super::retry;
+
+/// Waits out 429s for as long as Roblox asks, within the global retry policy,
+/// and passes the reported rate-limit budget on to the shared throttle.
+#[derive(Clone, Debug, Default)]
+pub struct RobloxRateLimitMiddleware;
 
 #[derive(Clone, Debug)]
 pub struct RobloxAuthMiddleware {
@@ -18,15 +19,22 @@ pub struct RobloxAuthMiddleware {
 
 impl RobloxRateLimitMiddleware {
     pub fn new() -> Self {
-        Self {
-            max_429_retries: 5,
-            cushion_ms: 75,
-        }
+        Self
     }
 
-    pub fn with_max_429_retries(mut self, n: usize) -> Self {
-        self.max_429_retries = n;
-        self
+    /// The budget left in the current rate-limit window, and how long until
+    /// it resets, when the response reports them.
+    fn rate_budget_from_headers(resp: &Response) -> Option<(u64, Duration)> {
+        let header = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.trim().parse::<u64>().ok())
+        };
+
+        let remaining = header("x-ratelimit-remaining")?;
+        let reset = header("x-ratelimit-reset")?;
+        Some((remaining, Duration::from_secs(reset)))
     }
 
     fn retry_wait_from_headers(resp: &Response) -> Duration {
@@ -86,28 +94,39 @@ impl Middleware for RobloxRateLimitMiddleware {
         extensions: &mut http::Extensions,
         next: Next<'_>,
     ) -> Result<Response> {
+        let policy = retry::policy();
         let mut req = req;
-        for attempt in 0..=self.max_429_retries {
+        let mut waited = Duration::ZERO;
+
+        for attempt in 0..=policy.max_retries {
             let req_clone = req.try_clone();
             let resp = next.clone().run(req, extensions).await?;
 
+            if let Some((remaining, reset)) = Self::rate_budget_from_headers(&resp) {
+                super::THROTTLE.on_rate_headers(remaining, reset);
+            }
+
             if resp.status() != StatusCode::TOO_MANY_REQUESTS {
                 return Ok(resp);
             }
 
-            if attempt >= self.max_429_retries {
+            super::THROTTLE.on_rate_limited();
+
+            let wait =
+                Self::retry_wait_from_headers(&resp) + Duration::from_millis(policy.cushion_ms);
+
+            if attempt >= policy.max_retries || waited + wait > policy.max_wait() {
                 return Ok(resp);
             }
 
-            let wait = Self::retry_wait_from_headers(&resp);
-
             warn!(
                 "Rate limited on attempt {}, retrying after {} seconds...",
                 attempt + 1,
                 wait.as_secs()
             );
 
-            tokio::time::sleep(wait + Duration::from_millis(self.cushion_ms)).await;
+            tokio::time::sleep(wait).await;
+            waited += wait;
 
             if let Some(cloned) = req_clone {
                 req = cloned;
@@ -0,0 +1,584 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::Result;
+use crate::api::catalog::{AssetDetails, ItemDetails, ItemDetailsPage, ItemDetailsRequest};
+use crate::api::model::{
+    DevProduct, DevProductPage, GamePass, GamePassPage, PriceInformation, ProductPriceInformation,
+};
+use crate::api::releases::{CHECKSUMS_ASSET, Release, ReleaseAsset, asset_name};
+use crate::api::universe::{Universe, UniverseConfiguration};
+use crate::sync::products::ProductType;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed)
+}
+
+/// A product in a mock fixture file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct MockProduct {
+    pub id: u64,
+    pub name: String,
+    pub description: String,
+    pub price: u64,
+    pub for_sale: bool,
+    pub regional_pricing: bool,
+    pub store_page_enabled: bool,
+    pub immutable: bool,
+    pub icon_asset_id: u64,
+    /// Reported as `updatedTimestamp`, and bumped by every create and update
+    pub updated: String,
+}
+
+/// The universe served by `--mock`, loaded from a TOML fixture.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct MockUniverse {
+    pub gamepasses: Vec<MockProduct>,
+    pub products: Vec<MockProduct>,
+    /// Unset when private servers are disabled
+    pub private_server_price: Option<u64>,
+    /// Unset when paid access is off
+    pub paid_access_price: Option<u64>,
+    pub catalog: Vec<MockCatalogItem>,
+    /// The latest release `self-update` finds
+    pub release: Option<MockRelease>,
+    /// Where the server listens, for download links
+    #[serde(skip)]
+    base_url: String,
+}
+
+/// A GitHub release in a mock fixture file, with one binary for the platform
+/// the mock runs on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct MockRelease {
+    pub tag: String,
+    /// Contents of the binary
+    pub binary: String,
+    /// Serve a checksum that doesn't match the binary
+    pub bad_checksum: bool,
+}
+
+impl MockRelease {
+    fn to_release(&self, base_url: &str) -> Release {
+        let asset = |name: String| ReleaseAsset {
+            browser_download_url: format!("{}/downloads/{}", base_url, name),
+            name,
+        };
+
+        Release {
+            tag_name: self.tag.clone(),
+            html_url: format!("{}/releases/{}", base_url, self.tag),
+            assets: vec![asset(asset_name()), asset(CHECKSUMS_ASSET.to_string())],
+        }
+    }
+
+    fn download(&self, name: &str) -> Option<String> {
+        if name == asset_name() {
+            return Some(self.binary.clone());
+        }
+
+        (name == CHECKSUMS_ASSET).then(|| {
+            let binary = match self.bad_checksum {
+                true => "tampered",
+                false => self.binary.as_str(),
+            };
+            format!("{:x}  {}\n", Sha256::digest(binary), asset_name())
+        })
+    }
+}
+
+/// An avatar item in a mock fixture file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct MockCatalogItem {
+    pub id: u64,
+    pub name: String,
+    pub price: Option<u64>,
+    pub for_sale: bool,
+}
+
+impl MockProduct {
+    fn enabled_features(&self) -> Option<Vec<String>> {
+        Some(
+            self.regional_pricing
+                .then(|| "RegionalPricing".to_string())
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    fn to_gamepass(&self) -> GamePass {
+        GamePass {
+            game_pass_id: self.id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            is_for_sale: self.for_sale,
+            icon_asset_id: self.icon_asset_id,
+            updated_timestamp: self.updated.clone(),
+            price_information: Some(PriceInformation {
+                default_price_in_robux: self.price,
+                enabled_features: self.enabled_features(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn to_dev_product(&self) -> DevProduct {
+        DevProduct {
+            product_id: self.id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            is_for_sale: self.for_sale,
+            store_page_enabled: self.store_page_enabled,
+            is_immutable: self.immutable,
+            updated_timestamp: self.updated.clone(),
+            price_information: Some(ProductPriceInformation {
+                default_price_in_robux: self.price,
+                enabled_features: self.enabled_features(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Applies the fields of a create/update form, as the real API would.
+    fn apply_form(&mut self, form: &HashMap<String, String>) {
+        let flag = |name: &str| form.get(name).map(|value| value == "true");
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.updated = format!("{}.{:09}", now.as_secs(), now.subsec_nanos());
+
+        if let Some(name) = form.get("name") {
+            self.name = name.clone();
+        }
+        if let Some(description) = form.get("description") {
+            self.description = description.clone();
+        }
+        if let Some(price) = form.get("price").and_then(|price| price.parse().ok()) {
+            self.price = price;
+        }
+        if let Some(for_sale) = flag("isForSale") {
+            self.for_sale = for_sale;
+        }
+        if let Some(regional_pricing) = flag("isRegionalPricingEnabled") {
+            self.regional_pricing = regional_pricing;
+        }
+        if let Some(store_page_enabled) = flag("storePageEnabled") {
+            self.store_page_enabled = store_page_enabled;
+        }
+    }
+}
+
+struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+impl MockResponse {
+    fn json<T: Serialize>(value: &T) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+
+    fn status(status: u16) -> Self {
+        Self {
+            status,
+            body: String::new(),
+        }
+    }
+}
+
+/// Pulls the text fields out of a `multipart/form-data` body.
+fn parse_multipart(content_type: &str, body: &str) -> HashMap<String, String> {
+    let Some(boundary) = content_type.split("boundary=").nth(1) else {
+        return HashMap::new();
+    };
+    let delimiter = format!("--{}", boundary.trim_matches('"'));
+
+    body.split(&delimiter)
+        .filter_map(|part| {
+            let (headers, value) = part.split_once("\r\n\r\n")?;
+            let name = headers.split("name=\"").nth(1)?.split('"').next()?;
+            Some((name.to_string(), value.trim_end_matches("\r\n").to_string()))
+        })
+        .collect()
+}
+
+/// The universe settings endpoints, which take JSON instead of forms.
+fn route_universe(
+    universe: &mut MockUniverse,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Option<MockResponse> {
+    let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let body = serde_json::from_str::<serde_json::Value>(body).unwrap_or_default();
+
+    let response = match (method, segments.as_slice()) {
+        ("GET", ["cloud", "v2", "universes", _]) => MockResponse::json(&Universe {
+            private_server_price_robux: universe.private_server_price,
+        }),
+        ("PATCH", ["cloud", "v2", "universes", _]) => {
+            universe.private_server_price = body["privateServerPriceRobux"].as_u64();
+            MockResponse::status(200)
+        }
+        ("GET", ["v1", "universes", _, "configuration"]) => {
+            MockResponse::json(&UniverseConfiguration {
+                is_for_sale: universe.paid_access_price.is_some(),
+                price: universe.paid_access_price,
+            })
+        }
+        ("PATCH", ["v2", "universes", _, "configuration"]) => {
+            universe.paid_access_price =
+                body["price"].as_u64().filter(|_| body["isForSale"] == true);
+            MockResponse::status(200)
+        }
+        ("GET", ["v2", "assets", id, "details"]) => {
+            match universe
+                .catalog
+                .iter()
+                .find(|item| id == &item.id.to_string())
+            {
+                Some(item) => MockResponse::json(&AssetDetails {
+                    name: item.name.clone(),
+                    price_in_robux: item.price,
+                    is_for_sale: item.for_sale,
+                }),
+                None => MockResponse::status(404),
+            }
+        }
+        ("POST", ["v1", "catalog", "items", "details"]) => {
+            let Ok(request) = serde_json::from_value::<ItemDetailsRequest>(body) else {
+                return Some(MockResponse::status(400));
+            };
+            let data = request
+                .items
+                .iter()
+                .filter_map(|reference| {
+                    let item = universe
+                        .catalog
+                        .iter()
+                        .find(|item| item.id == reference.id)?;
+                    Some(ItemDetails {
+                        id: item.id,
+                        name: item.name.clone(),
+                        price: item.price,
+                        price_status: (!item.for_sale).then(|| "Off Sale".to_string()),
+                    })
+                })
+                .collect();
+            MockResponse::json(&ItemDetailsPage { data })
+        }
+        ("GET", ["repos", _, _, "releases", "latest"]) => match &universe.release {
+            Some(release) => MockResponse::json(&release.to_release(&universe.base_url)),
+            None => MockResponse::status(404),
+        },
+        ("GET", ["downloads", name]) => {
+            match universe
+                .release
+                .as_ref()
+                .and_then(|release| release.download(name))
+            {
+                Some(body) => MockResponse { status: 200, body },
+                None => MockResponse::status(404),
+            }
+        }
+        ("POST", ["v1", "assets", id, "release"]) => {
+            match universe
+                .catalog
+                .iter_mut()
+                .find(|item| id == &item.id.to_string())
+            {
+                Some(item) => {
+                    item.price = body["priceConfiguration"]["priceInRobux"].as_u64();
+                    item.for_sale = body["saleStatus"] == "OnSale";
+                    MockResponse::status(200)
+                }
+                None => MockResponse::status(404),
+            }
+        }
+        _ => return None,
+    };
+
+    Some(response)
+}
+
+/// The slice of a listing a `pageSize` and `pageToken` query asks for, and
+/// the token of the page after it. Tokens are plain offsets.
+fn page(query: &str, len: usize) -> (Range<usize>, Option<String>) {
+    let param = |name: &str| {
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| value.parse::<usize>().ok())?
+        })
+    };
+
+    let size = param("pageSize").unwrap_or(len).max(1);
+    let start = param("pageToken").unwrap_or(0).min(len);
+    let end = (start + size).min(len);
+    (start..end, (end < len).then(|| end.to_string()))
+}
+
+fn route(
+    universe: &mut MockUniverse,
+    method: &str,
+    target: &str,
+    form: &HashMap<String, String>,
+) -> MockResponse {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (product_type, rest) = if let Some(rest) = path.strip_prefix("/game-passes/v1/universes/") {
+        (ProductType::GamePass, rest)
+    } else if let Some(rest) = path.strip_prefix("/developer-products/v2/universes/") {
+        (ProductType::DevProduct, rest)
+    } else {
+        return MockResponse::status(404);
+    };
+
+    // Skip the universe id, the fixture only holds one universe.
+    let segments = rest.split('/').skip(1).collect::<Vec<_>>();
+
+    let products = match product_type {
+        ProductType::GamePass => &mut universe.gamepasses,
+        ProductType::DevProduct => &mut universe.products,
+    };
+
+    match (method, segments.as_slice()) {
+        ("GET", [_, "creator"]) => {
+            let (range, next_page_token) = page(query, products.len());
+            let products = &products[range];
+
+            match product_type {
+                ProductType::GamePass => MockResponse::json(&GamePassPage {
+                    game_passes: products.iter().map(MockProduct::to_gamepass).collect(),
+                    next_page_token,
+                }),
+                ProductType::DevProduct => MockResponse::json(&DevProductPage {
+                    developer_products: products.iter().map(MockProduct::to_dev_product).collect(),
+                    next_page_token,
+                }),
+            }
+        }
+        ("GET", [_, id, "creator"]) => {
+            match products.iter().find(|product| id.parse() == Ok(product.id)) {
+                Some(product) => match product_type {
+                    ProductType::GamePass => MockResponse::json(&product.to_gamepass()),
+                    ProductType::DevProduct => MockResponse::json(&product.to_dev_product()),
+                },
+                None => MockResponse::status(404),
+            }
+        }
+        ("POST", [_]) => {
+            let next_id = universe
+                .gamepasses
+                .iter()
+                .chain(&universe.products)
+                .map(|product| product.id)
+                .max()
+                .unwrap_or(0)
+                + 1;
+
+            let mut product = MockProduct {
+                id: next_id,
+                ..Default::default()
+            };
+            product.apply_form(form);
+
+            let response = match product_type {
+                ProductType::GamePass => MockResponse::json(&product.to_gamepass()),
+                ProductType::DevProduct => MockResponse::json(&product.to_dev_product()),
+            };
+
+            match product_type {
+                ProductType::GamePass => universe.gamepasses.push(product),
+                ProductType::DevProduct => universe.products.push(product),
+            }
+
+            response
+        }
+        ("PATCH", [_, id]) => match products
+            .iter_mut()
+            .find(|product| id.parse() == Ok(product.id))
+        {
+            Some(product) => {
+                product.apply_form(form);
+                MockResponse::status(204)
+            }
+            None => MockResponse::status(404),
+        },
+        _ => MockResponse::status(404),
+    }
+}
+
+async fn handle_connection(stream: TcpStream, universe: Arc<Mutex<MockUniverse>>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+    let path = target.split('?').next().unwrap_or_default();
+
+    let mut content_length = 0;
+    let mut content_type = String::new();
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line == "\r\n" {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "content-type" => content_type = value.trim().to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let body = String::from_utf8_lossy(&body);
+    let form = parse_multipart(&content_type, &body);
+    let response = {
+        let mut universe = universe.lock().unwrap();
+        route_universe(&mut universe, &method, path, &body)
+            .unwrap_or_else(|| route(&mut universe, &method, &target, &form))
+    };
+    debug!("mock {} {} -> {}", method, path, response.status);
+
+    let reply = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    );
+
+    let mut stream = reader.into_inner();
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Starts a local server that mimics the Open Cloud product endpoints, backed
+/// by the fixture's products. Writes change the in-memory state, so a sync
+/// can be followed by a download in the same run. Returns the base URL.
+pub async fn start<P: AsRef<Path>>(fixture: P) -> Result<String> {
+    let data = tokio::fs::read_to_string(fixture.as_ref()).await?;
+    let mut universe: MockUniverse = toml::from_str(&data)
+        .map_err(|e| format!("invalid mock fixture {}: {}", fixture.as_ref().display(), e))?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://{}", listener.local_addr()?);
+    universe.base_url = base_url.clone();
+    let universe = Arc::new(Mutex::new(universe));
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let universe = universe.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, universe).await {
+                    debug!("mock server connection failed: {}", e);
+                }
+            });
+        }
+    });
+
+    RUNNING.store(true, Ordering::Relaxed);
+    info!("serving mock universe at {}", base_url);
+
+    Ok(base_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_and_creates_from_forms() {
+        let mut universe = MockUniverse {
+            gamepasses: vec![MockProduct {
+                id: 7,
+                name: "VIP".to_string(),
+                price: 100,
+                for_sale: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let body = "--b\r\nContent-Disposition: form-data; name=\"price\"\r\n\r\n80\r\n\
+                    --b\r\nContent-Disposition: form-data; name=\"isRegionalPricingEnabled\"\r\n\r\ntrue\r\n--b--\r\n";
+        let form = parse_multipart("multipart/form-data; boundary=b", body);
+
+        let resp = route(
+            &mut universe,
+            "PATCH",
+            "/game-passes/v1/universes/1/game-passes/7",
+            &form,
+        );
+        assert_eq!(resp.status, 204);
+        assert_eq!(universe.gamepasses[0].price, 80);
+        assert!(universe.gamepasses[0].regional_pricing);
+
+        let resp = route(
+            &mut universe,
+            "POST",
+            "/developer-products/v2/universes/1/developer-products",
+            &HashMap::from([("name".to_string(), "Coins".to_string())]),
+        );
+        assert_eq!(resp.status, 200);
+        assert_eq!(universe.products[0].id, 8);
+    }
+
+    #[test]
+    fn pages_listings() {
+        let mut universe = MockUniverse {
+            gamepasses: (1..=3)
+                .map(|id| MockProduct {
+                    id,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut list = |query: &str| {
+            let target = format!("/game-passes/v1/universes/1/game-passes/creator?{}", query);
+            let resp = route(&mut universe, "GET", &target, &HashMap::new());
+            let page: GamePassPage = serde_json::from_str(&resp.body).unwrap();
+            let ids = page
+                .game_passes
+                .iter()
+                .map(|gp| gp.game_pass_id)
+                .collect::<Vec<_>>();
+            (ids, page.next_page_token)
+        };
+
+        assert_eq!(list("pageSize=2"), (vec![1, 2], Some("2".to_string())));
+        assert_eq!(list("pageSize=2&pageToken=2"), (vec![3], None));
+        assert_eq!(list(""), (vec![1, 2, 3], None));
+    }
+}
@@ -1,33 +1,90 @@
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 
-use reqwest::Client;
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-// use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use reqwest::{Certificate, Client, Proxy, Response, StatusCode};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, RequestBuilder};
 use tokio::sync::Mutex;
 
+use crate::Result;
+use crate::api::cache::CacheMiddleware;
+use crate::api::connection::ConnectionPolicy;
 use crate::api::middleware::{RobloxAuthMiddleware, RobloxRateLimitMiddleware};
+use crate::api::recorder::RecordMiddleware;
+use crate::api::retry::TransientRetryMiddleware;
+use crate::api::throttle::Throttle;
+use crate::api::trace::TraceMiddleware;
 
+pub mod cache;
+pub mod catalog;
+pub mod connection;
+pub mod credentials;
+pub mod economy;
+pub mod error;
+pub mod keys;
 mod middleware;
+pub mod mock;
 pub mod model;
 pub mod products;
+pub mod recorder;
+pub mod releases;
+pub mod retry;
+pub mod throttle;
+pub mod thumbnails;
+pub mod trace;
+pub mod universe;
+pub mod webhooks;
 
 lazy_static::lazy_static! {
     static ref API_TOKEN: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
 
-    static ref API_CLIENT: ClientWithMiddleware = {
-        // let retry_policy = ExponentialBackoff::builder()
-        //         .build_with_max_retries(5);
+    pub static ref THROTTLE: Throttle = Throttle::new(8);
+
+    static ref BASE_URL: RwLock<String> = RwLock::new(DEFAULT_BASE_URL.to_string());
 
-        let client = Client::builder()
-            .user_agent(format!("rbx_product/{}", env!("CARGO_PKG_VERSION")))
-            .build().unwrap();
+    static ref NETWORK: RwLock<NetworkConfig> = RwLock::new(NetworkConfig::default());
+
+    static ref API_CLIENT: ClientWithMiddleware = {
+        let client = client_builder().build().unwrap();
 
         ClientBuilder::new(client)
+            .with(RecordMiddleware::new())
             .with(RobloxAuthMiddleware::new())
-            .with(RobloxRateLimitMiddleware::new().with_max_429_retries(5))
-            // .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .with(TransientRetryMiddleware::new())
+            .with(RobloxRateLimitMiddleware::new())
+            .with(CacheMiddleware)
+            .with(TraceMiddleware)
             .build()
     };
+
+    // The economy API authenticates with a cookie rather than an API key, so it
+    // gets its own client without the auth middleware.
+    static ref ECONOMY_CLIENT: ClientWithMiddleware = {
+        let client = client_builder().build().unwrap();
+
+        ClientBuilder::new(client)
+            .with(RecordMiddleware::new())
+            .with(TransientRetryMiddleware::new())
+            .with(RobloxRateLimitMiddleware::new())
+            .with(TraceMiddleware)
+            .build()
+    };
+
+    // Thumbnails are public, so the API key isn't sent along with them.
+    static ref THUMBNAIL_CLIENT: ClientWithMiddleware = {
+        let client = client_builder().build().unwrap();
+
+        ClientBuilder::new(client)
+            .with(TransientRetryMiddleware::new())
+            .with(RobloxRateLimitMiddleware::new())
+            .with(TraceMiddleware)
+            .build()
+    };
+
+    // Webhooks go to third parties, so they must never pass through the auth middleware.
+    static ref WEBHOOK_CLIENT: Client = client_builder().build().unwrap();
+
+    // Releases come from GitHub, which has nothing to do with the API key either.
+    static ref RELEASES_CLIENT: Client = client_builder().build().unwrap();
 }
 
 #[macro_export]
@@ -43,7 +100,147 @@ macro_rules! multipart_form {
 	};
 }
 
+/// Proxy, extra root certificates and connection settings shared by every
+/// client.
+#[derive(Debug, Clone, Default)]
+struct NetworkConfig {
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+    connection: ConnectionPolicy,
+}
+
+fn client_builder() -> reqwest::ClientBuilder {
+    let network = NETWORK.read().unwrap().clone();
+    let mut builder = network
+        .connection
+        .apply(Client::builder().user_agent(format!("rbx_product/{}", env!("CARGO_PKG_VERSION"))));
+
+    if let Some(proxy) = network.proxy {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in network.certificates {
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder
+}
+
+/// Sends every request through `proxy` and trusts the certificates in the PEM
+/// bundle at `ca_cert` on top of the system roots. Clients are built on first
+/// use, so this has to run before any request is made.
+///
+/// Without an explicit proxy, the usual `HTTPS_PROXY`/`HTTP_PROXY` variables
+/// still apply.
+pub fn configure_network(proxy: Option<&str>, ca_cert: Option<&Path>) -> Result<()> {
+    let mut network = NETWORK.read().unwrap().clone();
+    network.proxy = None;
+    network.certificates = vec![];
+
+    if let Some(proxy) = proxy {
+        network.proxy = Some(
+            Proxy::all(proxy)
+                .map_err(|e| format!("invalid proxy '{}': {}", proxy, e))?
+                .no_proxy(reqwest::NoProxy::from_env()),
+        );
+    }
+
+    if let Some(path) = ca_cert {
+        let pem = std::fs::read(path)
+            .map_err(|e| format!("failed to read CA bundle {}: {}", path.display(), e))?;
+        network.certificates = Certificate::from_pem_bundle(&pem)
+            .map_err(|e| format!("invalid CA bundle {}: {}", path.display(), e))?;
+
+        if network.certificates.is_empty() {
+            return Err(format!("no certificates found in {}", path.display()).into());
+        }
+    }
+
+    *NETWORK.write().unwrap() = network;
+    Ok(())
+}
+
+/// Sets the timeouts and pool size of every client. Like the proxy, this only
+/// applies to clients that haven't been built yet.
+pub fn set_connection_policy(policy: ConnectionPolicy) {
+    NETWORK.write().unwrap().connection = policy;
+}
+
+pub const DEFAULT_BASE_URL: &str = "https://apis.roblox.com";
+
+/// Points Open Cloud requests somewhere other than `apis.roblox.com`, e.g. a
+/// proxy or the `--mock` server.
+pub fn set_base_url(url: &str) {
+    *BASE_URL.write().unwrap() = url.trim_end_matches('/').to_string();
+}
+
+pub fn base_url() -> String {
+    BASE_URL.read().unwrap().clone()
+}
+
+/// A roblox.com website API, e.g. `develop`, or the `--mock` server standing
+/// in for it.
+pub fn website_url(subdomain: &str) -> String {
+    match mock::is_running() {
+        true => base_url(),
+        false => format!("https://{}.roblox.com", subdomain),
+    }
+}
+
+/// The `.ROBLOSECURITY` cookie website APIs authenticate with, from
+/// `RBX_COOKIE`. The mock server doesn't check who's asking.
+pub fn website_cookie(purpose: &str) -> Result<String> {
+    if mock::is_running() {
+        return Ok(String::new());
+    }
+
+    std::env::var("RBX_COOKIE")
+        .ok()
+        .filter(|cookie| !cookie.trim().is_empty())
+        .ok_or_else(|| {
+            format!(
+                "RBX_COOKIE must be set to a .ROBLOSECURITY cookie to {}",
+                purpose
+            )
+            .into()
+        })
+}
+
+/// Sends a cookie request that changes something. The website refuses the
+/// first attempt with a CSRF token, which the request is then repeated with.
+pub async fn send_with_csrf(request: impl Fn() -> RequestBuilder) -> Result<Response> {
+    let resp = request().send().await?;
+
+    let token = resp
+        .headers()
+        .get("x-csrf-token")
+        .and_then(|token| token.to_str().ok())
+        .map(str::to_string);
+
+    let resp = match token {
+        Some(token) if resp.status() == StatusCode::FORBIDDEN => {
+            request().header("X-CSRF-TOKEN", token).send().await?
+        }
+        _ => resp,
+    };
+
+    error::check_status(resp).await
+}
+
+/// Whether requests need a real API key, which isn't the case when they're
+/// answered by recordings or the mock server.
+pub fn requires_api_key() -> bool {
+    !recorder::is_replaying() && !mock::is_running()
+}
+
 pub async fn set_api_token(token: String) {
     let mut guard = API_TOKEN.lock().await;
     *guard = Some(token);
 }
+
+pub async fn api_token() -> Option<String> {
+    API_TOKEN
+        .lock()
+        .await
+        .clone()
+        .filter(|token| !token.trim().is_empty())
+}
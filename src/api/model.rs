@@ -62,6 +62,9 @@ pub struct ProductUpdateRequest {
     pub price: Option<u64>,
     pub is_regional_pricing_enabled: Option<bool>,
     pub store_page_enabled: Option<bool>,
+    /// Path of an icon to upload with the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
 }
 
 paginate_struct!(DevProduct, DevProductPage, developer_products);
@@ -73,9 +76,10 @@ impl From<&Product> for ProductUpdateRequest {
             name: p.get_title(),
             description: p.description.clone(),
             is_for_sale: Some(p.active),
-            price: Some(p.get_price() as u64),
+            price: Some(p.get_price(None)),
             is_regional_pricing_enabled: p.regional_pricing,
-            store_page_enabled: None,
+            store_page_enabled: p.store_page_enabled,
+            image: None,
         }
     }
 }
@@ -99,12 +103,12 @@ impl From<&GamePass> for Product {
         let features = gp
             .price_information
             .as_ref()
-            .map_or(None, |pi| pi.enabled_features.clone());
+            .and_then(|pi| pi.enabled_features.clone());
 
         Self {
             discount: None,
             prefix: None,
-            id: Some(gp.game_pass_id as u64),
+            id: Some(gp.game_pass_id),
             name: gp.name.clone(),
             description: Some(gp.description.clone()),
             active: gp.is_for_sale,
@@ -113,6 +117,23 @@ impl From<&GamePass> for Product {
                 .as_ref()
                 .map_or(0, |pi| pi.default_price_in_robux as i64),
             regional_pricing: features.map(|f| f.iter().any(|i| i == "RegionalPricing")),
+            store_page_enabled: None,
+            order: None,
+            attributes: None,
+            tags: None,
+            category: None,
+            aliases: None,
+            internal: None,
+            locked: None,
+            image: None,
+            image_hash: None,
+            icon_asset_id: (gp.icon_asset_id > 0).then_some(gp.icon_asset_id),
+            immutable: false,
+            plugin_title: None,
+            plugin_price: None,
+            plugin_description: None,
+            remote_updated: Some(gp.updated_timestamp.clone())
+                .filter(|updated| !updated.is_empty()),
         }
     }
 }
@@ -122,12 +143,12 @@ impl From<&DevProduct> for Product {
         let features = dp
             .price_information
             .as_ref()
-            .map_or(None, |pi| pi.enabled_features.clone());
+            .and_then(|pi| pi.enabled_features.clone());
 
         Self {
             discount: None,
             prefix: None,
-            id: Some(dp.product_id as u64),
+            id: Some(dp.product_id),
             name: dp.name.clone(),
             description: Some(dp.description.clone()),
             active: dp.is_for_sale,
@@ -136,6 +157,23 @@ impl From<&DevProduct> for Product {
                 .as_ref()
                 .map_or(0, |pi| pi.default_price_in_robux as i64),
             regional_pricing: features.map(|f| f.iter().any(|i| i == "RegionalPricing")),
+            store_page_enabled: Some(dp.store_page_enabled),
+            order: None,
+            attributes: None,
+            tags: None,
+            category: None,
+            aliases: None,
+            internal: None,
+            locked: None,
+            image: None,
+            image_hash: None,
+            icon_asset_id: None,
+            immutable: dp.is_immutable,
+            plugin_title: None,
+            plugin_price: None,
+            plugin_description: None,
+            remote_updated: Some(dp.updated_timestamp.clone())
+                .filter(|updated| !updated.is_empty()),
         }
     }
 }
@@ -165,6 +203,10 @@ impl From<&ProductUpdateRequest> for Form {
             );
         }
 
+        if let Some(store_page_enabled) = update.store_page_enabled {
+            form = form.text("storePageEnabled", store_page_enabled.to_string());
+        }
+
         form
     }
 }
@@ -1,13 +1,76 @@
-use super::API_CLIENT;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tokio::task::JoinHandle;
+
+use super::error::check_status;
 use super::model::{DevProduct, GamePass};
+use super::{API_CLIENT, THROTTLE, base_url, cache};
 
 use crate::Result;
 use crate::api::model::{DevProductPage, GamePassPage, ProductUpdateRequest};
-use crate::sync::products::{MultiProduct, Product};
+use crate::sync::products::{MultiProduct, Product, ProductType, resolve_path};
+
+/// Listing endpoints return at most this many products per page.
+const PAGE_SIZE: usize = 100;
+
+/// Just the cursor of a page, which can be read before the rest of it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageToken {
+    next_page_token: Option<String>,
+}
+
+/// Starts fetching a page in the background, once the shared throttle lets it.
+fn request_page(
+    url: &str,
+    token: Option<String>,
+) -> JoinHandle<reqwest_middleware::Result<Response>> {
+    let mut req = API_CLIENT
+        .get(url)
+        .query(&[("pageSize", PAGE_SIZE.to_string())]);
+
+    if let Some(token) = token {
+        req = req.query(&[("pageToken", token)]);
+    }
+
+    tokio::spawn(async move {
+        let _permit = THROTTLE.acquire().await;
+        req.send().await
+    })
+}
+
+/// Fetches every page of a listing. Each page's cursor is read first, so the
+/// next page is already on its way while the current one is deserialized.
+async fn fetch_pages<P: DeserializeOwned>(url: String) -> Result<Vec<P>> {
+    let mut pages = vec![];
+    let mut next = Some(request_page(&url, None));
+
+    while let Some(request) = next.take() {
+        let body = check_status(request.await??).await?.bytes().await?;
+
+        let token: PageToken = serde_json::from_slice(&body)?;
+        next = token
+            .next_page_token
+            .filter(|token| !token.is_empty())
+            .map(|token| request_page(&url, Some(token)));
+
+        pages.push(serde_json::from_slice(&body)?);
+    }
+
+    Ok(pages)
+}
 
 pub async fn fetch_all_products(universe_id: u64) -> Result<Vec<MultiProduct>> {
-    let gamepasses = fetch_all_gamepasses(universe_id).await?;
-    let products = fetch_all_dev_products(universe_id).await?;
+    cache::load(universe_id).await;
+
+    let (gamepasses, products) = tokio::try_join!(
+        fetch_all_gamepasses(universe_id),
+        fetch_all_dev_products(universe_id)
+    )?;
+
+    cache::save(universe_id).await;
 
     let mut all_products: Vec<MultiProduct> = Vec::new();
 
@@ -27,85 +90,106 @@ pub async fn fetch_all_products(universe_id: u64) -> Result<Vec<MultiProduct>> {
 }
 
 pub async fn fetch_all_dev_products(universe_id: u64) -> Result<Vec<DevProduct>> {
-    let mut products = vec![];
-
-    let page_size = 100;
-    let mut page_cursor: String = String::default();
-
-    loop {
-        let mut req = API_CLIENT
-            .get(&format!(
-                "https://apis.roblox.com/developer-products/v2/universes/{}/developer-products/creator",
-                universe_id
-            ))
-            .query(&[("pageSize", page_size.to_string())]);
-
-        if !page_cursor.is_empty() {
-            req = req.query(&[("pageToken", page_cursor.clone())]);
-        }
-
-        let resp: DevProductPage = req.send().await?.json().await?;
-
-        products.extend(resp.developer_products);
-
-        match resp.next_page_token {
-            Some(cursor) => {
-                page_cursor = cursor;
-            }
-            None => break,
-        }
-    }
-
-    Ok(products)
+    let pages: Vec<DevProductPage> = fetch_pages(format!(
+        "{}/developer-products/v2/universes/{}/developer-products/creator",
+        base_url(),
+        universe_id
+    ))
+    .await?;
+
+    Ok(pages
+        .into_iter()
+        .flat_map(|page| page.developer_products)
+        .collect())
 }
 
 pub async fn fetch_all_gamepasses(universe_id: u64) -> Result<Vec<GamePass>> {
-    let mut gamepasses = vec![];
-
-    let page_size = 100;
-    let mut page_cursor: String = String::default();
+    let pages: Vec<GamePassPage> = fetch_pages(format!(
+        "{}/game-passes/v1/universes/{}/game-passes/creator",
+        base_url(),
+        universe_id
+    ))
+    .await?;
+
+    Ok(pages
+        .into_iter()
+        .flat_map(|page| page.game_passes)
+        .collect())
+}
 
-    loop {
-        let mut req = API_CLIENT
-            .get(&format!(
-                "https://apis.roblox.com/game-passes/v1/universes/{}/game-passes/creator",
-                universe_id
-            ))
-            .query(&[("pageSize", page_size.to_string())]);
+/// Fetches one product by id, or `None` if the universe has no such product
+/// of that type.
+pub async fn fetch_product(
+    universe_id: u64,
+    product_type: ProductType,
+    id: u64,
+) -> Result<Option<Product>> {
+    let url = match product_type {
+        ProductType::GamePass => format!(
+            "{}/game-passes/v1/universes/{}/game-passes/{}/creator",
+            base_url(),
+            universe_id,
+            id
+        ),
+        ProductType::DevProduct => format!(
+            "{}/developer-products/v2/universes/{}/developer-products/{}/creator",
+            base_url(),
+            universe_id,
+            id
+        ),
+    };
+
+    let resp = API_CLIENT.get(url).send().await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
 
-        if !page_cursor.is_empty() {
-            req = req.query(&[("pageToken", page_cursor.clone())]);
-        }
+    let resp = check_status(resp).await?;
+    Ok(Some(match product_type {
+        ProductType::GamePass => Product::from(&resp.json::<GamePass>().await?),
+        ProductType::DevProduct => Product::from(&resp.json::<DevProduct>().await?),
+    }))
+}
 
-        let resp: GamePassPage = req.send().await?.json().await?;
+/// The multipart form for a create or update, with the image file attached
+/// when the request carries one.
+async fn form(update: &ProductUpdateRequest) -> Result<Form> {
+    let form = Form::from(update);
 
-        gamepasses.extend(resp.game_passes);
+    let Some(path) = &update.image else {
+        return Ok(form);
+    };
 
-        match resp.next_page_token {
-            Some(cursor) => {
-                page_cursor = cursor;
-            }
-            None => break,
-        }
-    }
+    let data = tokio::fs::read(resolve_path(path))
+        .await
+        .map_err(|e| format!("failed to read image {}: {}", path, e))?;
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
 
-    Ok(gamepasses)
+    Ok(form.part("imageFile", Part::bytes(data).file_name(file_name)))
 }
 
+/// Open Cloud has no batch endpoint for product updates, so each change is
+/// its own request. Listing is already batched by the page size.
 pub async fn update_dev_product(
     universe_id: u64,
     product_id: u64,
     update: &ProductUpdateRequest,
 ) -> Result<()> {
-    API_CLIENT
-        .patch(&format!(
-            "https://apis.roblox.com/developer-products/v2/universes/{}/developer-products/{}",
-            universe_id, product_id
+    let resp = API_CLIENT
+        .patch(format!(
+            "{}/developer-products/v2/universes/{}/developer-products/{}",
+            base_url(),
+            universe_id,
+            product_id
         ))
-        .multipart(update.into())
+        .multipart(form(update).await?)
         .send()
-        .await?
-        .error_for_status()?;
+        .await?;
+
+    check_status(resp).await?;
 
     Ok(())
 }
@@ -115,15 +199,18 @@ pub async fn update_gamepass(
     game_pass_id: u64,
     update: &ProductUpdateRequest,
 ) -> Result<()> {
-    API_CLIENT
-        .patch(&format!(
-            "https://apis.roblox.com/game-passes/v1/universes/{}/game-passes/{}",
-            universe_id, game_pass_id
+    let resp = API_CLIENT
+        .patch(format!(
+            "{}/game-passes/v1/universes/{}/game-passes/{}",
+            base_url(),
+            universe_id,
+            game_pass_id
         ))
-        .multipart(update.into())
+        .multipart(form(update).await?)
         .send()
-        .await?
-        .error_for_status()?;
+        .await?;
+
+    check_status(resp).await?;
 
     Ok(())
 }
@@ -132,36 +219,32 @@ pub async fn create_dev_product(
     universe_id: u64,
     product: &ProductUpdateRequest,
 ) -> Result<DevProduct> {
-    let resp: DevProduct = API_CLIENT
-        .post(&format!(
-            "https://apis.roblox.com/developer-products/v2/universes/{}/developer-products",
+    let resp = API_CLIENT
+        .post(format!(
+            "{}/developer-products/v2/universes/{}/developer-products",
+            base_url(),
             universe_id
         ))
-        .multipart(product.into())
+        .multipart(form(product).await?)
         .send()
-        .await?
-        .error_for_status()?
-        .json()
         .await?;
 
-    Ok(resp)
+    Ok(check_status(resp).await?.json().await?)
 }
 
 pub async fn create_gamepass(
     universe_id: u64,
     gamepass: &ProductUpdateRequest,
 ) -> Result<GamePass> {
-    let resp: GamePass = API_CLIENT
-        .post(&format!(
-            "https://apis.roblox.com/game-passes/v1/universes/{}/game-passes",
+    let resp = API_CLIENT
+        .post(format!(
+            "{}/game-passes/v1/universes/{}/game-passes",
+            base_url(),
             universe_id
         ))
-        .multipart(gamepass.into())
+        .multipart(form(gamepass).await?)
         .send()
-        .await?
-        .error_for_status()?
-        .json()
         .await?;
 
-    Ok(resp)
+    Ok(check_status(resp).await?.json().await?)
 }
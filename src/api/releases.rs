@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use super::{RELEASES_CLIENT, base_url, mock};
+use crate::Result;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/outofbears/rbx-products/releases/latest";
+
+/// The file listing a SHA-256 checksum for every binary in a release.
+pub const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// The release binary built for this platform, e.g. `rbx_products-x86_64-linux`.
+pub fn asset_name() -> String {
+    format!(
+        "rbx_products-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// The latest published release on GitHub, or the `--mock` server's.
+pub async fn fetch_latest_release() -> Result<Release> {
+    let url = match mock::is_running() {
+        true => format!(
+            "{}/repos/outofbears/rbx-products/releases/latest",
+            base_url()
+        ),
+        false => RELEASES_URL.to_string(),
+    };
+
+    let release = RELEASES_CLIENT
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| format!("failed to check for releases: {}", e))?
+        .json()
+        .await?;
+
+    Ok(release)
+}
+
+pub async fn download_asset(asset: &ReleaseAsset) -> Result<Vec<u8>> {
+    let bytes = RELEASES_CLIENT
+        .get(&asset.browser_download_url)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| format!("failed to download {}: {}", asset.name, e))?
+        .bytes()
+        .await?;
+
+    Ok(bytes.to_vec())
+}
@@ -0,0 +1,230 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use clap::Args;
+use log::warn;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use serde::{Deserialize, Serialize};
+
+/// How hard to try before giving up on a request. Set under `[metadata.retry]`
+/// in the products file, or with the `--max-retries` family of flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RetryPolicy {
+    /// Retries after a 429 before the response is returned as-is
+    pub max_retries: usize,
+    /// Milliseconds added to the wait Roblox asks for
+    pub cushion_ms: u64,
+    /// Most seconds to spend waiting on retries for a single request
+    pub max_wait_secs: u64,
+    /// Retries after a 5xx or network error, with exponential backoff
+    pub transient_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            cushion_ms: 75,
+            max_wait_secs: 120,
+            transient_retries: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn max_wait(&self) -> Duration {
+        Duration::from_secs(self.max_wait_secs)
+    }
+
+    /// Delay before the given transient retry: 1s, 2s, 4s... capped at 30s.
+    fn backoff(&self, attempt: u32) -> Duration {
+        Duration::from_secs(1u64 << attempt.min(5)).min(Duration::from_secs(30))
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::default());
+}
+
+pub fn set_policy(policy: RetryPolicy) {
+    *POLICY.write().unwrap() = policy;
+}
+
+pub fn policy() -> RetryPolicy {
+    *POLICY.read().unwrap()
+}
+
+/// Command line overrides for the products file's retry policy.
+#[derive(Args, Debug, Clone, Default)]
+pub struct RetryOptions {
+    /// Retries after being rate limited (default 5)
+    #[arg(long, global = true)]
+    pub max_retries: Option<usize>,
+    /// Extra milliseconds to wait on top of Retry-After (default 75)
+    #[arg(long, global = true)]
+    pub retry_cushion_ms: Option<u64>,
+    /// Most seconds to spend retrying a single request (default 120)
+    #[arg(long, global = true)]
+    pub max_retry_wait: Option<u64>,
+    /// Retries after server or network errors (default 3)
+    #[arg(long, global = true)]
+    pub transient_retries: Option<u32>,
+}
+
+impl RetryOptions {
+    pub fn apply(&self, mut policy: RetryPolicy) -> RetryPolicy {
+        if let Some(max_retries) = self.max_retries {
+            policy.max_retries = max_retries;
+        }
+        if let Some(cushion_ms) = self.retry_cushion_ms {
+            policy.cushion_ms = cushion_ms;
+        }
+        if let Some(max_wait_secs) = self.max_retry_wait {
+            policy.max_wait_secs = max_wait_secs;
+        }
+        if let Some(transient_retries) = self.transient_retries {
+            policy.transient_retries = transient_retries;
+        }
+        policy
+    }
+}
+
+fn is_transient(result: &Result<Response>) -> bool {
+    match result {
+        Ok(resp) => resp.status().is_server_error(),
+        Err(reqwest_middleware::Error::Reqwest(e)) => e.is_connect() || e.is_timeout(),
+        Err(_) => false,
+    }
+}
+
+/// Retries server errors and dropped connections with exponential backoff.
+/// Rate limits are left to `RobloxRateLimitMiddleware`, which knows how long
+/// Roblox wants us to wait.
+#[derive(Clone, Debug, Default)]
+pub struct TransientRetryMiddleware {
+    /// Overrides the global policy, for tests
+    policy: Option<RetryPolicy>,
+}
+
+impl TransientRetryMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    fn with_policy(policy: RetryPolicy) -> Self {
+        Self {
+            policy: Some(policy),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for TransientRetryMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let policy = self.policy.unwrap_or_else(policy);
+        let mut req = req;
+        let mut waited = Duration::ZERO;
+
+        for attempt in 0.. {
+            // Multipart bodies can't be cloned, so those requests only get one try.
+            let retry = req.try_clone();
+            let result = next.clone().run(req, extensions).await;
+
+            let wait = policy.backoff(attempt);
+            let Some(retry) = retry.filter(|_| {
+                is_transient(&result)
+                    && attempt < policy.transient_retries
+                    && waited + wait <= policy.max_wait()
+            }) else {
+                return result;
+            };
+
+            match &result {
+                Ok(resp) => warn!(
+                    "request failed with {}, retrying in {} seconds...",
+                    resp.status(),
+                    wait.as_secs()
+                ),
+                Err(e) => warn!(
+                    "request failed ({}), retrying in {} seconds...",
+                    e,
+                    wait.as_secs()
+                ),
+            }
+
+            tokio::time::sleep(wait).await;
+            waited += wait;
+            req = retry;
+        }
+
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn cli_flags_override_the_products_file() {
+        let from_file = RetryPolicy {
+            max_retries: 10,
+            cushion_ms: 200,
+            ..Default::default()
+        };
+        let options = RetryOptions {
+            max_retries: Some(2),
+            ..Default::default()
+        };
+
+        let policy = options.apply(from_file);
+        assert_eq!(policy.max_retries, 2);
+        assert_eq!(policy.cushion_ms, 200);
+        assert_eq!(policy.transient_retries, 3);
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_until_success() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let hits = std::sync::Arc::new(AtomicUsize::new(0));
+
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf).await;
+
+                let status = match counter.fetch_add(1, Ordering::Relaxed) {
+                    0 => "503 Service Unavailable",
+                    _ => "200 OK",
+                };
+                let reply = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                let _ = stream.write_all(reply.as_bytes()).await;
+            }
+        });
+
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(TransientRetryMiddleware::with_policy(RetryPolicy::default()))
+            .build();
+
+        let resp = client.get(&url).send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert_eq!(hits.load(Ordering::Relaxed), 2);
+    }
+}
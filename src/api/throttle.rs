@@ -0,0 +1,227 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use tokio::sync::Notify;
+
+/// Latency this many times above the running average counts as congestion.
+const LATENCY_SPIKE_FACTOR: f64 = 2.0;
+const LATENCY_DECREASE: f64 = 0.75;
+
+/// The request budget Roblox reported in its `x-ratelimit-*` headers.
+#[derive(Debug, Clone, Copy)]
+struct RateState {
+    remaining: u64,
+    resets_at: Instant,
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    limit: f64,
+    max: usize,
+    in_flight: usize,
+    average_latency: Option<Duration>,
+    rate: Option<RateState>,
+}
+
+impl ThrottleState {
+    /// How long to hold off before the next request, when the reported budget
+    /// is used up and its window hasn't reset yet.
+    fn rate_wait(&mut self, now: Instant) -> Option<Duration> {
+        let rate = self.rate?;
+
+        if rate.resets_at <= now {
+            self.rate = None;
+            return None;
+        }
+
+        (rate.remaining == 0).then(|| rate.resets_at - now)
+    }
+}
+
+/// Shared AIMD concurrency limiter: the limit grows by one per window of
+/// successful requests and is cut multiplicatively on 429s or latency spikes.
+/// Requests also wait for the rate-limit window to reset once the budget
+/// Roblox reports is spent, rather than running into 429s.
+#[derive(Debug)]
+pub struct Throttle {
+    state: Mutex<ThrottleState>,
+    notify: Notify,
+}
+
+pub struct ThrottlePermit<'a> {
+    throttle: &'a Throttle,
+    started: Instant,
+}
+
+impl Throttle {
+    pub fn new(max: usize) -> Self {
+        Self {
+            state: Mutex::new(ThrottleState {
+                limit: max.max(1) as f64,
+                max: max.max(1),
+                in_flight: 0,
+                average_latency: None,
+                rate: None,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn set_max(&self, max: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.max = max.max(1);
+        state.limit = state.limit.min(state.max as f64).max(1.0);
+        drop(state);
+
+        self.notify.notify_waiters();
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit as usize
+    }
+
+    pub async fn acquire(&self) -> ThrottlePermit<'_> {
+        loop {
+            let notified = self.notify.notified();
+
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let wait = state.rate_wait(Instant::now());
+
+                if wait.is_none() && state.in_flight < state.limit as usize {
+                    state.in_flight += 1;
+                    if let Some(rate) = &mut state.rate {
+                        rate.remaining = rate.remaining.saturating_sub(1);
+                    }
+
+                    return ThrottlePermit {
+                        throttle: self,
+                        started: Instant::now(),
+                    };
+                }
+
+                wait
+            };
+
+            match wait {
+                Some(wait) => {
+                    debug!(
+                        "rate limit budget spent, waiting {:?} for it to reset",
+                        wait
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = notified => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// Records the budget from a response's `x-ratelimit-remaining` and
+    /// `x-ratelimit-reset` headers. Requests still in flight will spend part of
+    /// it, so concurrency is capped to what's left.
+    pub fn on_rate_headers(&self, remaining: u64, reset: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let in_flight = state.in_flight.saturating_sub(1) as u64;
+
+        state.rate = Some(RateState {
+            remaining: remaining.saturating_sub(in_flight),
+            resets_at: Instant::now() + reset,
+        });
+        if (remaining as f64) < state.limit {
+            state.limit = (remaining as f64).max(1.0);
+            debug!(
+                "{} request(s) left in the rate limit window, reducing concurrency to {}",
+                remaining, state.limit as usize
+            );
+        }
+        drop(state);
+
+        self.notify.notify_waiters();
+    }
+
+    pub fn on_rate_limited(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.limit = (state.limit / 2.0).max(1.0);
+
+        debug!(
+            "rate limited, reducing concurrency to {}",
+            state.limit as usize
+        );
+    }
+
+    fn on_complete(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight = state.in_flight.saturating_sub(1);
+
+        let average = state.average_latency.unwrap_or(latency);
+
+        if latency.as_secs_f64() > average.as_secs_f64() * LATENCY_SPIKE_FACTOR {
+            state.limit = (state.limit * LATENCY_DECREASE).max(1.0);
+            debug!(
+                "latency rose to {:?}, reducing concurrency to {}",
+                latency, state.limit as usize
+            );
+        } else {
+            state.limit = (state.limit + 1.0 / state.limit).min(state.max as f64);
+        }
+
+        state.average_latency = Some(average.mul_f64(0.8) + latency.mul_f64(0.2));
+        drop(state);
+
+        self.notify.notify_waiters();
+    }
+}
+
+impl Drop for ThrottlePermit<'_> {
+    fn drop(&mut self) {
+        self.throttle.on_complete(self.started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halves_on_rate_limit_and_ramps_back_up() {
+        let throttle = Throttle::new(8);
+
+        throttle.on_rate_limited();
+        throttle.on_rate_limited();
+        assert_eq!(throttle.limit(), 2);
+
+        for _ in 0..20 {
+            throttle.on_complete(Duration::from_millis(100));
+        }
+        assert!(throttle.limit() > 2);
+        assert!(throttle.limit() <= 8);
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_rate_limit_window_to_reset() {
+        let throttle = Throttle::new(8);
+        throttle.on_rate_headers(2, Duration::from_millis(200));
+        assert_eq!(throttle.limit(), 2);
+
+        let started = Instant::now();
+        drop(throttle.acquire().await);
+        drop(throttle.acquire().await);
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        drop(throttle.acquire().await);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn backs_off_on_latency_spike() {
+        let throttle = Throttle::new(8);
+        throttle.on_complete(Duration::from_millis(100));
+
+        throttle.on_complete(Duration::from_millis(500));
+        assert_eq!(throttle.limit(), 6);
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use futures::{StreamExt, stream};
+use serde::Deserialize;
+
+use super::THUMBNAIL_CLIENT;
+use super::error::check_status;
+use crate::Result;
+use crate::sync::products::ProductType;
+
+/// The thumbnails API takes at most this many ids per request.
+const BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Thumbnail {
+    target_id: u64,
+    /// `Completed` once the image is ready; blocked or pending icons have no URL
+    state: String,
+    image_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ThumbnailPage {
+    data: Vec<Thumbnail>,
+}
+
+async fn thumbnail_urls(product_type: ProductType, ids: &[u64]) -> Result<Vec<(u64, String)>> {
+    let (endpoint, param) = match product_type {
+        ProductType::GamePass => ("game-passes", "gamePassIds"),
+        ProductType::DevProduct => ("developer-products", "developerProductIds"),
+    };
+
+    let mut urls = vec![];
+
+    for batch in ids.chunks(BATCH_SIZE) {
+        let ids = batch
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let resp = THUMBNAIL_CLIENT
+            .get(format!("https://thumbnails.roblox.com/v1/{}", endpoint))
+            .query(&[
+                (param, ids.as_str()),
+                ("size", "150x150"),
+                ("format", "Png"),
+            ])
+            .send()
+            .await?;
+        let page: ThumbnailPage = check_status(resp).await?.json().await?;
+
+        urls.extend(page.data.into_iter().filter_map(|thumbnail| {
+            let url = thumbnail
+                .image_url
+                .filter(|_| thumbnail.state == "Completed")?;
+            Some((thumbnail.target_id, url))
+        }));
+    }
+
+    Ok(urls)
+}
+
+/// Downloads the PNG icon of each product. Products without an icon, or whose
+/// icon fails to download, are left out.
+pub async fn fetch_icons(products: &[(ProductType, u64)]) -> BTreeMap<(ProductType, u64), Vec<u8>> {
+    // Recordings and the mock server have no thumbnails to serve.
+    if !super::requires_api_key() {
+        return BTreeMap::new();
+    }
+
+    let mut urls = vec![];
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        let ids = products
+            .iter()
+            .filter(|(t, _)| *t == product_type)
+            .map(|(_, id)| *id)
+            .collect::<Vec<_>>();
+
+        if ids.is_empty() {
+            continue;
+        }
+
+        match thumbnail_urls(product_type, &ids).await {
+            Ok(found) => urls.extend(found.into_iter().map(|(id, url)| (product_type, id, url))),
+            Err(e) => log::debug!("failed to look up {:?} icons: {}", product_type, e),
+        }
+    }
+
+    stream::iter(urls)
+        .map(|(product_type, id, url)| async move {
+            let resp = THUMBNAIL_CLIENT.get(&url).send().await.ok()?;
+            let bytes = check_status(resp).await.ok()?.bytes().await.ok()?;
+            Some(((product_type, id), bytes.to_vec()))
+        })
+        .buffer_unordered(8)
+        .filter_map(|icon| async move { icon })
+        .collect()
+        .await
+}
@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use http::HeaderMap;
+use log::info;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+const RATE_LIMIT_HEADERS: [&str; 4] = [
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "retry-after",
+];
+
+const SECRET_HEADERS: [&str; 3] = ["x-api-key", "cookie", "authorization"];
+
+fn rate_limits(headers: &HeaderMap) -> String {
+    RATE_LIMIT_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = headers.get(*name)?.to_str().ok()?;
+            Some(format!("{}={}", name, value))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Request headers with credentials replaced, safe to print.
+fn redacted(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                "<redacted>"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Logs every request that goes out under `--verbose`, with its status,
+/// latency and rate-limit headers. Failed requests also log their headers.
+#[derive(Clone, Debug, Default)]
+pub struct TraceMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for TraceMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return next.run(req, extensions).await;
+        }
+
+        let method = req.method().clone();
+        let url = req.url().clone();
+        let headers = req.headers().clone();
+        let started = Instant::now();
+
+        let result = next.run(req, extensions).await;
+        let elapsed = started.elapsed().as_millis();
+
+        match &result {
+            Ok(resp) => {
+                info!(
+                    "{} {} -> {} in {}ms {}",
+                    method,
+                    url,
+                    resp.status(),
+                    elapsed,
+                    rate_limits(resp.headers())
+                );
+
+                if !resp.status().is_success() {
+                    info!("  request headers: {}", redacted(&headers));
+                }
+            }
+            Err(e) => {
+                info!("{} {} -> failed in {}ms: {}", method, url, elapsed, e);
+                info!("  request headers: {}", redacted(&headers));
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn redacts_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("secret"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+
+        let printed = redacted(&headers);
+        assert!(!printed.contains("secret"));
+        assert!(printed.contains("x-api-key: <redacted>"));
+        assert!(printed.contains("accept: application/json"));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::check_status;
+use super::{API_CLIENT, ECONOMY_CLIENT, base_url, send_with_csrf, website_url};
+use crate::Result;
+
+/// The universe fields Open Cloud exposes that rbx-products manages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Universe {
+    /// Unset when private servers are disabled
+    #[serde(default)]
+    pub private_server_price_robux: Option<u64>,
+}
+
+/// Paid access isn't part of Open Cloud, so it's read and written through the
+/// website's configuration endpoints.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UniverseConfiguration {
+    pub is_for_sale: bool,
+    pub price: Option<u64>,
+}
+
+pub async fn fetch_universe(universe_id: u64) -> Result<Universe> {
+    let resp = API_CLIENT
+        .get(format!("{}/cloud/v2/universes/{}", base_url(), universe_id))
+        .send()
+        .await?;
+
+    Ok(check_status(resp).await?.json().await?)
+}
+
+pub async fn update_private_server_price(universe_id: u64, price: u64) -> Result<()> {
+    let resp = API_CLIENT
+        .patch(format!("{}/cloud/v2/universes/{}", base_url(), universe_id))
+        .query(&[("updateMask", "privateServerPriceRobux")])
+        .json(&Universe {
+            private_server_price_robux: Some(price),
+        })
+        .send()
+        .await?;
+
+    check_status(resp).await?;
+    Ok(())
+}
+
+/// Reads the paid access settings. Like the economy API, this only accepts a
+/// `.ROBLOSECURITY` cookie.
+pub async fn fetch_configuration(universe_id: u64, cookie: &str) -> Result<UniverseConfiguration> {
+    let resp = ECONOMY_CLIENT
+        .get(format!(
+            "{}/v1/universes/{}/configuration",
+            website_url("develop"),
+            universe_id
+        ))
+        .header("Cookie", format!(".ROBLOSECURITY={}", cookie))
+        .send()
+        .await?;
+
+    Ok(check_status(resp).await?.json().await?)
+}
+
+/// Sets the paid access price, or turns paid access off for `None`.
+pub async fn update_paid_access(universe_id: u64, cookie: &str, price: Option<u64>) -> Result<()> {
+    let body = UniverseConfiguration {
+        is_for_sale: price.is_some(),
+        price,
+    };
+
+    send_with_csrf(|| {
+        ECONOMY_CLIENT
+            .patch(format!(
+                "{}/v2/universes/{}/configuration",
+                website_url("develop"),
+                universe_id
+            ))
+            .header("Cookie", format!(".ROBLOSECURITY={}", cookie))
+            .json(&body)
+    })
+    .await?;
+
+    Ok(())
+}
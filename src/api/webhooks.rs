@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+use super::WEBHOOK_CLIENT;
+use crate::Result;
+
+pub async fn post_webhook<T: Serialize>(url: &str, payload: &T) -> Result<()> {
+    WEBHOOK_CLIENT
+        .post(url)
+        .json(payload)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
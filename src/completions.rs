@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+
+use clap::Command;
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+
+use crate::Result;
+use crate::sync::products::{FileFormat, find_products_file};
+
+/// The environment variable the shell sets when asking for completions.
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Just the keys of a products file.
+#[derive(Debug, Default, Deserialize)]
+struct ProductKeys {
+    #[serde(default)]
+    gamepasses: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    products: BTreeMap<String, IgnoredAny>,
+}
+
+fn read_keys(path: &Path) -> Option<ProductKeys> {
+    let data = std::fs::read(path).ok()?;
+    FileFormat::from_path(path).parse(&data).ok()
+}
+
+/// Candidates for a partly typed key. Comma separated lists like
+/// `--only vip,co` complete their last key.
+fn candidates(keys: &ProductKeys, current: &str) -> Vec<CompletionCandidate> {
+    let (head, partial) = match current.rsplit_once(',') {
+        Some((head, partial)) => (format!("{},", head), partial),
+        None => (String::new(), current),
+    };
+
+    keys.gamepasses
+        .keys()
+        .map(|key| (key, "gamepass"))
+        .chain(keys.products.keys().map(|key| (key, "developer product")))
+        .filter(|(key, _)| key.starts_with(partial))
+        .map(|(key, help)| {
+            CompletionCandidate::new(format!("{}{}", head, key)).help(Some(help.into()))
+        })
+        .collect()
+}
+
+/// Completes product keys from the products file in the working directory.
+/// The file is read as it is, without migrating it or following includes.
+pub fn product_keys(current: &OsStr) -> Vec<CompletionCandidate> {
+    match find_products_file().and_then(|path| read_keys(&path)) {
+        Some(keys) => candidates(&keys, &current.to_string_lossy()),
+        None => vec![],
+    }
+}
+
+/// Answers the completion requests made by the script `print_completions`
+/// prints. Returns without doing anything on a normal run.
+pub fn complete(factory: fn() -> Command) {
+    clap_complete::CompleteEnv::with_factory(factory)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Prints a completion script for `shell`. By default the script calls back
+/// into rbx-products, which lets it complete product keys; `standalone`
+/// scripts only know the flags and subcommands.
+pub fn print_completions(mut cmd: Command, shell: Shell, standalone: bool) -> Result<()> {
+    let name = cmd.get_name().to_string();
+    let mut stdout = std::io::stdout();
+
+    if standalone {
+        let mut script = vec![];
+        clap_complete::generate(shell, &mut cmd, &name, &mut script);
+        stdout.write_all(&script)?;
+        return Ok(());
+    }
+
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| format!("{} doesn't support dynamic completions", shell))?;
+    let bin = std::env::args().next().unwrap_or_else(|| name.clone());
+
+    completer.write_registration(COMPLETE_VAR, &name, &name, &bin, &mut stdout)?;
+    Ok(())
+}
+
+/// Writes a man page for the CLI and one for each subcommand into `dir`.
+pub fn write_manpages(cmd: Command, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(cmd, dir)
+        .map_err(|e| format!("failed to write man pages to {}: {}", dir.display(), e))?;
+
+    log::info!("wrote man pages to {}", dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_keys_from_the_products_file() {
+        let keys: ProductKeys = FileFormat::Toml
+            .parse(
+                br#"
+                [metadata]
+                universe-id = 1
+
+                [gamepasses.vip]
+                name = "VIP"
+                price = 100
+
+                [products.coins]
+                price = 25
+
+                [products.gems]
+                price = 50
+                "#,
+            )
+            .unwrap();
+
+        let values = |current| {
+            candidates(&keys, current)
+                .iter()
+                .map(|candidate| candidate.get_value().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(""), ["vip", "coins", "gems"]);
+        assert_eq!(values("g"), ["gems"]);
+        assert_eq!(values("vip,c"), ["vip,coins"]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use log::warn;
+
+use crate::sync::products::{VCSProducts, find_products_file};
+
+/// Universe id written by `init`, which has to be replaced before syncing.
+pub const PLACEHOLDER_UNIVERSE_ID: u64 = 1234;
+
+/// What a command needs to run at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Requirements {
+    pub products_file: bool,
+    pub api: bool,
+}
+
+fn hints(
+    requirements: Requirements,
+    api_key_set: bool,
+    products_file: Option<&Path>,
+    universe_id: Option<u64>,
+) -> Vec<String> {
+    let mut hints = vec![];
+
+    if requirements.api && !api_key_set {
+        hints.push(
+            "RBX_API_KEY is not set. Create an Open Cloud API key and run \
+             `export RBX_API_KEY=<key>`, add `RBX_API_KEY=<key>` to a .env file, or add \
+             a profile for this universe to ~/.config/rbx-products/credentials.toml"
+                .to_string(),
+        );
+    }
+
+    if requirements.products_file && products_file.is_none() {
+        hints.push(
+            "no products file found in this directory. Run `rbx-products init` to create \
+             products.toml, then set its universe-id"
+                .to_string(),
+        );
+    }
+
+    if requirements.api
+        && let (Some(path), Some(PLACEHOLDER_UNIVERSE_ID)) = (products_file, universe_id)
+    {
+        hints.push(format!(
+            "universe-id in {} is still the placeholder {}. Replace it with your \
+             experience's universe id (Creator Dashboard > experience > copy universe ID)",
+            path.display(),
+            PLACEHOLDER_UNIVERSE_ID
+        ));
+    }
+
+    hints
+}
+
+/// Prints a hint for each misconfiguration that would stop the command, and
+/// returns whether it's safe to go ahead.
+pub async fn check(requirements: Requirements) -> bool {
+    let api_key_set = !crate::api::requires_api_key() || crate::api::api_token().await.is_some();
+    let products_file = find_products_file();
+
+    let universe_id = match &products_file {
+        Some(_) if requirements.api => VCSProducts::get_products()
+            .await
+            .ok()
+            .map(|products| products.metadata.universe_id),
+        _ => None,
+    };
+
+    let hints = hints(
+        requirements,
+        api_key_set,
+        products_file.as_deref(),
+        universe_id,
+    );
+
+    for hint in &hints {
+        warn!("hint: {}", hint);
+    }
+
+    hints.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYNC: Requirements = Requirements {
+        products_file: true,
+        api: true,
+    };
+
+    #[test]
+    fn hints_for_each_misconfiguration() {
+        let path = Path::new("products.toml");
+
+        assert!(hints(SYNC, true, Some(path), Some(42)).is_empty());
+        assert_eq!(hints(SYNC, false, None, None).len(), 2);
+
+        let placeholder = hints(SYNC, true, Some(path), Some(PLACEHOLDER_UNIVERSE_ID));
+        assert_eq!(placeholder.len(), 1);
+        assert!(placeholder[0].contains("universe-id in products.toml"));
+    }
+
+    #[test]
+    fn local_commands_only_need_the_file() {
+        let local = Requirements {
+            products_file: true,
+            api: false,
+        };
+
+        assert!(hints(local, false, Some(Path::new("products.toml")), Some(1234)).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use env_logger::{Builder, Logger, Target, WriteStyle};
+use log::{Log, Metadata, Record, warn};
+
+use crate::Result;
+
+/// Log files are rotated once they grow past this size.
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+/// How many rotated log files are kept next to the current one.
+const KEPT_LOGS: usize = 3;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// env_logger's human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// `app.log` rotated to `app.log.1`, `app.log.2`, ...
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+/// A log file that moves itself aside once it gets too big.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_size: u64, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+            keep,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        for index in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                std::fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+
+        match self.keep {
+            0 => std::fs::remove_file(&self.path)?,
+            _ => std::fs::rename(&self.path, rotated_path(&self.path, 1))?,
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn json_line(time: &str, record: &Record) -> String {
+    serde_json::json!({
+        "time": time,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+fn builder(format: LogFormat) -> Builder {
+    let mut builder = Builder::from_default_env();
+
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let time = buf.timestamp_millis().to_string();
+            writeln!(buf, "{}", json_line(&time, record))
+        });
+    }
+
+    builder
+}
+
+/// Sends every record to stderr and, with `--log-file`, to the file too.
+struct Tee {
+    loggers: Vec<Logger>,
+}
+
+impl Log for Tee {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.loggers.iter().any(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        for logger in &self.loggers {
+            if logger.matches(record) {
+                logger.log(record);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        for logger in &self.loggers {
+            logger.flush();
+        }
+    }
+}
+
+/// Sets up logging for the run. Logs always go to stderr, without colors
+/// unless `color` is set; `file` also appends them to a rotating log file, so
+/// runs behind a terminal UI leave a history. A file that can't be opened is
+/// only warned about, and the run keeps logging to stderr.
+pub fn init(format: LogFormat, file: Option<&Path>, color: bool) -> Result<()> {
+    if std::env::var("RUST_LOG").is_err() {
+        if cfg!(debug_assertions) {
+            unsafe { std::env::set_var("RUST_LOG", "off,rbx_product=debug") }
+        } else {
+            unsafe { std::env::set_var("RUST_LOG", "rbx_product=info") }
+        }
+    }
+
+    let mut stderr = builder(format);
+    if !color {
+        stderr.write_style(WriteStyle::Never);
+    }
+
+    let mut loggers = vec![stderr.build()];
+    let mut error = None;
+
+    if let Some(path) = file {
+        match RotatingFile::open(path, MAX_LOG_SIZE, KEPT_LOGS) {
+            Ok(file) => loggers.push(
+                builder(format)
+                    .target(Target::Pipe(Box::new(file)))
+                    .write_style(WriteStyle::Never)
+                    .build(),
+            ),
+            Err(e) => error = Some(format!("failed to open log file {}: {}", path.display(), e)),
+        }
+    }
+
+    let level = loggers
+        .iter()
+        .map(|logger| logger.filter())
+        .max()
+        .unwrap_or(log::LevelFilter::Off);
+
+    log::set_boxed_logger(Box::new(Tee { loggers }))?;
+    log::set_max_level(level);
+
+    if let Some(e) = error {
+        warn!("{}", e);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_json_lines() {
+        let line = json_line(
+            "2024-05-01T12:00:00.000Z",
+            &Record::builder()
+                .level(log::Level::Warn)
+                .target("rbx_products::sync")
+                .args(format_args!("synced \"vip\""))
+                .build(),
+        );
+
+        assert_eq!(
+            line,
+            r#"{"level":"WARN","message":"synced \"vip\"","target":"rbx_products::sync","time":"2024-05-01T12:00:00.000Z"}"#
+        );
+    }
+
+    #[test]
+    fn rotates_full_log_files() {
+        let dir = std::env::temp_dir().join(format!("rbx-products-logs-{}", std::process::id()));
+        let path = dir.join("sync.log");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "fourth\n");
+        assert_eq!(read(rotated_path(&path, 1)), "third\n");
+        assert_eq!(read(rotated_path(&path, 2)), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,17 +1,54 @@
-use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{ArgValueCompleter, Shell};
 use log::info;
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 mod api;
+mod completions;
+mod hints;
+mod logging;
 pub mod sync;
 pub mod ui;
 pub mod utils;
 
-use crate::sync::download::Downloader;
-use crate::sync::upload::Uploader;
+use crate::api::connection::ConnectionOptions;
+use crate::api::retry::RetryOptions;
+use crate::hints::Requirements;
+use crate::logging::LogFormat;
+use crate::sync::add::{AddOptions, add_product};
+use crate::sync::adopt::adopt_product;
+use crate::sync::browse::browse_products;
+use crate::sync::clone::{CloneOptions, clone_universe};
+use crate::sync::conflict::ConflictOptions;
+use crate::sync::diff::DiffOptions;
+use crate::sync::download::{DownloadCommand, Downloader};
+use crate::sync::duplicates::resolve_duplicates;
+use crate::sync::filters::{FiltersCommand, run_filters};
+use crate::sync::github::CheckOutput;
+use crate::sync::link::link_products;
+use crate::sync::list::{ListOutput, ListSource, list_products};
+use crate::sync::migrate::migrate_products;
+use crate::sync::open::open_product;
+use crate::sync::orphans::{OrphansOptions, list_orphans};
+use crate::sync::prices::download_prices;
+use crate::sync::products::{FileFormat, ProductType};
+use crate::sync::rename::rename_product;
+use crate::sync::reorder::reorder_products;
+use crate::sync::report::{ReportFormat, print_report};
+use crate::sync::revenue::{RevenueOptions, print_revenue};
+use crate::sync::sale::{SaleAction, run_sale};
+use crate::sync::show::show_product;
+use crate::sync::spreadsheet::{SpreadsheetFormat, export_products, import_products};
+use crate::sync::undo::undo;
+use crate::sync::update::self_update;
+use crate::sync::upload::{SyncFilter, SyncOptions, Uploader};
+use crate::sync::validate::validate_products;
+use crate::sync::whoami::whoami;
+use crate::sync::workspace::WorkspaceOptions;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -19,45 +56,363 @@ struct Args {
     #[command(subcommand)]
     command: Option<Commands>,
     /// Automatically answer "yes" to all prompts
-    #[arg(short = 'y', long, default_value_t = false)]
+    #[arg(short = 'y', long, global = true, default_value_t = false)]
     yes: bool,
+    /// Fail instead of prompting when a command needs an answer
+    #[arg(long, global = true, default_value_t = false, conflicts_with = "yes")]
+    no_input: bool,
     #[arg(short = 'o', long, default_value_t = false)]
     overwrite: bool,
+    /// Always refetch remote products instead of revalidating cached responses
+    #[arg(long, global = true, default_value_t = false)]
+    no_cache: bool,
+    /// Fail on unknown keys in the products file instead of ignoring them
+    #[arg(long, global = true, default_value_t = false)]
+    strict: bool,
+    /// Never open terminal UIs or prompt, and exit with 2 when sync finds changes
+    #[arg(long, global = true, default_value_t = false)]
+    ci: bool,
+    /// Log every API request with its status, latency and rate-limit headers
+    #[arg(short = 'v', long, global = true, default_value_t = false)]
+    verbose: bool,
+    /// Disable colors, like setting NO_COLOR
+    #[arg(long, global = true, default_value_t = false)]
+    no_color: bool,
+    /// Save every API response to the recordings directory
+    #[arg(
+        long,
+        global = true,
+        default_value_t = false,
+        conflicts_with = "offline"
+    )]
+    record: bool,
+    /// Answer API requests from the recordings directory instead of the network
+    #[arg(long, global = true, default_value_t = false)]
+    offline: bool,
+    /// Open Cloud base URL, defaults to $RBX_API_BASE_URL or https://apis.roblox.com
+    #[arg(long, global = true)]
+    api_base_url: Option<String>,
+    /// Proxy for all requests, defaults to $RBX_PROXY (HTTPS_PROXY is also honored)
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+    /// PEM file of extra root certificates to trust, defaults to $RBX_CA_CERT
+    #[arg(long, global = true)]
+    ca_cert: Option<PathBuf>,
+    /// Serve the products in this fixture file from a local mock API
+    #[arg(long, global = true)]
+    mock: Option<PathBuf>,
+    #[command(flatten)]
+    retry: RetryOptions,
+    #[command(flatten)]
+    connection: ConnectionOptions,
+    /// Use the API key from this profile in the credentials file
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Directory used by --record and --offline
+    #[arg(long, global = true, default_value = ".rbx-products/recordings")]
+    recordings: PathBuf,
+    /// Also append logs to this file, rotated at 10 MB, defaults to $RBX_LOG_FILE
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+    /// Format of log lines, defaults to $RBX_LOG_FORMAT or text
+    #[arg(long, global = true, value_enum)]
+    log_format: Option<LogFormat>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Initializes the products file
-    Init,
+    Init {
+        #[arg(long, value_enum, default_value_t = FileFormat::Toml)]
+        format: FileFormat,
+    },
     /// Downloads all the products from the universe
-    Download,
+    Download {
+        #[command(subcommand)]
+        what: Option<DownloadCommand>,
+        /// Report what would change in the products file without writing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Rename keys that no longer match their product's name, keeping the
+        /// old key as an alias
+        #[arg(long)]
+        rename_keys: bool,
+        #[command(flatten)]
+        conflicts: ConflictOptions,
+        #[command(flatten)]
+        workspace: WorkspaceOptions,
+    },
     /// Syncs products between file and universe
-    Sync,
+    Sync {
+        #[command(flatten)]
+        options: SyncOptions,
+        #[command(flatten)]
+        filter: SyncFilter,
+        /// Report what would change without touching the universe or any files
+        #[arg(long)]
+        dry_run: bool,
+        /// Compare every product, including ones unchanged since the last sync
+        #[arg(long)]
+        full: bool,
+        #[command(flatten)]
+        workspace: WorkspaceOptions,
+    },
+    /// Lists the changes a sync would make, exiting with 2 if there are any
+    Check {
+        #[arg(long, value_enum, default_value_t = CheckOutput::Log)]
+        output: CheckOutput,
+    },
+    /// Prints a unified diff of the products file against the universe
+    Diff {
+        #[command(flatten)]
+        options: DiffOptions,
+    },
+    /// Writes the changes a sync would make to a plan file, without applying them
+    Plan {
+        #[arg(default_value = "plan.json")]
+        output: PathBuf,
+        /// Minutes until the plan expires and can no longer be applied
+        #[arg(long, default_value_t = 60)]
+        expires_in: u64,
+    },
+    /// Applies a plan file previously written by `plan`
+    Apply {
+        plan: PathBuf,
+        #[command(flatten)]
+        options: SyncOptions,
+    },
+    /// Adds a new product entry to the products file
+    Add {
+        product_type: ProductType,
+        name: String,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+    /// Lists products from the products file or the universe
+    List {
+        /// List the products that exist in the universe
+        #[arg(long, conflicts_with = "local")]
+        remote: bool,
+        /// List the products in the products file (default)
+        #[arg(long)]
+        local: bool,
+        /// Only list products with one of these tags, comma separated
+        #[arg(long, value_delimiter = ',')]
+        tag: Vec<String>,
+        /// Add approximate USD prices and DevEx payouts, using `revenue-model`
+        #[arg(long)]
+        usd: bool,
+        #[arg(long, value_enum, default_value_t = ListOutput::Table)]
+        output: ListOutput,
+    },
+    /// Exports products to a spreadsheet file
+    Export {
+        #[arg(long, value_enum, default_value_t = SpreadsheetFormat::Csv)]
+        format: SpreadsheetFormat,
+        /// File to write to, defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports products from a spreadsheet file, merging them into the products file
+    Import {
+        input: PathBuf,
+        #[arg(long, value_enum, default_value_t = SpreadsheetFormat::Csv)]
+        format: SpreadsheetFormat,
+    },
+    /// Prints projected gross and net earnings per product
+    Report {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// Tools for working on name-filters
+    Filters {
+        #[command(subcommand)]
+        command: FiltersCommand,
+    },
+    /// Writes the ids of matching remote products into local products without one
+    Link,
+    /// Prints sales counts and Robux earned per product over a period
+    Revenue {
+        #[command(flatten)]
+        options: RevenueOptions,
+    },
+    /// Binds a local product to a remote one that was created on the website
+    Adopt {
+        product_type: ProductType,
+        key: String,
+        id: u64,
+        /// Overwrite the local fields with the remote product's
+        #[arg(long, default_value_t = false)]
+        from_remote: bool,
+    },
+    /// Merges local products that point at the same remote id
+    Duplicates,
+    /// Copies a universe's products into another universe and a new products file
+    Clone {
+        #[command(flatten)]
+        options: CloneOptions,
+    },
+    /// Interactively browses every remote gamepass and product
+    Browse,
+    /// Lists remote products that aren't in the products file
+    Orphans {
+        #[command(flatten)]
+        options: OrphansOptions,
+    },
+    /// Opens a product's Creator Hub page in the browser
+    Open {
+        #[arg(add = ArgValueCompleter::new(completions::product_keys))]
+        key: String,
+        /// Which product type the key belongs to, when both have it
+        #[arg(long = "type")]
+        product_type: Option<ProductType>,
+        /// Open the public store page instead (gamepasses only)
+        #[arg(long)]
+        store: bool,
+        /// Print the URL instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Fetches one product by id and prints it next to its local entry
+    Show {
+        id: u64,
+        /// Which product type the id belongs to, tried in turn when unset
+        #[arg(long = "type")]
+        product_type: Option<ProductType>,
+    },
+    /// Renames a product's key and/or display name, keeping its id
+    Rename {
+        product_type: ProductType,
+        #[arg(add = ArgValueCompleter::new(completions::product_keys))]
+        key: String,
+        /// New key for the product in the products file
+        #[arg(long = "key")]
+        new_key: Option<String>,
+        /// New display name for the product
+        #[arg(long = "name")]
+        new_name: Option<String>,
+    },
+    /// Interactively reorders products for the in-game shop
+    Reorder { product_type: ProductType },
+    /// Starts or ends a discount across many products at once
+    Sale {
+        #[command(subcommand)]
+        action: SaleAction,
+    },
+    /// Restores the products changed by the last sync to how they were before it
+    Undo,
+    /// Checks the products file against Roblox marketplace limits
+    #[command(alias = "lint")]
+    Validate,
+    /// Rewrites the products file and its includes in the current schema
+    Migrate,
+    /// Checks the API key is valid, who owns it and that it has the needed scopes
+    Whoami,
+    /// Prints a shell completion script
+    Completions {
+        shell: Shell,
+        /// Only complete flags and subcommands, without calling back into
+        /// rbx-products for product keys
+        #[arg(long)]
+        standalone: bool,
+    },
+    /// Writes man pages for every command into a directory
+    Manpages { dir: PathBuf },
+    /// Replaces this binary with the latest GitHub release
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
 }
 
-fn init_logging() {
-    if std::env::var("RUST_LOG").is_err() {
-        if cfg!(debug_assertions) {
-            unsafe { std::env::set_var("RUST_LOG", "off,rbx_product=debug") }
-        } else {
-            unsafe { std::env::set_var("RUST_LOG", "rbx_product=info") }
+impl Commands {
+    /// The workspace options of a command that was asked to run for every game.
+    fn workspace(&self) -> Option<&WorkspaceOptions> {
+        match self {
+            Commands::Download { workspace, .. } | Commands::Sync { workspace, .. } => {
+                Some(workspace).filter(|workspace| workspace.all)
+            }
+            _ => None,
         }
     }
 
-    env_logger::init();
+    fn requirements(&self) -> Requirements {
+        let api = match self {
+            Commands::Init { .. }
+            | Commands::Completions { .. }
+            | Commands::Manpages { .. }
+            | Commands::SelfUpdate { .. } => {
+                return Requirements::default();
+            }
+            Commands::Whoami | Commands::Clone { .. } => {
+                return Requirements {
+                    products_file: false,
+                    api: true,
+                };
+            }
+            Commands::Download { .. }
+            | Commands::Sync { .. }
+            | Commands::Check { .. }
+            | Commands::Diff { .. }
+            | Commands::Plan { .. }
+            | Commands::Apply { .. }
+            | Commands::Link
+            | Commands::Browse
+            | Commands::Orphans { .. }
+            | Commands::Adopt { .. }
+            | Commands::Show { .. }
+            | Commands::Undo => true,
+            Commands::Add { options, .. } => options.create,
+            Commands::List { remote, local, .. } => {
+                ListSource::from_flags(*remote, *local) == ListSource::Remote
+            }
+            Commands::Filters { command } => command.remote(),
+            Commands::Sale { action } => !action.selection().no_sync,
+            _ => false,
+        };
+
+        Requirements {
+            products_file: true,
+            api,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
-    init_logging();
     let _ = color_eyre::install();
+    // After color_eyre, so the terminal is restored before its report prints.
+    ui::install_handlers();
+    // Answers the shell before anything else can write to stdout.
+    completions::complete(Args::command);
 
-    if let Some(token) = std::env::var("RBX_API_KEY").ok() {
-        api::set_api_token(token).await;
+    let args = Args::parse();
+
+    let log_format = match args.log_format {
+        Some(format) => Ok(format),
+        None => match std::env::var("RBX_LOG_FORMAT") {
+            Ok(format) => LogFormat::from_str(&format, true)
+                .map_err(|_| format!("invalid RBX_LOG_FORMAT '{}'", format)),
+            Err(_) => Ok(LogFormat::default()),
+        },
+    };
+    let log_file = args
+        .log_file
+        .clone()
+        .or_else(|| std::env::var_os("RBX_LOG_FILE").map(PathBuf::from));
+    let color = !args.no_color && std::env::var_os("NO_COLOR").is_none();
+    let logging = logging::init(
+        log_format.clone().unwrap_or_default(),
+        log_file.as_deref(),
+        color,
+    );
+    if let Err(e) = log_format.map_err(Into::into).and(logging) {
+        log::error!("Error: {}", e);
+        std::process::exit(1);
     }
 
-    let args = Args::parse();
     let command = match args.command {
         Some(cmd) => cmd,
         None => {
@@ -66,41 +421,283 @@ async fn main() {
         }
     };
 
-    // flags::FLAGS.auto_yes = args.yes;
+    // These only describe the CLI, so none of the setup below applies.
+    let described = match &command {
+        Commands::Completions { shell, standalone } => Some(completions::print_completions(
+            Args::command(),
+            *shell,
+            *standalone,
+        )),
+        Commands::Manpages { dir } => Some(completions::write_manpages(Args::command(), dir)),
+        _ => None,
+    };
+    if let Some(result) = described {
+        if let Err(e) = result {
+            log::error!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Each game reruns the command as its own process in its own directory,
+    // so none of the setup below applies to the workspace itself.
+    if let Some(workspace) = command.workspace() {
+        match sync::workspace::run_all(workspace.parallel, args.yes).await {
+            Err(e) => {
+                log::error!("Error: {}", e);
+                std::process::exit(1);
+            }
+            Ok(true) => std::process::exit(2),
+            Ok(false) => return,
+        }
+    }
+
+    ui::prompt::set_policy(if args.yes {
+        ui::prompt::PromptPolicy::Yes
+    } else if args.no_input {
+        ui::prompt::PromptPolicy::NoInput
+    } else {
+        ui::prompt::PromptPolicy::Ask
+    });
+
+    api::recorder::set_mode(if args.offline {
+        api::recorder::Mode::Replay(args.recordings.clone())
+    } else if args.record {
+        api::recorder::Mode::Record(args.recordings.clone())
+    } else {
+        api::recorder::Mode::Off
+    });
+
+    if let Some(url) = args
+        .api_base_url
+        .clone()
+        .or_else(|| std::env::var("RBX_API_BASE_URL").ok())
+    {
+        api::set_base_url(&url);
+    }
+
+    let proxy = args
+        .proxy
+        .clone()
+        .or_else(|| std::env::var("RBX_PROXY").ok());
+    let ca_cert = args
+        .ca_cert
+        .clone()
+        .or_else(|| std::env::var_os("RBX_CA_CERT").map(PathBuf::from));
+
+    if let Err(e) = api::configure_network(proxy.as_deref(), ca_cert.as_deref()) {
+        log::error!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(fixture) = &args.mock {
+        match api::mock::start(fixture).await {
+            Ok(url) => api::set_base_url(&url),
+            Err(e) => {
+                log::error!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Err(e) = ui::config::UserConfig::load()
+        .await
+        .and_then(|config| config.apply(color))
+    {
+        log::error!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    // A products file that can't be loaded would fail the command anyway, and
+    // its retry and connection settings can't be ignored silently.
+    let metadata = match sync::products::find_products_file() {
+        Some(_) => match sync::products::VCSProducts::get_products().await {
+            Ok(products) => Some(products.metadata),
+            Err(e) => {
+                log::error!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    // A profile picked with --profile, or listing this universe, wins over RBX_API_KEY.
+    let credentials = match api::credentials::Credentials::load().await {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            log::error!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let universe_id = metadata.as_ref().map(|metadata| metadata.universe_id);
+
+    match credentials.resolve(args.profile.as_deref(), universe_id) {
+        Ok(Some((name, key))) => {
+            log::debug!("using the API key from profile '{}'", name);
+            api::set_api_token(key.to_string()).await;
+        }
+        Ok(None) => {
+            if let Ok(token) = std::env::var("RBX_API_KEY") {
+                api::set_api_token(token).await;
+            }
+        }
+        Err(e) => {
+            log::error!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if !hints::check(command.requirements()).await {
+        return;
+    }
+
+    // The products file sets the baseline policy and flags override it.
+    let file_policy = metadata.as_ref().and_then(|metadata| metadata.retry);
+    api::retry::set_policy(args.retry.apply(file_policy.unwrap_or_default()));
+    let file_connection = metadata.and_then(|metadata| metadata.connection);
+    api::set_connection_policy(args.connection.apply(file_connection.unwrap_or_default()));
+
+    api::trace::set_enabled(args.verbose);
+    api::cache::set_enabled(!args.no_cache && args.mock.is_none());
+    sync::strict::set_enabled(args.strict);
+    ui::set_ci(args.ci);
+
+    // `--yes` answers sync's prompts the same way `--overwrite` skips them.
+    let skip_prompts = args.overwrite || args.yes;
+    let mut changes_detected = false;
 
     let result = match command {
-        Commands::Init => {
+        Commands::Init { format } => {
             info!("Initializing products file...");
 
-            if std::fs::exists("products.toml").unwrap() {
-                log::error!("products.toml already exists. Aborting initialization.");
+            if let Some(existing) = sync::products::find_products_file() {
+                log::error!(
+                    "{} already exists. Aborting initialization.",
+                    existing.display()
+                );
                 return;
             }
 
+            let path = format.default_path();
+            let universe_id = match sync::project::init_universe_id() {
+                Ok(universe_id) => universe_id,
+                Err(e) => {
+                    log::error!("{}", e);
+                    return;
+                }
+            };
+
             let products = sync::products::VCSProducts {
-                metadata: sync::products::Metadata {
-                    universe_id: 1234,
-                    discount_prefix: Some("💲{}% OFF💲 ".to_string()),
-                    luau_file: Some("products.luau".to_string()),
-                    name_filters: None,
-                },
-                gamepasses: HashMap::new(),
-                products: HashMap::new(),
+                metadata: sync::products::Metadata::new(universe_id),
+                path: Some(path.clone()),
+                ..Default::default()
             };
 
             match products.save_products().await {
                 Ok(_) => {
-                    info!("products.toml initialized successfully.");
+                    info!("{} initialized successfully.", path.display());
                     Ok(())
                 }
-                Err(e) => Err(format!("Failed to initialize products.toml: {}", e).into()),
+                Err(e) => Err(format!("Failed to initialize {}: {}", path.display(), e).into()),
             }
         }
-        Commands::Download => Downloader::download(args.overwrite).await,
-        Commands::Sync => Uploader::upload(args.overwrite).await,
+        Commands::Download {
+            what: None,
+            dry_run,
+            rename_keys,
+            conflicts,
+            ..
+        } => Downloader::download(conflicts.strategy(args.overwrite), rename_keys, dry_run).await,
+        Commands::Download {
+            what:
+                Some(DownloadCommand::Prices {
+                    regional_only,
+                    format,
+                    output,
+                }),
+            ..
+        } => download_prices(regional_only, format, output.as_deref()).await,
+        Commands::Sync {
+            mut options,
+            filter,
+            dry_run,
+            full,
+            ..
+        } => {
+            options.filter = filter;
+            options.dry_run = dry_run;
+            options.full = full;
+            Uploader::upload(skip_prompts, options)
+                .await
+                .map(|detected| changes_detected = args.ci && detected > 0)
+        }
+        Commands::Check { output } => Uploader::check(output)
+            .await
+            .map(|detected| changes_detected = detected > 0),
+        Commands::Diff { options } => Uploader::diff(options).await.map(|_| ()),
+        Commands::Plan { output, expires_in } => {
+            Uploader::write_plan(output, Duration::from_secs(expires_in * 60)).await
+        }
+        Commands::Apply { plan, options } => {
+            Uploader::apply_plan(plan, skip_prompts, options).await
+        }
+        Commands::Add {
+            product_type,
+            name,
+            options,
+        } => add_product(product_type, name, options).await,
+        Commands::List {
+            remote,
+            local,
+            tag,
+            usd,
+            output,
+        } => list_products(ListSource::from_flags(remote, local), &tag, usd, output).await,
+        Commands::Export { format, output } => export_products(format, output.as_deref()).await,
+        Commands::Import { input, format } => import_products(format, &input).await,
+        Commands::Report { format } => print_report(format).await,
+        Commands::Filters { command } => run_filters(command).await,
+        Commands::Link => link_products(args.yes).await,
+        Commands::Adopt {
+            product_type,
+            key,
+            id,
+            from_remote,
+        } => adopt_product(product_type, key, id, from_remote).await,
+        Commands::Duplicates => resolve_duplicates().await,
+        Commands::Browse => browse_products().await,
+        Commands::Orphans { options } => list_orphans(options).await,
+        Commands::Clone { options } => clone_universe(options, skip_prompts, args.overwrite).await,
+        Commands::Open {
+            key,
+            product_type,
+            store,
+            print,
+        } => open_product(key, product_type, store, print).await,
+        Commands::Show { id, product_type } => show_product(id, product_type).await,
+        Commands::Rename {
+            product_type,
+            key,
+            new_key,
+            new_name,
+        } => rename_product(product_type, key, new_key, new_name).await,
+        Commands::Revenue { options } => print_revenue(options).await,
+        Commands::Reorder { product_type } => reorder_products(product_type).await,
+        Commands::Sale { action } => run_sale(action, skip_prompts).await,
+        Commands::Validate => validate_products().await,
+        Commands::Migrate => migrate_products().await,
+        Commands::Undo => undo(skip_prompts).await,
+        Commands::SelfUpdate { check } => self_update(check).await,
+        Commands::Whoami => whoami().await,
+        Commands::Completions { .. } | Commands::Manpages { .. } => unreachable!(),
     };
 
-    if let Err(e) = result {
-        log::error!("Error: {}", e);
+    match result {
+        Err(e) => {
+            log::error!("Error: {}", e);
+            std::process::exit(1);
+        }
+        Ok(()) if changes_detected => std::process::exit(2),
+        Ok(()) => {}
     }
 }
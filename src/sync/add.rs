@@ -0,0 +1,184 @@
+use std::io::IsTerminal;
+
+use clap::Args;
+use crossterm::style::Stylize;
+use log::info;
+
+use crate::Result;
+use crate::sync::limits::{MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH};
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::sync::upload::Uploader;
+use crate::sync::validate::uploaded_title;
+use crate::ui::{prompt, theme};
+use crate::utils::{canonical_name, prompt_line};
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Price in Robux (prompted for if omitted)
+    #[arg(long)]
+    pub price: Option<u64>,
+    /// Product description (prompted for if omitted)
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Discount percentage
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub discount: Option<u8>,
+    /// Add the product as not for sale
+    #[arg(long, default_value_t = false)]
+    pub inactive: bool,
+    /// Enable regional pricing
+    #[arg(long, default_value_t = false)]
+    pub regional_pricing: bool,
+    /// Keep the product out of the generated Luau and TypeScript files
+    #[arg(long, default_value_t = false)]
+    pub internal: bool,
+    /// Immediately create the product in the universe
+    #[arg(long, default_value_t = false)]
+    pub create: bool,
+}
+
+/// Prints `label: used/max characters`, in red when over the limit, and
+/// returns whether the text fits.
+fn print_budget(label: &str, text: &str, max: usize) -> bool {
+    let (line, fits) = budget_line(label, text, max);
+
+    if fits || !theme::color_enabled() {
+        eprintln!("{}", line);
+    } else {
+        eprintln!("{}", line.red());
+    }
+
+    fits
+}
+
+fn budget_line(label: &str, text: &str, max: usize) -> (String, bool) {
+    let used = text.chars().count();
+    let line = format!("{}: {}/{} characters", label, used, max);
+
+    if used > max {
+        (format!("{} ({} over)", line, used - max), false)
+    } else {
+        (line, true)
+    }
+}
+
+pub async fn add_product(
+    product_type: ProductType,
+    name: String,
+    options: AddOptions,
+) -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+    let products_file = local_products.path.clone().unwrap_or_default();
+
+    let name = canonical_name(name, &local_products.metadata.filters());
+    let key = local_products.metadata.key_for(&name);
+
+    if key.is_empty() {
+        return Err(format!("'{}' does not produce a usable product key", name).into());
+    }
+
+    let entries = match product_type {
+        ProductType::GamePass => &local_products.gamepasses,
+        ProductType::DevProduct => &local_products.products,
+    };
+
+    if entries.contains_key(&key) {
+        return Err(format!(
+            "{:?} '{}' already exists in {}",
+            product_type,
+            key,
+            products_file.display()
+        )
+        .into());
+    }
+
+    let interactive = std::io::stdin().is_terminal() && prompt::allows_input();
+
+    let mut product = Product {
+        name,
+        active: !options.inactive,
+        discount: options.discount.filter(|d| *d > 0),
+        regional_pricing: options.regional_pricing.then_some(true),
+        internal: options.internal.then_some(true),
+        ..Default::default()
+    };
+
+    // Check the name as it will be uploaded, including the sale prefix, before
+    // asking for anything else.
+    let title = uploaded_title(&product, local_products.metadata.discount_prefix.clone());
+    if !print_budget("Name", &title, MAX_NAME_LENGTH) {
+        return Err(format!("name '{}' is too long for Roblox", title).into());
+    }
+
+    let price = match options.price {
+        Some(price) => price,
+        None if interactive => prompt_line("Price (Robux)")?
+            .parse::<u64>()
+            .map_err(|e| format!("invalid price: {}", e))?,
+        None => return Err("--price is required when not running interactively".into()),
+    };
+    product.price = price as i64;
+
+    let metadata = &local_products.metadata;
+
+    match options.description {
+        Some(description) => {
+            product.description = Some(description);
+            let expanded = product.get_description(Some(metadata)).unwrap_or_default();
+
+            if !print_budget("Description", &expanded, MAX_DESCRIPTION_LENGTH) {
+                return Err("description is too long for Roblox".into());
+            }
+        }
+        None if interactive => loop {
+            let label = format!(
+                "Description (optional, up to {} characters)",
+                MAX_DESCRIPTION_LENGTH
+            );
+            product.description = Some(prompt_line(&label)?).filter(|d| !d.is_empty());
+            let expanded = product.get_description(Some(metadata)).unwrap_or_default();
+
+            if expanded.is_empty() || print_budget("Description", &expanded, MAX_DESCRIPTION_LENGTH)
+            {
+                break;
+            }
+        },
+        None => {}
+    }
+
+    match product_type {
+        ProductType::GamePass => local_products.gamepasses.insert(key.clone(), product),
+        ProductType::DevProduct => local_products.products.insert(key.clone(), product),
+    };
+
+    local_products.save_products().await?;
+    info!(
+        "added {:?} '{}' to {}",
+        product_type,
+        key,
+        products_file.display()
+    );
+
+    if options.create {
+        Uploader::upload_new(product_type, &key).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_line_reports_overflow() {
+        assert_eq!(
+            budget_line("Name", "VIP", MAX_NAME_LENGTH),
+            ("Name: 3/50 characters".to_string(), true)
+        );
+        assert_eq!(
+            budget_line("Name", &"x".repeat(53), MAX_NAME_LENGTH),
+            ("Name: 53/50 characters (3 over)".to_string(), false)
+        );
+    }
+}
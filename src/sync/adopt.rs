@@ -0,0 +1,199 @@
+use log::info;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts};
+use crate::ui::diffs::DiffChange;
+use crate::utils::{NameFilter, canonical_name};
+
+/// The fields of a remote product as they'd be written to the products file.
+pub fn local_copy(remote: &Product, filters: &Option<Vec<NameFilter>>) -> Product {
+    Product {
+        id: remote.id,
+        name: canonical_name(remote.name.clone(), filters),
+        description: remote.description.clone(),
+        active: remote.active,
+        price: remote.price,
+        regional_pricing: remote.regional_pricing.filter(|enabled| *enabled),
+        store_page_enabled: remote.store_page_enabled,
+        icon_asset_id: remote.icon_asset_id,
+        ..Default::default()
+    }
+}
+
+/// Points `key` at the remote product `id`, creating the entry if it doesn't
+/// exist. Local fields are kept unless `from_remote` is set.
+fn bind(
+    local_products: &mut VCSProducts,
+    product_type: ProductType,
+    key: &str,
+    remote: &Product,
+    from_remote: bool,
+) -> Result<()> {
+    let id = remote.id.ok_or("remote product has no id")?;
+
+    if let Some((other, _)) = local_products
+        .products_of(product_type)
+        .iter()
+        .find(|(other, product)| *other != key && product.id == Some(id))
+    {
+        return Err(format!("{:?} '{}' already tracks id {}", product_type, other, id).into());
+    }
+
+    let copy = local_copy(remote, &local_products.metadata.filters());
+    let entries = local_products.products_of_mut(product_type);
+
+    let Some(local) = entries.get_mut(key) else {
+        entries.insert(key.to_string(), copy);
+        info!("added {:?} '{}' from id {}", product_type, key, id);
+        return Ok(());
+    };
+
+    match local.id {
+        Some(existing) if existing != id => {
+            return Err(format!(
+                "{:?} '{}' is already bound to id {}",
+                product_type, key, existing
+            )
+            .into());
+        }
+        _ => local.id = Some(id),
+    }
+    local.icon_asset_id = copy.icon_asset_id;
+
+    if from_remote {
+        local.name = copy.name;
+        local.description = copy.description;
+        local.active = copy.active;
+        local.price = copy.price;
+        local.regional_pricing = copy.regional_pricing;
+        local.store_page_enabled = copy.store_page_enabled;
+    }
+
+    info!("bound {:?} '{}' to id {}", product_type, key, id);
+    Ok(())
+}
+
+/// Binds a local entry to a product that was created on the website, then
+/// logs what the next sync would change about it.
+pub async fn adopt_product(
+    product_type: ProductType,
+    key: String,
+    id: u64,
+    from_remote: bool,
+) -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+
+    info!("fetching remote products");
+    let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+
+    let remote = remote_products
+        .iter()
+        .find_map(|multi_product| {
+            match (multi_product, product_type) {
+                (MultiProduct::GamePass(pass), ProductType::GamePass) => Some(pass),
+                (MultiProduct::DevProduct(prod), ProductType::DevProduct) => Some(prod),
+                _ => None,
+            }
+            .filter(|product| product.id == Some(id))
+        })
+        .ok_or_else(|| {
+            format!(
+                "no {:?} with id {} in universe {}",
+                product_type, id, local_products.metadata.universe_id
+            )
+        })?;
+
+    bind(&mut local_products, product_type, &key, remote, from_remote)?;
+
+    let pending = local_products.products_of(product_type)[&key]
+        .diff(remote, Some(&local_products.metadata))
+        .map(|diff| diff.diffs)
+        .unwrap_or_default();
+
+    for change in pending {
+        if let DiffChange::Changed(diff) = change {
+            let (old, new) = diff.values();
+            info!("sync will change {}: {} -> {}", diff.field(), old, new);
+        }
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote() -> Product {
+        Product {
+            id: Some(9),
+            name: "VIP".to_string(),
+            description: Some("From the website".to_string()),
+            price: 300,
+            active: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn binds_and_keeps_local_fields() {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip-pass".to_string(),
+            Product {
+                name: "VIP".to_string(),
+                price: 250,
+                ..Default::default()
+            },
+        );
+
+        bind(
+            &mut local_products,
+            ProductType::GamePass,
+            "vip-pass",
+            &remote(),
+            false,
+        )
+        .unwrap();
+        let vip = &local_products.gamepasses["vip-pass"];
+        assert_eq!((vip.id, vip.price), (Some(9), 250));
+
+        bind(
+            &mut local_products,
+            ProductType::GamePass,
+            "vip-pass",
+            &remote(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(local_products.gamepasses["vip-pass"].price, 300);
+    }
+
+    #[test]
+    fn refuses_ids_tracked_elsewhere() {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(9),
+                ..Default::default()
+            },
+        );
+
+        assert!(
+            bind(
+                &mut local_products,
+                ProductType::GamePass,
+                "vip-pass",
+                &remote(),
+                false
+            )
+            .is_err()
+        );
+        assert!(!local_products.gamepasses.contains_key("vip-pass"));
+    }
+}
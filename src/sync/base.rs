@@ -0,0 +1,87 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::sync::lockfile::SyncLock;
+use crate::sync::products::{MultiProduct, Product, ProductType};
+use crate::utils::is_censored;
+
+/// Where the base was recorded before it moved into the lockfile. It's still
+/// read until the next download or sync records the base again.
+const LEGACY_BASE_FILE: &str = ".rbx-products/base.json";
+
+/// The universe's products as of the last download or sync. Both the products
+/// file and the universe started from these values, so `download` can tell
+/// which side changed a field since.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteBase {
+    pub universe_id: u64,
+    pub products: Vec<(ProductType, Product)>,
+}
+
+impl RemoteBase {
+    pub fn from_remote(universe_id: u64, remote_products: &[MultiProduct]) -> Self {
+        Self {
+            universe_id,
+            products: remote_products
+                .iter()
+                .map(|multi_product| match multi_product {
+                    MultiProduct::GamePass(pass) => (ProductType::GamePass, pass.clone()),
+                    MultiProduct::DevProduct(prod) => (ProductType::DevProduct, prod.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// The recorded base for `universe_id`, if there is one.
+    pub async fn load(universe_id: u64) -> Option<Self> {
+        if let Some(base) = SyncLock::load(universe_id).await.and_then(|lock| lock.base) {
+            return Some(base);
+        }
+
+        let data = fs::read(LEGACY_BASE_FILE).await.ok()?;
+        serde_json::from_slice::<Self>(&data)
+            .ok()
+            .filter(|base| base.universe_id == universe_id)
+    }
+
+    /// Records the base, warning instead of failing since only later merges
+    /// depend on it. Censored descriptions are replaced with the previously
+    /// recorded ones, so the last clean copy is never lost.
+    pub async fn save(mut self, threshold: f64) {
+        if let Some(previous) = Self::load(self.universe_id).await {
+            self.keep_clean_descriptions(&previous, threshold);
+        }
+
+        match SyncLock::save_base(self).await {
+            Ok(()) => {
+                let _ = fs::remove_file(LEGACY_BASE_FILE).await;
+            }
+            Err(e) => warn!("failed to record the remote products: {}", e),
+        }
+    }
+
+    fn keep_clean_descriptions(&mut self, previous: &RemoteBase, threshold: f64) {
+        for (product_type, product) in &mut self.products {
+            let censored = product
+                .description
+                .as_deref()
+                .is_some_and(|description| is_censored(description, threshold));
+
+            if let Some(id) = product.id
+                && censored
+                && let Some(recorded) = previous.get(*product_type, id)
+            {
+                product.description = recorded.description.clone();
+            }
+        }
+    }
+
+    pub fn get(&self, product_type: ProductType, id: u64) -> Option<&Product> {
+        self.products
+            .iter()
+            .find(|(base_type, product)| *base_type == product_type && product.id == Some(id))
+            .map(|(_, product)| product)
+    }
+}
@@ -0,0 +1,75 @@
+use log::info;
+
+use crate::Result;
+use crate::api::cache;
+use crate::api::products::{fetch_all_dev_products, fetch_all_gamepasses};
+use crate::sync::products::{ProductType, VCSProducts};
+use crate::ui;
+use crate::ui::browse::{BrowseItem, BrowseViewer};
+use crate::ui::prompt;
+
+/// The key of the local product tracking a remote id.
+fn tracked_key(local_products: &VCSProducts, product_type: ProductType, id: u64) -> Option<String> {
+    local_products
+        .products_of(product_type)
+        .iter()
+        .find(|(_, product)| product.id == Some(id))
+        .map(|(key, _)| key.clone())
+}
+
+/// Opens a screen listing everything that exists on Roblox for the universe,
+/// whether or not the products file tracks it.
+pub async fn browse_products() -> Result<()> {
+    if !ui::is_interactive() || !prompt::allows_input() {
+        return Err("browse needs an interactive terminal, use `list --remote` instead".into());
+    }
+
+    let local_products = VCSProducts::get_products().await?;
+    let universe_id = local_products.metadata.universe_id;
+
+    info!("fetching remote products");
+    cache::load(universe_id).await;
+    let gamepasses = fetch_all_gamepasses(universe_id).await?;
+    let dev_products = fetch_all_dev_products(universe_id).await?;
+    cache::save(universe_id).await;
+
+    let mut items = gamepasses
+        .iter()
+        .map(|pass| BrowseItem {
+            product_type: ProductType::GamePass,
+            id: pass.game_pass_id,
+            name: pass.name.clone(),
+            description: pass.description.clone(),
+            price: pass
+                .price_information
+                .as_ref()
+                .map_or(0, |info| info.default_price_in_robux),
+            for_sale: pass.is_for_sale,
+            updated: pass.updated_timestamp.clone(),
+            key: tracked_key(&local_products, ProductType::GamePass, pass.game_pass_id),
+        })
+        .collect::<Vec<_>>();
+
+    items.extend(dev_products.iter().map(|product| {
+        BrowseItem {
+            product_type: ProductType::DevProduct,
+            id: product.product_id,
+            name: product.name.clone(),
+            description: product.description.clone(),
+            price: product
+                .price_information
+                .as_ref()
+                .map_or(0, |info| info.default_price_in_robux),
+            for_sale: product.is_for_sale,
+            updated: product.updated_timestamp.clone(),
+            key: tracked_key(&local_products, ProductType::DevProduct, product.product_id),
+        }
+    }));
+
+    if items.is_empty() {
+        info!("universe {} has no products", universe_id);
+        return Ok(());
+    }
+
+    BrowseViewer::browse(items).await
+}
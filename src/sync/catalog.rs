@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use crate::api::catalog::{AssetDetails, fetch_assets, release_asset};
+use crate::api::website_cookie;
+
+/// A group-owned avatar item, like a shirt or UGC accessory. Items are
+/// uploaded on the website, so only their price and sale state are synced.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CatalogItem {
+    pub id: u64,
+    /// For reference, the item's name isn't synced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub price: u64,
+    /// Whether the item is on sale
+    pub active: bool,
+}
+
+/// A catalog item whose price or sale state differs from the products file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogChange {
+    pub key: String,
+    pub item: CatalogItem,
+    pub remote_price: Option<u64>,
+    pub remote_active: bool,
+}
+
+impl CatalogChange {
+    /// Every changed field with its remote and local value.
+    pub fn fields(&self) -> Vec<(&'static str, String, String)> {
+        let mut fields = vec![];
+
+        if self.remote_price != Some(self.item.price) {
+            fields.push((
+                "price",
+                self.remote_price
+                    .map_or("none".to_string(), |price| price.to_string()),
+                self.item.price.to_string(),
+            ));
+        }
+        if self.remote_active != self.item.active {
+            fields.push((
+                "active",
+                self.remote_active.to_string(),
+                self.item.active.to_string(),
+            ));
+        }
+
+        fields
+    }
+}
+
+impl fmt::Display for CatalogChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self
+            .fields()
+            .iter()
+            .map(|(field, old, new)| format!("{} {} -> {}", field, old, new))
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "catalog item '{}' (id {}): {}",
+            self.key,
+            self.item.id,
+            fields.join(", ")
+        )
+    }
+}
+
+/// The change an item needs, if the catalog differs from it.
+fn change(key: &str, item: &CatalogItem, details: &AssetDetails) -> Option<CatalogChange> {
+    let change = CatalogChange {
+        key: key.to_string(),
+        item: item.clone(),
+        remote_price: details.price_in_robux,
+        remote_active: details.is_for_sale,
+    };
+
+    (!change.fields().is_empty()).then_some(change)
+}
+
+/// Fetches every catalog item and returns the ones that differ.
+pub async fn pending_changes(
+    catalog: &BTreeMap<String, CatalogItem>,
+) -> Result<Vec<CatalogChange>> {
+    if catalog.is_empty() {
+        return Ok(vec![]);
+    }
+
+    info!("fetching {} catalog item(s)", catalog.len());
+    let ids = catalog.values().map(|item| item.id).collect::<Vec<_>>();
+    let details = fetch_assets(&ids)
+        .await
+        .map_err(|e| format!("failed to fetch catalog items: {}", e))?;
+
+    Ok(catalog
+        .iter()
+        .filter_map(|(key, item)| change(key, item, details.get(&item.id)?))
+        .collect())
+}
+
+pub async fn apply_change(change: &CatalogChange) -> Result<()> {
+    let cookie = website_cookie("sync catalog items")?;
+    release_asset(
+        change.item.id,
+        &cookie,
+        change.item.price,
+        change.item.active,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_changed_price_and_sale_state() {
+        let item = CatalogItem {
+            id: 55,
+            name: Some("Team Shirt".to_string()),
+            price: 10,
+            active: true,
+        };
+        let details = |price, is_for_sale| AssetDetails {
+            name: "Team Shirt".to_string(),
+            price_in_robux: price,
+            is_for_sale,
+        };
+
+        assert!(change("shirt", &item, &details(Some(10), true)).is_none());
+
+        let pending = change("shirt", &item, &details(Some(5), false)).unwrap();
+        assert_eq!(
+            pending.to_string(),
+            "catalog item 'shirt' (id 55): price 5 -> 10, active false -> true"
+        );
+    }
+}
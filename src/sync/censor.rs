@@ -0,0 +1,152 @@
+use log::warn;
+
+use crate::sync::products::{Product, ProductType};
+use crate::utils::{censored_share, is_censored, render_table};
+
+/// Share of `#` characters from which a description counts as censored when
+/// `censor-threshold` isn't set.
+pub const DEFAULT_CENSOR_THRESHOLD: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// The products file still had a clean description
+    KeptLocal,
+    /// The last recorded remote description was clean
+    Restored,
+    /// No clean copy was found, so the censored text stays
+    Missing,
+}
+
+/// A remote description that Roblox's filter masked, and how it was handled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CensoredDescription {
+    pub product_type: ProductType,
+    pub key: String,
+    pub share: f64,
+    pub recovery: Recovery,
+}
+
+/// Replaces a censored remote description with the local one, or with the
+/// last recorded remote one, so the merge never takes the masked text.
+/// Returns `None` when the description isn't censored.
+pub fn recover_description(
+    remote: &mut Product,
+    product_type: ProductType,
+    key: &str,
+    local: Option<&Product>,
+    base: Option<&Product>,
+    threshold: f64,
+) -> Option<CensoredDescription> {
+    let description = remote.description.clone().unwrap_or_default();
+    if !is_censored(&description, threshold) {
+        return None;
+    }
+
+    let clean = |product: Option<&Product>| {
+        product
+            .and_then(|product| product.description.clone())
+            .filter(|description| {
+                !description.trim().is_empty() && !is_censored(description, threshold)
+            })
+    };
+
+    let recovery = if let Some(local) = clean(local) {
+        remote.description = Some(local);
+        Recovery::KeptLocal
+    } else if let Some(recorded) = clean(base) {
+        remote.description = Some(recorded);
+        Recovery::Restored
+    } else {
+        Recovery::Missing
+    };
+
+    Some(CensoredDescription {
+        product_type,
+        key: key.to_string(),
+        share: censored_share(&description),
+        recovery,
+    })
+}
+
+/// Warns about censored descriptions with a table of what happened to each.
+pub fn report_censored(censored: &[CensoredDescription]) {
+    if censored.is_empty() {
+        return;
+    }
+
+    warn!(
+        "{} remote description(s) were censored by Roblox",
+        censored.len()
+    );
+
+    let rows = censored
+        .iter()
+        .map(|entry| {
+            vec![
+                format!("{:?}", entry.product_type),
+                entry.key.clone(),
+                format!("{:.0}%", entry.share * 100.0),
+                match entry.recovery {
+                    Recovery::KeptLocal => "kept local",
+                    Recovery::Restored => "restored last known",
+                    Recovery::Missing => "no clean copy, edit it by hand",
+                }
+                .to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    eprint!(
+        "{}",
+        render_table(&["type", "key", "masked", "description"], &rows)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(description: &str) -> Product {
+        Product {
+            description: Some(description.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recovers_partially_censored_descriptions() {
+        let recover = |remote: &str, local: Option<&str>, base: Option<&str>| {
+            let mut remote = product(remote);
+            let local = local.map(product);
+            let base = base.map(product);
+            let censored = recover_description(
+                &mut remote,
+                ProductType::GamePass,
+                "vip",
+                local.as_ref(),
+                base.as_ref(),
+                DEFAULT_CENSOR_THRESHOLD,
+            )
+            .map(|censored| censored.recovery);
+
+            (remote.description.unwrap(), censored)
+        };
+
+        assert_eq!(
+            recover("#1 pass in the game", Some("Old"), None),
+            ("#1 pass in the game".to_string(), None)
+        );
+        assert_eq!(
+            recover("Get ##### ####", Some("Get bonus gems"), None),
+            ("Get bonus gems".to_string(), Some(Recovery::KeptLocal))
+        );
+        assert_eq!(
+            recover("Get ##### ####", Some("####"), Some("Get bonus gems")),
+            ("Get bonus gems".to_string(), Some(Recovery::Restored))
+        );
+        assert_eq!(
+            recover("Get ##### ####", None, None),
+            ("Get ##### ####".to_string(), Some(Recovery::Missing))
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use log::info;
+use tokio::fs;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::adopt::local_copy;
+use crate::sync::duplicates::free_key;
+use crate::sync::link::link_matching;
+use crate::sync::products::{Metadata, MultiProduct, ProductType, VCSProducts};
+use crate::sync::upload::Uploader;
+use crate::ui;
+use crate::ui::confirm::{ConfirmState, ConfirmViewer};
+
+#[derive(Args, Debug, Clone)]
+pub struct CloneOptions {
+    /// Universe to copy the products from
+    #[arg(long)]
+    pub from: u64,
+    /// Universe to create the products in
+    #[arg(long)]
+    pub to: u64,
+    /// Products file to write for the target universe
+    #[arg(long, default_value = "products.toml")]
+    pub output: PathBuf,
+    /// Replace the output file if it already exists
+    #[arg(long)]
+    pub force: bool,
+    /// Log what would be created without touching the universe or any files
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// A products file for universe `to` holding copies of `remote_products`,
+/// without ids or icons so they're created fresh.
+fn cloned(remote_products: &[MultiProduct], to: u64) -> VCSProducts {
+    let mut products = VCSProducts {
+        metadata: Metadata::new(to),
+        ..Default::default()
+    };
+    let filters = products.metadata.filters();
+
+    for multi_product in remote_products {
+        let (product_type, remote) = match multi_product {
+            MultiProduct::GamePass(pass) => (ProductType::GamePass, pass),
+            MultiProduct::DevProduct(prod) => (ProductType::DevProduct, prod),
+        };
+
+        let mut copy = local_copy(remote, &filters);
+        copy.id = None;
+        copy.icon_asset_id = None;
+
+        let key = products.metadata.key_for(&copy.name);
+        let entries = products.products_of_mut(product_type);
+        let key = free_key(entries, &key);
+        entries.insert(key, copy);
+    }
+
+    products
+}
+
+/// Copies the products of one universe into another, creating them there,
+/// and writes a products file for the target universe.
+pub async fn clone_universe(options: CloneOptions, yes: bool, overwrite: bool) -> Result<()> {
+    if options.from == options.to {
+        return Err("--from and --to are the same universe".into());
+    }
+
+    if options.output.exists() && !options.force && !overwrite {
+        return Err(format!(
+            "{} already exists, pass --force to replace it",
+            options.output.display()
+        )
+        .into());
+    }
+
+    info!("fetching products of universe {}", options.from);
+    let source = fetch_all_products(options.from).await?;
+    let mut products = cloned(&source, options.to);
+
+    info!("fetching products of universe {}", options.to);
+    let target = fetch_all_products(options.to).await?;
+
+    // Products already in the target, say from an earlier clone that was cut
+    // short, are tracked instead of created twice.
+    let existing = link_matching(&mut products, &target);
+    if existing > 0 {
+        info!(
+            "{} product(s) already exist in universe {} and will be tracked",
+            existing, options.to
+        );
+    }
+
+    let pending = [ProductType::GamePass, ProductType::DevProduct]
+        .into_iter()
+        .flat_map(|product_type| {
+            products
+                .products_of(product_type)
+                .iter()
+                .filter(|(_, product)| product.id.is_none())
+                .map(move |(key, product)| (product_type, key, product))
+        })
+        .collect::<Vec<_>>();
+
+    for (product_type, key, product) in &pending {
+        info!(
+            "{} {:?} '{}': {} ({} R$)",
+            match options.dry_run {
+                true => "would create",
+                false => "will create",
+            },
+            product_type,
+            key,
+            product.name,
+            product.price
+        );
+    }
+
+    if options.dry_run {
+        info!("dry run, nothing was changed");
+        return Ok(());
+    }
+
+    if !pending.is_empty() && !yes {
+        if !ui::can_prompt() {
+            return Err("pass --yes to create products when not running interactively".into());
+        }
+
+        let prompt = format!(
+            "Create {} product(s) in universe {}?",
+            pending.len(),
+            options.to
+        );
+        if ConfirmViewer::show_prompt(prompt).await? != ConfirmState::Confirmed {
+            info!("nothing was cloned");
+            return Ok(());
+        }
+    }
+
+    if options.output.exists() {
+        fs::remove_file(&options.output).await?;
+    }
+    products.path = Some(options.output.clone());
+
+    let products = Uploader::create_all(products, target).await?;
+    products.save_products().await?;
+    products.serialize_luau().await?;
+
+    info!(
+        "cloned universe {} into {}, wrote {}",
+        options.from,
+        options.to,
+        options.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::Product;
+
+    fn remote(id: u64, name: &str) -> Product {
+        Product {
+            id: Some(id),
+            name: name.to_string(),
+            price: 100,
+            active: true,
+            icon_asset_id: Some(77),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn copies_products_without_ids() {
+        let source = [
+            MultiProduct::GamePass(remote(1, "VIP")),
+            MultiProduct::GamePass(remote(2, "VIP")),
+            MultiProduct::DevProduct(remote(3, "Coins")),
+        ];
+
+        let mut products = cloned(&source, 42);
+        assert_eq!(products.metadata.universe_id, 42);
+        assert_eq!(
+            products.gamepasses.keys().collect::<Vec<_>>(),
+            ["vip", "vip-2"]
+        );
+        assert!(
+            products
+                .gamepasses
+                .values()
+                .chain(products.products.values())
+                .all(|product| product.id.is_none() && product.icon_asset_id.is_none())
+        );
+
+        let target = [MultiProduct::DevProduct(remote(9, "Coins"))];
+        assert_eq!(link_matching(&mut products, &target), 1);
+        assert_eq!(products.products["coins"].id, Some(9));
+    }
+}
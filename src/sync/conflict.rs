@@ -0,0 +1,389 @@
+use clap::Args;
+use log::{info, warn};
+
+use crate::Result;
+use crate::sync::adopt::local_copy;
+use crate::sync::fields::{self, FIELDS, Field};
+use crate::sync::products::{Product, ProductType};
+use crate::ui;
+use crate::ui::merge::MergeViewer;
+use crate::ui::prompt::{self, PromptPolicy};
+use crate::utils::{NameFilter, canonical_name, prompt_line};
+
+/// How `download` settles fields that differ between an entry and its remote
+/// product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Keep the products file's values
+    #[default]
+    PreferLocal,
+    /// Take the universe's values
+    PreferRemote,
+    /// Take whichever side changed, and ask when both did
+    Interactive,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+#[group(multiple = false)]
+pub struct ConflictOptions {
+    /// Keep local names, descriptions, prices and pricing settings (default)
+    #[arg(long)]
+    pub prefer_local: bool,
+    /// Overwrite local fields with the universe's values
+    #[arg(long)]
+    pub prefer_remote: bool,
+    /// Ask which side to keep for every field both sides changed
+    #[arg(long)]
+    pub interactive: bool,
+}
+
+impl ConflictOptions {
+    /// The chosen strategy. `--overwrite` is the older spelling of
+    /// `--prefer-remote`.
+    pub fn strategy(&self, overwrite: bool) -> ConflictStrategy {
+        if self.interactive {
+            ConflictStrategy::Interactive
+        } else if self.prefer_remote || (overwrite && !self.prefer_local) {
+            ConflictStrategy::PreferRemote
+        } else {
+            ConflictStrategy::PreferLocal
+        }
+    }
+}
+
+/// A field that the products file and the universe both changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldConflict {
+    pub field: &'static Field,
+    /// The value both sides started from, if the last download or sync was
+    /// recorded
+    pub base: Option<String>,
+    pub local: String,
+    pub remote: String,
+    pub take_remote: bool,
+}
+
+/// The conflicts of one entry, waiting for the user to pick a side.
+#[derive(Debug, Clone)]
+pub struct PendingMerge {
+    pub product_type: ProductType,
+    pub key: String,
+    pub conflicts: Vec<FieldConflict>,
+    remote: Product,
+}
+
+impl PendingMerge {
+    /// Writes the remote value of every conflict resolved in its favour.
+    pub fn apply(&self, product: &mut Product) {
+        for conflict in self.conflicts.iter().filter(|c| c.take_remote) {
+            if let Some(merge) = &conflict.field.merge {
+                (merge.take)(product, &self.remote);
+            }
+        }
+
+        if product.regional_pricing == Some(false) {
+            product.regional_pricing = None;
+        }
+    }
+}
+
+impl ConflictStrategy {
+    /// Fails early when the strategy needs answers that can't be given.
+    /// `--yes` answers every conflict with the local value instead.
+    pub fn ensure_usable(&self) -> Result<()> {
+        if *self != ConflictStrategy::Interactive {
+            return Ok(());
+        }
+
+        match prompt::policy() {
+            PromptPolicy::Yes => Ok(()),
+            PromptPolicy::NoInput => Err("--interactive can't ask with --no-input set".into()),
+            PromptPolicy::Ask if !ui::can_prompt() => {
+                Err("--interactive needs a terminal to ask in".into())
+            }
+            PromptPolicy::Ask => Ok(()),
+        }
+    }
+
+    /// Whether a differing field takes the remote value, or `None` when the
+    /// user has to decide.
+    fn pick(&self, base: Option<&str>, local: &str, remote: &str) -> Option<bool> {
+        match self {
+            ConflictStrategy::PreferLocal => Some(false),
+            ConflictStrategy::PreferRemote => Some(true),
+            ConflictStrategy::Interactive => match base {
+                Some(base) if base == local => Some(true),
+                Some(base) if base == remote => Some(false),
+                _ => None,
+            },
+        }
+    }
+
+    /// Merges a remote product into its existing entry, or converts it into a
+    /// new entry. The id, sale state and icon always come from the universe,
+    /// and local-only fields like `order` or `image` always stay. Censored
+    /// descriptions should be recovered before merging. Fields the
+    /// strategy can't settle are kept local and returned for the user to pick.
+    pub fn merge(
+        &self,
+        product_type: ProductType,
+        key: &str,
+        existing: Option<&Product>,
+        remote: &Product,
+        base: Option<&Product>,
+        filters: &Option<Vec<NameFilter>>,
+    ) -> (Product, Option<PendingMerge>) {
+        let remote = local_copy(remote, filters);
+        let Some(local) = existing else {
+            return (remote, None);
+        };
+
+        let base = base.map(|base| local_copy(base, filters));
+        let mut merged = Product {
+            id: remote.id,
+            name: canonical_name(local.name.clone(), filters),
+            active: remote.active,
+            discount: local.discount.filter(|_| local.has_discount()),
+            icon_asset_id: remote.icon_asset_id,
+            immutable: false,
+            ..local.clone()
+        };
+        let mut conflicts = vec![];
+
+        // Fields without a merge either always come from the universe or only
+        // exist in the products file.
+        for field in FIELDS {
+            let Some(merge) = &field.merge else {
+                continue;
+            };
+
+            // An unset store page isn't managed locally yet, so there's
+            // nothing to keep.
+            if field == &fields::STORE_PAGE && merged.store_page_enabled.is_none() {
+                merged.store_page_enabled = remote.store_page_enabled;
+                continue;
+            }
+
+            let local_value = (merge.read)(&merged).to_string();
+            let remote_value = (merge.read)(&remote).to_string();
+            if local_value == remote_value {
+                continue;
+            }
+
+            let base_value = base.as_ref().map(|base| (merge.read)(base).to_string());
+            match self.pick(base_value.as_deref(), &local_value, &remote_value) {
+                Some(true) => (merge.take)(&mut merged, &remote),
+                Some(false) => {}
+                None => conflicts.push(FieldConflict {
+                    field,
+                    base: base_value,
+                    local: local_value,
+                    remote: remote_value,
+                    take_remote: false,
+                }),
+            }
+        }
+
+        if merged.regional_pricing == Some(false) {
+            merged.regional_pricing = None;
+        }
+
+        let pending = (!conflicts.is_empty()).then(|| PendingMerge {
+            product_type,
+            key: key.to_string(),
+            conflicts,
+            remote,
+        });
+
+        (merged, pending)
+    }
+}
+
+/// Asks about each conflict on stderr, for when the merge screen can't be
+/// drawn.
+fn resolve_plain(merges: &mut [PendingMerge]) -> Result<()> {
+    for merge in merges {
+        for conflict in &mut merge.conflicts {
+            conflict.take_remote = loop {
+                let answer = prompt_line(&format!(
+                    "{:?} '{}' {}: local {}, remote {}. Keep [l]ocal or [r]emote? [l]",
+                    merge.product_type,
+                    merge.key,
+                    conflict.field.name,
+                    conflict.local,
+                    conflict.remote
+                ))?;
+
+                match answer.trim().to_lowercase().as_str() {
+                    "" | "l" | "local" => break false,
+                    "r" | "remote" => break true,
+                    answer => warn!("'{}' isn't l or r", answer),
+                }
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// Lets the user pick a side for every conflict, in the merge screen when a
+/// terminal UI can be drawn. Returns false if the merge was cancelled.
+pub async fn resolve(merges: &mut [PendingMerge]) -> Result<bool> {
+    if merges.is_empty() {
+        return Ok(true);
+    }
+
+    let conflicts = merges
+        .iter()
+        .map(|merge| merge.conflicts.len())
+        .sum::<usize>();
+    match prompt::policy() {
+        PromptPolicy::Ask => {}
+        PromptPolicy::Yes => {
+            info!(
+                "keeping the local value of {} conflicting field(s) (--yes)",
+                conflicts
+            );
+            return Ok(true);
+        }
+        PromptPolicy::NoInput => {
+            return Err(format!(
+                "{} conflicting field(s) need an answer, but --no-input is set",
+                conflicts
+            )
+            .into());
+        }
+    }
+
+    if !ui::is_interactive() {
+        resolve_plain(merges)?;
+        return Ok(true);
+    }
+
+    let Some(choices) = MergeViewer::merge(merges).await? else {
+        return Ok(false);
+    };
+
+    for (conflict, take_remote) in merges
+        .iter_mut()
+        .flat_map(|merge| merge.conflicts.iter_mut())
+        .zip(choices)
+    {
+        conflict.take_remote = take_remote;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local() -> Product {
+        Product {
+            id: Some(1),
+            name: "VIP".to_string(),
+            prefix: Some("⭐".to_string()),
+            description: Some("Local copy".to_string()),
+            price: 100,
+            order: Some(3),
+            ..Default::default()
+        }
+    }
+
+    fn remote() -> Product {
+        Product {
+            id: Some(1),
+            name: "VIP Room".to_string(),
+            description: Some("Remote copy".to_string()),
+            price: 250,
+            active: true,
+            regional_pricing: Some(true),
+            ..Default::default()
+        }
+    }
+
+    fn merge(
+        strategy: ConflictStrategy,
+        base: Option<&Product>,
+    ) -> (Product, Option<PendingMerge>) {
+        strategy.merge(
+            ProductType::GamePass,
+            "vip",
+            Some(&local()),
+            &remote(),
+            base,
+            &None,
+        )
+    }
+
+    #[test]
+    fn keeps_one_side_of_every_field() {
+        let (kept, pending) = merge(ConflictStrategy::PreferLocal, None);
+        assert!(pending.is_none());
+        assert_eq!(
+            (kept.name.as_str(), kept.price, kept.regional_pricing),
+            ("VIP", 100, None)
+        );
+        assert_eq!((kept.active, kept.order), (true, Some(3)));
+
+        let (replaced, _) = merge(ConflictStrategy::PreferRemote, None);
+        assert_eq!(
+            (replaced.name.as_str(), replaced.price, replaced.prefix),
+            ("VIP Room", 250, None)
+        );
+        assert_eq!(replaced.description.as_deref(), Some("Remote copy"));
+        assert_eq!(replaced.order, Some(3));
+    }
+
+    #[test]
+    fn only_asks_about_fields_both_sides_changed() {
+        // Since the base, the price changed locally, the name and regional
+        // pricing remotely, and the description on both sides.
+        let base = Product {
+            id: Some(1),
+            name: "VIP".to_string(),
+            description: Some("Original".to_string()),
+            price: 250,
+            active: true,
+            ..Default::default()
+        };
+
+        let (merged, pending) = merge(ConflictStrategy::Interactive, Some(&base));
+        assert_eq!(merged.name, "VIP Room");
+        assert_eq!(merged.price, 100);
+        assert_eq!(merged.regional_pricing, Some(true));
+
+        let mut pending = pending.unwrap();
+        assert_eq!(
+            pending
+                .conflicts
+                .iter()
+                .map(|conflict| conflict.field.name)
+                .collect::<Vec<_>>(),
+            vec!["description"]
+        );
+        assert_eq!(pending.conflicts[0].base.as_deref(), Some("Original"));
+
+        let mut resolved = merged.clone();
+        pending.conflicts[0].take_remote = true;
+        pending.apply(&mut resolved);
+        assert_eq!(resolved.description.as_deref(), Some("Remote copy"));
+
+        let (_, pending) = merge(ConflictStrategy::Interactive, None);
+        assert_eq!(pending.unwrap().conflicts.len(), 4);
+    }
+
+    #[test]
+    fn picks_the_strategy_from_flags() {
+        let options = ConflictOptions::default();
+        assert_eq!(options.strategy(false), ConflictStrategy::PreferLocal);
+        assert_eq!(options.strategy(true), ConflictStrategy::PreferRemote);
+
+        let options = ConflictOptions {
+            prefer_local: true,
+            ..Default::default()
+        };
+        assert_eq!(options.strategy(true), ConflictStrategy::PreferLocal);
+    }
+}
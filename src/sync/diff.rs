@@ -0,0 +1,377 @@
+use std::io::IsTerminal;
+
+use clap::Args;
+use crossterm::style::Stylize;
+
+use crate::sync::products::ProductType;
+use crate::sync::report::{RevenueModel, format_usd};
+use crate::ui::diffs::{DiffChange, ProductDiff};
+use crate::ui::theme;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Only print the keys of products that differ
+    #[arg(long, conflicts_with = "stat")]
+    pub name_only: bool,
+    /// Print a summary of changed fields per product
+    #[arg(long)]
+    pub stat: bool,
+    /// Annotate prices with approximate USD amounts, using `revenue-model`
+    #[arg(long)]
+    pub usd: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+/// The text diff of one product, remote on the left and local on the right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    /// Table of the products file the entry is under, e.g. `gamepasses`
+    pub section: &'static str,
+    pub key: String,
+    /// `None` for products that would be created
+    pub id: Option<u64>,
+    pub lines: Vec<DiffLine>,
+    /// Differs, but is left alone because it's locked
+    pub locked: bool,
+}
+
+fn field_line(diff: &ProductDiff, value: String) -> String {
+    format!("{} = {}", diff.field().replace(' ', "-"), value)
+}
+
+fn section(product_type: ProductType) -> &'static str {
+    match product_type {
+        ProductType::GamePass => "gamepasses",
+        ProductType::DevProduct => "products",
+    }
+}
+
+impl DiffEntry {
+    pub fn new(
+        product_type: ProductType,
+        key: String,
+        id: Option<u64>,
+        changes: &[DiffChange],
+    ) -> Self {
+        let mut lines = vec![];
+
+        for change in changes {
+            match change {
+                DiffChange::Unchanged(diff) if id.is_some() => {
+                    lines.push(DiffLine::Context(field_line(diff, diff.values().1)));
+                }
+                DiffChange::Changed(diff) if id.is_some() => {
+                    let (old, new) = diff.values();
+                    lines.push(DiffLine::Removed(field_line(diff, old)));
+                    lines.push(DiffLine::Added(field_line(diff, new)));
+                }
+                DiffChange::Unchanged(diff)
+                | DiffChange::Changed(diff)
+                | DiffChange::Created(diff) => {
+                    lines.push(DiffLine::Added(field_line(diff, diff.values().1)));
+                }
+            }
+        }
+
+        Self {
+            section: section(product_type),
+            key,
+            id,
+            lines,
+            locked: false,
+        }
+    }
+
+    /// An entry outside the product sections, from `(field, remote, local)`
+    /// values that differ.
+    pub fn fields(
+        section: &'static str,
+        key: String,
+        id: u64,
+        fields: &[(&str, String, String)],
+    ) -> Self {
+        let lines = fields
+            .iter()
+            .flat_map(|(field, old, new)| {
+                [
+                    DiffLine::Removed(format!("{} = {}", field, old)),
+                    DiffLine::Added(format!("{} = {}", field, new)),
+                ]
+            })
+            .collect();
+
+        Self {
+            section,
+            key,
+            id: Some(id),
+            lines,
+            locked: false,
+        }
+    }
+
+    pub fn locked(product_type: ProductType, key: String, id: Option<u64>) -> Self {
+        Self {
+            section: section(product_type),
+            key,
+            id,
+            lines: vec![],
+            locked: true,
+        }
+    }
+
+    /// Follows every price line with what players pay and what the developer
+    /// cashes out, e.g. `price = 400  # ~$5.00 paid, ~$1.06 payout`.
+    pub fn annotate_prices(&mut self, model: &RevenueModel) {
+        for line in &mut self.lines {
+            let (DiffLine::Context(text) | DiffLine::Removed(text) | DiffLine::Added(text)) = line;
+
+            if let Some(price) = text
+                .strip_prefix("price = ")
+                .and_then(|price| price.parse::<u64>().ok())
+            {
+                text.push_str(&format!(
+                    "  # ~{} paid, ~{} payout",
+                    format_usd(model.consumer_usd(price)),
+                    format_usd(model.payout_usd(price))
+                ));
+            }
+        }
+    }
+
+    /// The key as it appears in the products file, e.g. `gamepasses.vip`.
+    pub fn path(&self) -> String {
+        format!("{}.{}", self.section, self.key)
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        let added = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Added(_)))
+            .count();
+        let removed = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Removed(_)))
+            .count();
+
+        (added, removed)
+    }
+}
+
+fn paint(text: String, color: bool, style: fn(String) -> String) -> String {
+    if color { style(text) } else { text }
+}
+
+fn unified(entries: &[DiffEntry], color: bool) -> String {
+    let mut out = String::new();
+
+    for entry in entries {
+        let path = entry.path();
+        if entry.locked {
+            out.push_str(&paint(format!("@@ locked {} @@\n", path), color, |s| {
+                s.yellow().to_string()
+            }));
+            continue;
+        }
+
+        let (old, header) = match entry.id {
+            Some(id) => (format!("a/{} (id {})", path, id), format!("@@ {} @@", path)),
+            None => ("/dev/null".to_string(), format!("@@ new {} @@", path)),
+        };
+
+        let bold = |s: String| s.bold().to_string();
+        out.push_str(&paint(format!("--- {}\n", old), color, bold));
+        out.push_str(&paint(format!("+++ b/{}\n", path), color, bold));
+        out.push_str(&paint(format!("{}\n", header), color, |s| {
+            s.cyan().to_string()
+        }));
+
+        for line in &entry.lines {
+            let line = match line {
+                DiffLine::Context(text) => format!(" {}\n", text),
+                DiffLine::Removed(text) => {
+                    paint(format!("-{}\n", text), color, |s| s.red().to_string())
+                }
+                DiffLine::Added(text) => {
+                    paint(format!("+{}\n", text), color, |s| s.green().to_string())
+                }
+            };
+            out.push_str(&line);
+        }
+    }
+
+    out
+}
+
+fn stat(entries: &[DiffEntry], color: bool) -> String {
+    let width = entries
+        .iter()
+        .map(|entry| entry.path().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    let (mut total_added, mut total_removed) = (0, 0);
+
+    for entry in entries {
+        if entry.locked {
+            out.push_str(&format!(
+                " {:width$} | {}\n",
+                entry.path(),
+                paint("locked".to_string(), color, |s| s.yellow().to_string()),
+                width = width
+            ));
+            continue;
+        }
+
+        let (added, removed) = entry.counts();
+        total_added += added;
+        total_removed += removed;
+
+        out.push_str(&format!(
+            " {:width$} | {:>2} {}{}\n",
+            entry.path(),
+            added + removed,
+            paint("+".repeat(added), color, |s| s.green().to_string()),
+            paint("-".repeat(removed), color, |s| s.red().to_string()),
+            width = width
+        ));
+    }
+
+    out.push_str(&format!(
+        " {} product(s) changed, {} insertion(s)(+), {} deletion(s)(-)\n",
+        entries.iter().filter(|entry| !entry.locked).count(),
+        total_added,
+        total_removed
+    ));
+
+    out
+}
+
+pub fn render(entries: &[DiffEntry], options: &DiffOptions, color: bool) -> String {
+    if options.name_only {
+        entries
+            .iter()
+            .map(|entry| match entry.locked {
+                true => format!("{} (locked)\n", entry.path()),
+                false => format!("{}\n", entry.path()),
+            })
+            .collect()
+    } else if options.stat {
+        stat(entries, color)
+    } else {
+        unified(entries, color)
+    }
+}
+
+/// Colors are only used when printing to a terminal.
+pub fn print(entries: &[DiffEntry], options: &DiffOptions) {
+    let color = std::io::stdout().is_terminal() && theme::color_enabled();
+    print!("{}", render(entries, options, color));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::fields;
+
+    fn entries() -> Vec<DiffEntry> {
+        vec![
+            DiffEntry::new(
+                ProductType::GamePass,
+                "vip".to_string(),
+                Some(7),
+                &[
+                    DiffChange::Unchanged(ProductDiff::new(&fields::TITLE, "VIP", "VIP")),
+                    DiffChange::Changed(ProductDiff::new(&fields::PRICE, 100, 80)),
+                ],
+            ),
+            DiffEntry::new(
+                ProductType::DevProduct,
+                "coins".to_string(),
+                None,
+                &[DiffChange::Unchanged(ProductDiff::new(
+                    &fields::PRICE,
+                    0,
+                    25,
+                ))],
+            ),
+            DiffEntry::locked(ProductType::DevProduct, "gems".to_string(), Some(9)),
+            DiffEntry::fields(
+                "catalog",
+                "shirt".to_string(),
+                55,
+                &[("price", "5".to_string(), "10".to_string())],
+            ),
+        ]
+    }
+
+    #[test]
+    fn renders_unified_diff() {
+        assert_eq!(
+            render(&entries(), &DiffOptions::default(), false),
+            "--- a/gamepasses.vip (id 7)\n\
+             +++ b/gamepasses.vip\n\
+             @@ gamepasses.vip @@\n \
+             name = \"VIP\"\n\
+             -price = 100\n\
+             +price = 80\n\
+             --- /dev/null\n\
+             +++ b/products.coins\n\
+             @@ new products.coins @@\n\
+             +price = 25\n\
+             @@ locked products.gems @@\n\
+             --- a/catalog.shirt (id 55)\n\
+             +++ b/catalog.shirt\n\
+             @@ catalog.shirt @@\n\
+             -price = 5\n\
+             +price = 10\n"
+        );
+    }
+
+    #[test]
+    fn annotates_prices_with_usd() {
+        let mut entry = entries().remove(0);
+        entry.annotate_prices(&RevenueModel::default());
+
+        assert_eq!(
+            entry.lines[1..],
+            [
+                DiffLine::Removed("price = 100  # ~$1.25 paid, ~$0.27 payout".to_string()),
+                DiffLine::Added("price = 80  # ~$1.00 paid, ~$0.21 payout".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_name_only_and_stat() {
+        let name_only = DiffOptions {
+            name_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            render(&entries(), &name_only, false),
+            "gamepasses.vip\nproducts.coins\nproducts.gems (locked)\ncatalog.shirt\n"
+        );
+
+        let stat_options = DiffOptions {
+            stat: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            render(&entries(), &stat_options, false),
+            " gamepasses.vip |  2 +-\n \
+             products.coins |  1 +\n \
+             products.gems  | locked\n \
+             catalog.shirt  |  2 +-\n \
+             3 product(s) changed, 3 insertion(s)(+), 2 deletion(s)(-)\n"
+        );
+    }
+}
@@ -1,9 +1,121 @@
-use log::info;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use log::{info, warn};
 
 use crate::Result;
 use crate::api::products::fetch_all_products;
+use crate::sync::base::RemoteBase;
+use crate::sync::censor::{recover_description, report_censored};
+use crate::sync::conflict::{self, ConflictStrategy};
+use crate::sync::duplicates::resolve_key_collision;
+use crate::sync::list::ListOutput;
 use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts};
-use crate::utils::{canonical_name, format_name, is_censored};
+use crate::sync::rename::move_key;
+use crate::ui;
+use crate::ui::confirm::{ConfirmState, ConfirmViewer};
+use crate::utils::canonical_name;
+
+#[derive(Subcommand, Debug)]
+pub enum DownloadCommand {
+    /// Writes a report of remote prices and regional pricing state
+    Prices {
+        /// Only include products with regional pricing enabled locally or remotely
+        #[arg(long, default_value_t = false)]
+        regional_only: bool,
+        #[arg(long, value_enum, default_value_t = ListOutput::Table)]
+        format: ListOutput,
+        /// Writes the report to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Inserts a couple of example products without ids, for universes that don't
+/// have any yet. `sync` creates them once they've been edited.
+fn scaffold_examples(local_products: &mut VCSProducts) {
+    local_products.gamepasses.insert(
+        "vip".to_string(),
+        Product {
+            name: "VIP".to_string(),
+            description: Some("Access to VIP perks".to_string()),
+            price: 250,
+            active: true,
+            ..Default::default()
+        },
+    );
+    local_products.products.insert(
+        "coins-100".to_string(),
+        Product {
+            name: "100 Coins".to_string(),
+            description: Some("A small pouch of coins".to_string()),
+            price: 25,
+            active: true,
+            ..Default::default()
+        },
+    );
+}
+
+/// Lists the products a download would add to or change in the products file,
+/// with the fields that differ.
+fn describe_changes(before: &VCSProducts, after: &VCSProducts) -> Vec<String> {
+    let mut changes = vec![];
+
+    for (product_type, old, new) in [
+        (ProductType::GamePass, &before.gamepasses, &after.gamepasses),
+        (ProductType::DevProduct, &before.products, &after.products),
+    ] {
+        for (key, product) in new {
+            let Some(existing) = old.get(key) else {
+                changes.push(format!("add {:?} '{}'", product_type, key));
+                continue;
+            };
+
+            let (serde_json::Value::Object(existing), serde_json::Value::Object(product)) = (
+                serde_json::to_value(existing).unwrap_or_default(),
+                serde_json::to_value(product).unwrap_or_default(),
+            ) else {
+                continue;
+            };
+
+            let fields = product
+                .iter()
+                .filter(|(field, value)| existing.get(*field) != Some(value))
+                .map(|(field, _)| field.as_str())
+                .collect::<Vec<_>>();
+
+            if !fields.is_empty() {
+                changes.push(format!(
+                    "update {:?} '{}': {}",
+                    product_type,
+                    key,
+                    fields.join(", ")
+                ));
+            }
+        }
+    }
+
+    changes
+}
+
+/// The key an entry matched by id should move to, now that its name generates
+/// a different one. Only keys still generated from the `previous` name move,
+/// so keys the user picked or renamed the entry away from stay put.
+fn renamed_key(
+    entries: &BTreeMap<String, Product>,
+    key: &str,
+    product: &Product,
+    previous: &str,
+    generated: &str,
+) -> Option<String> {
+    (key == previous
+        && key != generated
+        && !generated.is_empty()
+        && !product.has_alias(generated)
+        && !entries.contains_key(generated))
+    .then(|| generated.to_string())
+}
 
 pub struct Downloader {
     local_products: VCSProducts,
@@ -31,110 +143,230 @@ impl Downloader {
         })
     }
 
-    pub async fn download(overwrite: bool) -> Result<()> {
+    pub async fn download(
+        strategy: ConflictStrategy,
+        rename_keys: bool,
+        dry_run: bool,
+    ) -> Result<()> {
+        // A dry run only counts conflicts, so it never has to ask.
+        if !dry_run {
+            strategy.ensure_usable()?;
+        }
         let downloader = Downloader::create().await?;
 
         let mut local_products_data = downloader.local_products;
         let remote_product_data = downloader.remote_products;
+        let original = local_products_data.clone();
 
-        let filters = &local_products_data.metadata.name_filters;
+        if remote_product_data.is_empty() {
+            warn!("0 remote products found, add products locally and run sync");
+
+            let local_empty = local_products_data.gamepasses.is_empty()
+                && local_products_data.products.is_empty();
+
+            if local_empty
+                && !dry_run
+                && ui::can_prompt()
+                && ConfirmViewer::show_prompt("Add example products to the products file?").await?
+                    == ConfirmState::Confirmed
+            {
+                scaffold_examples(&mut local_products_data);
+                local_products_data.save_products().await?;
+                local_products_data.serialize_luau().await?;
+
+                info!("added example products, edit them and run sync to create them");
+            }
+
+            return Ok(());
+        }
+
+        let filters = &local_products_data.metadata.filters();
 
         info!(
-            "merging local products, and remote products (overwrite: {})",
-            overwrite
+            "merging local products, and remote products (strategy: {:?})",
+            strategy
         );
 
-        remote_product_data.iter().for_each(|multi_product| {
+        let universe_id = local_products_data.metadata.universe_id;
+        let base = RemoteBase::load(universe_id).await;
+        let mut pending_merges = vec![];
+        let mut censored = vec![];
+        let mut renames = vec![];
+        let threshold = local_products_data.metadata.censor_threshold();
+
+        for multi_product in &remote_product_data {
             let (product, product_type): (Product, ProductType) = match multi_product {
                 MultiProduct::GamePass(prod) => (prod.clone(), ProductType::GamePass),
                 MultiProduct::DevProduct(prod) => (prod.clone(), ProductType::DevProduct),
             };
 
-            let name = format_name(canonical_name(product.name.clone(), &filters));
+            let name = local_products_data
+                .metadata
+                .key_for(&canonical_name(product.name.clone(), filters));
 
-            let existing = match product_type {
-                ProductType::GamePass => local_products_data.gamepasses.iter().find(|(_, x)| {
-                    x.id.map(|id| id as i64).unwrap_or(-1)
-                        == product.id.map(|id| id as i64).unwrap_or(-1)
-                }),
+            let local_entries = match product_type {
+                ProductType::GamePass => &local_products_data.gamepasses,
+                ProductType::DevProduct => &local_products_data.products,
+            };
 
-                ProductType::DevProduct => local_products_data.products.iter().find(|(_, x)| {
+            // Match on id first, then fall back to entries that were renamed
+            // away from this key before they had an id.
+            let existing = local_entries
+                .iter()
+                .find(|(_, x)| {
                     x.id.map(|id| id as i64).unwrap_or(-1)
                         == product.id.map(|id| id as i64).unwrap_or(-1)
-                }),
-            };
+                })
+                .or_else(|| {
+                    local_entries
+                        .iter()
+                        .find(|(_, x)| x.id.is_none() && x.has_alias(&name))
+                });
 
-            let mut product = Product {
-                id: product.id,
-                name: if !overwrite && existing.is_some() {
-                    canonical_name(existing.unwrap().1.name.clone(), &filters)
-                } else {
-                    canonical_name(product.name.clone(), &filters)
-                },
-                prefix: if !overwrite && let Some(existing_product) = existing {
-                    existing_product.1.prefix.clone()
-                } else {
-                    None
-                },
-                description: if !overwrite && let Some(existing_product) = existing {
-                    existing_product.1.description.clone()
-                } else {
-                    product.description
-                },
-                active: product.active,
-                discount: match existing {
-                    Some((_, existing_product)) if existing_product.has_discount() => {
-                        existing_product.discount
-                    }
-                    _ => None,
-                },
-                price: if let Some(existing_product) = existing {
-                    if overwrite {
-                        product.price
-                    } else {
-                        existing_product.1.price
-                    }
-                } else {
-                    product.price
-                },
-                regional_pricing: if let Some(existing_product) = existing {
-                    if overwrite {
-                        product.regional_pricing
-                    } else {
-                        existing_product.1.regional_pricing
-                    }
-                } else {
-                    product.regional_pricing
-                },
-            };
+            if let Some((key, _)) = existing.filter(|(_, existing)| existing.is_locked()) {
+                info!("{:?} '{}' is locked, keeping it as is", product_type, key);
+                continue;
+            }
+
+            let existing_key = existing.map(|(key, _)| key.as_str()).unwrap_or(&name);
+            let existing_product = existing.map(|(_, existing_product)| existing_product);
+            let base_product = product
+                .id
+                .and_then(|id| base.as_ref()?.get(product_type, id));
+
+            // The name the key was generated from: the remote name at the last
+            // download or sync, or the entry's own name before this merge.
+            let previous_key = existing_product.map(|existing_product| {
+                let previous = base_product.map_or(&existing_product.name, |base| &base.name);
+                local_products_data
+                    .metadata
+                    .key_for(&canonical_name(previous.clone(), filters))
+            });
+
+            let mut product = product;
+            censored.extend(recover_description(
+                &mut product,
+                product_type,
+                existing_key,
+                existing_product,
+                base_product,
+                threshold,
+            ));
 
-            if let Some(regional_pricing) = product.regional_pricing
-                && !regional_pricing
+            let (product, pending) = strategy.merge(
+                product_type,
+                existing_key,
+                existing_product,
+                &product,
+                base_product,
+                filters,
+            );
+            pending_merges.extend(pending);
+
+            // The merged name, so a kept local name keeps its key.
+            let generated = local_products_data.metadata.key_for(&product.name);
+            if let Some((key, existing_product)) = existing
+                && existing_product.id.is_some()
+                && let Some(previous_key) = &previous_key
+                && let Some(new_key) = renamed_key(
+                    local_entries,
+                    key,
+                    existing_product,
+                    previous_key,
+                    &generated,
+                )
             {
-                product.regional_pricing = None;
+                renames.push((product_type, key.clone(), new_key));
             }
 
-            if !overwrite && let Some(existing_product) = existing {
-                if let Some(desc) = product.description.clone()
-                    && is_censored(&desc)
+            // A key held by another remote product means two names canonicalize
+            // the same way, and inserting would silently drop one of them.
+            let key = match existing {
+                Some((key, _)) => key.clone(),
+                None if local_entries
+                    .get(&name)
+                    .is_some_and(|taken| taken.id.is_some() || taken.is_locked()) =>
                 {
-                    product.description = existing_product.1.description.clone();
+                    resolve_key_collision(local_entries, product_type, &name, &product)?
                 }
-            }
-
-            let key = match existing.is_none() {
-                true => name.clone(),
-                false => existing.unwrap().0.clone(),
+                None => name.clone(),
             };
 
             match product_type {
                 ProductType::GamePass => local_products_data.gamepasses.insert(key, product),
                 ProductType::DevProduct => local_products_data.products.insert(key, product),
             };
-        });
+        }
+
+        report_censored(&censored);
+
+        if !rename_keys && !renames.is_empty() {
+            info!(
+                "{} key(s) no longer match their product's name, download with --rename-keys to rename them",
+                renames.len()
+            );
+        }
+
+        let conflicts = pending_merges
+            .iter()
+            .map(|merge| merge.conflicts.len())
+            .sum::<usize>();
+
+        if dry_run {
+            if conflicts > 0 {
+                info!(
+                    "would ask about {} field(s) changed both locally and remotely",
+                    conflicts
+                );
+            }
+
+            if rename_keys {
+                for (product_type, key, new_key) in &renames {
+                    info!("would rename {:?} '{}' to '{}'", product_type, key, new_key);
+                }
+            }
+
+            let changes = describe_changes(&original, &local_products_data);
+            for change in &changes {
+                info!("would {}", change);
+            }
+            if changes.is_empty() {
+                info!("products file is up to date with the universe");
+            }
+
+            local_products_data.preview_generated().await?;
+            info!("dry run, nothing was changed");
+            return Ok(());
+        }
+
+        if !conflict::resolve(&mut pending_merges).await? {
+            info!("merge cancelled, the products file was left as is");
+            return Ok(());
+        }
+
+        for merge in &pending_merges {
+            if let Some(product) = local_products_data
+                .products_of_mut(merge.product_type)
+                .get_mut(&merge.key)
+            {
+                merge.apply(product);
+            }
+        }
+
+        if rename_keys {
+            for (product_type, key, new_key) in &renames {
+                match move_key(&mut local_products_data, *product_type, key, new_key) {
+                    Ok(()) => info!("renamed {:?} '{}' to '{}'", product_type, key, new_key),
+                    Err(e) => warn!("failed to rename {:?} '{}': {}", product_type, key, e),
+                }
+            }
+        }
 
         info!("finished merging products, saving to disk");
         local_products_data.save_products().await?;
+        RemoteBase::from_remote(universe_id, &remote_product_data)
+            .save(threshold)
+            .await;
 
         info!("serializing products to luau format");
         local_products_data.serialize_luau().await?;
@@ -142,3 +374,87 @@ impl Downloader {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_keys_that_no_longer_match() {
+        let product = Product {
+            id: Some(1),
+            aliases: Some(vec!["vip-room".to_string()]),
+            ..Default::default()
+        };
+        let mut entries = BTreeMap::from([("vip".to_string(), product.clone())]);
+
+        assert_eq!(
+            renamed_key(&entries, "vip", &product, "vip", "vip-pass"),
+            Some("vip-pass".to_string())
+        );
+        assert_eq!(renamed_key(&entries, "vip", &product, "vip", "vip"), None);
+        // Renamed away from on purpose.
+        assert_eq!(
+            renamed_key(&entries, "vip", &product, "vip", "vip-room"),
+            None
+        );
+        // A key the user picked instead of the generated one.
+        assert_eq!(
+            renamed_key(&entries, "vip", &product, "vip-lounge", "vip-pass"),
+            None
+        );
+
+        entries.insert("vip-pass".to_string(), Product::default());
+        assert_eq!(
+            renamed_key(&entries, "vip", &product, "vip", "vip-pass"),
+            None
+        );
+    }
+
+    #[test]
+    fn describes_added_and_changed_products() {
+        let mut before = VCSProducts::default();
+        before.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(1),
+                price: 100,
+                ..Default::default()
+            },
+        );
+
+        let mut after = before.clone();
+        after.gamepasses.get_mut("vip").unwrap().price = 80;
+        after
+            .products
+            .insert("coins".to_string(), Product::default());
+
+        assert_eq!(
+            describe_changes(&before, &after),
+            vec![
+                "update GamePass 'vip': price".to_string(),
+                "add DevProduct 'coins'".to_string()
+            ]
+        );
+        assert!(describe_changes(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn scaffolds_untracked_examples() {
+        let mut local_products = VCSProducts::default();
+        scaffold_examples(&mut local_products);
+
+        let examples = local_products
+            .gamepasses
+            .values()
+            .chain(local_products.products.values())
+            .collect::<Vec<_>>();
+
+        assert_eq!(examples.len(), 2);
+        assert!(
+            examples
+                .iter()
+                .all(|product| product.id.is_none() && product.active)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use log::{info, warn};
+
+use crate::Result;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::ui;
+use crate::ui::prompt::{self, PromptPolicy};
+use crate::utils::prompt_line;
+
+/// Local entries of one product type that point at the same remote id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateId {
+    pub product_type: ProductType,
+    pub id: u64,
+    pub keys: Vec<String>,
+}
+
+pub fn find_duplicate_ids(local_products: &VCSProducts) -> Vec<DuplicateId> {
+    let mut duplicates = vec![];
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        let mut by_id: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+
+        for (key, product) in local_products.products_of(product_type) {
+            if let Some(id) = product.id {
+                by_id.entry(id).or_default().push(key.clone());
+            }
+        }
+
+        duplicates.extend(by_id.into_iter().filter(|(_, keys)| keys.len() > 1).map(
+            |(id, keys)| DuplicateId {
+                product_type,
+                id,
+                keys,
+            },
+        ));
+    }
+
+    duplicates
+}
+
+/// `base`, or `base` with the lowest number from 2 up appended that isn't a
+/// key yet.
+pub fn free_key(entries: &BTreeMap<String, Product>, base: &str) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => base.to_string(),
+            n => format!("{}-{}", base, n),
+        })
+        .find(|key| !entries.contains_key(key))
+        .expect("there's always a free key")
+}
+
+/// Keeps `keep` and removes the other entries of the group, recording their
+/// keys as aliases so downloads don't bring them back.
+fn merge(local_products: &mut VCSProducts, duplicate: &DuplicateId, keep: &str) {
+    let product_type = duplicate.product_type;
+    let mut merged = vec![];
+
+    for key in duplicate.keys.iter().filter(|key| *key != keep) {
+        let entries = local_products.products_of_mut(product_type);
+        if let Some(removed) = entries.remove(key) {
+            merged.push(key.clone());
+            merged.extend(removed.aliases.unwrap_or_default());
+        }
+        local_products.sources.remove(&(product_type, key.clone()));
+    }
+
+    if let Some(kept) = local_products.products_of_mut(product_type).get_mut(keep) {
+        let aliases = kept.aliases.get_or_insert_with(Vec::new);
+        for key in merged {
+            if key != keep && !aliases.contains(&key) {
+                aliases.push(key);
+            }
+        }
+    }
+}
+
+/// Picks a key for a downloaded product whose canonical key already belongs to
+/// another remote product, asking when possible and numbering it otherwise.
+pub fn resolve_key_collision(
+    entries: &BTreeMap<String, Product>,
+    product_type: ProductType,
+    key: &str,
+    remote: &Product,
+) -> Result<String> {
+    let suggested = free_key(entries, key);
+    let owner = entries.get(key).and_then(|product| product.id).unwrap_or(0);
+
+    if !ui::can_prompt() || prompt::policy() != PromptPolicy::Ask {
+        warn!(
+            "{:?} '{}' (id: {}) would use key '{}', which belongs to id {}, saving it as '{}'",
+            product_type,
+            remote.name,
+            remote.id.unwrap_or(0),
+            key,
+            owner,
+            suggested
+        );
+        return Ok(suggested);
+    }
+
+    loop {
+        let answer = prompt_line(&format!(
+            "{:?} '{}' (id: {}) would use key '{}', which belongs to id {}. Key [{}]",
+            product_type,
+            remote.name,
+            remote.id.unwrap_or(0),
+            key,
+            owner,
+            suggested
+        ))?;
+
+        match answer.as_str() {
+            "" => return Ok(suggested),
+            answer if entries.contains_key(answer) => {
+                warn!("'{}' is taken too", answer);
+            }
+            answer => return Ok(answer.to_string()),
+        }
+    }
+}
+
+/// Lists local entries that share a remote id and merges each group into the
+/// entry the user picks.
+pub async fn resolve_duplicates() -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+    let duplicates = find_duplicate_ids(&local_products);
+
+    if duplicates.is_empty() {
+        info!("no products share an id");
+        return Ok(());
+    }
+
+    for duplicate in &duplicates {
+        warn!(
+            "{:?} id {} is used by {}",
+            duplicate.product_type,
+            duplicate.id,
+            duplicate.keys.join(", ")
+        );
+    }
+
+    if !ui::can_prompt() || !prompt::allows_input() {
+        return Err(format!(
+            "{} id(s) are used more than once, run `duplicates` in a terminal to merge them",
+            duplicates.len()
+        )
+        .into());
+    }
+
+    for duplicate in &duplicates {
+        let keep = loop {
+            let answer = prompt_line(&format!(
+                "Keep which {:?} for id {} ({}), empty to skip",
+                duplicate.product_type,
+                duplicate.id,
+                duplicate.keys.join("/")
+            ))?;
+
+            if answer.is_empty() || duplicate.keys.contains(&answer) {
+                break answer;
+            }
+            warn!("'{}' isn't one of them", answer);
+        };
+
+        if keep.is_empty() {
+            continue;
+        }
+
+        merge(&mut local_products, duplicate, &keep);
+        info!(
+            "merged {} into {:?} '{}'",
+            duplicate
+                .keys
+                .iter()
+                .filter(|key| **key != keep)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", "),
+            duplicate.product_type,
+            keep
+        );
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: u64) -> Product {
+        Product {
+            id: Some(id),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merges_entries_sharing_an_id() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("vip".to_string(), product(1));
+        local_products.gamepasses.insert(
+            "vip-old".to_string(),
+            Product {
+                aliases: Some(vec!["legacy-vip".to_string()]),
+                ..product(1)
+            },
+        );
+        local_products
+            .gamepasses
+            .insert("admin".to_string(), product(2));
+        local_products
+            .products
+            .insert("coins".to_string(), product(1));
+
+        let duplicates = find_duplicate_ids(&local_products);
+        assert_eq!(
+            duplicates,
+            vec![DuplicateId {
+                product_type: ProductType::GamePass,
+                id: 1,
+                keys: vec!["vip".to_string(), "vip-old".to_string()],
+            }]
+        );
+
+        merge(&mut local_products, &duplicates[0], "vip");
+        assert!(!local_products.gamepasses.contains_key("vip-old"));
+        assert_eq!(
+            local_products.gamepasses["vip"].aliases,
+            Some(vec!["vip-old".to_string(), "legacy-vip".to_string()])
+        );
+        assert!(find_duplicate_ids(&local_products).is_empty());
+    }
+
+    #[test]
+    fn numbers_taken_keys() {
+        let mut entries = BTreeMap::new();
+        entries.insert("vip".to_string(), product(1));
+        entries.insert("vip-2".to_string(), product(2));
+
+        assert_eq!(free_key(&entries, "vip"), "vip-3");
+        assert_eq!(free_key(&entries, "admin"), "admin");
+    }
+}
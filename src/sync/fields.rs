@@ -0,0 +1,382 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use dyn_fmt::AsStrFormatExt;
+
+use crate::sync::products::{Metadata, Product, image_hash};
+
+/// The value of a synced field on one side of a diff.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FieldValue {
+    Text(String),
+    Number(u64),
+    Flag(bool),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(text) => f.write_str(text),
+            FieldValue::Number(number) => write!(f, "{}", number),
+            FieldValue::Flag(flag) => write!(f, "{}", flag),
+        }
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(text: String) -> Self {
+        FieldValue::Text(text)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        FieldValue::Text(text.to_string())
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(number: u64) -> Self {
+        FieldValue::Number(number)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(flag: bool) -> Self {
+        FieldValue::Flag(flag)
+    }
+}
+
+/// The remote and local values of a field, or `None` when the field isn't
+/// synced for the product.
+type Accessor = fn(&Product, &Product, Option<&Metadata>) -> Option<(FieldValue, FieldValue)>;
+
+/// A field `sync` compares between the products file and the universe.
+/// Everything that diffs products, from the viewer to `diff` and the
+/// notifications, works from [`FIELDS`], so a field added there shows up
+/// everywhere.
+pub struct Field {
+    /// Name in logs, `diff` output and notifications
+    pub name: &'static str,
+    /// Label in the diff viewer
+    pub label: &'static str,
+    /// Free text, which the viewer diffs word by word instead of formatting
+    pub text: bool,
+    /// Reads the values from the local product and the remote `other`
+    pub values: Accessor,
+    /// How values are shown in logs and outside the viewer's word diffs
+    pub format: fn(&FieldValue) -> String,
+    /// Whether the remote value already matches the local one
+    pub equal: fn(&FieldValue, &FieldValue) -> bool,
+    /// How `download` merges the field into an existing entry, or `None` when
+    /// it always comes from the universe or only exists in the products file
+    pub merge: Option<Merge>,
+}
+
+/// Reads and replaces a field's value as it's stored in an entry, for
+/// settling conflicts between the products file and the universe.
+pub struct Merge {
+    /// The stored value, without sale prefixes or expanded variables
+    pub read: fn(&Product) -> FieldValue,
+    /// Replaces the entry's value with the remote product's
+    pub take: fn(&mut Product, &Product),
+}
+
+impl fmt::Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+// Fields are identified by name, which is unique in the registry.
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Field {}
+
+impl PartialOrd for Field {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Field {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(other.name)
+    }
+}
+
+impl Field {
+    /// The remote and local values, if the field is synced for `local`.
+    pub fn compare(
+        &self,
+        local: &Product,
+        other: &Product,
+        metadata: Option<&Metadata>,
+    ) -> Option<(FieldValue, FieldValue, bool)> {
+        let (old, new) = (self.values)(local, other, metadata)?;
+        let equal = (self.equal)(&old, &new);
+        Some((old, new, equal))
+    }
+
+    /// A value as the diff viewer shows it. Text is left unquoted.
+    pub fn display(&self, value: &FieldValue) -> String {
+        match self.text {
+            true => value.to_string(),
+            false => (self.format)(value),
+        }
+    }
+}
+
+fn same(old: &FieldValue, new: &FieldValue) -> bool {
+    old == new
+}
+
+fn plain(value: &FieldValue) -> String {
+    value.to_string()
+}
+
+fn quoted(value: &FieldValue) -> String {
+    format!("{:?}", value.to_string())
+}
+
+/// Image hashes are shortened like git commits, with `none` for a missing one.
+fn short_hash(value: &FieldValue) -> String {
+    match value.to_string().as_str() {
+        "" => "none".to_string(),
+        hash => hash.chars().take(12).collect(),
+    }
+}
+
+/// The title as it's uploaded. Plugin titles already include any sale prefix.
+fn title(local: &Product, metadata: Option<&Metadata>) -> String {
+    if let Some(metadata) = metadata
+        && local.plugin_title.is_none()
+        && let (Some(discount), Some(prefix)) = (local.discount, &metadata.discount_prefix)
+    {
+        return format!("{} {}", prefix.format(&[discount]), local.get_title());
+    }
+
+    local.get_title()
+}
+
+pub static TITLE: Field = Field {
+    name: "name",
+    label: "Title",
+    text: true,
+    values: |local, other, metadata| {
+        Some((other.name.clone().into(), title(local, metadata).into()))
+    },
+    format: quoted,
+    equal: same,
+    merge: Some(Merge {
+        read: |product| product.name.clone().into(),
+        take: |product, remote| {
+            product.name = remote.name.clone();
+            product.prefix = None;
+        },
+    }),
+};
+
+pub static DESCRIPTION: Field = Field {
+    name: "description",
+    label: "Description",
+    text: true,
+    values: |local, other, metadata| {
+        Some((
+            other.description.clone().unwrap_or_default().into(),
+            local.get_description(metadata).unwrap_or_default().into(),
+        ))
+    },
+    format: quoted,
+    equal: same,
+    merge: Some(Merge {
+        read: |product| product.description.clone().unwrap_or_default().into(),
+        take: |product, remote| product.description = remote.description.clone(),
+    }),
+};
+
+pub static PRICE: Field = Field {
+    name: "price",
+    label: "Price",
+    text: false,
+    values: |local, other, metadata| {
+        Some((
+            (other.price as u64).into(),
+            local.get_price(metadata).into(),
+        ))
+    },
+    format: plain,
+    equal: same,
+    merge: Some(Merge {
+        read: |product| (product.price as u64).into(),
+        take: |product, remote| product.price = remote.price,
+    }),
+};
+
+pub static REGIONAL_PRICING: Field = Field {
+    name: "regional pricing",
+    label: "Regional Pricing",
+    text: false,
+    values: |local, other, _| {
+        Some((
+            other.regional_pricing.unwrap_or(false).into(),
+            local.regional_pricing.unwrap_or(false).into(),
+        ))
+    },
+    format: plain,
+    equal: same,
+    merge: Some(Merge {
+        read: |product| product.regional_pricing.unwrap_or(false).into(),
+        take: |product, remote| product.regional_pricing = remote.regional_pricing,
+    }),
+};
+
+pub static ACTIVE: Field = Field {
+    name: "active",
+    label: "Active",
+    text: false,
+    values: |local, other, _| Some((other.active.into(), local.active.into())),
+    format: plain,
+    equal: same,
+    merge: None,
+};
+
+/// The hash recorded at the last upload against the image file's current one.
+/// An unreadable image fails validation, so it isn't diffed.
+pub static IMAGE: Field = Field {
+    name: "image",
+    label: "Image",
+    text: false,
+    values: |local, _, _| {
+        let current = image_hash(local.image.as_deref()?)?;
+        Some((
+            local.image_hash.clone().unwrap_or_default().into(),
+            current.into(),
+        ))
+    },
+    format: short_hash,
+    equal: same,
+    merge: None,
+};
+
+/// Only worth diffing when sync can put the icon back by uploading the image
+/// again.
+pub static ICON: Field = Field {
+    name: "icon",
+    label: "Icon",
+    text: false,
+    values: |local, other, _| {
+        local.image.as_ref()?;
+        Some((other.icon_asset_id?.into(), local.icon_asset_id?.into()))
+    },
+    format: plain,
+    equal: same,
+    merge: None,
+};
+
+pub static STORE_PAGE: Field = Field {
+    name: "store page",
+    label: "Store Page",
+    text: false,
+    values: |local, other, _| {
+        Some((
+            other.store_page_enabled.unwrap_or(false).into(),
+            local.store_page_enabled?.into(),
+        ))
+    },
+    format: plain,
+    equal: same,
+    merge: Some(Merge {
+        read: |product| product.store_page_enabled.unwrap_or(false).into(),
+        take: |product, remote| product.store_page_enabled = remote.store_page_enabled,
+    }),
+};
+
+/// Every synced field, in the order diffs list them.
+pub static FIELDS: [&Field; 8] = [
+    &TITLE,
+    &DESCRIPTION,
+    &PRICE,
+    &REGIONAL_PRICING,
+    &ACTIVE,
+    &IMAGE,
+    &ICON,
+    &STORE_PAGE,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique() {
+        for (index, field) in FIELDS.iter().enumerate() {
+            assert!(
+                FIELDS[index + 1..]
+                    .iter()
+                    .all(|other| other.name != field.name),
+                "{} is registered twice",
+                field.name
+            );
+        }
+    }
+
+    #[test]
+    fn skips_fields_that_arent_synced() {
+        let local = Product {
+            name: "VIP".to_string(),
+            price: 100,
+            ..Default::default()
+        };
+        let remote = Product {
+            description: Some(String::new()),
+            icon_asset_id: Some(3),
+            ..local.clone()
+        };
+
+        assert_eq!(
+            TITLE.compare(&local, &remote, None),
+            Some(("VIP".into(), "VIP".into(), true))
+        );
+        assert_eq!(ICON.compare(&local, &remote, None), None);
+        assert_eq!(STORE_PAGE.compare(&local, &remote, None), None);
+        assert_eq!((IMAGE.format)(&"".into()), "none");
+        assert_eq!((TITLE.format)(&"VIP".into()), "\"VIP\"");
+        assert_eq!(TITLE.display(&"VIP".into()), "VIP");
+    }
+
+    #[test]
+    fn merges_take_the_remote_value() {
+        let remote = Product {
+            name: "VIP Room".to_string(),
+            price: 250,
+            regional_pricing: Some(true),
+            ..Default::default()
+        };
+
+        for field in FIELDS {
+            let Some(merge) = &field.merge else {
+                continue;
+            };
+
+            let mut local = Product {
+                name: "VIP".to_string(),
+                prefix: Some("⭐".to_string()),
+                description: Some("Local copy".to_string()),
+                store_page_enabled: Some(true),
+                ..Default::default()
+            };
+            (merge.take)(&mut local, &remote);
+            assert_eq!(
+                (merge.read)(&local),
+                (merge.read)(&remote),
+                "{}",
+                field.name
+            );
+        }
+    }
+}
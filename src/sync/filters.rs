@@ -0,0 +1,112 @@
+use clap::Subcommand;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::products::Metadata;
+use crate::sync::products::{MultiProduct, VCSProducts};
+use crate::utils::{canonical_name, render_table};
+
+#[derive(Subcommand, Debug)]
+pub enum FiltersCommand {
+    /// Prints how names are canonicalized under the configured name-filters
+    Test {
+        /// Raw names to preview
+        names: Vec<String>,
+        /// Preview every product name in the universe
+        #[arg(long, default_value_t = false)]
+        remote: bool,
+    },
+}
+
+impl FiltersCommand {
+    pub fn remote(&self) -> bool {
+        match self {
+            FiltersCommand::Test { remote, .. } => *remote,
+        }
+    }
+}
+
+const HEADERS: [&str; 3] = ["name", "canonical name", "key"];
+
+fn preview_rows(names: &[String], metadata: &Metadata) -> Vec<Vec<String>> {
+    names
+        .iter()
+        .map(|name| {
+            let canonical = canonical_name(name.clone(), &metadata.filters());
+            let key = metadata.key_for(&canonical);
+            vec![name.clone(), canonical, key]
+        })
+        .collect()
+}
+
+pub async fn run_filters(command: FiltersCommand) -> Result<()> {
+    let FiltersCommand::Test { mut names, remote } = command;
+
+    let local_products = VCSProducts::get_products().await?;
+
+    if remote {
+        let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+        names.extend(
+            remote_products
+                .iter()
+                .map(|multi_product| match multi_product {
+                    MultiProduct::GamePass(prod) | MultiProduct::DevProduct(prod) => {
+                        prod.name.clone()
+                    }
+                }),
+        );
+    }
+
+    if names.is_empty() {
+        return Err("nothing to preview, pass names or --remote".into());
+    }
+
+    let rows = preview_rows(&names, &local_products.metadata);
+    print!("{}", render_table(&HEADERS, &rows));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::*;
+    use crate::utils::NameFilter;
+
+    #[test]
+    fn previews_filtered_names() {
+        let metadata = Metadata {
+            name_filters: Some(vec![NameFilter::from(Regex::new(r"💲\d+% OFF💲").unwrap())]),
+            ..Default::default()
+        };
+        let rows = preview_rows(&["💲30% OFF💲 Super Speed!".to_string()], &metadata);
+
+        assert_eq!(
+            rows,
+            [vec![
+                "💲30% OFF💲 Super Speed!".to_string(),
+                "Super Speed!".to_string(),
+                "super-speed".to_string(),
+            ]]
+        );
+    }
+
+    #[test]
+    fn rewrites_names_with_templates() {
+        let metadata: Metadata = toml::from_str(
+            r#"
+            universe-id = 1
+            name-filters = [
+                { pattern = '(Tier \d+) \(Winter\)', replace = "$1" },
+                "!",
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let rows = preview_rows(&["Sword Tier 2 (Winter)!".to_string()], &metadata);
+        assert_eq!(rows[0][1], "Sword Tier 2");
+        assert_eq!(rows[0][2], "sword-tier-2");
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use tokio::io::AsyncWriteExt;
+
+use crate::Result;
+use crate::sync::products::ProductType;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckOutput {
+    /// Log pending changes
+    #[default]
+    Log,
+    /// Emit GitHub Actions workflow commands and a step summary
+    Github,
+}
+
+/// One pending field change, as shown in annotations and the summary table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRow {
+    pub action: &'static str,
+    pub product_type: ProductType,
+    pub key: String,
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Escapes a workflow command message, per the GitHub Actions docs.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+pub fn annotations(rows: &[PendingRow], risks: &[String]) -> Vec<String> {
+    let mut lines = rows
+        .iter()
+        .map(|row| {
+            let message = match row.action {
+                "create" => format!(
+                    "{:?} '{}' will be created: {}",
+                    row.product_type, row.key, row.after
+                ),
+                _ => format!(
+                    "{:?} '{}' {}: {} -> {}",
+                    row.product_type, row.key, row.field, row.before, row.after
+                ),
+            };
+            format!("::notice title=rbx-products::{}", escape_data(&message))
+        })
+        .collect::<Vec<_>>();
+
+    lines.extend(
+        risks
+            .iter()
+            .map(|risk| format!("::warning title=Revenue risk::{}", escape_data(risk))),
+    );
+
+    lines
+}
+
+pub fn summary_table(rows: &[PendingRow]) -> String {
+    if rows.is_empty() {
+        return "### rbx-products\n\nNo pending product changes.\n".to_string();
+    }
+
+    let mut out = String::from("### rbx-products pending changes\n\n");
+    out.push_str("| Change | Type | Key | Field | Before | After |\n");
+    out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+
+    for row in rows {
+        out.push_str(&format!(
+            "| {} | {:?} | `{}` | {} | {} | {} |\n",
+            row.action,
+            row.product_type,
+            escape_cell(&row.key),
+            escape_cell(&row.field),
+            escape_cell(&row.before),
+            escape_cell(&row.after)
+        ));
+    }
+
+    out
+}
+
+/// Prints annotations, and appends the summary to `$GITHUB_STEP_SUMMARY` when
+/// it's set (or prints it otherwise).
+pub async fn emit(rows: &[PendingRow], risks: &[String]) -> Result<()> {
+    for line in annotations(rows, risks) {
+        println!("{}", line);
+    }
+
+    let summary = summary_table(rows);
+
+    match std::env::var("GITHUB_STEP_SUMMARY") {
+        Ok(path) if !path.is_empty() => {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(Path::new(&path))
+                .await?;
+            file.write_all(summary.as_bytes()).await?;
+        }
+        _ => print!("{}", summary),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<PendingRow> {
+        vec![
+            PendingRow {
+                action: "update",
+                product_type: ProductType::GamePass,
+                key: "vip".to_string(),
+                field: "price".to_string(),
+                before: "100".to_string(),
+                after: "80".to_string(),
+            },
+            PendingRow {
+                action: "create",
+                product_type: ProductType::DevProduct,
+                key: "coins".to_string(),
+                field: "new product".to_string(),
+                before: String::new(),
+                after: "Coins | 100% (25 R$)".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn renders_annotations_and_summary() {
+        assert_eq!(
+            annotations(&rows(), &["vip: taken off sale".to_string()]),
+            [
+                "::notice title=rbx-products::GamePass 'vip' price: 100 -> 80",
+                "::notice title=rbx-products::DevProduct 'coins' will be created: Coins | 100%25 (25 R$)",
+                "::warning title=Revenue risk::vip: taken off sale",
+            ]
+        );
+
+        assert_eq!(
+            summary_table(&rows()),
+            "### rbx-products pending changes\n\n\
+             | Change | Type | Key | Field | Before | After |\n\
+             | --- | --- | --- | --- | --- | --- |\n\
+             | update | GamePass | `vip` | price | 100 | 80 |\n\
+             | create | DevProduct | `coins` | new product |  | Coins \\| 100% (25 R$) |\n"
+        );
+    }
+}
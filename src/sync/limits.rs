@@ -0,0 +1,100 @@
+use crate::sync::products::{Metadata, ProductType, VCSProducts};
+
+// Known Roblox marketplace limits. Roblox doesn't publish all of these in one
+// place, so they're kept together here to update as they change.
+
+pub const MIN_PRICE: i64 = 1;
+pub const MAX_PRICE: i64 = 1_000_000_000;
+pub const MAX_NAME_LENGTH: usize = 50;
+pub const MAX_DESCRIPTION_LENGTH: usize = 1000;
+/// Image formats accepted for product icons.
+pub const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+
+/// Fraction of a cap at which the catalog is reported as nearly full.
+const WARN_THRESHOLD: f64 = 0.9;
+
+/// Catalog cap set in the products file. Roblox doesn't document one, so
+/// there's none unless `max-gamepasses` or `max-dev-products` is given.
+pub fn max_products(metadata: &Metadata, product_type: ProductType) -> Option<usize> {
+    match product_type {
+        ProductType::GamePass => metadata.max_gamepasses,
+        ProductType::DevProduct => metadata.max_dev_products,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatalogUsage {
+    pub product_type: ProductType,
+    pub used: usize,
+    pub max: usize,
+}
+
+impl CatalogUsage {
+    pub fn is_over(&self) -> bool {
+        self.used > self.max
+    }
+
+    pub fn is_near(&self) -> bool {
+        self.used as f64 >= self.max as f64 * WARN_THRESHOLD
+    }
+
+    pub fn describe(&self) -> String {
+        let noun = match self.product_type {
+            ProductType::GamePass => "gamepasses",
+            ProductType::DevProduct => "developer products",
+        };
+
+        format!("{} of {} {} used", self.used, self.max, noun)
+    }
+}
+
+pub fn catalog_usage(local_products: &VCSProducts) -> Vec<CatalogUsage> {
+    [
+        (ProductType::GamePass, local_products.gamepasses.len()),
+        (ProductType::DevProduct, local_products.products.len()),
+    ]
+    .into_iter()
+    .filter_map(|(product_type, used)| {
+        Some(CatalogUsage {
+            product_type,
+            used,
+            max: max_products(&local_products.metadata, product_type)?,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_near_and_over_caps() {
+        let usage = |used| CatalogUsage {
+            product_type: ProductType::GamePass,
+            used,
+            max: 100,
+        };
+
+        assert!(!usage(89).is_near());
+        assert!(usage(97).is_near() && !usage(97).is_over());
+        assert!(usage(101).is_over());
+        assert_eq!(usage(97).describe(), "97 of 100 gamepasses used");
+    }
+
+    #[test]
+    fn only_configured_caps_are_reported() {
+        let mut products = VCSProducts::default();
+        assert!(catalog_usage(&products).is_empty());
+
+        products.metadata.max_dev_products = Some(5);
+        assert_eq!(
+            catalog_usage(&products),
+            vec![CatalogUsage {
+                product_type: ProductType::DevProduct,
+                used: 0,
+                max: 5,
+            }]
+        );
+    }
+}
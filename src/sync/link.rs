@@ -0,0 +1,208 @@
+use log::{info, warn};
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::products::{MultiProduct, ProductType, VCSProducts};
+use crate::ui;
+use crate::ui::confirm::{ConfirmState, ConfirmViewer};
+use crate::utils::canonical_name;
+
+/// A remote product matched to a local entry that doesn't have an id yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Link {
+    product_type: ProductType,
+    key: String,
+    id: u64,
+    remote_name: String,
+}
+
+/// Matches untracked local products to remote products by canonical name or
+/// key. Local products with more than one candidate are returned separately,
+/// so a guess is never written.
+fn find_links(
+    local_products: &VCSProducts,
+    remote_products: &[MultiProduct],
+) -> (Vec<Link>, Vec<(ProductType, String)>) {
+    let filters = &local_products.metadata.filters();
+
+    let mut links = vec![];
+    let mut ambiguous = vec![];
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        let entries = match product_type {
+            ProductType::GamePass => &local_products.gamepasses,
+            ProductType::DevProduct => &local_products.products,
+        };
+
+        // Remote products of this type that nothing local points at yet.
+        let candidates = remote_products
+            .iter()
+            .filter_map(|multi_product| match (product_type, multi_product) {
+                (ProductType::GamePass, MultiProduct::GamePass(prod))
+                | (ProductType::DevProduct, MultiProduct::DevProduct(prod)) => Some(prod),
+                _ => None,
+            })
+            .filter(|prod| {
+                prod.id
+                    .is_some_and(|id| !entries.values().any(|local| local.id == Some(id)))
+            })
+            .collect::<Vec<_>>();
+
+        let mut keys = entries
+            .iter()
+            .filter(|(_, product)| product.id.is_none())
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|(key, _)| *key);
+
+        for (key, product) in keys {
+            let local_name = canonical_name(product.name.clone(), filters);
+
+            let matches = candidates
+                .iter()
+                .filter(|remote| {
+                    let remote_name = canonical_name(remote.name.clone(), filters);
+                    remote_name == local_name
+                        || local_products.metadata.key_for(&remote_name) == *key
+                })
+                .collect::<Vec<_>>();
+
+            match matches.as_slice() {
+                [] => {}
+                [remote] => links.push(Link {
+                    product_type,
+                    key: key.clone(),
+                    id: remote.id.unwrap(),
+                    remote_name: remote.name.clone(),
+                }),
+                _ => ambiguous.push((product_type, key.clone())),
+            }
+        }
+    }
+
+    (links, ambiguous)
+}
+
+fn write_links(local_products: &mut VCSProducts, links: &[Link]) {
+    for link in links {
+        if let Some(product) = local_products
+            .products_of_mut(link.product_type)
+            .get_mut(&link.key)
+        {
+            product.id = Some(link.id);
+        }
+    }
+}
+
+/// Links every untracked local product that unambiguously matches a remote
+/// one, without asking. Returns how many were linked.
+pub fn link_matching(local_products: &mut VCSProducts, remote_products: &[MultiProduct]) -> usize {
+    let (links, _) = find_links(local_products, remote_products);
+    write_links(local_products, &links);
+    links.len()
+}
+
+/// Writes the ids of remote products into local entries created before the
+/// products were tracked, e.g. passes made in the Creator Dashboard.
+pub async fn link_products(yes: bool) -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+
+    info!("fetching remote products");
+    let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+
+    let (links, ambiguous) = find_links(&local_products, &remote_products);
+
+    for (product_type, key) in &ambiguous {
+        warn!(
+            "{:?} '{}' matches more than one remote product, set its id by hand",
+            product_type, key
+        );
+    }
+
+    if links.is_empty() {
+        info!("no untracked products matched remote products");
+        return Ok(());
+    }
+
+    let summary = links
+        .iter()
+        .map(|link| {
+            format!(
+                "{:?} '{}' -> {} ({})",
+                link.product_type, link.key, link.id, link.remote_name
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for line in &summary {
+        info!("{}", line);
+    }
+
+    if !yes {
+        if !ui::can_prompt() {
+            return Err("pass --yes to link products when not running interactively".into());
+        }
+
+        let prompt = format!("Link these products?\n{}", summary.join("\n"));
+        if ConfirmViewer::show_prompt(prompt).await? != ConfirmState::Confirmed {
+            info!("no products were linked");
+            return Ok(());
+        }
+    }
+
+    write_links(&mut local_products, &links);
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    info!("linked {} product(s)", links.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::Product;
+
+    fn product(id: Option<u64>, name: &str) -> Product {
+        Product {
+            id,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn links_unique_matches_only() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("vip".to_string(), product(None, "VIP"));
+        local_products
+            .gamepasses
+            .insert("speed".to_string(), product(None, "Speed"));
+        local_products
+            .gamepasses
+            .insert("tracked".to_string(), product(Some(3), "Tracked"));
+
+        let remote = [
+            MultiProduct::GamePass(product(Some(1), "VIP")),
+            MultiProduct::GamePass(product(Some(2), "Speed")),
+            MultiProduct::GamePass(product(Some(4), "speed")),
+            MultiProduct::GamePass(product(Some(3), "Tracked")),
+            MultiProduct::DevProduct(product(Some(5), "VIP")),
+        ];
+
+        let (links, ambiguous) = find_links(&local_products, &remote);
+
+        assert_eq!(
+            links,
+            [Link {
+                product_type: ProductType::GamePass,
+                key: "vip".to_string(),
+                id: 1,
+                remote_name: "VIP".to_string(),
+            }]
+        );
+        assert_eq!(ambiguous, [(ProductType::GamePass, "speed".to_string())]);
+    }
+}
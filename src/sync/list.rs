@@ -0,0 +1,231 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::products::{Attributes, MultiProduct, ProductType, VCSProducts};
+use crate::sync::report::format_usd;
+use crate::utils::{canonical_name, render_csv, render_table};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListOutput {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+/// Where `list` reads the products from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListSource {
+    /// The products file
+    #[default]
+    Local,
+    /// The universe, with keys from the products file where tracked
+    Remote,
+}
+
+impl ListSource {
+    /// The source picked with `--local` or `--remote`, which can't be combined.
+    pub fn from_flags(remote: bool, local: bool) -> Self {
+        match (remote, local) {
+            (true, false) => ListSource::Remote,
+            _ => ListSource::Local,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListEntry {
+    pub key: String,
+    pub product_type: ProductType,
+    pub id: Option<u64>,
+    pub price: i64,
+    pub effective_price: u64,
+    /// Approximate USD a player pays, with `--usd`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_usd: Option<f64>,
+    /// Approximate USD the developer cashes out per sale, with `--usd`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_usd: Option<f64>,
+    pub active: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<Attributes>,
+}
+
+const HEADERS: [&str; 8] = [
+    "key",
+    "type",
+    "id",
+    "price",
+    "effective price",
+    "active",
+    "locked",
+    "attributes",
+];
+
+const USD_HEADERS: [&str; 2] = ["price (USD)", "payout (USD)"];
+
+fn headers(usd: bool) -> Vec<&'static str> {
+    let mut headers = HEADERS.to_vec();
+    if usd {
+        headers.splice(5..5, USD_HEADERS);
+    }
+    headers
+}
+
+impl ListEntry {
+    fn row(&self) -> Vec<String> {
+        let usd = [self.price_usd, self.payout_usd]
+            .into_iter()
+            .flatten()
+            .map(format_usd);
+
+        [
+            self.key.clone(),
+            format!("{:?}", self.product_type),
+            self.id.map_or("-".to_string(), |id| id.to_string()),
+            self.price.to_string(),
+            self.effective_price.to_string(),
+        ]
+        .into_iter()
+        .chain(usd)
+        .chain([
+            self.active.to_string(),
+            if self.locked { "locked" } else { "" }.to_string(),
+            self.attributes
+                .as_ref()
+                .map(|attributes| serde_json::to_string(attributes).unwrap_or_default())
+                .unwrap_or_default(),
+        ])
+        .collect()
+    }
+}
+
+fn local_entries(local_products: &VCSProducts, tags: &[String]) -> Vec<ListEntry> {
+    let gamepasses = local_products
+        .gamepasses
+        .iter()
+        .map(|(key, product)| (ProductType::GamePass, key, product));
+    let products = local_products
+        .products
+        .iter()
+        .map(|(key, product)| (ProductType::DevProduct, key, product));
+
+    gamepasses
+        .chain(products)
+        .filter(|(_, _, product)| product.matches_tags(tags))
+        .map(|(product_type, key, product)| ListEntry {
+            key: key.clone(),
+            product_type,
+            id: product.id,
+            price: product.price,
+            effective_price: product.get_price(Some(&local_products.metadata)),
+            price_usd: None,
+            payout_usd: None,
+            active: product.active,
+            locked: product.is_locked(),
+            order: product.order,
+            tags: product.tags.clone(),
+            attributes: product.attributes.clone(),
+        })
+        .collect()
+}
+
+async fn remote_entries(local_products: &VCSProducts, tags: &[String]) -> Result<Vec<ListEntry>> {
+    let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+    let filters = &local_products.metadata.filters();
+
+    Ok(remote_products
+        .iter()
+        .filter_map(|multi_product| {
+            let (product_type, product, local) = match multi_product {
+                MultiProduct::GamePass(prod) => {
+                    (ProductType::GamePass, prod, &local_products.gamepasses)
+                }
+                MultiProduct::DevProduct(prod) => {
+                    (ProductType::DevProduct, prod, &local_products.products)
+                }
+            };
+
+            // Prefer the local key for products we already track.
+            let tracked = local.iter().find(|(_, local_product)| {
+                local_product.id.is_some() && local_product.id == product.id
+            });
+
+            // Tags only exist locally, so untracked products never match them.
+            if !tags.is_empty() && !tracked.is_some_and(|(_, local)| local.matches_tags(tags)) {
+                return None;
+            }
+
+            let key = tracked.map(|(key, _)| key.clone()).unwrap_or_else(|| {
+                local_products
+                    .metadata
+                    .key_for(&canonical_name(product.name.clone(), filters))
+            });
+
+            Some(ListEntry {
+                key,
+                product_type,
+                id: product.id,
+                price: product.price,
+                effective_price: product.get_price(None),
+                price_usd: None,
+                payout_usd: None,
+                active: product.active,
+                locked: tracked.is_some_and(|(_, local_product)| local_product.is_locked()),
+                order: tracked.and_then(|(_, local_product)| local_product.order),
+                tags: tracked.and_then(|(_, local_product)| local_product.tags.clone()),
+                attributes: tracked.and_then(|(_, local_product)| local_product.attributes.clone()),
+            })
+        })
+        .collect())
+}
+
+pub async fn list_products(
+    source: ListSource,
+    tags: &[String],
+    usd: bool,
+    output: ListOutput,
+) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+
+    let mut entries = match source {
+        ListSource::Local => local_entries(&local_products, tags),
+        ListSource::Remote => remote_entries(&local_products, tags).await?,
+    };
+
+    entries.sort_by(|a, b| (a.product_type, &a.key).cmp(&(b.product_type, &b.key)));
+
+    if usd {
+        let model = local_products
+            .metadata
+            .revenue_model
+            .clone()
+            .unwrap_or_default();
+
+        for entry in &mut entries {
+            entry.price_usd = Some(model.consumer_usd(entry.effective_price));
+            entry.payout_usd = Some(model.payout_usd(entry.effective_price));
+        }
+    }
+
+    let headers = headers(usd);
+    let rows = entries.iter().map(ListEntry::row).collect::<Vec<_>>();
+
+    let rendered = match output {
+        ListOutput::Table => render_table(&headers, &rows),
+        ListOutput::Csv => render_csv(&headers, &rows)?,
+        ListOutput::Json => serde_json::to_string_pretty(&entries)? + "\n",
+    };
+
+    print!("{}", rendered);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::Result;
+use crate::sync::base::RemoteBase;
+use crate::sync::products::{Metadata, Product, ProductType, image_hash};
+use crate::sync::validate::uploaded_title;
+
+/// Where the state of the last download and sync is recorded.
+const LOCK_FILE: &str = ".rbx-products/sync.lock";
+
+/// A product as it was when the last sync left it matching the universe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LockEntry {
+    pub product_type: ProductType,
+    pub id: u64,
+    /// Hash of everything a sync would upload for the product
+    pub content_hash: String,
+    /// The remote product's `updatedTimestamp` at the time
+    pub remote_updated: String,
+}
+
+/// Products known to match the universe after the last sync. A product whose
+/// local content and remote timestamp are both unchanged since can't differ,
+/// so `sync` doesn't diff it again. The lock also holds the base `download`
+/// merges from.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyncLock {
+    pub universe_id: u64,
+    pub products: Vec<LockEntry>,
+    /// The universe's products as of the last download or sync
+    #[serde(default)]
+    pub base: Option<RemoteBase>,
+}
+
+/// Hashes the product as it would be uploaded, so changes to the metadata
+/// (sale prefix, rounding) or to an image file count too.
+pub fn content_hash(product: &Product, metadata: &Metadata) -> String {
+    let content = serde_json::json!({
+        "product": product,
+        "title": uploaded_title(product, metadata.discount_prefix.clone()),
+        "price": product.get_price(Some(metadata)),
+        "description": product.get_description(Some(metadata)),
+        "image": product.image.as_deref().and_then(image_hash),
+    });
+
+    format!("{:x}", Sha256::digest(content.to_string()))
+}
+
+impl SyncLock {
+    pub fn new(universe_id: u64) -> Self {
+        Self {
+            universe_id,
+            products: vec![],
+            base: None,
+        }
+    }
+
+    /// The recorded lock for `universe_id`, if there is one.
+    pub async fn load(universe_id: u64) -> Option<Self> {
+        let data = fs::read(LOCK_FILE).await.ok()?;
+        serde_json::from_slice::<Self>(&data)
+            .ok()
+            .filter(|lock| lock.universe_id == universe_id)
+    }
+
+    async fn write(&self) -> Result<()> {
+        fs::create_dir_all(".rbx-products").await?;
+        fs::write(LOCK_FILE, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Records the lock, keeping the recorded base unless it has its own.
+    /// Warns instead of failing since the next sync just compares everything
+    /// without it.
+    pub async fn save(mut self) {
+        if self.base.is_none() {
+            self.base = Self::load(self.universe_id)
+                .await
+                .and_then(|lock| lock.base);
+        }
+
+        if let Err(e) = self.write().await {
+            warn!("failed to record the sync state in {}: {}", LOCK_FILE, e);
+        }
+    }
+
+    /// Records the base of the next merge, keeping the products the last sync
+    /// left matching the universe.
+    pub async fn save_base(base: RemoteBase) -> Result<()> {
+        let mut lock = Self::load(base.universe_id)
+            .await
+            .unwrap_or_else(|| Self::new(base.universe_id));
+        lock.base = Some(base);
+        lock.write()
+            .await
+            .map_err(|e| format!("failed to write {}: {}", LOCK_FILE, e).into())
+    }
+
+    /// Whether neither side of the product changed since it was recorded.
+    pub fn unchanged(
+        &self,
+        product_type: ProductType,
+        local: &Product,
+        remote: &Product,
+        metadata: &Metadata,
+    ) -> bool {
+        let (Some(id), Some(remote_updated)) = (local.id, &remote.remote_updated) else {
+            return false;
+        };
+
+        self.products.iter().any(|entry| {
+            entry.product_type == product_type
+                && entry.id == id
+                && &entry.remote_updated == remote_updated
+                && entry.content_hash == content_hash(local, metadata)
+        })
+    }
+
+    /// Records a product that matches its remote.
+    pub fn insert(
+        &mut self,
+        product_type: ProductType,
+        local: &Product,
+        remote: &Product,
+        metadata: &Metadata,
+    ) {
+        let (Some(id), Some(remote_updated)) = (local.id, &remote.remote_updated) else {
+            return;
+        };
+
+        self.products.push(LockEntry {
+            product_type,
+            id,
+            content_hash: content_hash(local, metadata),
+            remote_updated: remote_updated.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_changes_on_either_side() {
+        let metadata = Metadata::default();
+        let local = Product {
+            id: Some(7),
+            name: "VIP".to_string(),
+            price: 100,
+            ..Default::default()
+        };
+        let remote = Product {
+            remote_updated: Some("2024-05-01T12:00:00Z".to_string()),
+            ..local.clone()
+        };
+
+        let mut lock = SyncLock::new(1);
+        lock.insert(ProductType::GamePass, &local, &remote, &metadata);
+        assert!(lock.unchanged(ProductType::GamePass, &local, &remote, &metadata));
+        assert!(!lock.unchanged(ProductType::DevProduct, &local, &remote, &metadata));
+
+        let edited = Product {
+            price: 120,
+            ..local.clone()
+        };
+        assert!(!lock.unchanged(ProductType::GamePass, &edited, &remote, &metadata));
+
+        let touched = Product {
+            remote_updated: Some("2024-05-02T08:00:00Z".to_string()),
+            ..remote.clone()
+        };
+        assert!(!lock.unchanged(ProductType::GamePass, &local, &touched, &metadata));
+
+        let on_sale = Metadata {
+            round_to: Some(5),
+            ..Default::default()
+        };
+        let discounted = Product {
+            discount: Some(33),
+            ..local.clone()
+        };
+        lock.insert(ProductType::GamePass, &discounted, &remote, &metadata);
+        assert!(!lock.unchanged(ProductType::GamePass, &discounted, &remote, &on_sale));
+    }
+}
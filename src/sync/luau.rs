@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use handlebars::{Handlebars, no_escape};
+use serde::Serialize;
+
+use crate::Result;
+use crate::sync::products::{Attributes, Metadata, Product, ProductType, VCSProducts};
+
+/// A product as exposed to `luau-template` files.
+#[derive(Debug, Serialize)]
+struct TemplateProduct<'a> {
+    key: &'a str,
+    id: u64,
+    name: &'a str,
+    description: Option<String>,
+    price: u64,
+    base_price: i64,
+    discount: u8,
+    active: bool,
+    order: Option<u32>,
+    category: Option<&'a str>,
+    tags: &'a [String],
+    attributes: Option<&'a Attributes>,
+}
+
+#[derive(Debug, Serialize)]
+struct TemplateContext<'a> {
+    universe_id: u64,
+    gamepasses: Vec<TemplateProduct<'a>>,
+    products: Vec<TemplateProduct<'a>>,
+    gamepass_order: Vec<&'a String>,
+    product_order: Vec<&'a String>,
+}
+
+fn template_products<'a>(
+    products: &'a BTreeMap<String, Product>,
+    metadata: &Metadata,
+) -> Vec<TemplateProduct<'a>> {
+    let mut values = products
+        .iter()
+        .map(|(key, product)| TemplateProduct {
+            key,
+            id: product.id.unwrap_or(0),
+            name: &product.name,
+            description: product.get_description(Some(metadata)),
+            price: product.get_price(Some(metadata)),
+            base_price: product.price,
+            discount: product.discount.unwrap_or(0),
+            active: product.active,
+            order: product.order,
+            category: product.category.as_deref(),
+            tags: product.tags.as_deref().unwrap_or_default(),
+            attributes: product.attributes.as_ref(),
+        })
+        .collect::<Vec<_>>();
+
+    values.sort_by(|a, b| (a.id, a.key).cmp(&(b.id, b.key)));
+    values
+}
+
+/// Renders a Handlebars `luau-template` with the local products. Output is not
+/// HTML-escaped, so values are inserted exactly as they appear in the file.
+pub fn render_template(template: &str, local_products: &VCSProducts) -> Result<String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(no_escape);
+
+    let context = TemplateContext {
+        universe_id: local_products.metadata.universe_id,
+        gamepasses: template_products(&local_products.gamepasses, &local_products.metadata),
+        products: template_products(&local_products.products, &local_products.metadata),
+        gamepass_order: local_products.shop_order(ProductType::GamePass),
+        product_order: local_products.shop_order(ProductType::DevProduct),
+    };
+
+    handlebars
+        .render_template(template, &context)
+        .map_err(|e| format!("failed to render luau template: {}", e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_products_in_id_order() {
+        let mut local_products = VCSProducts::default();
+        for (key, id, price) in [("vip", 2, 100), ("coins", 1, 50)] {
+            local_products.gamepasses.insert(
+                key.to_string(),
+                Product {
+                    id: Some(id),
+                    name: key.to_uppercase(),
+                    price,
+                    discount: (key == "vip").then_some(20),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let template = "{{#each gamepasses}}{{key}}={{id}}:{{price}}/{{base_price}}\n{{/each}}";
+
+        assert_eq!(
+            render_template(template, &local_products).unwrap(),
+            "coins=1:50/50\nvip=2:80/100\n"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use log::{debug, info};
+use serde_json::Value;
+use tokio::fs;
+use toml_edit::{DocumentMut, value};
+
+use crate::Result;
+use crate::sync::products::{FileFormat, VCSProducts};
+
+/// Layout version of products files written by this build. Files without a
+/// `schema-version` predate versioning and count as version 1.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The structural edits a migration can make, for both TOML documents and
+/// YAML/JSON data.
+trait Document {
+    fn version(&self) -> Option<u32>;
+    fn set_version(&mut self, version: u32);
+    /// Renames a key in every game pass and developer product entry,
+    /// returning how many entries had it.
+    fn rename_product_key(&mut self, from: &str, to: &str) -> usize;
+}
+
+impl Document for DocumentMut {
+    fn version(&self) -> Option<u32> {
+        self.get("schema-version")
+            .and_then(|item| item.as_integer())
+            .map(|version| version as u32)
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self["schema-version"] = value(version as i64);
+    }
+
+    fn rename_product_key(&mut self, from: &str, to: &str) -> usize {
+        let mut renamed = 0;
+
+        for section in ["gamepasses", "products"] {
+            let Some(entries) = self
+                .get_mut(section)
+                .and_then(|item| item.as_table_like_mut())
+            else {
+                continue;
+            };
+
+            for (_, entry) in entries.iter_mut() {
+                let Some(entry) = entry.as_table_like_mut() else {
+                    continue;
+                };
+
+                if let Some(item) = entry.remove(from) {
+                    if entry.get(to).is_none() {
+                        entry.insert(to, item);
+                    }
+                    renamed += 1;
+                }
+            }
+        }
+
+        renamed
+    }
+}
+
+impl Document for Value {
+    fn version(&self) -> Option<u32> {
+        self.get("schema-version")
+            .and_then(Value::as_u64)
+            .map(|version| version as u32)
+    }
+
+    fn set_version(&mut self, version: u32) {
+        if let Value::Object(root) = self {
+            root.insert("schema-version".to_string(), version.into());
+        }
+    }
+
+    fn rename_product_key(&mut self, from: &str, to: &str) -> usize {
+        let mut renamed = 0;
+
+        for section in ["gamepasses", "products"] {
+            let Some(Value::Object(entries)) = self.get_mut(section) else {
+                continue;
+            };
+
+            for entry in entries.values_mut() {
+                let Value::Object(entry) = entry else {
+                    continue;
+                };
+
+                if let Some(item) = entry.remove(from) {
+                    entry.entry(to).or_insert(item);
+                    renamed += 1;
+                }
+            }
+        }
+
+        renamed
+    }
+}
+
+struct Migration {
+    /// The version this migration upgrades to
+    version: u32,
+    /// What changed, logged when an entry was affected
+    note: &'static str,
+    apply: fn(&mut dyn Document) -> usize,
+}
+
+const MIGRATIONS: [Migration; 1] = [Migration {
+    version: 2,
+    note: "renamed exclude-from-luau to internal",
+    apply: |document| document.rename_product_key("exclude-from-luau", "internal"),
+}];
+
+/// Applies every migration newer than the document's version, or than
+/// `version` when given. Returns the version it started from and the notes of
+/// the migrations that changed something.
+fn upgrade(document: &mut dyn Document, version: Option<u32>) -> Result<(u32, Vec<String>)> {
+    let from = version.or(document.version()).unwrap_or(1);
+    if from > SCHEMA_VERSION {
+        return Err(format!(
+            "schema version {} is newer than this rbx-products supports ({}), update it first",
+            from, SCHEMA_VERSION
+        )
+        .into());
+    }
+
+    let mut notes = vec![];
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from) {
+        let changed = (migration.apply)(document);
+        if changed > 0 {
+            notes.push(format!("{} ({} product(s))", migration.note, changed));
+        }
+    }
+
+    Ok((from, notes))
+}
+
+/// Migrates file data to the current schema, or returns `None` when it's
+/// already current. Files given a `version` are included files, which take
+/// the version of the main file and don't record their own.
+fn migrate(format: FileFormat, data: &[u8], version: Option<u32>) -> Result<Option<Migrated>> {
+    let mut data = match format {
+        FileFormat::Toml => MigratedData::Toml(String::from_utf8(data.to_vec())?.parse()?),
+        format => MigratedData::Value(format.parse(data)?),
+    };
+
+    let document: &mut dyn Document = match &mut data {
+        MigratedData::Toml(document) => document,
+        MigratedData::Value(value) => value,
+    };
+
+    let (from, notes) = upgrade(document, version)?;
+    if from == SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    if version.is_none() {
+        document.set_version(SCHEMA_VERSION);
+    }
+
+    let data = match data {
+        MigratedData::Toml(document) => document.to_string(),
+        MigratedData::Value(value) => match format {
+            FileFormat::Yaml => serde_yaml::to_string(&value)?,
+            _ => serde_json::to_string_pretty(&value)? + "\n",
+        },
+    };
+
+    Ok(Some(Migrated { from, notes, data }))
+}
+
+enum MigratedData {
+    Toml(DocumentMut),
+    Value(Value),
+}
+
+struct Migrated {
+    from: u32,
+    notes: Vec<String>,
+    data: String,
+}
+
+/// Upgrades a TOML document about to be saved, so saving an older file
+/// leaves it in the current schema. Included files pass the main file's
+/// `version`.
+pub fn upgrade_document(document: &mut DocumentMut, version: Option<u32>) -> Result<()> {
+    upgrade(document, version)?;
+    Ok(())
+}
+
+/// Reads a products file, upgrading its data to the current schema if it was
+/// written with an older one. The file itself is left alone until it's saved
+/// or `migrate` runs. Returns the data and the version it was written with.
+pub async fn load(path: &Path, version: Option<u32>) -> Result<(Vec<u8>, u32)> {
+    let data = fs::read(path).await?;
+    let Some(migrated) = migrate(FileFormat::from_path(path), &data, version)? else {
+        return Ok((data, version.unwrap_or(SCHEMA_VERSION)));
+    };
+
+    if version.is_none() {
+        info!(
+            "{} uses schema version {}, run `rbx_products migrate` to upgrade it to {}",
+            path.display(),
+            migrated.from,
+            SCHEMA_VERSION
+        );
+    }
+    for note in &migrated.notes {
+        debug!("{}: {}", path.display(), note);
+    }
+
+    Ok((migrated.data.into_bytes(), migrated.from))
+}
+
+/// Writes the products file and its includes back in the current schema.
+pub async fn migrate_products() -> Result<()> {
+    let products = VCSProducts::get_products().await?;
+    let from = products.file_version.unwrap_or(SCHEMA_VERSION);
+    if from == SCHEMA_VERSION {
+        info!(
+            "products file is already at schema version {}",
+            SCHEMA_VERSION
+        );
+        return Ok(());
+    }
+
+    products.save_products().await?;
+    info!(
+        "migrated products file from schema version {} to {}",
+        from, SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_old_keys_and_records_the_version() {
+        let source = r#"[metadata]
+universe-id = 1
+
+# Shown in game
+[gamepasses.vip]
+name = "VIP"
+exclude-from-luau = true
+"#;
+
+        let migrated = migrate(FileFormat::Toml, source.as_bytes(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(migrated.from, 1);
+        assert_eq!(
+            migrated.notes,
+            ["renamed exclude-from-luau to internal (1 product(s))"]
+        );
+        assert!(migrated.data.starts_with("schema-version = 2\n"));
+        assert!(migrated.data.contains("# Shown in game\n[gamepasses.vip]"));
+        assert!(migrated.data.contains("internal = true"));
+        assert!(!migrated.data.contains("exclude-from-luau"));
+
+        assert!(
+            migrate(FileFormat::Toml, migrated.data.as_bytes(), None)
+                .unwrap()
+                .is_none()
+        );
+
+        let json = r#"{"products": {"coins": {"exclude-from-luau": true}}}"#;
+        let migrated = migrate(FileFormat::Json, json.as_bytes(), Some(1))
+            .unwrap()
+            .unwrap();
+        let value: Value = serde_json::from_str(&migrated.data).unwrap();
+        assert_eq!(value["products"]["coins"]["internal"], true);
+        assert!(value.get("schema-version").is_none());
+    }
+
+    #[tokio::test]
+    async fn upgrades_in_memory_until_saved() {
+        let dir = std::env::temp_dir().join(format!("rbx-products-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("products.toml");
+        let source = "[metadata]\nuniverse-id = 1\n\n[gamepasses.vip]\nname = \"VIP\"\nexclude-from-luau = true\n";
+        fs::write(&path, source).await.unwrap();
+
+        let (data, from) = load(&path, None).await.unwrap();
+        assert_eq!(from, 1);
+        assert!(String::from_utf8(data).unwrap().contains("internal = true"));
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), source);
+
+        let mut document: DocumentMut = source.parse().unwrap();
+        upgrade_document(&mut document, None).unwrap();
+        assert!(document.to_string().contains("internal = true"));
+        assert!(!document.to_string().contains("exclude-from-luau"));
+
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_files() {
+        assert!(migrate(FileFormat::Toml, b"schema-version = 99\n", None).is_err());
+    }
+}
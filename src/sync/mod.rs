@@ -1,3 +1,45 @@
+pub mod add;
+pub mod adopt;
+pub mod base;
+pub mod browse;
+pub mod catalog;
+pub mod censor;
+pub mod clone;
+pub mod conflict;
+pub mod diff;
 pub mod download;
+pub mod duplicates;
+pub mod fields;
+pub mod filters;
+pub mod github;
+pub mod limits;
+pub mod link;
+pub mod list;
+pub mod lockfile;
+pub mod luau;
+pub mod migrate;
+pub mod monetization;
+pub mod notify;
+pub mod open;
+pub mod orphans;
+pub mod plan;
+pub mod plugins;
+pub mod prices;
 pub mod products;
+pub mod project;
+pub mod rename;
+pub mod reorder;
+pub mod report;
+pub mod revenue;
+pub mod sale;
+pub mod show;
+pub mod spreadsheet;
+pub mod strict;
+pub mod transform;
+pub mod typescript;
+pub mod undo;
+pub mod update;
 pub mod upload;
+pub mod validate;
+pub mod whoami;
+pub mod workspace;
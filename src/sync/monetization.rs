@@ -0,0 +1,183 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use crate::api::universe::{
+    fetch_configuration, fetch_universe, update_paid_access, update_private_server_price,
+};
+use crate::api::website_cookie;
+
+/// Universe-level prices. Each is only managed when it's set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Monetization {
+    /// Robux per month for a private server, which also enables them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_server_price: Option<u64>,
+    /// Robux to join the experience, or 0 to make it free
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paid_access_price: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    PrivateServerPrice,
+    PaidAccessPrice,
+}
+
+impl Setting {
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::PrivateServerPrice => "private server price",
+            Setting::PaidAccessPrice => "paid access price",
+        }
+    }
+
+    /// The key under `[metadata.monetization]`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Setting::PrivateServerPrice => "private-server-price",
+            Setting::PaidAccessPrice => "paid-access-price",
+        }
+    }
+}
+
+/// A universe price that differs from the products file. `None` means the
+/// feature is off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    pub setting: Setting,
+    pub remote: Option<u64>,
+    pub local: Option<u64>,
+}
+
+fn price(price: Option<u64>) -> String {
+    price.map_or("off".to_string(), |price| price.to_string())
+}
+
+impl SettingChange {
+    pub fn remote_value(&self) -> String {
+        price(self.remote)
+    }
+
+    pub fn local_value(&self) -> String {
+        price(self.local)
+    }
+}
+
+impl fmt::Display for SettingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let robux = |price: Option<u64>| price.map_or("off".to_string(), |p| format!("{} R$", p));
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.setting.label(),
+            robux(self.remote),
+            robux(self.local)
+        )
+    }
+}
+
+/// The universe's current prices, for the settings the products file manages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteMonetization {
+    pub private_server_price: Option<u64>,
+    pub paid_access_price: Option<u64>,
+}
+
+fn cookie() -> Result<String> {
+    website_cookie("manage paid access")
+}
+
+impl Monetization {
+    /// Fetches the current value of every managed setting.
+    pub async fn fetch_remote(&self, universe_id: u64) -> Result<RemoteMonetization> {
+        let mut remote = RemoteMonetization::default();
+
+        if self.private_server_price.is_some() {
+            remote.private_server_price = fetch_universe(universe_id)
+                .await?
+                .private_server_price_robux;
+        }
+
+        if self.paid_access_price.is_some() {
+            let configuration = fetch_configuration(universe_id, &cookie()?).await?;
+            remote.paid_access_price = configuration.price.filter(|_| configuration.is_for_sale);
+        }
+
+        Ok(remote)
+    }
+
+    /// The settings that differ from the universe.
+    pub fn changes(&self, remote: &RemoteMonetization) -> Vec<SettingChange> {
+        let mut changes = vec![];
+
+        if let Some(price) = self.private_server_price
+            && remote.private_server_price != Some(price)
+        {
+            changes.push(SettingChange {
+                setting: Setting::PrivateServerPrice,
+                remote: remote.private_server_price,
+                local: Some(price),
+            });
+        }
+
+        if let Some(price) = self.paid_access_price {
+            let local = Some(price).filter(|price| *price > 0);
+            if remote.paid_access_price != local {
+                changes.push(SettingChange {
+                    setting: Setting::PaidAccessPrice,
+                    remote: remote.paid_access_price,
+                    local,
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+pub async fn apply_change(universe_id: u64, change: &SettingChange) -> Result<()> {
+    match change.setting {
+        Setting::PrivateServerPrice => {
+            let price = change.local.ok_or("private servers can't be turned off")?;
+            update_private_server_price(universe_id, price).await
+        }
+        Setting::PaidAccessPrice => update_paid_access(universe_id, &cookie()?, change.local).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_managed_prices_only() {
+        let remote = RemoteMonetization {
+            private_server_price: None,
+            paid_access_price: Some(25),
+        };
+
+        assert!(Monetization::default().changes(&remote).is_empty());
+
+        let local = Monetization {
+            private_server_price: Some(100),
+            paid_access_price: Some(0),
+        };
+        let changes = local.changes(&remote);
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "private server price: off -> 100 R$",
+                "paid access price: 25 R$ -> off",
+            ]
+        );
+
+        let local = Monetization {
+            paid_access_price: Some(25),
+            ..Default::default()
+        };
+        assert!(local.changes(&remote).is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::ErrorCode;
+use crate::api::webhooks::post_webhook;
+use crate::sync::products::ProductType;
+
+const DEFAULT_TEMPLATE: &str = "Synced {count} product change(s):\n{changes}";
+const DEFAULT_ITEM_TEMPLATE: &str = "- {action} {type} '{name}': {old_price} -> {new_price} R$";
+
+/// Discord rejects messages longer than this.
+const DISCORD_MAX_LENGTH: usize = 2000;
+
+/// Shape of the request body sent to a webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookFormat {
+    /// The rendered message plus the changes and failures as JSON
+    Json,
+    /// A Discord webhook message
+    Discord,
+    /// A Slack incoming webhook message
+    Slack,
+}
+
+/// A single webhook that receives a rendered message after a sync.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NotificationTarget {
+    pub url: String,
+    /// Message template, supports `{count}`, `{campaign}`, `{changes}`,
+    /// `{failed}` and `{failures}`
+    pub template: Option<String>,
+    /// Template for each line of `{changes}`, supports `{action}`, `{type}`,
+    /// `{key}`, `{name}`, `{old_price}`, `{new_price}` and `{discount}`
+    pub item_template: Option<String>,
+    /// Payload format, guessed from the URL when unset
+    pub format: Option<WebhookFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+}
+
+/// What a sync actually changed remotely, used to fill in notification templates.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChangeSummary {
+    pub action: ChangeAction,
+    pub product_type: ProductType,
+    pub key: String,
+    pub name: String,
+    pub old_price: Option<u64>,
+    pub new_price: Option<u64>,
+    pub discount: Option<u8>,
+}
+
+/// A planned change that the API rejected, with a stable failure category.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FailureSummary {
+    pub code: ErrorCode,
+    pub product_type: ProductType,
+    pub key: String,
+    pub message: String,
+}
+
+fn render(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |out, (name, value)| {
+            out.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+fn format_price(price: Option<u64>) -> String {
+    price.map_or("-".to_string(), |p| p.to_string())
+}
+
+impl ChangeSummary {
+    fn render(&self, template: &str) -> String {
+        let action = match self.action {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+        };
+
+        let product_type = match self.product_type {
+            ProductType::GamePass => "gamepass",
+            ProductType::DevProduct => "product",
+        };
+
+        render(
+            template,
+            &[
+                ("action", action.to_string()),
+                ("type", product_type.to_string()),
+                ("key", self.key.clone()),
+                ("name", self.name.clone()),
+                ("old_price", format_price(self.old_price)),
+                ("new_price", format_price(self.new_price)),
+                ("discount", self.discount.unwrap_or(0).to_string()),
+            ],
+        )
+    }
+}
+
+impl NotificationTarget {
+    pub fn format(&self) -> WebhookFormat {
+        if let Some(format) = self.format {
+            return format;
+        }
+
+        let host = reqwest::Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+
+        if host == "discord.com" || host.ends_with(".discord.com") || host == "discordapp.com" {
+            WebhookFormat::Discord
+        } else if host == "hooks.slack.com" {
+            WebhookFormat::Slack
+        } else {
+            WebhookFormat::Json
+        }
+    }
+
+    fn payload(
+        &self,
+        changes: &[ChangeSummary],
+        failures: &[FailureSummary],
+        campaign: Option<&str>,
+    ) -> serde_json::Value {
+        let message = self.render(changes, failures, campaign);
+
+        match self.format() {
+            WebhookFormat::Json => serde_json::json!({
+                "message": message,
+                "campaign": campaign,
+                "changes": changes,
+                "failures": failures,
+            }),
+            WebhookFormat::Discord => {
+                let content = if message.chars().count() > DISCORD_MAX_LENGTH {
+                    let mut truncated = message
+                        .chars()
+                        .take(DISCORD_MAX_LENGTH - 1)
+                        .collect::<String>();
+                    truncated.push('…');
+                    truncated
+                } else {
+                    message
+                };
+
+                serde_json::json!({ "content": content })
+            }
+            WebhookFormat::Slack => serde_json::json!({ "text": message }),
+        }
+    }
+
+    pub fn render(
+        &self,
+        changes: &[ChangeSummary],
+        failures: &[FailureSummary],
+        campaign: Option<&str>,
+    ) -> String {
+        let item_template = self
+            .item_template
+            .as_deref()
+            .unwrap_or(DEFAULT_ITEM_TEMPLATE);
+
+        let lines = changes
+            .iter()
+            .map(|change| change.render(item_template))
+            .collect::<Vec<_>>();
+
+        render(
+            self.template.as_deref().unwrap_or(DEFAULT_TEMPLATE),
+            &[
+                ("count", changes.len().to_string()),
+                ("campaign", campaign.unwrap_or_default().to_string()),
+                ("changes", lines.join("\n")),
+                ("failed", failures.len().to_string()),
+                (
+                    "failures",
+                    failures
+                        .iter()
+                        .map(|f| format!("- [{}] {}: {}", f.code, f.key, f.message))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+            ],
+        )
+    }
+}
+
+/// Posts the rendered summary to every configured target. Failures are logged
+/// rather than returned, since the sync itself already succeeded.
+pub async fn notify_all(
+    targets: &BTreeMap<String, NotificationTarget>,
+    changes: &[ChangeSummary],
+    failures: &[FailureSummary],
+    campaign: Option<&str>,
+) {
+    if changes.is_empty() && failures.is_empty() {
+        return;
+    }
+
+    for (name, target) in targets {
+        let payload = target.payload(changes, failures, campaign);
+
+        match post_webhook(&target.url, &payload).await {
+            Ok(()) => info!("sent notification '{}'", name),
+            Err(e) => warn!("failed to send notification '{}': {}", name, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_templates() {
+        let target = NotificationTarget {
+            url: String::new(),
+            template: Some(
+                "{campaign} sale! {count} deals:\n{changes}\n{failed} failed:\n{failures}"
+                    .to_string(),
+            ),
+            item_template: Some("{name} now {new_price} R$ (was {old_price})".to_string()),
+            format: None,
+        };
+
+        let changes = [ChangeSummary {
+            action: ChangeAction::Updated,
+            product_type: ProductType::GamePass,
+            key: "vip".to_string(),
+            name: "VIP".to_string(),
+            old_price: Some(100),
+            new_price: Some(80),
+            discount: Some(20),
+        }];
+
+        let failures = [FailureSummary {
+            code: ErrorCode::Moderation,
+            product_type: ProductType::DevProduct,
+            key: "coins".to_string(),
+            message: "name was moderated".to_string(),
+        }];
+
+        assert_eq!(
+            target.render(&changes, &failures, Some("Summer")),
+            "Summer sale! 1 deals:\nVIP now 80 R$ (was 100)\n1 failed:\n- [moderation] coins: name was moderated"
+        );
+    }
+
+    #[test]
+    fn formats_payload_for_chat_webhooks() {
+        let target = |url: &str| NotificationTarget {
+            url: url.to_string(),
+            template: Some("{count} change(s)".to_string()),
+            ..Default::default()
+        };
+
+        let discord = target("https://discord.com/api/webhooks/1/abc");
+        assert_eq!(discord.format(), WebhookFormat::Discord);
+        assert_eq!(
+            discord.payload(&[], &[], None),
+            serde_json::json!({ "content": "0 change(s)" })
+        );
+
+        let slack = target("https://hooks.slack.com/services/T/B/X");
+        assert_eq!(
+            slack.payload(&[], &[], None),
+            serde_json::json!({ "text": "0 change(s)" })
+        );
+
+        let mut generic = target("https://example.com/hook");
+        assert_eq!(generic.format(), WebhookFormat::Json);
+        generic.format = Some(WebhookFormat::Slack);
+        assert_eq!(generic.format(), WebhookFormat::Slack);
+    }
+}
@@ -0,0 +1,172 @@
+use std::process::Command;
+
+use log::info;
+
+use crate::Result;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+
+/// Finds a product by key, or by one of its old keys, in either product type.
+fn resolve<'a>(
+    local_products: &'a VCSProducts,
+    key: &str,
+    product_type: Option<ProductType>,
+) -> Result<(ProductType, &'a String, &'a Product)> {
+    let mut matches = vec![];
+
+    for (candidate_type, entries) in [
+        (ProductType::GamePass, &local_products.gamepasses),
+        (ProductType::DevProduct, &local_products.products),
+    ] {
+        if product_type.is_some_and(|product_type| product_type != candidate_type) {
+            continue;
+        }
+
+        let found = entries.get_key_value(key).or_else(|| {
+            entries.iter().find(|(_, product)| {
+                product
+                    .aliases
+                    .as_ref()
+                    .is_some_and(|aliases| aliases.iter().any(|alias| alias == key))
+            })
+        });
+
+        if let Some((key, product)) = found {
+            matches.push((candidate_type, key, product));
+        }
+    }
+
+    match matches.as_slice() {
+        [] => Err(format!("no product with key '{}'", key).into()),
+        [found] => Ok(*found),
+        _ => Err(format!(
+            "'{}' is both a gamepass and a product, pass --type to pick one",
+            key
+        )
+        .into()),
+    }
+}
+
+/// The Creator Hub page for a product, or its public store page with `store`.
+fn product_url(
+    universe_id: u64,
+    product_type: ProductType,
+    id: u64,
+    store: bool,
+) -> Result<String> {
+    Ok(match (product_type, store) {
+        (ProductType::GamePass, false) => format!(
+            "https://create.roblox.com/dashboard/creations/experiences/{}/passes/{}/configure",
+            universe_id, id
+        ),
+        (ProductType::DevProduct, false) => format!(
+            "https://create.roblox.com/dashboard/creations/experiences/{}/developer-products/{}/configure",
+            universe_id, id
+        ),
+        (ProductType::GamePass, true) => format!("https://www.roblox.com/game-pass/{}", id),
+        (ProductType::DevProduct, true) => {
+            return Err("developer products don't have a store page".into());
+        }
+    })
+}
+
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", url]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+
+    command.spawn().map(|_| ())
+}
+
+/// Opens a product's configuration page in the browser. With `print`, the URL
+/// is written to stdout instead, e.g. for terminals without a browser.
+pub async fn open_product(
+    key: String,
+    product_type: Option<ProductType>,
+    store: bool,
+    print: bool,
+) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+    let (product_type, key, product) = resolve(&local_products, &key, product_type)?;
+
+    let id = product.id.ok_or_else(|| {
+        format!(
+            "{:?} '{}' hasn't been uploaded yet, so it has no page",
+            product_type, key
+        )
+    })?;
+    let url = product_url(local_products.metadata.universe_id, product_type, id, store)?;
+
+    if print {
+        println!("{}", url);
+        return Ok(());
+    }
+
+    info!("opening {}", url);
+    open_in_browser(&url).map_err(|e| format!("failed to open a browser ({}): {}", e, url).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_products() -> VCSProducts {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(7),
+                aliases: Some(vec!["vip-pass".to_string()]),
+                ..Default::default()
+            },
+        );
+        local_products.products.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(8),
+                ..Default::default()
+            },
+        );
+        local_products
+    }
+
+    #[test]
+    fn resolves_keys_and_aliases() {
+        let local_products = local_products();
+
+        assert!(resolve(&local_products, "vip", None).is_err());
+        assert_eq!(
+            resolve(&local_products, "vip", Some(ProductType::DevProduct))
+                .unwrap()
+                .2
+                .id,
+            Some(8)
+        );
+
+        let (product_type, key, _) = resolve(&local_products, "vip-pass", None).unwrap();
+        assert_eq!(product_type, ProductType::GamePass);
+        assert_eq!(key, "vip");
+    }
+
+    #[test]
+    fn builds_creator_hub_and_store_urls() {
+        assert_eq!(
+            product_url(1, ProductType::DevProduct, 8, false).unwrap(),
+            "https://create.roblox.com/dashboard/creations/experiences/1/developer-products/8/configure"
+        );
+        assert_eq!(
+            product_url(1, ProductType::GamePass, 7, true).unwrap(),
+            "https://www.roblox.com/game-pass/7"
+        );
+        assert!(product_url(1, ProductType::DevProduct, 8, true).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use clap::Args;
+use log::info;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::adopt::local_copy;
+use crate::sync::duplicates::free_key;
+use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts};
+use crate::utils::render_table;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct OrphansOptions {
+    /// Add the orphans to the products file
+    #[arg(long, conflicts_with = "ignore")]
+    pub adopt: bool,
+    /// Record the orphans in `ignored-ids` so sync stops warning about them
+    #[arg(long)]
+    pub ignore: bool,
+    /// Only adopt or ignore these ids, instead of every orphan
+    pub ids: Vec<u64>,
+}
+
+/// Remote products whose ids aren't in the products file or its ignore list.
+pub fn find_orphans<'a>(
+    local_products: &VCSProducts,
+    remote_products: &'a [MultiProduct],
+) -> Vec<(ProductType, &'a Product)> {
+    let ignored = local_products
+        .metadata
+        .ignored_ids
+        .clone()
+        .unwrap_or_default();
+
+    remote_products
+        .iter()
+        .map(|multi_product| match multi_product {
+            MultiProduct::GamePass(pass) => (ProductType::GamePass, pass),
+            MultiProduct::DevProduct(prod) => (ProductType::DevProduct, prod),
+        })
+        .filter(|(product_type, product)| {
+            product.id.is_some_and(|id| {
+                !ignored.contains(&id)
+                    && !local_products
+                        .products_of(*product_type)
+                        .values()
+                        .any(|local| local.id == Some(id))
+            })
+        })
+        .collect()
+}
+
+/// Adds a remote product under a key derived from its name, with a number
+/// appended if the key is taken. Returns the key.
+fn adopt(local_products: &mut VCSProducts, product_type: ProductType, remote: &Product) -> String {
+    let product = local_copy(remote, &local_products.metadata.filters());
+
+    let key = local_products.metadata.key_for(&product.name);
+
+    let entries = local_products.products_of_mut(product_type);
+    let key = free_key(entries, &key);
+    entries.insert(key.clone(), product);
+
+    key
+}
+
+/// Lists remote products the products file doesn't manage, optionally adding
+/// them to it or ignoring them from now on.
+pub async fn list_orphans(options: OrphansOptions) -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+
+    info!("fetching remote products");
+    let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+
+    let orphans = find_orphans(&local_products, &remote_products)
+        .into_iter()
+        .filter(|(_, product)| {
+            options.ids.is_empty() || product.id.is_some_and(|id| options.ids.contains(&id))
+        })
+        .map(|(product_type, product)| (product_type, product.clone()))
+        .collect::<Vec<_>>();
+
+    if orphans.is_empty() {
+        info!("no remote products are missing from the products file");
+        return Ok(());
+    }
+
+    if options.adopt {
+        for (product_type, product) in &orphans {
+            let key = adopt(&mut local_products, *product_type, product);
+            info!("adopted {:?} '{}' as '{}'", product_type, product.name, key);
+        }
+    } else if options.ignore {
+        let ignored = local_products.metadata.ignored_ids.get_or_insert_default();
+        ignored.extend(orphans.iter().filter_map(|(_, product)| product.id));
+        ignored.sort_unstable();
+        ignored.dedup();
+        info!("ignoring {} remote product(s)", orphans.len());
+    } else {
+        let rows = orphans
+            .iter()
+            .map(|(product_type, product)| {
+                vec![
+                    format!("{:?}", product_type),
+                    product.id.unwrap_or(0).to_string(),
+                    product.name.clone(),
+                    product.price.to_string(),
+                    product.active.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        print!(
+            "{}",
+            render_table(&["type", "id", "name", "price", "active"], &rows)
+        );
+        info!("pass --adopt to add them to the products file, or --ignore to stop tracking them");
+        return Ok(());
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(id: u64, name: &str) -> Product {
+        Product {
+            id: Some(id),
+            name: name.to_string(),
+            price: 100,
+            active: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_untracked_and_skips_ignored() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("vip".to_string(), remote(1, "VIP"));
+        local_products.metadata.ignored_ids = Some(vec![3]);
+
+        let remote_products = vec![
+            MultiProduct::GamePass(remote(1, "VIP")),
+            MultiProduct::GamePass(remote(2, "Admin")),
+            MultiProduct::DevProduct(remote(3, "Old Coins")),
+            MultiProduct::DevProduct(remote(1, "Coins")),
+        ];
+
+        let orphans = find_orphans(&local_products, &remote_products)
+            .into_iter()
+            .map(|(product_type, product)| (product_type, product.id.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            orphans,
+            vec![(ProductType::GamePass, 2), (ProductType::DevProduct, 1)]
+        );
+    }
+
+    #[test]
+    fn adopts_under_a_free_key() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("vip".to_string(), Product::default());
+
+        let key = adopt(
+            &mut local_products,
+            ProductType::GamePass,
+            &remote(5, "VIP"),
+        );
+
+        assert_eq!(key, "vip-2");
+        assert_eq!(local_products.gamepasses[&key].id, Some(5));
+        assert_eq!(local_products.gamepasses[&key].price, 100);
+    }
+}
@@ -0,0 +1,251 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::Result;
+use crate::api::model::ProductUpdateRequest;
+use crate::sync::products::{MultiProduct, ProductType};
+
+/// A reviewable list of the remote writes a sync would perform. Produced by
+/// `Uploader::plan` and executed by `Uploader::apply`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyncPlan {
+    pub universe_id: u64,
+    /// Hash of the remote products the plan was computed against
+    pub remote_hash: String,
+    /// Unix timestamp (seconds) after which the plan must not be applied
+    pub expires_at: Option<u64>,
+    pub changes: Vec<PlannedChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum PlannedChange {
+    #[serde(rename_all = "kebab-case")]
+    Create {
+        product_type: ProductType,
+        key: String,
+        request: ProductUpdateRequest,
+    },
+    #[serde(rename_all = "kebab-case")]
+    Update {
+        product_type: ProductType,
+        key: String,
+        id: u64,
+        request: ProductUpdateRequest,
+    },
+}
+
+impl PlannedChange {
+    pub fn product_type(&self) -> ProductType {
+        match self {
+            PlannedChange::Create { product_type, .. }
+            | PlannedChange::Update { product_type, .. } => *product_type,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            PlannedChange::Create { key, .. } | PlannedChange::Update { key, .. } => key,
+        }
+    }
+}
+
+impl SyncPlan {
+    pub fn new(universe_id: u64) -> Self {
+        Self {
+            universe_id,
+            remote_hash: String::new(),
+            expires_at: None,
+            changes: vec![],
+        }
+    }
+
+    pub fn with_expiry(mut self, ttl: Duration) -> Self {
+        self.expires_at = Some((unix_now() + ttl).as_secs());
+        self
+    }
+
+    /// Refuses plans that have expired or whose remote state no longer matches
+    /// the universe, so an old plan can't be applied over newer changes.
+    pub fn verify(&self, remote_products: &[MultiProduct]) -> Result<()> {
+        if let Some(expires_at) = self.expires_at
+            && unix_now().as_secs() > expires_at
+        {
+            return Err("plan has expired, run `plan` again to refresh it".into());
+        }
+
+        if self.remote_hash != remote_state_hash(remote_products) {
+            return Err(
+                "remote products have changed since the plan was created, run `plan` again".into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fails if the plan would make more than `max_changes` modifications.
+    pub fn check_budget(&self, max_changes: Option<usize>) -> Result<()> {
+        match max_changes {
+            Some(max) if self.changes.len() > max => Err(format!(
+                "sync would make {} change(s), exceeding --max-changes {}",
+                self.changes.len(),
+                max
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn creates(&self) -> usize {
+        self.changes
+            .iter()
+            .filter(|c| matches!(c, PlannedChange::Create { .. }))
+            .count()
+    }
+
+    pub fn updates(&self) -> usize {
+        self.changes.len() - self.creates()
+    }
+
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read(path).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Hashes the remote products in a stable order, independent of the order the
+/// API returned them in.
+pub fn remote_state_hash(remote_products: &[MultiProduct]) -> String {
+    let mut entries = remote_products
+        .iter()
+        .map(|multi_product| match multi_product {
+            MultiProduct::GamePass(product) => (ProductType::GamePass, product),
+            MultiProduct::DevProduct(product) => (ProductType::DevProduct, product),
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by_key(|(product_type, product)| (*product_type, product.id));
+
+    let mut hasher = Sha256::new();
+    for (product_type, product) in entries {
+        hasher.update(serde_json::to_vec(&(product_type, product)).unwrap_or_default());
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::Product;
+
+    fn remote(id: u64, price: i64) -> MultiProduct {
+        MultiProduct::GamePass(Product {
+            id: Some(id),
+            name: format!("pass {}", id),
+            price,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn remote_hash_ignores_order() {
+        let a = remote_state_hash(&[remote(1, 10), remote(2, 20)]);
+        let b = remote_state_hash(&[remote(2, 20), remote(1, 10)]);
+
+        assert_eq!(a, b);
+        assert_ne!(a, remote_state_hash(&[remote(1, 10), remote(2, 25)]));
+    }
+
+    #[test]
+    fn verify_rejects_drift_and_expiry() {
+        let products = [remote(1, 10)];
+        let mut plan = SyncPlan::new(1).with_expiry(Duration::from_secs(60));
+        plan.remote_hash = remote_state_hash(&products);
+
+        assert!(plan.verify(&products).is_ok());
+        assert!(plan.verify(&[remote(1, 15)]).is_err());
+
+        plan.expires_at = Some(0);
+        assert!(plan.verify(&products).is_err());
+    }
+
+    #[test]
+    fn budget_caps_the_number_of_changes() {
+        let update = |id| PlannedChange::Update {
+            product_type: ProductType::GamePass,
+            key: format!("pass-{}", id),
+            id,
+            request: ProductUpdateRequest::default(),
+        };
+        let mut plan = SyncPlan::new(1);
+        plan.changes = vec![update(1), update(2), update(3)];
+
+        assert!(plan.check_budget(None).is_ok());
+        assert!(plan.check_budget(Some(3)).is_ok());
+
+        let error = plan.check_budget(Some(2)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "sync would make 3 change(s), exceeding --max-changes 2"
+        );
+        assert!(SyncPlan::new(1).check_budget(Some(0)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn saved_plans_load_unchanged() {
+        let mut plan = SyncPlan::new(7).with_expiry(Duration::from_secs(60));
+        plan.remote_hash = remote_state_hash(&[remote(1, 10)]);
+        plan.changes = vec![
+            PlannedChange::Create {
+                product_type: ProductType::DevProduct,
+                key: "coins".to_string(),
+                request: ProductUpdateRequest {
+                    name: "Coins".to_string(),
+                    price: Some(25),
+                    ..Default::default()
+                },
+            },
+            PlannedChange::Update {
+                product_type: ProductType::GamePass,
+                key: "vip".to_string(),
+                id: 1,
+                request: ProductUpdateRequest {
+                    name: "VIP".to_string(),
+                    price: Some(15),
+                    ..Default::default()
+                },
+            },
+        ];
+
+        let path =
+            std::env::temp_dir().join(format!("rbx-products-plan-{}.json", std::process::id()));
+        plan.save(&path).await.unwrap();
+        let loaded = SyncPlan::load(&path).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded, plan);
+        assert_eq!((loaded.creates(), loaded.updates()), (1, 1));
+        assert_eq!(loaded.changes[1].key(), "vip");
+    }
+}
@@ -0,0 +1,284 @@
+use std::process::Stdio;
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::Result;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::sync::validate::uploaded_title;
+
+/// Points in a sync where plugins are asked for changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hook {
+    /// Rewrite the title each product is uploaded with
+    Name,
+    /// Rewrite the price each product is uploaded with
+    Price,
+    /// Reject products before anything is uploaded
+    Validate,
+}
+
+/// An external program listed under `[[metadata.plugins]]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Plugin {
+    /// Program to run, relative to the working directory or on the PATH
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    pub hooks: Vec<Hook>,
+}
+
+/// A product as plugins receive it.
+#[derive(Debug, Serialize)]
+struct PluginProduct<'a> {
+    #[serde(rename = "type")]
+    product_type: ProductType,
+    key: &'a str,
+    /// The title as it would be uploaded, prefixes included
+    title: String,
+    /// The price as it would be uploaded, after discounts and rounding
+    price: u64,
+    product: &'a Product,
+}
+
+#[derive(Debug, Serialize)]
+struct PluginInput<'a> {
+    hook: Hook,
+    products: Vec<PluginProduct<'a>>,
+}
+
+/// A product a plugin changed. Fields a hook doesn't use are ignored.
+#[derive(Debug, Deserialize)]
+struct PluginChange {
+    #[serde(rename = "type")]
+    product_type: ProductType,
+    key: String,
+    title: Option<String>,
+    price: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginError {
+    #[serde(rename = "type")]
+    product_type: Option<ProductType>,
+    key: Option<String>,
+    message: String,
+}
+
+/// What a plugin prints. Products it leaves out keep their values.
+#[derive(Debug, Default, Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    products: Vec<PluginChange>,
+    #[serde(default)]
+    errors: Vec<PluginError>,
+}
+
+fn input(local_products: &VCSProducts, hook: Hook) -> PluginInput<'_> {
+    let metadata = &local_products.metadata;
+    let products = [ProductType::GamePass, ProductType::DevProduct]
+        .into_iter()
+        .flat_map(|product_type| {
+            local_products
+                .products_of(product_type)
+                .iter()
+                .map(move |(key, product)| PluginProduct {
+                    product_type,
+                    key,
+                    title: uploaded_title(product, metadata.discount_prefix.clone()),
+                    price: product.get_price(Some(metadata)),
+                    product,
+                })
+        })
+        .collect();
+
+    PluginInput { hook, products }
+}
+
+impl Plugin {
+    /// Runs the plugin with the products as JSON on stdin, and reads its
+    /// changes as JSON from stdout. Its stderr goes straight to the terminal.
+    async fn run(&self, input: &PluginInput<'_>) -> Result<PluginOutput> {
+        debug!(
+            "running plugin '{}' for the {:?} hook",
+            self.command, input.hook
+        );
+
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("failed to start plugin '{}': {}", self.command, e))?;
+
+        let mut stdin = child.stdin.take().ok_or("plugin stdin is unavailable")?;
+        stdin.write_all(&serde_json::to_vec(input)?).await?;
+        drop(stdin);
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(format!("plugin '{}' failed with {}", self.command, output.status).into());
+        }
+
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Ok(PluginOutput::default());
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("plugin '{}' printed invalid JSON: {}", self.command, e).into())
+    }
+}
+
+fn plugins_for(local_products: &VCSProducts, hook: Hook) -> Vec<Plugin> {
+    local_products
+        .metadata
+        .plugins
+        .iter()
+        .flatten()
+        .filter(|plugin| plugin.hooks.contains(&hook))
+        .cloned()
+        .collect()
+}
+
+fn apply_output(local_products: &mut VCSProducts, hook: Hook, output: PluginOutput) -> Result<()> {
+    for change in output.products {
+        let product = local_products
+            .products_of_mut(change.product_type)
+            .get_mut(&change.key)
+            .ok_or_else(|| {
+                format!(
+                    "plugin changed {:?} '{}', which isn't in the products file",
+                    change.product_type, change.key
+                )
+            })?;
+
+        match hook {
+            Hook::Name => product.plugin_title = change.title.or(product.plugin_title.take()),
+            Hook::Price => product.plugin_price = change.price.or(product.plugin_price),
+            Hook::Validate => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the name and price plugins in the order they're listed. Each one
+/// sees the titles and prices the ones before it chose. Nothing is written
+/// back to the products file.
+pub async fn apply_transforms(local_products: &mut VCSProducts) -> Result<()> {
+    for hook in [Hook::Name, Hook::Price] {
+        for plugin in plugins_for(local_products, hook) {
+            let output = plugin.run(&input(local_products, hook)).await?;
+            apply_output(local_products, hook, output)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the validate plugins, logging every error they report and failing if
+/// there were any.
+pub async fn validate(local_products: &VCSProducts) -> Result<()> {
+    let mut errors = 0;
+
+    for plugin in plugins_for(local_products, Hook::Validate) {
+        let output = plugin.run(&input(local_products, Hook::Validate)).await?;
+
+        for e in &output.errors {
+            match (e.product_type, &e.key) {
+                (Some(product_type), Some(key)) => {
+                    error!("{:?} '{}': {}", product_type, key, e.message)
+                }
+                (_, Some(key)) => error!("'{}': {}", key, e.message),
+                _ => error!("{}", e.message),
+            }
+        }
+        errors += output.errors.len();
+    }
+
+    match errors {
+        0 => Ok(()),
+        errors => Err(format!("plugins reported {} problem(s)", errors).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_products() -> VCSProducts {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                name: "VIP".to_string(),
+                price: 100,
+                active: true,
+                discount: Some(50),
+                ..Default::default()
+            },
+        );
+        local_products
+    }
+
+    #[test]
+    fn overrides_uploaded_titles_and_prices() {
+        let mut local_products = local_products();
+        let output: PluginOutput = serde_json::from_str(
+            r#"{"products": [{"type": "game-pass", "key": "vip", "title": "[VIP] Room", "price": 45}]}"#,
+        )
+        .unwrap();
+        apply_output(&mut local_products, Hook::Name, output).unwrap();
+
+        let vip = &local_products.gamepasses["vip"];
+        assert_eq!(uploaded_title(vip, None), "[VIP] Room");
+        assert_eq!(vip.get_price(None), 50);
+
+        let input = input(&local_products, Hook::Price);
+        assert_eq!(input.products[0].title, "[VIP] Room");
+        assert_eq!(input.products[0].price, 50);
+
+        let output: PluginOutput =
+            serde_json::from_str(r#"{"products": [{"type": "dev-product", "key": "vip"}]}"#)
+                .unwrap();
+        assert!(apply_output(&mut local_products, Hook::Price, output).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_plugins_over_stdin_and_stdout() {
+        let mut local_products = local_products();
+        local_products.metadata.plugins = Some(vec![
+            Plugin {
+                command: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    r#"cat > /dev/null; echo '{"products": [{"type": "game-pass", "key": "vip", "price": 99}]}'"#
+                        .to_string(),
+                ],
+                hooks: vec![Hook::Price],
+            },
+            Plugin {
+                command: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    r#"grep -q '"price":99' && echo '{"errors": [{"message": "too cheap"}]}'"#
+                        .to_string(),
+                ],
+                hooks: vec![Hook::Validate],
+            },
+        ]);
+
+        apply_transforms(&mut local_products).await.unwrap();
+        assert_eq!(local_products.gamepasses["vip"].get_price(None), 99);
+
+        assert_eq!(
+            validate(&local_products).await.unwrap_err().to_string(),
+            "plugins reported 1 problem(s)"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use log::info;
+use serde::Serialize;
+
+use crate::Result;
+use crate::api::products::fetch_all_products;
+use crate::sync::list::ListOutput;
+use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts};
+use crate::utils::{canonical_name, render_csv, render_table};
+
+/// Remote price and regional pricing state of a product, next to what the
+/// products file asks for.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct PriceEntry {
+    key: String,
+    product_type: ProductType,
+    id: Option<u64>,
+    price: i64,
+    regional_pricing: bool,
+    local_regional_pricing: Option<bool>,
+}
+
+const HEADERS: [&str; 6] = [
+    "key",
+    "type",
+    "id",
+    "default price",
+    "regional pricing",
+    "local regional pricing",
+];
+
+impl PriceEntry {
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.key.clone(),
+            format!("{:?}", self.product_type),
+            self.id.map_or("-".to_string(), |id| id.to_string()),
+            self.price.to_string(),
+            self.regional_pricing.to_string(),
+            self.local_regional_pricing
+                .map_or("-".to_string(), |enabled| enabled.to_string()),
+        ]
+    }
+}
+
+fn price_entries(
+    local_products: &VCSProducts,
+    remote_products: &[MultiProduct],
+    regional_only: bool,
+) -> Vec<PriceEntry> {
+    let filters = &local_products.metadata.filters();
+
+    let mut entries = remote_products
+        .iter()
+        .map(|multi_product| {
+            let (product_type, product, local) = match multi_product {
+                MultiProduct::GamePass(prod) => {
+                    (ProductType::GamePass, prod, &local_products.gamepasses)
+                }
+                MultiProduct::DevProduct(prod) => {
+                    (ProductType::DevProduct, prod, &local_products.products)
+                }
+            };
+
+            let tracked = local.iter().find(|(_, local_product)| {
+                local_product.id.is_some() && local_product.id == product.id
+            });
+
+            PriceEntry {
+                key: tracked.map(|(key, _)| key.clone()).unwrap_or_else(|| {
+                    local_products
+                        .metadata
+                        .key_for(&canonical_name(product.name.clone(), filters))
+                }),
+                product_type,
+                id: product.id,
+                price: product.price,
+                regional_pricing: product.regional_pricing.unwrap_or(false),
+                local_regional_pricing: tracked.map(|(_, local_product): (_, &Product)| {
+                    local_product.regional_pricing.unwrap_or(false)
+                }),
+            }
+        })
+        .filter(|entry| {
+            !regional_only || entry.regional_pricing || entry.local_regional_pricing == Some(true)
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| (a.product_type, &a.key).cmp(&(b.product_type, &b.key)));
+    entries
+}
+
+/// Writes a report of the universe's current prices and regional pricing
+/// state. Roblox computes per-market prices itself and doesn't return them
+/// through Open Cloud, so the report shows the default price each market's
+/// price is derived from.
+pub async fn download_prices(
+    regional_only: bool,
+    format: ListOutput,
+    output: Option<&Path>,
+) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+    let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+
+    let entries = price_entries(&local_products, &remote_products, regional_only);
+
+    let mismatched = entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .local_regional_pricing
+                .is_some_and(|local| local != entry.regional_pricing)
+        })
+        .count();
+
+    if mismatched > 0 {
+        info!(
+            "{} product(s) have regional pricing set differently in the products file, run `sync` to apply it",
+            mismatched
+        );
+    }
+
+    let rows = entries.iter().map(PriceEntry::row).collect::<Vec<_>>();
+
+    let rendered = match format {
+        ListOutput::Table => render_table(&HEADERS, &rows),
+        ListOutput::Csv => render_csv(&HEADERS, &rows)?,
+        ListOutput::Json => serde_json::to_string_pretty(&entries)? + "\n",
+    };
+
+    match output {
+        Some(path) => {
+            tokio::fs::write(path, rendered).await?;
+            info!("wrote price report to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: u64, regional_pricing: Option<bool>) -> Product {
+        Product {
+            id: Some(id),
+            name: format!("Pass {}", id),
+            price: 100,
+            regional_pricing,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn regional_filter_keeps_enabled_and_mismatched() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("tracked".to_string(), product(2, Some(true)));
+
+        let remote = [
+            MultiProduct::GamePass(product(1, Some(true))),
+            MultiProduct::GamePass(product(2, Some(false))),
+            MultiProduct::GamePass(product(3, Some(false))),
+        ];
+
+        let entries = price_entries(&local_products, &remote, true);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.key.as_str(), entry.local_regional_pricing))
+                .collect::<Vec<_>>(),
+            [("pass-1", None), ("tracked", Some(true))]
+        );
+        assert_eq!(price_entries(&local_products, &remote, false).len(), 3);
+    }
+}
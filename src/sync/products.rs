@@ -1,34 +1,104 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-use dyn_fmt::AsStrFormatExt;
+use log::{info, warn};
 use nestify::nest;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tokio::{fs, io::AsyncWriteExt};
-use toml_edit::{Array, Item, Table, Value};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use toml_edit::{Array, InlineTable, Item, Table, Value};
 
-use crate::utils::{deserialize_regex_vec, serialize_regex_vec};
+use crate::api::connection::ConnectionPolicy;
+use crate::api::retry::RetryPolicy;
+use crate::sync::catalog::CatalogItem;
+use crate::sync::censor::DEFAULT_CENSOR_THRESHOLD;
+use crate::sync::fields::FIELDS;
+use crate::sync::luau::render_template;
+use crate::sync::migrate::{self, SCHEMA_VERSION};
+use crate::sync::monetization::Monetization;
+use crate::sync::notify::NotificationTarget;
+use crate::sync::plugins::Plugin;
+use crate::sync::report::RevenueModel;
+use crate::sync::strict;
+use crate::sync::typescript;
+use crate::utils::{NameFilter, SlugOptions, UNICODE_FILTERS, UnicodeKeys, glob_regex};
 use crate::{
     Result,
-    ui::diffs::{DiffChange, ProductDiff, ProductDiffs},
+    ui::diffs::{DiffChange, DiffKind, ProductDiff, ProductDiffs},
 };
 
-type ProductKeyPair<'T> = (&'T String, &'T Product);
+type ProductKeyPair<'a> = (&'a String, &'a Product);
+
+pub type Attributes = BTreeMap<String, serde_json::Value>;
 
 nest! {
     #[derive(Default, Debug, Clone, Serialize, Deserialize)]*
     #[serde(rename_all = "kebab-case")]*
     pub struct VCSProducts {
+        /// Layout version the file was written with, see `migrate`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub schema_version: Option<u32>,
+
         pub metadata: pub struct Metadata {
             pub universe_id: u64,
             pub luau_file: Option<String>,
+            /// Handlebars template used to generate the luau file instead of the built-in layout
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub luau_template: Option<String>,
+            /// Typings for roblox-ts, either a `.d.ts` for the luau module or a standalone `.ts` file
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub typescript_file: Option<String>,
             pub discount_prefix: Option<String>,
-            #[serde(default, deserialize_with = "deserialize_regex_vec", serialize_with = "serialize_regex_vec")]
-            pub name_filters: Option<Vec<Regex>>,
+            #[serde(default)]
+            pub name_filters: Option<Vec<NameFilter>>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub revenue_model: Option<RevenueModel>,
+            /// Discounted prices are rounded to a multiple of this
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub round_to: Option<u64>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub round_style: Option<RoundStyle>,
+            /// Rate limit and backoff budgets for API requests
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub retry: Option<RetryPolicy>,
+            /// Request timeouts and connection pooling
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub connection: Option<ConnectionPolicy>,
+            /// Other files to load products from, relative to this one. File names may use `*` and `?`
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub include: Option<Vec<String>>,
+            /// Remote products that are deliberately left out of this file
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub ignored_ids: Option<Vec<u64>>,
+            /// Fail on keys that aren't part of the schema instead of ignoring them
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub strict: Option<bool>,
+            /// How names are turned into keys
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub slug: Option<SlugOptions>,
+            /// Private server and paid access prices, synced along with the products
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub monetization: Option<Monetization>,
+            /// Share of `#` characters from which a remote description counts as censored
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub censor_threshold: Option<f64>,
+            /// Gamepass count to warn about as the universe approaches it
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub max_gamepasses: Option<usize>,
+            /// Developer product count to warn about as the universe approaches it
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub max_dev_products: Option<usize>,
+            /// External programs that rewrite names and prices or add checks
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub plugins: Option<Vec<Plugin>>,
+            /// Rhai script, relative to this file, that adjusts products before upload
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub transform: Option<String>,
         },
 
         #[serde(default)]
-        pub gamepasses: HashMap<String, pub struct Product {
+        pub gamepasses: BTreeMap<String, pub struct Product {
             pub id: Option<u64>,
             pub name: String,
             pub prefix: Option<String>,
@@ -37,16 +107,292 @@ nest! {
             pub discount: Option<u8>,
             pub price: i64,
             pub regional_pricing: Option<bool>,
+            /// Whether a dev product is listed on the experience's store page
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub store_page_enabled: Option<bool>,
+            /// Position in the in-game shop, unique within a product type
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub order: Option<u32>,
+            /// Free-form game metadata, ignored by the sync but included in generated files
+            #[serde(default, alias = "extra", skip_serializing_if = "Option::is_none")]
+            pub attributes: Option<Attributes>,
+            /// Labels for selecting groups of products in bulk commands and in game
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub tags: Option<Vec<String>>,
+            /// Nested table the product is grouped under in the generated files
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub category: Option<String>,
+            /// Previous keys of this product, kept so downloads don't re-import them
+            pub aliases: Option<Vec<String>>,
+            /// Still synced, but left out of the generated Luau and TypeScript files
+            #[serde(default, alias = "exclude-from-luau", skip_serializing_if = "Option::is_none")]
+            pub internal: Option<bool>,
+            /// Never synced or overwritten by downloads, for products managed by hand
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub locked: Option<bool>,
+            /// Icon uploaded with the product, relative to the working directory
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub image: Option<String>,
+            /// SHA-256 of the image when it was last uploaded
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub image_hash: Option<String>,
+            /// Icon asset of a game pass when it was last downloaded or synced
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub icon_asset_id: Option<u64>,
+            /// Set on remote dev products that Roblox no longer lets anyone edit
+            #[serde(skip)]
+            pub immutable: bool,
+            /// Title a plugin or transform script chose for this run, uploaded as is
+            #[serde(skip)]
+            pub plugin_title: Option<String>,
+            /// Price a plugin or transform script chose for this run, uploaded as is
+            #[serde(skip)]
+            pub plugin_price: Option<u64>,
+            /// Description a transform script chose for this run, uploaded as is
+            #[serde(skip)]
+            pub plugin_description: Option<String>,
+            /// When Roblox last changed a remote product
+            #[serde(skip)]
+            pub remote_updated: Option<String>,
         }>,
 
         #[serde(default)]
-        pub products: HashMap<String, Product>,
+        pub products: BTreeMap<String, Product>,
+
+        /// Group-owned avatar items whose price and sale state are synced
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub catalog: BTreeMap<String, CatalogItem>,
+
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub notifications: BTreeMap<String, NotificationTarget>,
+
+        /// File the products were loaded from, or should be saved to
+        #[serde(skip)]
+        pub path: Option<PathBuf>,
+
+        /// Included file each product came from. Products missing here live in `path`.
+        #[serde(skip)]
+        pub sources: BTreeMap<(ProductType, String), PathBuf>,
+
+        /// Schema version `path` was written with, which saving upgrades
+        #[serde(skip)]
+        pub file_version: Option<u32>,
+    }
+}
+
+/// The contents of a file listed in `include`: products, without metadata.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct IncludedProducts {
+    #[serde(default)]
+    gamepasses: BTreeMap<String, Product>,
+    #[serde(default)]
+    products: BTreeMap<String, Product>,
+}
+
+/// Expands an `include` entry into the files it names, sorted. Globs are only
+/// supported in the file name, not in directories.
+fn expand_include(base: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let path = base.join(pattern);
+    let file_pattern = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("invalid include '{}'", pattern))?;
+
+    if !file_pattern.contains(['*', '?']) {
+        return Ok(vec![path]);
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(format!(
+            "include '{}' uses a glob in a directory, only file names can have globs",
+            pattern
+        )
+        .into());
+    }
+
+    let matcher = glob_regex(file_pattern)?;
+    let mut files = std::fs::read_dir(&dir)
+        .map_err(|e| format!("include '{}': {}: {}", pattern, dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| matcher.is_match(name))
+        })
+        .collect::<Vec<_>>();
+
+    files.sort();
+    Ok(files)
+}
+
+/// How discounted prices are rounded to `round-to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundStyle {
+    #[default]
+    Down,
+    Nearest,
+    /// Nearest multiple minus one, so 347 becomes 349 with `round-to = 10`
+    Psychological,
+}
+
+impl Metadata {
+    /// The name filters to canonicalize with. Without configured filters,
+    /// the defaults keep non-ASCII letters if the slug options don't strip
+    /// them anyway.
+    pub fn filters(&self) -> Option<Vec<NameFilter>> {
+        let unicode = self.slug.as_ref().map(|slug| slug.unicode);
+        match &self.name_filters {
+            Some(filters) if !filters.is_empty() => Some(filters.clone()),
+            _ if matches!(unicode, None | Some(UnicodeKeys::Strip)) => None,
+            _ => Some(UNICODE_FILTERS.clone()),
+        }
+    }
+
+    /// The settings `init` starts a products file with.
+    pub fn new(universe_id: u64) -> Self {
+        Self {
+            universe_id,
+            discount_prefix: Some("💲{}% OFF💲 ".to_string()),
+            luau_file: Some("products.luau".to_string()),
+            name_filters: None,
+            ..Default::default()
+        }
+    }
+
+    /// The key for a canonical name, using the configured slug options.
+    pub fn key_for(&self, name: &str) -> String {
+        self.slug.clone().unwrap_or_default().key(name)
+    }
+
+    /// Whether unknown keys are errors, from `strict = true` or `--strict`.
+    pub fn is_strict(&self) -> bool {
+        strict::is_enabled() || self.strict == Some(true)
+    }
+
+    pub fn censor_threshold(&self) -> f64 {
+        self.censor_threshold.unwrap_or(DEFAULT_CENSOR_THRESHOLD)
+    }
+
+    /// Fails on settings that can't mean anything, before any command uses
+    /// them.
+    pub fn check(&self) -> Result<()> {
+        if let Some(threshold) = self.censor_threshold
+            && !(0.0..=1.0).contains(&threshold)
+        {
+            return Err(format!(
+                "censor-threshold must be between 0.0 and 1.0, not {}",
+                threshold
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Rounds a discounted price according to `round-to` and `round-style`,
+    /// never going above the full price or below 1 R$.
+    pub fn round_price(&self, price: u64, full_price: u64) -> u64 {
+        let style = self.round_style.unwrap_or_default();
+        let step = match (self.round_to, style) {
+            (Some(step), _) if step > 1 => step,
+            (_, RoundStyle::Psychological) => 10,
+            _ => return price,
+        };
+
+        let rounded = match style {
+            RoundStyle::Down => price / step * step,
+            RoundStyle::Nearest => (price + step / 2) / step * step,
+            RoundStyle::Psychological => ((price + step / 2) / step * step).saturating_sub(1),
+        };
+
+        rounded.clamp(1, full_price.max(1))
+    }
+}
+
+const PRODUCT_FILES: [&str; 4] = [
+    "products.toml",
+    "products.yaml",
+    "products.yml",
+    "products.json",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum FileFormat {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl FileFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => FileFormat::Yaml,
+            Some("json") => FileFormat::Json,
+            _ => FileFormat::Toml,
+        }
+    }
+
+    pub fn default_path(self) -> PathBuf {
+        match self {
+            FileFormat::Toml => "products.toml".into(),
+            FileFormat::Yaml => "products.yaml".into(),
+            FileFormat::Json => "products.json".into(),
+        }
+    }
+
+    /// Parses file data written in this format.
+    pub fn parse<T: serde::de::DeserializeOwned>(self, data: &[u8]) -> Result<T> {
+        Ok(match self {
+            FileFormat::Toml => toml::from_slice(data)?,
+            FileFormat::Yaml => serde_yaml::from_slice(data)?,
+            FileFormat::Json => serde_json::from_slice(data)?,
+        })
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Finds the products file in the working directory, whichever format it uses.
+pub fn find_products_file() -> Option<PathBuf> {
+    find_products_file_in(Path::new(""))
+}
+
+/// Finds the products file in `dir`, whichever format it uses.
+pub fn find_products_file_in(dir: &Path) -> Option<PathBuf> {
+    PRODUCT_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.exists())
+}
+
+/// Removes null values so optional fields are omitted from YAML/JSON output,
+/// matching how the TOML writer drops them.
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum ProductType {
+    #[value(name = "gamepass")]
     GamePass,
+    #[value(name = "product", alias = "devproduct")]
     DevProduct,
 }
 pub enum MultiProduct {
@@ -54,38 +400,195 @@ pub enum MultiProduct {
     DevProduct(Product),
 }
 
-macro_rules! check_diff {
-    ($diffs:expr, $field:expr, $old:expr, $new:expr, $variant:ident) => {
-        if $old != $new {
-            $diffs.push(DiffChange::Changed(ProductDiff::$variant(
-                $old.clone(),
-                $new.clone(),
-            )));
-        } else {
-            $diffs.push(DiffChange::Unchanged(ProductDiff::$variant(
-                $old.clone(),
-                $new.clone(),
-            )));
+impl VCSProducts {
+    pub fn products_of(&self, product_type: ProductType) -> &BTreeMap<String, Product> {
+        match product_type {
+            ProductType::GamePass => &self.gamepasses,
+            ProductType::DevProduct => &self.products,
         }
-    };
-}
+    }
+
+    pub fn products_of_mut(&mut self, product_type: ProductType) -> &mut BTreeMap<String, Product> {
+        match product_type {
+            ProductType::GamePass => &mut self.gamepasses,
+            ProductType::DevProduct => &mut self.products,
+        }
+    }
 
-impl VCSProducts {
     pub async fn get_products() -> Result<Self> {
-        let file_data = fs::read("products.toml").await?;
-        let products: VCSProducts = toml::from_slice(&file_data)?;
+        let path = find_products_file().unwrap_or_else(|| FileFormat::Toml.default_path());
+        let (file_data, version) = migrate::load(&path, None).await?;
+
+        let format = FileFormat::from_path(&path);
+        let mut products: VCSProducts = format.parse(&file_data)?;
+        products.metadata.check()?;
+
+        if products.metadata.is_strict() {
+            strict::check(&path, &format.parse(&file_data)?, &products)?;
+        }
+
+        set_products_dir(path.parent().unwrap_or(Path::new("")));
+        products.path = Some(path);
+        products.file_version = Some(version);
+        products.load_includes(version).await?;
         Ok(products)
     }
 
+    /// Merges in the products of every file listed in `include`, remembering
+    /// where each came from so it's saved back there. Included files are
+    /// migrated from the main file's schema `version`.
+    async fn load_includes(&mut self, version: u32) -> Result<()> {
+        let Some(patterns) = self.metadata.include.clone() else {
+            return Ok(());
+        };
+
+        let base = self
+            .path
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let mut loaded = vec![];
+
+        for pattern in &patterns {
+            let files = expand_include(&base, pattern)?;
+            if files.is_empty() {
+                warn!("include '{}' matched no files", pattern);
+            }
+
+            for file in files {
+                if loaded.contains(&file) {
+                    continue;
+                }
+
+                let (data, _) = migrate::load(&file, Some(version))
+                    .await
+                    .map_err(|e| format!("failed to read {}: {}", file.display(), e))?;
+                let format = FileFormat::from_path(&file);
+                let included: IncludedProducts = format
+                    .parse(&data)
+                    .map_err(|e| format!("invalid {}: {}", file.display(), e))?;
+
+                if self.metadata.is_strict() {
+                    strict::check(&file, &format.parse(&data)?, &included)?;
+                }
+
+                for (product_type, entries) in [
+                    (ProductType::GamePass, included.gamepasses),
+                    (ProductType::DevProduct, included.products),
+                ] {
+                    for (key, product) in entries {
+                        let target = match product_type {
+                            ProductType::GamePass => &mut self.gamepasses,
+                            ProductType::DevProduct => &mut self.products,
+                        };
+
+                        if target.contains_key(&key) {
+                            return Err(format!(
+                                "{:?} '{}' in {} is defined more than once",
+                                product_type,
+                                key,
+                                file.display()
+                            )
+                            .into());
+                        }
+
+                        target.insert(key.clone(), product);
+                        self.sources.insert((product_type, key), file.clone());
+                    }
+                }
+
+                loaded.push(file);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Splits products by the included file they belong to. Files whose
+    /// products were all removed are kept, so they get emptied on save.
+    fn included_files(&self) -> BTreeMap<PathBuf, IncludedProducts> {
+        let mut files = BTreeMap::<PathBuf, IncludedProducts>::new();
+
+        for ((product_type, key), file) in &self.sources {
+            let included = files.entry(file.clone()).or_default();
+            match product_type {
+                ProductType::GamePass => {
+                    if let Some(product) = self.gamepasses.get(key) {
+                        included.gamepasses.insert(key.clone(), product.clone());
+                    }
+                }
+                ProductType::DevProduct => {
+                    if let Some(product) = self.products.get(key) {
+                        included.products.insert(key.clone(), product.clone());
+                    }
+                }
+            }
+        }
+
+        files
+    }
+
     pub async fn save_products(&self) -> Result<()> {
-        let mut toml_products: toml_edit::DocumentMut;
+        let path = self
+            .path
+            .clone()
+            .or_else(find_products_file)
+            .unwrap_or_else(|| FileFormat::Toml.default_path());
 
-        if let Ok(data) = fs::read("products.toml").await {
-            let document_string = String::from_utf8(data.clone())?;
-            toml_products = document_string.parse()?;
-        } else {
-            toml_products = toml_edit::DocumentMut::new();
+        if self.sources.is_empty() {
+            return self.save_main(&path).await;
+        }
+
+        let mut main = self.clone();
+        for (product_type, key) in self.sources.keys() {
+            match product_type {
+                ProductType::GamePass => main.gamepasses.remove(key),
+                ProductType::DevProduct => main.products.remove(key),
+            };
         }
+        main.save_main(&path).await?;
+
+        for (file, included) in self.included_files() {
+            match FileFormat::from_path(&file) {
+                FileFormat::Toml => {
+                    let version = self.file_version.unwrap_or(SCHEMA_VERSION);
+                    let mut document = read_document(&file, Some(version)).await?;
+                    write_product_sections(&mut document, &included.gamepasses, &included.products);
+                    // Included files often hold a single product type, so drop empty sections.
+                    document
+                        .retain(|_, item| item.as_table().is_none_or(|table| !table.is_empty()));
+                    fs::write(&file, document.to_string()).await?;
+                }
+                format => write_serialized(&file, format, &included).await?,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn save_main(&self, path: &Path) -> Result<()> {
+        match FileFormat::from_path(path) {
+            FileFormat::Toml => self.save_toml(path).await,
+            format => {
+                let current = VCSProducts {
+                    schema_version: Some(SCHEMA_VERSION),
+                    ..self.clone()
+                };
+                write_serialized(path, format, &current).await
+            }
+        }
+    }
+
+    /// Writes through toml_edit so comments and formatting in the existing
+    /// file are preserved.
+    async fn save_toml(&self, path: &Path) -> Result<()> {
+        let mut toml_products = read_document(path, None).await?;
+        set_value(
+            toml_products.as_table_mut(),
+            "schema-version",
+            SCHEMA_VERSION as i64,
+        );
 
         let metadata = section(&mut toml_products, "metadata");
 
@@ -96,6 +599,16 @@ impl VCSProducts {
         );
         set_value(metadata, "universe-id", self.metadata.universe_id as i64);
         set_or_remove(metadata, "luau-file", self.metadata.luau_file.clone());
+        set_or_remove(
+            metadata,
+            "luau-template",
+            self.metadata.luau_template.clone(),
+        );
+        set_or_remove(
+            metadata,
+            "typescript-file",
+            self.metadata.typescript_file.clone(),
+        );
 
         let filters = self
             .metadata
@@ -103,70 +616,261 @@ impl VCSProducts {
             .clone()
             .unwrap_or_default()
             .iter()
-            .map(|x| x.as_str().to_string())
+            .map(|filter| {
+                let pattern = filter.pattern.as_str();
+                match &filter.replace {
+                    Some(replace) => Value::InlineTable(InlineTable::from_iter([
+                        ("pattern", Value::from(pattern)),
+                        ("replace", Value::from(replace.as_str())),
+                    ])),
+                    None => Value::from(pattern),
+                }
+            })
             .collect::<Vec<_>>();
 
-        set_value(metadata, "name-filters", Array::from_iter(filters.iter()));
-
-        let gamepasses = section(&mut toml_products, "gamepasses");
-        for (key, gamepass) in &self.gamepasses {
-            update_product_table(gamepasses, key, gamepass);
-        }
+        set_value(metadata, "name-filters", Array::from_iter(filters));
+        set_or_remove(
+            metadata,
+            "ignored-ids",
+            self.metadata
+                .ignored_ids
+                .as_ref()
+                .filter(|ids| !ids.is_empty())
+                .map(|ids| Array::from_iter(ids.iter().map(|id| *id as i64))),
+        );
 
-        let products = section(&mut toml_products, "products");
-        for (key, product) in &self.products {
-            update_product_table(products, key, product);
-        }
+        write_product_sections(&mut toml_products, &self.gamepasses, &self.products);
 
-        fs::write("products.toml", toml_products.to_string()).await?;
+        fs::write(path, toml_products.to_string()).await?;
         Ok(())
     }
 
+    /// Keys in in-game shop order: products with an `order` first, then the
+    /// rest alphabetically.
+    pub fn shop_order(&self, product_type: ProductType) -> Vec<&String> {
+        let entries = match product_type {
+            ProductType::GamePass => &self.gamepasses,
+            ProductType::DevProduct => &self.products,
+        };
+
+        let mut keys = entries.keys().collect::<Vec<_>>();
+        keys.sort_by_key(|key| (entries[*key].order.is_none(), entries[*key].order, *key));
+        keys
+    }
+
+    /// A copy without `internal` products, for the generated files.
+    fn exported(&self) -> Self {
+        let mut exported = self.clone();
+        exported
+            .gamepasses
+            .retain(|_, product| !product.is_internal());
+        exported
+            .products
+            .retain(|_, product| !product.is_internal());
+        exported
+    }
+
     pub async fn serialize_luau(&self) -> Result<()> {
+        self.exported().write_generated().await
+    }
+
+    /// Generates the Luau and TypeScript files into a temporary directory
+    /// instead of their configured paths, for dry runs.
+    pub async fn preview_generated(&self) -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rbx-products-dry-run-{}", std::process::id()));
+        fs::create_dir_all(&dir).await?;
+
+        let mut preview = self.exported();
+        let metadata = &mut preview.metadata;
+
+        for file in [&mut metadata.luau_file, &mut metadata.typescript_file]
+            .into_iter()
+            .flatten()
+        {
+            let target = dir.join(Path::new(file.as_str()).file_name().unwrap_or_default());
+            info!("would write {} (preview at {})", file, target.display());
+            *file = target.to_string_lossy().to_string();
+        }
+
+        preview.write_generated().await
+    }
+
+    async fn write_generated(&self) -> Result<()> {
+        if let Some(typescript_file) = &self.metadata.typescript_file {
+            let declaration = typescript_file.ends_with(".d.ts");
+            fs::write(typescript_file, typescript::render(self, declaration)).await?;
+        }
+
         let products_lua_file = match self.metadata.luau_file.clone() {
             Some(file) => file,
             None => return Ok(()),
         };
 
-        let mut file = fs::File::create(products_lua_file).await?;
-        let mut contents = String::new();
+        if let Some(template) = &self.metadata.luau_template {
+            let template = fs::read_to_string(template)
+                .await
+                .map_err(|e| format!("failed to read luau template '{}': {}", template, e))?;
 
-        let serialize = |contents: &mut String, products: &HashMap<String, Product>| {
-            let mut values: Vec<ProductKeyPair<'_>> = products.into_iter().collect();
+            fs::write(products_lua_file, render_template(&template, self)?).await?;
+            return Ok(());
+        }
 
-            values.sort_by(|a: &ProductKeyPair<'_>, b: &ProductKeyPair<'_>| a.1.id.cmp(&b.1.id));
+        fs::write(products_lua_file, self.render_luau()).await?;
 
-            for (index, product) in values.iter().enumerate() {
-                *contents += &format!(
-                    "\t\t[{:?}] = {{ id = {:?}, price = {} }}",
-                    product.0,
-                    product.1.id.unwrap_or(0),
-                    product.1.get_price()
-                );
+        Ok(())
+    }
 
-                if index != products.len() - 1 {
-                    *contents += ",\n";
-                } else {
-                    *contents += "\n";
+    /// The built-in Luau module layout.
+    fn render_luau(&self) -> String {
+        let entry = |(key, product): &ProductKeyPair<'_>| {
+            let attributes = match &product.attributes {
+                Some(attributes) if !attributes.is_empty() => {
+                    format!(", attributes = {}", attributes_to_luau(attributes))
                 }
+                _ => String::new(),
+            };
+            let tags = match &product.tags {
+                Some(tags) if !tags.is_empty() => format!(
+                    ", tags = {{ {} }}",
+                    tags.iter()
+                        .map(|tag| format!("{:?}", tag))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                _ => String::new(),
+            };
+
+            format!(
+                "[{:?}] = {{ id = {:?}, price = {}, basePrice = {}, discount = {}{}{} }}",
+                key,
+                product.id.unwrap_or(0),
+                product.get_price(Some(&self.metadata)),
+                product.price,
+                product.discount.unwrap_or(0),
+                tags,
+                attributes
+            )
+        };
+
+        let entries = |values: &[ProductKeyPair<'_>], indent: &str| {
+            values
+                .iter()
+                .map(|value| format!("{}{}", indent, entry(value)))
+                .collect::<Vec<_>>()
+                .join(",\n")
+                + if values.is_empty() { "" } else { "\n" }
+        };
+
+        let mut types = String::new();
+        let mut tables = String::new();
+
+        for (name, type_name, products) in [
+            ("Gamepasses", "GamepassCategories", &self.gamepasses),
+            ("Products", "ProductCategories", &self.products),
+        ] {
+            let Some(categories) = categorized(products) else {
+                let mut values = products.iter().collect::<Vec<_>>();
+                values.sort_by_key(|(_, product)| product.id);
+
+                tables += &format!(
+                    "\t{} = {{\n{}\t}} :: {{[string]: Product}},\n\n",
+                    name,
+                    entries(&values, "\t\t")
+                );
+                continue;
+            };
+
+            types += &format!("export type {} = {{\n", type_name);
+            tables += &format!("\t{} = {{\n", name);
+
+            for (category, values) in &categories {
+                let keys = values
+                    .iter()
+                    .map(|(key, _)| format!("[{:?}]: Product", key))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                types += &format!("\t{} {{ {} }},\n", luau_field(category, ":"), keys);
+                tables += &format!(
+                    "\t\t{} {{\n{}\t\t}},\n",
+                    luau_field(category, " ="),
+                    entries(values, "\t\t\t")
+                );
             }
+
+            types += "}\n\n";
+            tables += &format!("\t}} :: {},\n\n", type_name);
+        }
+
+        let order = |product_type| {
+            self.shop_order(product_type)
+                .iter()
+                .map(|key| format!("{:?}", key))
+                .collect::<Vec<_>>()
+                .join(", ")
         };
 
+        let mut contents = String::new();
         contents += "-- This file is automatically generated by rbx-products. Do not edit this file directly.\n";
-        contents += "export type Product = { id: number, price: number }\n\n";
-        contents += "return {\n\tGamepasses = {\n";
-        serialize(&mut contents, &self.gamepasses);
-        contents += "\t} :: {[string]: Product},\n\n\tProducts = {\n";
-        serialize(&mut contents, &self.products);
-        contents += "\t} :: {[string]: Product}\n}";
-
-        file.write_all(contents.as_bytes()).await?;
+        contents += "export type Product = { id: number, price: number, basePrice: number, discount: number, tags: {string}?, attributes: {[string]: any}? }\n\n";
+        contents += &types;
+        contents += "return {\n";
+        contents += &tables;
+        contents += &format!(
+            "\tGamepassOrder = {{ {} }} :: {{string}},\n",
+            order(ProductType::GamePass)
+        );
+        contents += &format!(
+            "\tProductOrder = {{ {} }} :: {{string}}\n}}",
+            order(ProductType::DevProduct)
+        );
 
-        Ok(())
+        contents
     }
 }
 
 impl Product {
+    pub fn has_alias(&self, key: &str) -> bool {
+        self.aliases
+            .as_ref()
+            .is_some_and(|aliases| aliases.iter().any(|alias| alias == key))
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.internal.unwrap_or(false)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|t| t == tag))
+    }
+
+    /// Whether the product has one of `tags`, or `tags` is empty.
+    pub fn matches_tags(&self, tags: &[String]) -> bool {
+        tags.is_empty() || tags.iter().any(|tag| self.has_tag(tag))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.unwrap_or(false)
+    }
+
+    /// The hash of the image file if it differs from the last uploaded one.
+    /// Unreadable images fail validation before a sync gets here.
+    pub fn changed_image(&self) -> Option<String> {
+        let hash = image_hash(self.image.as_ref()?)?;
+        (self.image_hash.as_ref() != Some(&hash)).then_some(hash)
+    }
+
+    /// Whether the remote icon is no longer the one recorded in the products
+    /// file, meaning it was replaced outside the tool.
+    pub fn icon_changed(&self, remote: &Product) -> bool {
+        matches!(
+            (self.icon_asset_id, remote.icon_asset_id),
+            (Some(recorded), Some(current)) if recorded != current
+        )
+    }
+
     pub fn has_discount(&self) -> bool {
         if let Some(discount) = self.discount
             && discount > 0
@@ -177,17 +881,48 @@ impl Product {
         }
     }
 
-    pub fn get_price(&self) -> u64 {
+    /// The price to charge, after any discount. With metadata, discounted
+    /// prices are also rounded as configured.
+    pub fn get_price(&self, metadata: Option<&Metadata>) -> u64 {
+        if let Some(price) = self.plugin_price {
+            return price;
+        }
+
         if let Some(discount) = self.discount
             && discount > 0
         {
-            (self.price as f64 * (1.0 - (discount as f64 / 100.0))).floor() as u64
+            let price = (self.price as f64 * (1.0 - (discount as f64 / 100.0))).floor() as u64;
+
+            match metadata {
+                Some(metadata) if price > 0 => metadata.round_price(price, self.price as u64),
+                _ => price,
+            }
         } else {
             self.price as u64
         }
     }
 
+    /// The description with `{price}`, `{original_price}` and `{discount}`
+    /// replaced by the product's current values, so copy like "Now only
+    /// {price} R$!" follows the price.
+    pub fn get_description(&self, metadata: Option<&Metadata>) -> Option<String> {
+        if let Some(description) = &self.plugin_description {
+            return Some(description.clone());
+        }
+
+        self.description.as_ref().map(|description| {
+            description
+                .replace("{price}", &self.get_price(metadata).to_string())
+                .replace("{original_price}", &self.price.to_string())
+                .replace("{discount}", &self.discount.unwrap_or(0).to_string())
+        })
+    }
+
     pub fn get_title(&self) -> String {
+        if let Some(title) = &self.plugin_title {
+            return title.clone();
+        }
+
         if self.has_discount() {
             return self.name.clone();
         }
@@ -200,55 +935,58 @@ impl Product {
     }
 
     pub fn diff(&self, other: &Self, metadata: Option<&Metadata>) -> Option<ProductDiffs> {
-        let mut diffs = vec![] as Vec<DiffChange>;
-
-        let title = if let Some(metadata) = metadata {
-            if let (Some(discount), Some(prefix)) = (self.discount, &metadata.discount_prefix) {
-                format!("{} {}", prefix.format(&[discount]), self.get_title())
-            } else {
-                self.get_title()
-            }
-        } else {
-            self.get_title()
-        };
-
-        let active = self.active;
-        let price = self.get_price();
-        let description = self.description.clone().unwrap_or(String::default());
-
-        check_diff!(diffs, Title, other.name, title, Title);
-        check_diff!(
-            diffs,
-            Description,
-            other.description.clone().unwrap(),
-            description,
-            Description
-        );
-        check_diff!(diffs, Price, other.price as u64, price, Price);
-        check_diff!(
-            diffs,
-            RegionalPricing,
-            other.regional_pricing.unwrap_or(false),
-            self.regional_pricing.unwrap_or(false),
-            RegionalPricing
-        );
-        check_diff!(diffs, Active, other.active, active, Active);
-
-        let has_diffs = diffs.iter().any(|d| match d {
-            DiffChange::Changed(_) => true,
-            _ => false,
-        });
+        let diffs = self.field_diffs(other, metadata);
+        let has_diffs = diffs.iter().any(|d| matches!(d, DiffChange::Changed(_)));
 
         if has_diffs {
             Some(ProductDiffs {
                 name: self.name.clone(),
-                id: self.id.unwrap_or(0) as u64,
+                id: self.id.unwrap_or(0),
+                kind: DiffKind::Changed,
                 diffs,
             })
         } else {
             None
         }
     }
+
+    /// Every synced field compared against the remote product `other`,
+    /// changed or not.
+    pub fn field_diffs(&self, other: &Self, metadata: Option<&Metadata>) -> Vec<DiffChange> {
+        FIELDS
+            .iter()
+            .filter_map(|field| {
+                let (old, new, equal) = field.compare(self, other, metadata)?;
+                let diff = ProductDiff { field, old, new };
+                Some(match equal {
+                    true => DiffChange::Unchanged(diff),
+                    false => DiffChange::Changed(diff),
+                })
+            })
+            .collect()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PRODUCTS_DIR: RwLock<PathBuf> = RwLock::new(PathBuf::new());
+}
+
+/// Sets the directory of the loaded products file, which image paths are
+/// relative to.
+pub fn set_products_dir(dir: &Path) {
+    *PRODUCTS_DIR.write().unwrap() = dir.to_path_buf();
+}
+
+/// Resolves an image path from the products file against the file's
+/// directory, like `include` and `transform` paths.
+pub fn resolve_path(path: &str) -> PathBuf {
+    PRODUCTS_DIR.read().unwrap().join(path)
+}
+
+/// Hex SHA-256 of an image file, or `None` if it can't be read.
+pub fn image_hash(path: &str) -> Option<String> {
+    let data = std::fs::read(resolve_path(path)).ok()?;
+    Some(format!("{:x}", Sha256::digest(&data)))
 }
 
 impl Product {
@@ -261,6 +999,63 @@ impl Product {
         set_or_remove(table, "discount", self.discount.map(|d| d as i64));
         set_value(table, "price", self.price);
         set_or_remove(table, "regional-pricing", self.regional_pricing);
+        set_or_remove(table, "store-page-enabled", self.store_page_enabled);
+        set_or_remove(table, "order", self.order.map(|order| order as i64));
+        set_or_remove(
+            table,
+            "internal",
+            self.internal.filter(|internal| *internal),
+        );
+        set_or_remove(table, "locked", self.locked.filter(|locked| *locked));
+        set_or_remove(table, "image", self.image.clone());
+        set_or_remove(table, "image-hash", self.image_hash.clone());
+        set_or_remove(
+            table,
+            "icon-asset-id",
+            self.icon_asset_id.map(|id| id as i64),
+        );
+        set_or_remove(
+            table,
+            "aliases",
+            self.aliases
+                .as_ref()
+                .filter(|aliases| !aliases.is_empty())
+                .map(|aliases| Array::from_iter(aliases.iter())),
+        );
+        set_or_remove(table, "category", self.category.clone());
+        set_or_remove(
+            table,
+            "tags",
+            self.tags
+                .as_ref()
+                .filter(|tags| !tags.is_empty())
+                .map(|tags| Array::from_iter(tags.iter())),
+        );
+
+        // Attributes keep the key and layout they were written with, so an
+        // `[gamepasses.<key>.extra]` section stays a section.
+        let key = if table.contains_key("extra") {
+            "extra"
+        } else {
+            "attributes"
+        };
+        let attributes = self
+            .attributes
+            .as_ref()
+            .filter(|attributes| !attributes.is_empty())
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .filter_map(|(k, v)| json_to_toml(v).map(|v| (k.as_str(), v)))
+                    .collect::<InlineTable>()
+            });
+
+        match attributes {
+            Some(attributes) if table.get(key).is_some_and(Item::is_table) => {
+                table[key] = Item::Table(attributes.into_table());
+            }
+            attributes => set_or_remove(table, key, attributes.map(Value::InlineTable)),
+        }
     }
 }
 
@@ -296,6 +1091,109 @@ fn set_value<V: Into<Value>>(table: &mut Table, key: &str, value: V) {
     }
 }
 
+/// TOML has no null, so null attribute values are dropped.
+fn json_to_toml(value: &serde_json::Value) -> Option<Value> {
+    use serde_json::Value as Json;
+
+    match value {
+        Json::Null => None,
+        Json::Bool(b) => Some((*b).into()),
+        Json::Number(n) => n
+            .as_i64()
+            .map(Value::from)
+            .or_else(|| n.as_f64().map(Value::from)),
+        Json::String(s) => Some(s.as_str().into()),
+        Json::Array(items) => Some(Value::Array(
+            items.iter().filter_map(json_to_toml).collect(),
+        )),
+        Json::Object(map) => Some(Value::InlineTable(
+            map.iter()
+                .filter_map(|(k, v)| json_to_toml(v).map(|v| (k.as_str(), v)))
+                .collect(),
+        )),
+    }
+}
+
+fn json_to_luau(value: &serde_json::Value) -> String {
+    use serde_json::Value as Json;
+
+    match value {
+        Json::Null => "nil".to_string(),
+        Json::Bool(b) => b.to_string(),
+        Json::Number(n) => n.to_string(),
+        Json::String(s) => format!("{:?}", s),
+        Json::Array(items) => format!(
+            "{{ {} }}",
+            items
+                .iter()
+                .map(json_to_luau)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Json::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .map(|(k, v)| format!("[{:?}] = {}", k, json_to_luau(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Category of products without one, once others in their table have one.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Products grouped by `category` and sorted by id, or `None` when none of
+/// them has a category and the generated table stays flat.
+pub fn categorized(
+    products: &BTreeMap<String, Product>,
+) -> Option<BTreeMap<&str, Vec<ProductKeyPair<'_>>>> {
+    if products.values().all(|product| product.category.is_none()) {
+        return None;
+    }
+
+    let mut categories = BTreeMap::<&str, Vec<ProductKeyPair<'_>>>::new();
+    for (key, product) in products {
+        categories
+            .entry(product.category.as_deref().unwrap_or(UNCATEGORIZED))
+            .or_default()
+            .push((key, product));
+    }
+
+    for values in categories.values_mut() {
+        values.sort_by_key(|(key, product)| (product.id, *key));
+    }
+
+    Some(categories)
+}
+
+/// A table field, bare when the name is a valid identifier so game code can
+/// write `Products.Boosts`, followed by `separator`.
+fn luau_field(name: &str, separator: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if identifier {
+        format!("{}{}", name, separator)
+    } else {
+        format!("[{:?}]{}", name, separator)
+    }
+}
+
+fn attributes_to_luau(attributes: &Attributes) -> String {
+    format!(
+        "{{ {} }}",
+        attributes
+            .iter()
+            .map(|(k, v)| format!("[{:?}] = {}", k, json_to_luau(v)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
 fn set_or_remove<V: Into<Value>>(table: &mut Table, key: &str, value: Option<V>) {
     match value {
         Some(value) => set_value(table, key, value),
@@ -305,9 +1203,547 @@ fn set_or_remove<V: Into<Value>>(table: &mut Table, key: &str, value: Option<V>)
     }
 }
 
+/// Reads a TOML file to save over, upgraded to the current schema first, or
+/// starts an empty document if the file doesn't exist yet.
+async fn read_document(path: &Path, version: Option<u32>) -> Result<toml_edit::DocumentMut> {
+    let data = match fs::read(path).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(toml_edit::DocumentMut::new());
+        }
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e).into()),
+    };
+
+    let mut document = String::from_utf8(data)?.parse()?;
+    migrate::upgrade_document(&mut document, version)?;
+    Ok(document)
+}
+
+/// Writes a YAML or JSON file in full, leaving out null fields.
+async fn write_serialized<T: Serialize>(path: &Path, format: FileFormat, data: &T) -> Result<()> {
+    let mut value = serde_json::to_value(data)?;
+    strip_nulls(&mut value);
+
+    let contents = match format {
+        FileFormat::Yaml => serde_yaml::to_string(&value)?,
+        _ => serde_json::to_string_pretty(&value)? + "\n",
+    };
+
+    fs::write(path, contents).await?;
+    Ok(())
+}
+
+fn write_product_sections(
+    document: &mut toml_edit::DocumentMut,
+    gamepasses: &BTreeMap<String, Product>,
+    products: &BTreeMap<String, Product>,
+) {
+    for (name, entries) in [("gamepasses", gamepasses), ("products", products)] {
+        let table = section(document, name);
+        for (key, product) in entries {
+            update_product_table(table, key, product);
+        }
+        table.retain(|key, _| entries.contains_key(key));
+    }
+}
+
 fn update_product_table(table: &mut Table, key: &str, product: &Product) {
     match table.get_mut(key).and_then(Item::as_table_mut) {
         Some(existing) => product.apply_to_table(existing),
         None => table[key] = product.into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::fields;
+
+    #[test]
+    fn attributes_render_to_toml_and_luau() {
+        let attributes = Attributes::from([
+            ("icon".to_string(), serde_json::json!(123)),
+            ("tags".to_string(), serde_json::json!(["new", "hot"])),
+            ("unset".to_string(), serde_json::Value::Null),
+        ]);
+
+        let product = Product {
+            attributes: Some(attributes.clone()),
+            ..Default::default()
+        };
+
+        let mut table = Table::new();
+        product.apply_to_table(&mut table);
+        assert_eq!(
+            table["attributes"].to_string(),
+            r#"{ icon = 123, tags = ["new", "hot"] }"#
+        );
+
+        assert_eq!(
+            attributes_to_luau(&attributes),
+            r#"{ ["icon"] = 123, ["tags"] = { "new", "hot" }, ["unset"] = nil }"#
+        );
+    }
+
+    #[test]
+    fn slugs_keys_with_the_configured_options() {
+        let key_for = |slug: &str, name: &str| {
+            let metadata: Metadata =
+                toml::from_str(&format!("universe-id = 1\nslug = {}", slug)).unwrap();
+            metadata.key_for(&crate::utils::canonical_name(name, &metadata.filters()))
+        };
+
+        assert_eq!(key_for("{}", "Épée de Feu 2"), "p-e-de-feu-2");
+        assert_eq!(
+            key_for(r#"{ unicode = "transliterate" }"#, "Épée de Feu 2"),
+            "epee-de-feu-2"
+        );
+        assert_eq!(key_for(r#"{ unicode = "keep" }"#, "東京 Pass"), "東京-pass");
+        assert_eq!(
+            key_for(r#"{ separator = "_", case = "upper" }"#, "Super Speed!"),
+            "SUPER_SPEED"
+        );
+        assert_eq!(
+            key_for(r#"{ separator = "", case = "preserve" }"#, "Super Speed"),
+            "SuperSpeed"
+        );
+    }
+
+    #[test]
+    fn nests_categorized_products_in_luau() {
+        let mut local_products = VCSProducts::default();
+        for (key, id, category) in [
+            ("2x-coins", 2, Some("Boosts")),
+            ("vip", 1, None),
+            ("starter", 3, Some("Starter Packs")),
+        ] {
+            local_products.products.insert(
+                key.to_string(),
+                Product {
+                    id: Some(id),
+                    price: 10,
+                    category: category.map(String::from),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let luau = local_products.render_luau();
+        assert!(luau.contains(
+            "export type ProductCategories = {\n\
+             \tBoosts: { [\"2x-coins\"]: Product },\n\
+             \t[\"Starter Packs\"]: { [\"starter\"]: Product },\n\
+             \tUncategorized: { [\"vip\"]: Product },\n\
+             }\n"
+        ));
+        assert!(luau.contains(
+            "\tProducts = {\n\
+             \t\tBoosts = {\n\
+             \t\t\t[\"2x-coins\"] = { id = 2, price = 10, basePrice = 10, discount = 0 }\n\
+             \t\t},\n"
+        ));
+        assert!(luau.contains("\t} :: ProductCategories,\n"));
+        assert!(luau.contains("\tGamepasses = {\n\t} :: {[string]: Product},\n"));
+    }
+
+    #[test]
+    fn extra_sections_pass_through_to_luau() {
+        let source = r#"
+[metadata]
+universe-id = 1
+
+[gamepasses.vip]
+id = 1
+name = "VIP"
+price = 100
+active = true
+
+[gamepasses.vip.extra]
+color = "gold"
+featured = true
+slots = [1, 2]
+"#;
+        let local_products: VCSProducts = toml::from_str(source).unwrap();
+        let vip = &local_products.gamepasses["vip"];
+
+        let mut document = source.parse::<toml_edit::DocumentMut>().unwrap();
+        let table = document["gamepasses"]["vip"].as_table_mut().unwrap();
+        vip.apply_to_table(table);
+        assert!(document.to_string().contains(
+            "[gamepasses.vip.extra]\ncolor = \"gold\"\nfeatured = true\nslots = [1, 2]\n"
+        ));
+
+        assert!(local_products.render_luau().contains(
+            r#"attributes = { ["color"] = "gold", ["featured"] = true, ["slots"] = { 1, 2 } }"#
+        ));
+    }
+
+    #[test]
+    fn shop_order_puts_ordered_products_first() {
+        let mut local_products = VCSProducts::default();
+        for (key, order) in [("c", None), ("b", Some(2)), ("a", None), ("d", Some(1))] {
+            local_products.products.insert(
+                key.to_string(),
+                Product {
+                    order,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(
+            local_products.shop_order(ProductType::DevProduct),
+            ["d", "b", "a", "c"]
+        );
+    }
+
+    #[test]
+    fn rounds_discounted_prices() {
+        let product = Product {
+            price: 499,
+            discount: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(product.get_price(None), 349);
+
+        let metadata = |round_to, round_style| Metadata {
+            round_to,
+            round_style,
+            ..Default::default()
+        };
+
+        assert_eq!(product.get_price(Some(&metadata(None, None))), 349);
+        assert_eq!(product.get_price(Some(&metadata(Some(25), None))), 325);
+        assert_eq!(
+            product.get_price(Some(&metadata(Some(25), Some(RoundStyle::Nearest)))),
+            350
+        );
+        assert_eq!(
+            product.get_price(Some(&metadata(None, Some(RoundStyle::Psychological)))),
+            349
+        );
+        assert_eq!(
+            product.get_price(Some(&metadata(Some(100), Some(RoundStyle::Psychological)))),
+            299
+        );
+
+        let cheap = Product {
+            price: 5,
+            discount: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(cheap.get_price(Some(&metadata(Some(10), None))), 1);
+    }
+
+    #[test]
+    fn store_page_is_only_diffed_when_set_locally() {
+        let remote = Product {
+            name: "Coins".to_string(),
+            description: Some(String::new()),
+            store_page_enabled: Some(false),
+            ..Default::default()
+        };
+
+        let mut local = Product {
+            name: "Coins".to_string(),
+            ..Default::default()
+        };
+        assert!(local.diff(&remote, None).is_none());
+
+        local.store_page_enabled = Some(true);
+        let diffs = local.diff(&remote, None).unwrap();
+        assert!(diffs.diffs.contains(&DiffChange::Changed(ProductDiff::new(
+            &fields::STORE_PAGE,
+            false,
+            true
+        ))));
+    }
+
+    #[test]
+    fn image_is_diffed_until_its_hash_is_recorded() {
+        let path =
+            std::env::temp_dir().join(format!("rbx-products-icon-{}.png", std::process::id()));
+        std::fs::write(&path, b"icon").unwrap();
+
+        let remote = Product {
+            name: "Coins".to_string(),
+            description: Some(String::new()),
+            ..Default::default()
+        };
+        let mut local = Product {
+            image: Some(path.to_string_lossy().to_string()),
+            ..remote.clone()
+        };
+
+        let hash = local.changed_image().unwrap();
+        let diffs = local.diff(&remote, None).unwrap();
+        assert!(diffs.diffs.contains(&DiffChange::Changed(ProductDiff::new(
+            &fields::IMAGE,
+            String::new(),
+            hash.clone()
+        ))));
+
+        local.image_hash = Some(hash);
+        assert!(local.changed_image().is_none());
+        assert!(local.diff(&remote, None).is_none());
+
+        let replaced = Product {
+            icon_asset_id: Some(2),
+            ..remote.clone()
+        };
+        local.icon_asset_id = Some(1);
+        assert!(local.icon_changed(&replaced));
+        assert!(
+            local
+                .diff(&replaced, None)
+                .unwrap()
+                .diffs
+                .contains(&DiffChange::Changed(ProductDiff::new(&fields::ICON, 2, 1)))
+        );
+
+        local.image = None;
+        assert!(local.diff(&replaced, None).is_none());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn censor_threshold_is_a_share() {
+        let metadata = |threshold| Metadata {
+            censor_threshold: Some(threshold),
+            ..Default::default()
+        };
+
+        assert!(Metadata::default().check().is_ok());
+        assert!(metadata(0.0).check().is_ok());
+        assert!(metadata(1.0).check().is_ok());
+        assert!(metadata(30.0).check().is_err());
+        assert!(metadata(-0.1).check().is_err());
+        assert!(metadata(f64::NAN).check().is_err());
+    }
+
+    #[test]
+    fn image_paths_are_relative_to_the_products_file() {
+        let dir = std::env::temp_dir().join(format!("rbx-products-images-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("art")).unwrap();
+        std::fs::write(dir.join("art/coins.png"), b"icon").unwrap();
+
+        set_products_dir(&dir);
+        let hash = image_hash("art/coins.png");
+        let absolute = resolve_path(&dir.join("art/coins.png").to_string_lossy());
+        set_products_dir(Path::new(""));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(hash.is_some());
+        assert_eq!(absolute, dir.join("art/coins.png"));
+    }
+
+    #[test]
+    fn expands_description_variables() {
+        let product = Product {
+            description: Some("Now only {price} R$ (was {original_price}, {discount}% off)".into()),
+            price: 500,
+            discount: Some(30),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            product.get_description(None).as_deref(),
+            Some("Now only 350 R$ (was 500, 30% off)")
+        );
+
+        let remote = Product {
+            description: Some("Now only 500 R$ (was 500, 0% off)".into()),
+            price: 350,
+            ..product.clone()
+        };
+        let diffs = product.diff(&remote, None).unwrap();
+        assert!(diffs.diffs.contains(&DiffChange::Changed(ProductDiff::new(
+            &fields::DESCRIPTION,
+            "Now only 500 R$ (was 500, 0% off)",
+            "Now only 350 R$ (was 500, 30% off)"
+        ))));
+    }
+
+    #[tokio::test]
+    async fn saving_twice_is_byte_identical() {
+        let path = std::env::temp_dir().join(format!("rbx-products-{}.toml", std::process::id()));
+        let product = |price| Product {
+            name: "Product".to_string(),
+            price,
+            active: true,
+            ..Default::default()
+        };
+
+        let mut products = VCSProducts {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        for (key, price) in [("zeta", 1), ("alpha", 2), ("mid", 3), ("beta", 4)] {
+            products.gamepasses.insert(key.to_string(), product(price));
+        }
+
+        products.save_products().await.unwrap();
+        let first = fs::read_to_string(&path).await.unwrap();
+        products.save_products().await.unwrap();
+        let second = fs::read_to_string(&path).await.unwrap();
+        fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(first, second);
+
+        let keys = ["alpha", "beta", "mid", "zeta"]
+            .map(|key| first.find(&format!("[gamepasses.{}]", key)).unwrap());
+        assert!(keys.is_sorted());
+    }
+
+    #[test]
+    fn finds_products_files_in_any_format() {
+        assert_eq!(FileFormat::from_path("products.yml"), FileFormat::Yaml);
+        assert_eq!(FileFormat::from_path("a/products.json"), FileFormat::Json);
+        assert_eq!(FileFormat::from_path("products.toml"), FileFormat::Toml);
+
+        let dir = std::env::temp_dir().join(format!("rbx-products-formats-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(find_products_file_in(&dir), None);
+
+        std::fs::write(dir.join("products.json"), "{}").unwrap();
+        assert_eq!(find_products_file_in(&dir), Some(dir.join("products.json")));
+
+        // TOML wins when several formats are present.
+        std::fs::write(dir.join("products.toml"), "").unwrap();
+        let found = find_products_file_in(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, Some(dir.join("products.toml")));
+    }
+
+    #[tokio::test]
+    async fn yaml_and_json_files_round_trip() {
+        let mut products = VCSProducts::default();
+        products.metadata.universe_id = 42;
+        products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(7),
+                name: "VIP".to_string(),
+                price: 100,
+                active: true,
+                ..Default::default()
+            },
+        );
+
+        for extension in ["yaml", "json"] {
+            let path = std::env::temp_dir().join(format!(
+                "rbx-products-{}.{}",
+                std::process::id(),
+                extension
+            ));
+            products.path = Some(path.clone());
+            products.save_products().await.unwrap();
+
+            let data = fs::read(&path).await.unwrap();
+            fs::remove_file(&path).await.unwrap();
+            let contents = String::from_utf8(data.clone()).unwrap();
+            let loaded: VCSProducts = FileFormat::from_path(&path).parse(&data).unwrap();
+
+            assert!(!contents.contains("null"), "{}", contents);
+            assert!(contents.contains("universe-id"), "{}", contents);
+            assert_eq!(loaded.schema_version, Some(SCHEMA_VERSION));
+            assert_eq!(loaded.metadata.universe_id, 42);
+            assert_eq!(loaded.gamepasses["vip"].id, Some(7));
+            assert_eq!(loaded.gamepasses["vip"].price, 100);
+        }
+    }
+
+    #[tokio::test]
+    async fn unreadable_files_are_not_saved_over() {
+        let dir =
+            std::env::temp_dir().join(format!("rbx-products-unreadable-{}", std::process::id()));
+        fs::create_dir_all(&dir).await.unwrap();
+
+        let missing = read_document(&dir.join("products.toml"), None).await;
+        // A directory can't be read as a file, like a file we lack access to.
+        let unreadable = read_document(&dir, None).await;
+        fs::remove_dir_all(&dir).await.unwrap();
+
+        assert!(missing.unwrap().is_empty());
+        assert!(unreadable.is_err());
+    }
+
+    #[tokio::test]
+    async fn saves_included_products_back_to_their_file() {
+        let dir = std::env::temp_dir().join(format!("rbx-products-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("passes")).await.unwrap();
+        fs::write(
+            dir.join("passes/vip.toml"),
+            "# VIP passes\n[gamepasses.vip]\nname = \"VIP\"\nprice = 100\nactive = true\n",
+        )
+        .await
+        .unwrap();
+        fs::write(dir.join("passes/notes.txt"), "not included")
+            .await
+            .unwrap();
+
+        let mut products = VCSProducts {
+            path: Some(dir.join("products.toml")),
+            ..Default::default()
+        };
+        products.metadata.include = Some(vec!["passes/*.toml".to_string()]);
+        products.load_includes(SCHEMA_VERSION).await.unwrap();
+
+        assert_eq!(products.gamepasses["vip"].price, 100);
+
+        products.gamepasses.get_mut("vip").unwrap().price = 80;
+        products.products.insert(
+            "coins".to_string(),
+            Product {
+                name: "Coins".to_string(),
+                price: 25,
+                ..Default::default()
+            },
+        );
+        products.save_products().await.unwrap();
+
+        let main = fs::read_to_string(dir.join("products.toml")).await.unwrap();
+        let included = fs::read_to_string(dir.join("passes/vip.toml"))
+            .await
+            .unwrap();
+        fs::remove_dir_all(&dir).await.unwrap();
+
+        assert!(main.contains("[products.coins]"));
+        assert!(!main.contains("vip"));
+        assert!(included.starts_with("# VIP passes\n"));
+        assert!(included.contains("price = 80"));
+        assert!(!included.contains("coins"));
+        assert!(!included.contains("[products]"));
+    }
+
+    #[test]
+    fn internal_products_are_not_exported() {
+        let products: VCSProducts = toml::from_str(
+            r#"
+            [metadata]
+            universe-id = 1
+
+            [gamepasses.vip]
+            name = "VIP"
+            price = 100
+            active = true
+
+            [gamepasses.staff]
+            name = "Staff"
+            price = 1
+            active = true
+            exclude-from-luau = true
+            "#,
+        )
+        .unwrap();
+
+        assert!(products.gamepasses["staff"].is_internal());
+        assert_eq!(
+            products.exported().gamepasses.keys().collect::<Vec<_>>(),
+            ["vip"]
+        );
+        assert_eq!(products.gamepasses.len(), 2);
+    }
+}
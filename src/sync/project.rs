@@ -0,0 +1,280 @@
+use std::path::{Path, PathBuf};
+
+use log::info;
+use serde_json::Value;
+
+use crate::Result;
+use crate::hints::PLACEHOLDER_UNIVERSE_ID;
+use crate::ui::can_prompt;
+use crate::ui::prompt::{self, PromptPolicy};
+use crate::utils::prompt_line;
+
+/// Rojo's default project file, checked before any other `*.project.json`.
+/// Rojo 7 can store the universe as `gameId` and the place as `placeId` or
+/// `servePlaceIds`.
+const ROJO_DEFAULT: &str = "default.project.json";
+const ROJO_SUFFIX: &str = ".project.json";
+
+/// Mantle's config and the state file it writes after a deploy or import,
+/// which is where the experience and place asset ids actually end up.
+const MANTLE_FILES: [&str; 4] = [
+    "mantle.yml",
+    "mantle.yaml",
+    ".mantle-state.yml",
+    ".mantle-state.yaml",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectedId {
+    Universe(u64),
+    Place(u64),
+}
+
+/// An id found in an existing Roblox project file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    pub id: DetectedId,
+    pub source: PathBuf,
+}
+
+fn as_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn push(found: &mut Vec<DetectedId>, id: DetectedId) {
+    if !found.contains(&id) {
+        found.push(id);
+    }
+}
+
+fn collect(value: &Value, found: &mut Vec<DetectedId>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match key.as_str() {
+                    "gameId" | "universeId" | "experienceId" => {
+                        if let Some(id) = as_id(child) {
+                            push(found, DetectedId::Universe(id));
+                        }
+                    }
+                    "placeId" | "startPlaceId" => {
+                        if let Some(id) = as_id(child) {
+                            push(found, DetectedId::Place(id));
+                        }
+                    }
+                    "servePlaceIds" => {
+                        for id in child.as_array().into_iter().flatten().filter_map(as_id) {
+                            push(found, DetectedId::Place(id));
+                        }
+                    }
+                    // Mantle outputs: `experience: { assetId }` and `place: { assetId }`
+                    "experience" | "place" => {
+                        if let Some(id) = child.get("assetId").and_then(as_id) {
+                            push(
+                                found,
+                                match key.as_str() {
+                                    "experience" => DetectedId::Universe(id),
+                                    _ => DetectedId::Place(id),
+                                },
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+
+                collect(child, found);
+            }
+        }
+        Value::Array(values) => {
+            for child in values {
+                collect(child, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn ids_in(value: &Value) -> Vec<DetectedId> {
+    let mut found = vec![];
+    collect(value, &mut found);
+    found
+}
+
+fn parse(path: &Path, contents: &str) -> Option<Value> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(contents).ok()
+    } else {
+        serde_yaml::from_str(contents).ok()
+    }
+}
+
+/// The project files to check in `dir`: Rojo's default project, the other
+/// Rojo projects by name, then Mantle's files.
+fn project_files(dir: &Path) -> Vec<String> {
+    let mut rojo = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(ROJO_SUFFIX) && name != ROJO_DEFAULT)
+        .collect::<Vec<_>>();
+    rojo.sort();
+
+    std::iter::once(ROJO_DEFAULT.to_string())
+        .chain(rojo)
+        .chain(MANTLE_FILES.iter().map(ToString::to_string))
+        .collect()
+}
+
+/// Looks for Rojo and Mantle project files in `dir` and returns the universe
+/// and place ids they mention, in file order. Files that can't be read or
+/// parsed are skipped.
+pub fn detect(dir: &Path) -> Vec<Detection> {
+    let mut detections: Vec<Detection> = vec![];
+
+    for name in project_files(dir) {
+        let path = dir.join(&name);
+        let Some(value) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| parse(&path, &contents))
+        else {
+            continue;
+        };
+
+        for id in ids_in(&value) {
+            if detections.iter().all(|detection| detection.id != id) {
+                detections.push(Detection {
+                    id,
+                    source: PathBuf::from(&name),
+                });
+            }
+        }
+    }
+
+    detections
+}
+
+/// The first universe id among the detections, with the file it came from.
+pub fn universe_id(detections: &[Detection]) -> Option<(u64, &Path)> {
+    detections.iter().find_map(|detection| match detection.id {
+        DetectedId::Universe(id) => Some((id, detection.source.as_path())),
+        DetectedId::Place(_) => None,
+    })
+}
+
+/// The universe id `init` writes: one found in the project files in the current
+/// directory, offered as the default when running interactively, or the
+/// placeholder when nothing is found.
+pub fn init_universe_id() -> Result<u64> {
+    let detections = detect(Path::new("."));
+
+    for detection in &detections {
+        match detection.id {
+            DetectedId::Universe(id) => {
+                info!("found universe {} in {}", id, detection.source.display())
+            }
+            DetectedId::Place(id) => info!("found place {} in {}", id, detection.source.display()),
+        }
+    }
+
+    let Some((detected, source)) = universe_id(&detections) else {
+        return Ok(PLACEHOLDER_UNIVERSE_ID);
+    };
+
+    // `--yes` takes the detected id as if enter was pressed.
+    if !can_prompt() || prompt::policy() != PromptPolicy::Ask {
+        info!("using universe {} from {}", detected, source.display());
+        return Ok(detected);
+    }
+
+    let answer = prompt_line(&format!(
+        "Universe id [{} from {}]",
+        detected,
+        source.display()
+    ))?;
+
+    if answer.is_empty() {
+        return Ok(detected);
+    }
+
+    answer
+        .parse()
+        .map_err(|e| format!("invalid universe id '{}': {}", answer, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rojo_game_and_place_ids() {
+        let value = serde_json::json!({
+            "name": "game",
+            "gameId": 4567,
+            "servePlaceIds": [89, 90],
+            "tree": { "$className": "DataModel" }
+        });
+
+        assert_eq!(
+            ids_in(&value),
+            vec![
+                DetectedId::Universe(4567),
+                DetectedId::Place(89),
+                DetectedId::Place(90)
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_every_rojo_project() {
+        let dir = std::env::temp_dir().join(format!("rbx-products-rojo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("place.project.json"), r#"{ "gameId": 22 }"#).unwrap();
+        std::fs::write(dir.join("default.project.json"), r#"{ "placeId": 3 }"#).unwrap();
+        std::fs::write(dir.join("notes.json"), r#"{ "gameId": 44 }"#).unwrap();
+
+        let detections = detect(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            detections,
+            vec![
+                Detection {
+                    id: DetectedId::Place(3),
+                    source: PathBuf::from("default.project.json"),
+                },
+                Detection {
+                    id: DetectedId::Universe(22),
+                    source: PathBuf::from("place.project.json"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_mantle_state_outputs() {
+        let state = "\
+version: '5'
+environments:
+  production:
+    - id: experience_singleton
+      outputs:
+        experience:
+          assetId: 1234567
+          startPlaceId: 7654321
+    - id: place_start
+      outputs:
+        place:
+          assetId: 7654321
+";
+        let value = parse(Path::new(".mantle-state.yml"), state).unwrap();
+
+        assert_eq!(
+            ids_in(&value),
+            vec![DetectedId::Universe(1234567), DetectedId::Place(7654321)]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use log::info;
+
+use crate::Result;
+use crate::sync::products::{ProductType, VCSProducts};
+
+/// Moves an entry to `new_key`, recording the old key as an alias. A product
+/// from an included file stays in that file under its new key.
+pub fn move_key(
+    local_products: &mut VCSProducts,
+    product_type: ProductType,
+    key: &str,
+    new_key: &str,
+) -> Result<()> {
+    let entries = local_products.products_of_mut(product_type);
+
+    if entries.contains_key(new_key) {
+        return Err(format!("{:?} '{}' already exists", product_type, new_key).into());
+    }
+
+    let mut product = entries
+        .remove(key)
+        .ok_or_else(|| format!("{:?} '{}' not found in products.toml", product_type, key))?;
+
+    let aliases = product.aliases.get_or_insert_with(Vec::new);
+    aliases.retain(|alias| alias != new_key);
+    if !aliases.iter().any(|alias| alias == key) {
+        aliases.push(key.to_string());
+    }
+
+    entries.insert(new_key.to_string(), product);
+
+    if let Some(source) = local_products
+        .sources
+        .remove(&(product_type, key.to_string()))
+    {
+        local_products
+            .sources
+            .insert((product_type, new_key.to_string()), source);
+    }
+
+    Ok(())
+}
+
+/// Renames a product's key and/or display name while keeping its id, recording
+/// the old key as an alias.
+pub async fn rename_product(
+    product_type: ProductType,
+    key: String,
+    new_key: Option<String>,
+    new_name: Option<String>,
+) -> Result<()> {
+    if new_key.is_none() && new_name.is_none() {
+        return Err("nothing to rename, pass --key and/or --name".into());
+    }
+
+    let mut local_products = VCSProducts::get_products().await?;
+
+    let product = local_products
+        .products_of_mut(product_type)
+        .get_mut(&key)
+        .ok_or_else(|| format!("{:?} '{}' not found in products.toml", product_type, key))?;
+
+    if let Some(name) = new_name {
+        info!(
+            "renamed {:?} '{}' display name to '{}'",
+            product_type, key, name
+        );
+        product.name = name;
+    }
+
+    if let Some(new_key) = new_key.filter(|new_key| *new_key != key) {
+        move_key(&mut local_products, product_type, &key, &new_key)?;
+        info!("renamed {:?} key '{}' to '{}'", product_type, key, new_key);
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    info!("run `sync` to push display name changes to the universe");
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use log::info;
+
+use crate::Result;
+use crate::sync::products::{ProductType, VCSProducts};
+use crate::ui;
+use crate::ui::prompt;
+use crate::ui::reorder::ReorderViewer;
+
+/// Opens the reorder screen for one product type and rewrites every product's
+/// `order` to match the saved list.
+pub async fn reorder_products(product_type: ProductType) -> Result<()> {
+    if !ui::is_interactive() || !prompt::allows_input() {
+        return Err("reorder needs an interactive terminal".into());
+    }
+
+    let mut local_products = VCSProducts::get_products().await?;
+
+    let items = local_products
+        .shop_order(product_type)
+        .into_iter()
+        .map(|key| {
+            let name = match product_type {
+                ProductType::GamePass => &local_products.gamepasses[key].name,
+                ProductType::DevProduct => &local_products.products[key].name,
+            };
+            (key.clone(), name.clone())
+        })
+        .collect::<Vec<_>>();
+
+    if items.is_empty() {
+        info!("no {:?} entries to reorder", product_type);
+        return Ok(());
+    }
+
+    let Some(keys) = ReorderViewer::reorder(items).await? else {
+        info!("reorder cancelled");
+        return Ok(());
+    };
+
+    let entries = match product_type {
+        ProductType::GamePass => &mut local_products.gamepasses,
+        ProductType::DevProduct => &mut local_products.products,
+    };
+
+    for (index, key) in keys.iter().enumerate() {
+        if let Some(product) = entries.get_mut(key) {
+            product.order = Some(index as u32 + 1);
+        }
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    info!(
+        "saved shop order for {} {:?} entries",
+        keys.len(),
+        product_type
+    );
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::utils::{render_csv, render_table};
+
+/// How Robux spent on a product translates into developer earnings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RevenueModel {
+    /// Fraction of each sale the developer receives after the marketplace fee
+    pub developer_share: f64,
+    /// Optional estimate of Premium payouts, as a fraction of gross
+    pub premium_payout_rate: f64,
+    /// USD paid per Robux cashed out through DevEx
+    pub devex_rate: f64,
+    /// USD a player pays per Robux, from the standard 400 R$ for $4.99 package
+    pub consumer_rate: f64,
+}
+
+impl Default for RevenueModel {
+    fn default() -> Self {
+        Self {
+            developer_share: 0.7,
+            premium_payout_rate: 0.0,
+            devex_rate: 0.0038,
+            consumer_rate: 0.0125,
+        }
+    }
+}
+
+impl RevenueModel {
+    pub fn net(&self, gross: u64) -> u64 {
+        let share = (gross as f64 * self.developer_share).floor();
+        let premium = gross as f64 * self.premium_payout_rate;
+
+        (share + premium).floor() as u64
+    }
+
+    /// Approximate USD the developer cashes out for one sale at `gross`.
+    pub fn payout_usd(&self, gross: u64) -> f64 {
+        self.net(gross) as f64 * self.devex_rate
+    }
+
+    /// Approximate USD a player spends on the Robux for one sale at `gross`.
+    pub fn consumer_usd(&self, gross: u64) -> f64 {
+        gross as f64 * self.consumer_rate
+    }
+}
+
+pub fn format_usd(amount: f64) -> String {
+    format!("${:.2}", amount)
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Csv,
+    Html,
+}
+
+const HEADERS: [&str; 6] = ["key", "type", "id", "price", "gross", "net"];
+
+fn report_rows(local_products: &VCSProducts) -> Vec<Vec<String>> {
+    let model = local_products
+        .metadata
+        .revenue_model
+        .clone()
+        .unwrap_or_default();
+
+    let mut entries = local_products
+        .gamepasses
+        .iter()
+        .map(|(key, product)| (ProductType::GamePass, key, product))
+        .chain(
+            local_products
+                .products
+                .iter()
+                .map(|(key, product)| (ProductType::DevProduct, key, product)),
+        )
+        .collect::<Vec<(ProductType, &String, &Product)>>();
+
+    entries.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    entries
+        .into_iter()
+        .map(|(product_type, key, product)| {
+            let gross = product.get_price(Some(&local_products.metadata));
+
+            vec![
+                key.clone(),
+                format!("{:?}", product_type),
+                product.id.map_or("-".to_string(), |id| id.to_string()),
+                product.price.to_string(),
+                gross.to_string(),
+                model.net(gross).to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(rows: &[Vec<String>]) -> String {
+    let cells = |tag: &str, row: &[String]| {
+        row.iter()
+            .map(|cell| format!("<{tag}>{}</{tag}>", escape_html(cell)))
+            .collect::<String>()
+    };
+
+    let headers = HEADERS.map(String::from);
+    let mut html = String::from("<table>\n");
+    html += &format!("  <tr>{}</tr>\n", cells("th", &headers));
+
+    for row in rows {
+        html += &format!("  <tr>{}</tr>\n", cells("td", row));
+    }
+
+    html += "</table>\n";
+    html
+}
+
+/// Prints projected gross and net earnings per sale for every local product.
+pub async fn print_report(format: ReportFormat) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+    let rows = report_rows(&local_products);
+
+    let output = match format {
+        ReportFormat::Table => render_table(&HEADERS, &rows),
+        ReportFormat::Csv => render_csv(&HEADERS, &rows)?,
+        ReportFormat::Html => render_html(&rows),
+    };
+
+    print!("{}", output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn net_applies_share_and_premium_estimate() {
+        let model = RevenueModel::default();
+        assert_eq!(model.net(100), 70);
+        assert_eq!(model.net(99), 69);
+
+        let model = RevenueModel {
+            developer_share: 0.7,
+            premium_payout_rate: 0.05,
+            ..Default::default()
+        };
+        assert_eq!(model.net(100), 75);
+    }
+
+    #[test]
+    fn estimates_usd_from_rates() {
+        let model = RevenueModel::default();
+        assert_eq!(format_usd(model.consumer_usd(400)), "$5.00");
+        assert_eq!(format_usd(model.payout_usd(1000)), "$2.66");
+        assert_eq!(format_usd(model.payout_usd(0)), "$0.00");
+    }
+}
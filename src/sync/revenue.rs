@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use clap::Args;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::Result;
+use crate::api::economy::{Sale, SalesOwner, fetch_sales};
+use crate::sync::list::ListOutput;
+use crate::sync::products::{ProductType, VCSProducts};
+use crate::utils::{render_csv, render_table};
+
+#[derive(Args, Debug, Clone)]
+pub struct RevenueOptions {
+    /// Id of the user that owns the experience
+    #[arg(long, conflicts_with = "group", required_unless_present = "group")]
+    pub user: Option<u64>,
+    /// Id of the group that owns the experience
+    #[arg(long)]
+    pub group: Option<u64>,
+    /// First day to include (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    pub since: String,
+    /// Day to stop before (YYYY-MM-DD), defaults to now
+    #[arg(long, value_parser = parse_date)]
+    pub until: Option<String>,
+    #[arg(long, value_enum, default_value_t = ListOutput::Table)]
+    pub output: ListOutput,
+}
+
+fn parse_date(s: &str) -> std::result::Result<String, String> {
+    let date = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+
+    if date.is_match(s) {
+        Ok(s.to_string())
+    } else {
+        Err(format!("'{}' is not a YYYY-MM-DD date", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct RevenueEntry {
+    key: String,
+    product_type: Option<ProductType>,
+    id: u64,
+    sales: u64,
+    robux: i64,
+}
+
+const HEADERS: [&str; 5] = ["key", "type", "id", "sales", "robux"];
+
+impl RevenueEntry {
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.key.clone(),
+            self.product_type
+                .map_or("-".to_string(), |t| format!("{:?}", t)),
+            self.id.to_string(),
+            self.sales.to_string(),
+            self.robux.to_string(),
+        ]
+    }
+}
+
+fn sale_type(kind: &str) -> Option<ProductType> {
+    match kind {
+        "GamePass" => Some(ProductType::GamePass),
+        "DeveloperProduct" => Some(ProductType::DevProduct),
+        _ => None,
+    }
+}
+
+/// Totals sales per product in this universe, named by their products file
+/// key where one matches, sorted by Robux earned.
+fn revenue_entries(
+    local_products: &VCSProducts,
+    sales: &[Sale],
+    until: Option<&str>,
+) -> Vec<RevenueEntry> {
+    let universe_id = local_products.metadata.universe_id;
+    let mut totals = BTreeMap::<(Option<ProductType>, u64), RevenueEntry>::new();
+
+    for sale in sales {
+        let in_universe = sale
+            .details
+            .place
+            .as_ref()
+            .and_then(|place| place.universe_id)
+            .is_none_or(|id| id == universe_id);
+
+        if !in_universe || until.is_some_and(|until| sale.created.as_str() >= until) {
+            continue;
+        }
+
+        let Some(id) = sale.details.id else {
+            continue;
+        };
+
+        let product_type = sale_type(&sale.details.kind);
+        let key = product_type
+            .and_then(|product_type| {
+                let entries = match product_type {
+                    ProductType::GamePass => &local_products.gamepasses,
+                    ProductType::DevProduct => &local_products.products,
+                };
+
+                entries
+                    .iter()
+                    .find(|(_, product)| product.id == Some(id))
+                    .map(|(key, _)| key.clone())
+            })
+            .unwrap_or_else(|| sale.details.name.clone());
+
+        let entry = totals
+            .entry((product_type, id))
+            .or_insert_with(|| RevenueEntry {
+                key,
+                product_type,
+                id,
+                sales: 0,
+                robux: 0,
+            });
+
+        entry.sales += 1;
+        entry.robux += sale.currency.amount;
+    }
+
+    let mut entries = totals.into_values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.robux.cmp(&a.robux).then_with(|| a.key.cmp(&b.key)));
+    entries
+}
+
+pub async fn print_revenue(options: RevenueOptions) -> Result<()> {
+    let cookie = std::env::var("RBX_COOKIE")
+        .ok()
+        .filter(|cookie| !cookie.trim().is_empty())
+        .ok_or("RBX_COOKIE must be set to a .ROBLOSECURITY cookie to read sales")?;
+
+    let owner = match (options.user, options.group) {
+        (_, Some(group)) => SalesOwner::Group(group),
+        (Some(user), None) => SalesOwner::User(user),
+        (None, None) => return Err("pass --user or --group".into()),
+    };
+
+    let local_products = VCSProducts::get_products().await?;
+    let sales = fetch_sales(owner, &cookie, &options.since).await?;
+
+    let entries = revenue_entries(&local_products, &sales, options.until.as_deref());
+    let rows = entries.iter().map(RevenueEntry::row).collect::<Vec<_>>();
+
+    let rendered = match options.output {
+        ListOutput::Table => render_table(&HEADERS, &rows),
+        ListOutput::Csv => render_csv(&HEADERS, &rows)?,
+        ListOutput::Json => serde_json::to_string_pretty(&entries)? + "\n",
+    };
+
+    print!("{}", rendered);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::economy::{SaleCurrency, SaleDetails, SalePlace};
+    use crate::sync::products::Product;
+
+    fn sale(id: u64, kind: &str, amount: i64, created: &str, universe_id: u64) -> Sale {
+        Sale {
+            created: created.to_string(),
+            details: SaleDetails {
+                id: Some(id),
+                name: format!("Remote {}", id),
+                kind: kind.to_string(),
+                place: Some(SalePlace {
+                    universe_id: Some(universe_id),
+                }),
+            },
+            currency: SaleCurrency { amount },
+        }
+    }
+
+    #[test]
+    fn totals_sales_per_local_key() {
+        let mut local_products = VCSProducts::default();
+        local_products.metadata.universe_id = 10;
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let sales = [
+            sale(1, "GamePass", 70, "2026-09-02T10:00:00Z", 10),
+            sale(1, "GamePass", 70, "2026-09-01T10:00:00Z", 10),
+            sale(2, "DeveloperProduct", 17, "2026-09-01T09:00:00Z", 10),
+            sale(3, "GamePass", 500, "2026-09-01T08:00:00Z", 99),
+            sale(2, "DeveloperProduct", 17, "2026-10-02T00:00:00Z", 10),
+        ];
+
+        let entries = revenue_entries(&local_products, &sales, Some("2026-10-01"));
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.key.as_str(), entry.sales, entry.robux))
+                .collect::<Vec<_>>(),
+            [("vip", 2, 140), ("Remote 2", 1, 17)]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        assert!(parse_date("2026-09-01").is_ok());
+        assert!(parse_date("09/01/2026").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use clap::{Args, Subcommand};
+use log::info;
+use regex::Regex;
+
+use crate::Result;
+use crate::sync::products::{ProductType, VCSProducts};
+use crate::sync::upload::{SyncFilter, SyncOptions, Uploader};
+
+/// Which products a sale command applies to.
+#[derive(Args, Debug, Clone, Default)]
+pub struct SaleSelection {
+    /// Only include this product type
+    #[arg(long = "type")]
+    pub product_type: Option<ProductType>,
+    /// Only include products whose key matches this regex
+    #[arg(long)]
+    pub filter: Option<Regex>,
+    /// Only include products with one of these tags, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pub tag: Vec<String>,
+    /// Only update the products file, without syncing
+    #[arg(long, default_value_t = false)]
+    pub no_sync: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SaleAction {
+    /// Applies a discount to the selected products and syncs
+    Start {
+        /// Discount percentage
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=100))]
+        percent: u8,
+        #[command(flatten)]
+        selection: SaleSelection,
+        #[command(flatten)]
+        options: SyncOptions,
+    },
+    /// Removes the discount from the selected products and syncs
+    End {
+        #[command(flatten)]
+        selection: SaleSelection,
+        #[command(flatten)]
+        options: SyncOptions,
+    },
+}
+
+impl SaleAction {
+    pub fn selection(&self) -> &SaleSelection {
+        match self {
+            SaleAction::Start { selection, .. } | SaleAction::End { selection, .. } => selection,
+        }
+    }
+}
+
+/// Sets (or clears) the discount on every selected product, returning how many
+/// products were changed.
+fn set_discount(
+    local_products: &mut VCSProducts,
+    selection: &SaleSelection,
+    discount: Option<u8>,
+) -> usize {
+    let mut changed = 0;
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        if selection.product_type.is_some_and(|t| t != product_type) {
+            continue;
+        }
+
+        let entries = match product_type {
+            ProductType::GamePass => &mut local_products.gamepasses,
+            ProductType::DevProduct => &mut local_products.products,
+        };
+
+        for (key, product) in entries.iter_mut() {
+            if selection.filter.as_ref().is_some_and(|f| !f.is_match(key))
+                || !product.matches_tags(&selection.tag)
+            {
+                continue;
+            }
+
+            if product.discount != discount {
+                product.discount = discount;
+                changed += 1;
+            }
+        }
+    }
+
+    changed
+}
+
+pub async fn run_sale(action: SaleAction, overwrite: bool) -> Result<()> {
+    let (selection, mut options, discount) = match action {
+        SaleAction::Start {
+            percent,
+            selection,
+            options,
+        } => (selection, options, Some(percent)),
+        SaleAction::End { selection, options } => (selection, options, None),
+    };
+
+    let mut local_products = VCSProducts::get_products().await?;
+    let changed = set_discount(&mut local_products, &selection, discount);
+
+    match discount {
+        Some(percent) => info!("applied {}% discount to {} product(s)", percent, changed),
+        None => info!("removed discount from {} product(s)", changed),
+    }
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    if selection.no_sync || changed == 0 {
+        return Ok(());
+    }
+
+    // Only push the products the sale touched.
+    options.filter = SyncFilter {
+        only: selection.filter.into_iter().collect(),
+        product_type: selection.product_type,
+        tag: selection.tag,
+    };
+
+    Uploader::upload(overwrite, options).await.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::Product;
+
+    #[test]
+    fn applies_to_selected_products_only() {
+        let mut local_products = VCSProducts::default();
+        for key in ["coins-100", "coins-500", "vip"] {
+            local_products
+                .products
+                .insert(key.to_string(), Product::default());
+        }
+        local_products
+            .gamepasses
+            .insert("coins-pass".to_string(), Product::default());
+
+        let selection = SaleSelection {
+            product_type: Some(ProductType::DevProduct),
+            filter: Some(Regex::new("^coins-").unwrap()),
+            no_sync: true,
+            ..Default::default()
+        };
+
+        assert_eq!(set_discount(&mut local_products, &selection, Some(30)), 2);
+        assert_eq!(local_products.products["coins-500"].discount, Some(30));
+        assert_eq!(local_products.products["vip"].discount, None);
+        assert_eq!(local_products.gamepasses["coins-pass"].discount, None);
+
+        assert_eq!(set_discount(&mut local_products, &selection, None), 2);
+        assert_eq!(local_products.products["coins-100"].discount, None);
+    }
+
+    #[test]
+    fn applies_to_tagged_products_only() {
+        let mut local_products = VCSProducts::default();
+        for (key, tags) in [("snowman", vec!["seasonal"]), ("vip", vec!["vip"])] {
+            local_products.gamepasses.insert(
+                key.to_string(),
+                Product {
+                    tags: Some(tags.into_iter().map(String::from).collect()),
+                    ..Default::default()
+                },
+            );
+        }
+        local_products
+            .products
+            .insert("coins".to_string(), Product::default());
+
+        let selection = SaleSelection {
+            tag: vec!["seasonal".to_string(), "event".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(set_discount(&mut local_products, &selection, Some(20)), 1);
+        assert_eq!(local_products.gamepasses["snowman"].discount, Some(20));
+        assert_eq!(local_products.gamepasses["vip"].discount, None);
+    }
+}
@@ -0,0 +1,135 @@
+use log::info;
+
+use crate::Result;
+use crate::api::products::fetch_product;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::ui::diffs::DiffChange;
+use crate::utils::render_table;
+
+/// The local product tracking a remote id.
+fn find_local(
+    local_products: &VCSProducts,
+    product_type: ProductType,
+    id: u64,
+) -> Option<(&String, &Product)> {
+    local_products
+        .products_of(product_type)
+        .iter()
+        .find(|(_, product)| product.id == Some(id))
+}
+
+/// One row per synced field, marking the ones that differ.
+fn rows(
+    remote: &Product,
+    local: Option<&Product>,
+    local_products: &VCSProducts,
+) -> Vec<Vec<String>> {
+    let diffs = match local {
+        Some(local) => local.field_diffs(remote, Some(&local_products.metadata)),
+        None => remote.field_diffs(remote, None),
+    };
+
+    diffs
+        .iter()
+        .map(|change| {
+            let (diff, changed) = match change {
+                DiffChange::Changed(diff) => (diff, true),
+                DiffChange::Unchanged(diff) | DiffChange::Created(diff) => (diff, false),
+            };
+            let (remote_value, local_value) = diff.values();
+
+            vec![
+                diff.field().to_string(),
+                remote_value,
+                local.map_or("-".to_string(), |_| local_value),
+                if changed { "*" } else { "" }.to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// Fetches a single product by id and prints it next to the local entry
+/// tracking it, without paging through the whole universe.
+pub async fn show_product(id: u64, product_type: Option<ProductType>) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+    let universe_id = local_products.metadata.universe_id;
+
+    // The products file usually knows the type, which saves a request.
+    let candidates = match product_type {
+        Some(product_type) => vec![product_type],
+        None => {
+            match [ProductType::GamePass, ProductType::DevProduct]
+                .into_iter()
+                .find(|product_type| find_local(&local_products, *product_type, id).is_some())
+            {
+                Some(product_type) => vec![product_type],
+                None => vec![ProductType::GamePass, ProductType::DevProduct],
+            }
+        }
+    };
+
+    let mut found = None;
+    for product_type in candidates {
+        if let Some(remote) = fetch_product(universe_id, product_type, id).await? {
+            found = Some((product_type, remote));
+            break;
+        }
+    }
+
+    let (product_type, remote) =
+        found.ok_or_else(|| format!("universe {} has no product with id {}", universe_id, id))?;
+    let local = find_local(&local_products, product_type, id);
+
+    match local {
+        Some((key, _)) => println!("{:?} '{}' (id {})", product_type, key, id),
+        None => println!("{:?} {:?} (id {})", product_type, remote.name, id),
+    }
+    print!(
+        "{}",
+        render_table(
+            &["field", "remote", "local", ""],
+            &rows(&remote, local.map(|(_, product)| product), &local_products)
+        )
+    );
+
+    if local.is_none() {
+        info!("id {} isn't in the products file, adopt it to track it", id);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_fields_that_differ() {
+        let remote = Product {
+            id: Some(1),
+            name: "VIP".to_string(),
+            description: Some("vip room".to_string()),
+            price: 100,
+            active: true,
+            ..Default::default()
+        };
+        let local = Product {
+            price: 80,
+            ..remote.clone()
+        };
+        let local_products = VCSProducts::default();
+
+        let tracked = rows(&remote, Some(&local), &local_products);
+        let price = tracked.iter().find(|row| row[0] == "price").unwrap();
+        assert_eq!(price, &["price", "100", "80", "*"]);
+        let name = tracked.iter().find(|row| row[0] == "name").unwrap();
+        assert_eq!(name, &["name", "\"VIP\"", "\"VIP\"", ""]);
+
+        let untracked = rows(&remote, None, &local_products);
+        assert!(
+            untracked
+                .iter()
+                .all(|row| row[2] == "-" && row[3].is_empty())
+        );
+    }
+}
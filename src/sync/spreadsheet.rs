@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use crate::sync::products::{Attributes, Product, ProductType, VCSProducts};
+use crate::utils::canonical_name;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpreadsheetFormat {
+    #[default]
+    Csv,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SpreadsheetRow {
+    #[serde(rename = "type")]
+    product_type: ProductType,
+    key: String,
+    id: Option<u64>,
+    name: String,
+    description: Option<String>,
+    price: i64,
+    discount: Option<u8>,
+    active: bool,
+    /// JSON-encoded, since a cell can't hold a table
+    #[serde(default, with = "json_cell")]
+    attributes: Option<Attributes>,
+}
+
+mod json_cell {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    use crate::sync::products::Attributes;
+
+    pub fn serialize<S: Serializer>(
+        attributes: &Option<Attributes>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match attributes {
+            Some(attributes) if !attributes.is_empty() => serializer.serialize_str(
+                &serde_json::to_string(attributes).map_err(serde::ser::Error::custom)?,
+            ),
+            _ => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Attributes>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(cell) if !cell.trim().is_empty() => serde_json::from_str(&cell)
+                .map(Some)
+                .map_err(D::Error::custom),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn export_rows(local_products: &VCSProducts) -> Vec<SpreadsheetRow> {
+    let mut rows = [ProductType::GamePass, ProductType::DevProduct]
+        .into_iter()
+        .flat_map(|product_type| {
+            let entries = match product_type {
+                ProductType::GamePass => &local_products.gamepasses,
+                ProductType::DevProduct => &local_products.products,
+            };
+
+            entries.iter().map(move |(key, product)| SpreadsheetRow {
+                product_type,
+                key: key.clone(),
+                id: product.id,
+                name: product.name.clone(),
+                description: product.description.clone(),
+                price: product.price,
+                discount: product.discount,
+                active: product.active,
+                attributes: product.attributes.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    rows.sort_by(|a, b| (a.product_type, &a.key).cmp(&(b.product_type, &b.key)));
+    rows
+}
+
+/// Merges spreadsheet rows into the local products, matching on id first and
+/// then on key, like download does for remote products.
+fn merge_rows(local_products: &mut VCSProducts, rows: Vec<SpreadsheetRow>) -> (usize, usize) {
+    let (mut updated, mut added) = (0, 0);
+    let metadata = local_products.metadata.clone();
+
+    for row in rows {
+        let entries = match row.product_type {
+            ProductType::GamePass => &mut local_products.gamepasses,
+            ProductType::DevProduct => &mut local_products.products,
+        };
+
+        let existing_key = entries
+            .iter()
+            .find(|(_, product)| row.id.is_some() && product.id == row.id)
+            .map(|(key, _)| key.clone())
+            .or_else(|| entries.contains_key(&row.key).then(|| row.key.clone()));
+
+        let key = existing_key.clone().unwrap_or_else(|| {
+            if row.key.is_empty() {
+                metadata.key_for(&canonical_name(row.name.clone(), &metadata.filters()))
+            } else {
+                row.key.clone()
+            }
+        });
+
+        let product = entries.entry(key).or_insert_with(Product::default);
+
+        product.id = row.id.or(product.id);
+        product.name = row.name;
+        product.description = row.description.filter(|d| !d.is_empty());
+        product.price = row.price;
+        product.discount = row.discount.filter(|d| *d > 0);
+        product.active = row.active;
+        product.attributes = row.attributes.filter(|a| !a.is_empty());
+
+        match existing_key {
+            Some(_) => updated += 1,
+            None => added += 1,
+        }
+    }
+
+    (updated, added)
+}
+
+pub async fn export_products(format: SpreadsheetFormat, output: Option<&Path>) -> Result<()> {
+    let local_products = VCSProducts::get_products().await?;
+
+    let data = match format {
+        SpreadsheetFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for row in export_rows(&local_products) {
+                writer.serialize(row)?;
+            }
+            writer.into_inner()?
+        }
+    };
+
+    match output {
+        Some(path) => {
+            tokio::fs::write(path, data).await?;
+            info!("exported products to {}", path.display());
+        }
+        None => print!("{}", String::from_utf8(data)?),
+    }
+
+    Ok(())
+}
+
+pub async fn import_products(format: SpreadsheetFormat, input: &Path) -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+    let data = tokio::fs::read(input).await?;
+
+    let rows = match format {
+        SpreadsheetFormat::Csv => csv::Reader::from_reader(data.as_slice())
+            .deserialize::<SpreadsheetRow>()
+            .collect::<std::result::Result<Vec<_>, _>>()?,
+    };
+
+    let (updated, added) = merge_rows(&mut local_products, rows);
+    info!("imported {} updated and {} new product(s)", updated, added);
+
+    local_products.save_products().await?;
+    local_products.serialize_luau().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_merges_by_id() {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(7),
+                name: "VIP".to_string(),
+                price: 100,
+                prefix: Some("[HOT]".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mut rows = export_rows(&local_products);
+        rows[0].key = "renamed-in-sheet".to_string();
+        rows[0].price = 80;
+        rows.push(SpreadsheetRow {
+            product_type: ProductType::DevProduct,
+            key: String::new(),
+            id: None,
+            name: "100 Coins".to_string(),
+            description: None,
+            price: 25,
+            discount: None,
+            active: true,
+            attributes: Some(Attributes::from([(
+                "icon".to_string(),
+                serde_json::json!(42),
+            )])),
+        });
+
+        assert_eq!(merge_rows(&mut local_products, rows), (1, 1));
+
+        let vip = &local_products.gamepasses["vip"];
+        assert_eq!(vip.price, 80);
+        assert_eq!(vip.prefix.as_deref(), Some("[HOT]"));
+        assert_eq!(
+            local_products.products["100-coins"]
+                .attributes
+                .as_ref()
+                .unwrap()["icon"],
+            42
+        );
+    }
+
+    #[test]
+    fn attributes_round_trip_through_csv() {
+        let mut local_products = VCSProducts::default();
+        local_products.products.insert(
+            "coins".to_string(),
+            Product {
+                attributes: Some(Attributes::from([(
+                    "icon".to_string(),
+                    serde_json::json!("rbxassetid://1"),
+                )])),
+                ..Default::default()
+            },
+        );
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        for row in export_rows(&local_products) {
+            writer.serialize(row).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+
+        let rows = csv::Reader::from_reader(data.as_slice())
+            .deserialize::<SpreadsheetRow>()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(rows, export_rows(&local_products));
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::Result;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Old spellings that are still read, and the key they're read as.
+const ALIASES: [(&str, &str); 2] = [("exclude-from-luau", "internal"), ("extra", "attributes")];
+
+/// Turns on strict parsing for every file, as with `--strict`.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn find_unknown(table: &str, source: &Value, parsed: &Value, unknown: &mut Vec<String>) {
+    let (Value::Object(source), Value::Object(parsed)) = (source, parsed) else {
+        return;
+    };
+
+    for (key, value) in source {
+        let known = parsed.get(key).or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| alias == key)
+                .and_then(|(_, target)| parsed.get(*target))
+        });
+
+        let path = match table {
+            "" => key.clone(),
+            table => format!("{}.{}", table, key),
+        };
+
+        match known {
+            Some(known) => find_unknown(&path, value, known, unknown),
+            // Empty tables and lists are dropped when saving, so they're
+            // missing from `parsed` even when the key is known.
+            None if is_empty(value) => {}
+            None => unknown.push(match table {
+                "" => format!("unknown key '{}' at the top level", key),
+                table => format!("unknown key '{}' in [{}]", key, table),
+            }),
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => object.is_empty(),
+        Value::Array(array) => array.is_empty(),
+        _ => false,
+    }
+}
+
+/// Every key of `source`, the file as plain data, that didn't end up in
+/// `parsed`. Anything serde ignored while parsing is missing from it.
+pub fn unknown_keys<T: Serialize>(source: &Value, parsed: &T) -> Result<Vec<String>> {
+    let mut unknown = vec![];
+    find_unknown("", source, &serde_json::to_value(parsed)?, &mut unknown);
+    Ok(unknown)
+}
+
+/// Fails with every unknown key of a parsed file.
+pub fn check<T: Serialize>(path: &Path, source: &Value, parsed: &T) -> Result<()> {
+    let unknown = unknown_keys(source, parsed)?;
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "{} has {} unknown key(s):\n  {}",
+        path.display(),
+        unknown.len(),
+        unknown.join("\n  ")
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::VCSProducts;
+
+    fn unknown(source: &str) -> Vec<String> {
+        let parsed: VCSProducts = toml::from_str(source).unwrap();
+        let source: Value = toml::from_str(source).unwrap();
+        unknown_keys(&source, &parsed).unwrap()
+    }
+
+    #[test]
+    fn names_the_table_of_unknown_keys() {
+        let source = r#"
+            verison = 2
+
+            [metadata]
+            universe-id = 1
+            name-filters = []
+
+            [gamepasses.vip]
+            name = "VIP"
+            price = 100
+            active = true
+            dicount = 20
+            exclude-from-luau = true
+            extra = { color = "gold" }
+
+            [gamepasses.vip.attributes-typo]
+            color = "gold"
+        "#;
+
+        assert_eq!(
+            unknown(source),
+            [
+                "unknown key 'attributes-typo' in [gamepasses.vip]",
+                "unknown key 'dicount' in [gamepasses.vip]",
+                "unknown key 'verison' at the top level",
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, info};
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use sha2::{Digest, Sha256};
+
+use crate::Result;
+use crate::sync::products::{Product, ProductType, VCSProducts};
+use crate::sync::validate::uploaded_title;
+
+/// Scripts are stopped after this many operations per product, so a stray
+/// loop can't hang a sync.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A stable bucket in `0..buckets` for `key`, the same on every run and
+/// machine, for splitting products into price tests.
+fn bucket(key: &str, buckets: i64) -> i64 {
+    let hash = Sha256::digest(key.as_bytes());
+    let value = u64::from_be_bytes(hash[..8].try_into().unwrap());
+    (value % buckets.max(1) as u64) as i64
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("transform: {}", text));
+    engine.on_debug(|text, _, _| debug!("transform: {}", text));
+    engine.register_fn("bucket", bucket);
+    engine
+}
+
+fn optional(value: Option<impl Into<Dynamic>>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+/// The `product` variable a script sees. `title`, `price` and `description`
+/// are what would be uploaded; the rest is read only.
+fn product_map(
+    local_products: &VCSProducts,
+    product_type: ProductType,
+    key: &str,
+    product: &Product,
+) -> Map {
+    let metadata = &local_products.metadata;
+    let kind = match product_type {
+        ProductType::GamePass => "game-pass",
+        ProductType::DevProduct => "dev-product",
+    };
+    let tags: Array = product
+        .tags
+        .iter()
+        .flatten()
+        .cloned()
+        .map(Dynamic::from)
+        .collect();
+
+    let mut map = Map::new();
+    map.insert("type".into(), kind.into());
+    map.insert("key".into(), key.into());
+    map.insert("name".into(), product.name.clone().into());
+    map.insert(
+        "title".into(),
+        uploaded_title(product, metadata.discount_prefix.clone()).into(),
+    );
+    map.insert(
+        "price".into(),
+        (product.get_price(Some(metadata)) as i64).into(),
+    );
+    map.insert("original_price".into(), product.price.into());
+    map.insert(
+        "discount".into(),
+        optional(product.discount.map(|discount| discount as i64)),
+    );
+    map.insert(
+        "description".into(),
+        optional(product.get_description(Some(metadata))),
+    );
+    map.insert("active".into(), product.active.into());
+    map.insert("tags".into(), tags.into());
+    map.insert("category".into(), optional(product.category.clone()));
+    map
+}
+
+/// Copies the script's changes to `title`, `price` and `description` back
+/// onto the product. Unchanged values are left alone.
+fn apply_changes(product: &mut Product, before: &Map, after: &Map) -> Result<()> {
+    let changed = |field: &str| {
+        let old = before.get(field).map(ToString::to_string);
+        let new = after.get(field).map(ToString::to_string);
+        (old != new).then(|| after.get(field).cloned().unwrap_or(Dynamic::UNIT))
+    };
+
+    if let Some(title) = changed("title") {
+        let title = title
+            .into_string()
+            .map_err(|kind| format!("title must be a string, not {}", kind))?;
+        product.plugin_title = Some(title);
+    }
+
+    if let Some(price) = changed("price") {
+        let price = price
+            .as_int()
+            .map_err(|kind| format!("price must be an integer, not {}", kind))?;
+        let price = u64::try_from(price).map_err(|_| format!("price {} is negative", price))?;
+        product.plugin_price = Some(price);
+    }
+
+    if let Some(description) = changed("description") {
+        let description = match description.is_unit() {
+            true => String::new(),
+            false => description
+                .into_string()
+                .map_err(|kind| format!("description must be a string, not {}", kind))?,
+        };
+        product.plugin_description = Some(description);
+    }
+
+    Ok(())
+}
+
+fn run(engine: &Engine, ast: &AST, local_products: &mut VCSProducts) -> Result<()> {
+    let universe_id = local_products.metadata.universe_id as i64;
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        let keys: Vec<String> = local_products
+            .products_of(product_type)
+            .keys()
+            .cloned()
+            .collect();
+
+        for key in keys {
+            let before = product_map(
+                local_products,
+                product_type,
+                &key,
+                &local_products.products_of(product_type)[&key],
+            );
+
+            let mut scope = Scope::new();
+            scope.push_constant("universe_id", universe_id);
+            scope.push("product", before.clone());
+            engine
+                .run_ast_with_scope(&mut scope, ast)
+                .map_err(|e| format!("transform failed for {:?} '{}': {}", product_type, key, e))?;
+
+            let after = scope
+                .get_value::<Map>("product")
+                .ok_or_else(|| format!("transform replaced `product` for '{}'", key))?;
+            let product = local_products
+                .products_of_mut(product_type)
+                .get_mut(&key)
+                .unwrap();
+            apply_changes(product, &before, &after)
+                .map_err(|e| format!("transform for {:?} '{}': {}", product_type, key, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn script_path(local_products: &VCSProducts, script: &str) -> PathBuf {
+    local_products
+        .path
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""))
+        .join(script)
+}
+
+/// Runs the `transform` script from the metadata once for every product.
+/// Like plugins, its changes only apply to this run's uploads.
+pub async fn apply_script(local_products: &mut VCSProducts) -> Result<()> {
+    let Some(script) = local_products.metadata.transform.clone() else {
+        return Ok(());
+    };
+
+    let path = script_path(local_products, &script);
+    let source = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("failed to read transform {}: {}", path.display(), e))?;
+
+    let engine = engine();
+    let ast = engine
+        .compile(&source)
+        .map_err(|e| format!("failed to parse transform {}: {}", path.display(), e))?;
+
+    debug!("running transform {}", path.display());
+    run(&engine, &ast, local_products)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::validate::ensure_valid;
+
+    fn transform(source: &str) -> Result<VCSProducts> {
+        let mut local_products = VCSProducts::default();
+        local_products.metadata.universe_id = 7;
+        for (key, name, price, tags) in [
+            ("vip", "VIP", 100, vec!["eu"]),
+            ("radio", "Radio", 50, vec![]),
+        ] {
+            local_products.gamepasses.insert(
+                key.to_string(),
+                Product {
+                    name: name.to_string(),
+                    price,
+                    active: true,
+                    description: Some("Costs {price} R$".to_string()),
+                    tags: Some(tags.into_iter().map(String::from).collect()),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let engine = engine();
+        let ast = engine.compile(source)?;
+        run(&engine, &ast, &mut local_products)?;
+        Ok(local_products)
+    }
+
+    #[test]
+    fn adjusts_titles_prices_and_descriptions() {
+        let local_products = transform(
+            r#"
+            if "eu" in product.tags {
+                product.title += " (EU)";
+                product.price = product.price * 2;
+                product.description = `${product.description}, only in Europe`;
+            }
+            "#,
+        )
+        .unwrap();
+
+        let vip = &local_products.gamepasses["vip"];
+        assert_eq!(vip.get_title(), "VIP (EU)");
+        assert_eq!(vip.get_price(None), 200);
+        assert_eq!(
+            vip.get_description(None).as_deref(),
+            Some("Costs 100 R$, only in Europe")
+        );
+
+        let radio = &local_products.gamepasses["radio"];
+        assert_eq!(radio.plugin_title, None);
+        assert_eq!(radio.plugin_price, None);
+        assert_eq!(radio.plugin_description, None);
+    }
+
+    #[test]
+    fn buckets_are_stable() {
+        assert_eq!(bucket("vip", 2), bucket("vip", 2));
+        assert!((0..4).contains(&bucket("radio", 4)));
+
+        let local_products = transform(
+            r#"
+            if universe_id == 7 && bucket(product.key, 1) == 0 {
+                product.price -= 1;
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(local_products.gamepasses["radio"].get_price(None), 49);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let error = transform(r#"product.price = "free";"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "transform for GamePass 'radio': price must be an integer, not string"
+        );
+
+        assert!(transform("product.price = -5;").is_err());
+        assert!(transform("loop {}").is_err());
+    }
+
+    #[test]
+    fn out_of_range_prices_fail_validation() {
+        let local_products = transform("product.price = 0;").unwrap();
+        assert_eq!(local_products.gamepasses["vip"].get_price(None), 0);
+        assert!(ensure_valid(&local_products).is_err());
+
+        let local_products = transform("product.price = 2_000_000_000;").unwrap();
+        assert!(ensure_valid(&local_products).is_err());
+
+        assert!(ensure_valid(&transform("").unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use crate::sync::products::{Product, ProductType, VCSProducts, categorized};
+
+const HEADER: &str =
+    "// This file is automatically generated by rbx-products. Do not edit this file directly.\n";
+
+fn sorted(products: &BTreeMap<String, Product>) -> Vec<(&String, &Product)> {
+    let mut values = products.iter().collect::<Vec<_>>();
+    values.sort_by_key(|(key, product)| (product.id, *key));
+    values
+}
+
+fn quote(key: &str) -> String {
+    serde_json::to_string(key).unwrap_or_default()
+}
+
+fn key_union(products: &[(&String, &Product)]) -> String {
+    if products.is_empty() {
+        return "never".to_string();
+    }
+
+    products
+        .iter()
+        .map(|(key, _)| quote(key))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Renders typings for roblox-ts. A `.d.ts` path declares the shape of the
+/// generated Luau module, anything else gets a standalone `.ts` module with the
+/// same ids and prices.
+pub fn render(local_products: &VCSProducts, declaration: bool) -> String {
+    let gamepasses = sorted(&local_products.gamepasses);
+    let products = sorted(&local_products.products);
+
+    let mut contents = String::from(HEADER);
+    contents += "export interface Product {\n\treadonly id: number;\n\treadonly price: number;\n\treadonly basePrice: number;\n\treadonly discount: number;\n\treadonly tags?: readonly string[];\n\treadonly attributes?: Readonly<Record<string, unknown>>;\n}\n\n";
+    contents += &format!("export type GamepassKey = {};\n", key_union(&gamepasses));
+    contents += &format!("export type ProductKey = {};\n", key_union(&products));
+
+    for (name, key_type, values, entries) in [
+        (
+            "Gamepasses",
+            "GamepassKey",
+            &gamepasses,
+            &local_products.gamepasses,
+        ),
+        (
+            "Products",
+            "ProductKey",
+            &products,
+            &local_products.products,
+        ),
+    ] {
+        contents += "\n";
+
+        if declaration {
+            contents += &format!("export declare const {}: {{\n", name);
+            // Categorized products are nested, like in the Luau module.
+            match categorized(entries) {
+                Some(categories) => {
+                    for (category, values) in categories {
+                        contents += &format!("\treadonly {}: {{\n", quote(category));
+                        for (key, _) in values {
+                            contents += &format!("\t\treadonly {}: Product;\n", quote(key));
+                        }
+                        contents += "\t};\n";
+                    }
+                }
+                None => {
+                    for (key, _) in values {
+                        contents += &format!("\treadonly {}: Product;\n", quote(key));
+                    }
+                }
+            }
+            contents += "};\n";
+        } else {
+            contents += &format!("export const {} = {{\n", name);
+            for (key, product) in values {
+                let attributes = match &product.attributes {
+                    Some(attributes) if !attributes.is_empty() => format!(
+                        ", attributes: {}",
+                        serde_json::to_string(attributes).unwrap_or_default()
+                    ),
+                    _ => String::new(),
+                };
+                let tags = match &product.tags {
+                    Some(tags) if !tags.is_empty() => format!(
+                        ", tags: {}",
+                        serde_json::to_string(tags).unwrap_or_default()
+                    ),
+                    _ => String::new(),
+                };
+
+                contents += &format!(
+                    "\t{}: {{ id: {}, price: {}, basePrice: {}, discount: {}{}{} }},\n",
+                    quote(key),
+                    product.id.unwrap_or(0),
+                    product.get_price(Some(&local_products.metadata)),
+                    product.price,
+                    product.discount.unwrap_or(0),
+                    tags,
+                    attributes
+                );
+            }
+            contents += &format!("}} as const satisfies Record<{}, Product>;\n", key_type);
+        }
+    }
+
+    contents += "\n";
+
+    for (name, key_type, product_type) in [
+        ("GamepassOrder", "GamepassKey", ProductType::GamePass),
+        ("ProductOrder", "ProductKey", ProductType::DevProduct),
+    ] {
+        if declaration {
+            contents += &format!("export declare const {}: readonly {}[];\n", name, key_type);
+        } else {
+            let keys = local_products
+                .shop_order(product_type)
+                .iter()
+                .map(|key| quote(key))
+                .collect::<Vec<_>>();
+
+            contents += &format!(
+                "export const {}: readonly {}[] = [{}];\n",
+                name,
+                key_type,
+                keys.join(", ")
+            );
+        }
+    }
+
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_products() -> VCSProducts {
+        let mut local_products = VCSProducts::default();
+        local_products.gamepasses.insert(
+            "vip".to_string(),
+            Product {
+                id: Some(7),
+                price: 100,
+                discount: Some(50),
+                ..Default::default()
+            },
+        );
+        local_products
+    }
+
+    #[test]
+    fn renders_module_with_key_unions() {
+        let output = render(&local_products(), false);
+
+        assert!(output.contains("export type GamepassKey = \"vip\";\n"));
+        assert!(output.contains("export type ProductKey = never;\n"));
+        assert!(
+            output.contains("\t\"vip\": { id: 7, price: 50, basePrice: 100, discount: 50 },\n")
+        );
+        assert!(output.contains("} as const satisfies Record<ProductKey, Product>;\n"));
+        assert!(
+            output.contains("export const GamepassOrder: readonly GamepassKey[] = [\"vip\"];\n")
+        );
+    }
+
+    #[test]
+    fn renders_declarations() {
+        let output = render(&local_products(), true);
+
+        assert!(
+            output
+                .contains("export declare const Gamepasses: {\n\treadonly \"vip\": Product;\n};\n")
+        );
+        assert!(!output.contains("satisfies"));
+    }
+
+    #[test]
+    fn declares_categories_as_nested_objects() {
+        let mut local_products = local_products();
+        local_products.gamepasses.get_mut("vip").unwrap().category = Some("Perks".to_string());
+
+        let output = render(&local_products, true);
+        assert!(output.contains(
+            "export declare const Gamepasses: {\n\treadonly \"Perks\": {\n\t\treadonly \"vip\": Product;\n\t};\n};\n"
+        ));
+    }
+}
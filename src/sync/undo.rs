@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::Result;
+use crate::api::model::ProductUpdateRequest;
+use crate::api::products::{fetch_all_products, update_dev_product, update_gamepass};
+use crate::sync::plan::unix_now;
+use crate::sync::products::{MultiProduct, Product, ProductType};
+use crate::ui;
+use crate::ui::confirm::{ConfirmState, ConfirmViewer};
+use crate::ui::diffs::{DiffViewer, ProductDiffs};
+use crate::ui::preview::ImageProtocol;
+
+/// Where a backup is written before every sync or apply.
+const BACKUP_DIR: &str = ".rbx-products/backups";
+
+/// The remote state of every product a sync touched, taken just before the
+/// sync changed it.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Backup {
+    pub universe_id: u64,
+    pub updated: Vec<BackedUpProduct>,
+    /// Products the sync created, which Roblox doesn't let us delete again
+    #[serde(default)]
+    pub created: Vec<BackedUpProduct>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BackedUpProduct {
+    pub product_type: ProductType,
+    pub key: String,
+    /// For updates, the product as it was in the universe before the sync
+    pub product: Product,
+}
+
+impl Backup {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.created.is_empty()
+    }
+
+    /// Writes the backup, to a new timestamped file the first time and to the
+    /// same file afterwards.
+    pub async fn save(&mut self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => {
+                fs::create_dir_all(BACKUP_DIR).await?;
+                let path = Path::new(BACKUP_DIR).join(format!("{}.json", unix_now().as_millis()));
+                self.path = Some(path.clone());
+                path
+            }
+        };
+
+        fs::write(path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// The newest backup that hasn't been undone yet.
+    pub async fn latest() -> Result<Self> {
+        let mut entries = fs::read_dir(BACKUP_DIR)
+            .await
+            .map_err(|_| "no backups found, nothing has been synced from this directory")?;
+
+        let mut paths = vec![];
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                paths.push(path);
+            }
+        }
+
+        // Names are millisecond timestamps, so the longest and then largest is newest.
+        let path = paths
+            .into_iter()
+            .max_by_key(|path| {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                (name.len(), name.to_string())
+            })
+            .ok_or("no backups left to undo")?;
+
+        let mut backup: Backup = serde_json::from_slice(&fs::read(&path).await?)
+            .map_err(|e| format!("failed to read backup {}: {}", path.display(), e))?;
+        backup.path = Some(path);
+        Ok(backup)
+    }
+
+    /// Renames the backup so the next `undo` goes back one sync further.
+    async fn mark_undone(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            fs::rename(path, path.with_extension("json.undone")).await?;
+        }
+        Ok(())
+    }
+
+    /// The changes that would put each updated product back the way it was.
+    fn reverse_diffs(&self, remote_products: &[MultiProduct]) -> Vec<(ProductType, ProductDiffs)> {
+        self.updated
+            .iter()
+            .filter_map(|backed_up| {
+                let id = backed_up.product.id?;
+                let remote = remote_products.iter().find_map(|multi_product| {
+                    match (multi_product, backed_up.product_type) {
+                        (MultiProduct::GamePass(pass), ProductType::GamePass) => Some(pass),
+                        (MultiProduct::DevProduct(prod), ProductType::DevProduct) => Some(prod),
+                        _ => None,
+                    }
+                    .filter(|product| product.id == Some(id))
+                })?;
+
+                backed_up
+                    .product
+                    .diff(remote, None)
+                    .map(|diff| (backed_up.product_type, diff))
+            })
+            .collect()
+    }
+}
+
+/// Pushes the remote state from before the last sync back to the universe.
+pub async fn undo(skip_prompts: bool) -> Result<()> {
+    let backup = Backup::latest().await?;
+    let remote_products = fetch_all_products(backup.universe_id).await?;
+    let all_diffs = backup.reverse_diffs(&remote_products);
+
+    for created in &backup.created {
+        warn!(
+            "{:?} '{}' (id: {}) was created by the sync and can't be deleted, take it off sale by hand if needed",
+            created.product_type,
+            created.key,
+            created.product.id.unwrap_or(0)
+        );
+    }
+
+    if all_diffs.is_empty() {
+        info!("the universe already matches the backup, nothing to undo");
+        return backup.mark_undone().await;
+    }
+
+    let selected = if skip_prompts {
+        all_diffs
+            .iter()
+            .map(|(product_type, diff)| (*product_type, diff.id))
+            .collect()
+    } else if ui::can_prompt() {
+        let selected =
+            DiffViewer::confirm_diffs(all_diffs.clone(), BTreeMap::new(), ImageProtocol::Off)
+                .await?
+                .into_iter()
+                .map(|index| (all_diffs[index].0, all_diffs[index].1.id))
+                .collect::<Vec<_>>();
+
+        if ConfirmViewer::show_prompt("Would you like to undo the last sync?").await?
+            == ConfirmState::Closed
+        {
+            info!("user aborted undo.");
+            return Ok(());
+        }
+        selected
+    } else {
+        for (product_type, diff) in &all_diffs {
+            info!(
+                "{:?} '{}' (id: {}) would be restored",
+                product_type, diff.name, diff.id
+            );
+        }
+        return Err("pass --yes to undo without prompting".into());
+    };
+
+    let mut failed = 0;
+
+    for backed_up in &backup.updated {
+        let Some(id) = backed_up.product.id else {
+            continue;
+        };
+        if !selected.contains(&(backed_up.product_type, id)) {
+            continue;
+        }
+
+        let request = ProductUpdateRequest::from(&backed_up.product);
+        let result = match backed_up.product_type {
+            ProductType::GamePass => update_gamepass(backup.universe_id, id, &request).await,
+            ProductType::DevProduct => update_dev_product(backup.universe_id, id, &request).await,
+        };
+
+        match result {
+            Ok(()) => info!("restored {:?} '{}'", backed_up.product_type, backed_up.key),
+            Err(e) => {
+                log::error!(
+                    "failed to restore {:?} '{}': {}",
+                    backed_up.product_type,
+                    backed_up.key,
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} product(s) couldn't be restored", failed).into());
+    }
+
+    backup.mark_undone().await?;
+    info!(
+        "run `download` to bring the products file in line, or the next sync will redo these changes"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::fields;
+    use crate::ui::diffs::{DiffChange, ProductDiff};
+
+    #[test]
+    fn reverses_changed_fields_only() {
+        let before = Product {
+            id: Some(7),
+            name: "VIP".to_string(),
+            description: Some(String::new()),
+            price: 100,
+            active: true,
+            ..Default::default()
+        };
+        let after = Product {
+            price: 80,
+            ..before.clone()
+        };
+
+        let backup = Backup {
+            universe_id: 1,
+            updated: vec![BackedUpProduct {
+                product_type: ProductType::GamePass,
+                key: "vip".to_string(),
+                product: before.clone(),
+            }],
+            ..Default::default()
+        };
+
+        let diffs = backup.reverse_diffs(&[MultiProduct::GamePass(after)]);
+        assert_eq!(diffs.len(), 1);
+        assert!(
+            diffs[0]
+                .1
+                .diffs
+                .contains(&DiffChange::Changed(ProductDiff::new(
+                    &fields::PRICE,
+                    80,
+                    100
+                )))
+        );
+
+        assert!(
+            backup
+                .reverse_diffs(&[MultiProduct::GamePass(before)])
+                .is_empty()
+        );
+    }
+}
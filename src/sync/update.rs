@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use log::info;
+use sha2::{Digest, Sha256};
+
+use crate::Result;
+use crate::api::releases::{
+    CHECKSUMS_ASSET, Release, asset_name, download_asset, fetch_latest_release,
+};
+use crate::ui::confirm::{ConfirmState, ConfirmViewer};
+
+/// `v1.2.3` or `1.2.3-beta` as numbers. Pre-release suffixes are ignored.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Finds `name` in a `sha256sum` style listing of `<hex>  <file>` lines.
+fn checksum_for<'a>(checksums: &'a str, name: &str) -> Option<&'a str> {
+    checksums.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+        (file.trim().trim_start_matches('*') == name).then_some(hash)
+    })
+}
+
+/// Downloads this platform's binary and checks it against the release's
+/// checksums, refusing anything that can't be verified.
+async fn download_verified(release: &Release) -> Result<Vec<u8>> {
+    let name = asset_name();
+    let binary = release.asset(&name).ok_or_else(|| {
+        format!(
+            "release {} has no binary for this platform ({}), see {}",
+            release.tag_name, name, release.html_url
+        )
+    })?;
+    let checksums = release.asset(CHECKSUMS_ASSET).ok_or_else(|| {
+        format!(
+            "release {} has no {}, refusing to install an unverified binary",
+            release.tag_name, CHECKSUMS_ASSET
+        )
+    })?;
+
+    let checksums = String::from_utf8(download_asset(checksums).await?)?;
+    let expected = checksum_for(&checksums, &name)
+        .ok_or_else(|| format!("{} doesn't list {}", CHECKSUMS_ASSET, name))?;
+
+    let data = download_asset(binary).await?;
+    let actual = format!("{:x}", Sha256::digest(&data));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "checksum mismatch for {}: expected {}, got {}",
+            name, expected, actual
+        )
+        .into());
+    }
+
+    Ok(data)
+}
+
+/// Writes the new binary next to `exe` and moves it into place, so a failed
+/// write never leaves a half-written executable behind.
+fn replace_executable(exe: &Path, data: &[u8]) -> Result<()> {
+    let staged = exe.with_extension("new");
+    std::fs::write(&staged, data)
+        .map_err(|e| format!("failed to write {}: {}", staged.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // Windows won't overwrite a running executable, but it will rename one.
+    #[cfg(windows)]
+    std::fs::rename(exe, exe.with_extension("old"))?;
+
+    std::fs::rename(&staged, exe)
+        .map_err(|e| format!("failed to replace {}: {}", exe.display(), e))?;
+    Ok(())
+}
+
+/// Checks GitHub for a newer release and, unless `check_only`, replaces the
+/// running executable with its verified binary.
+pub async fn self_update(check_only: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = fetch_latest_release().await?;
+
+    if !is_newer(&release.tag_name, current) {
+        info!("rbx_products {} is up to date", current);
+        return Ok(());
+    }
+
+    if check_only {
+        println!(
+            "rbx_products {} is available (installed: {}), see {}",
+            release.tag_name, current, release.html_url
+        );
+        return Ok(());
+    }
+
+    let prompt = format!("Update rbx_products {} to {}?", current, release.tag_name);
+    if ConfirmViewer::show_prompt(prompt).await? == ConfirmState::Closed {
+        info!("update cancelled");
+        return Ok(());
+    }
+
+    let data = download_verified(&release).await?;
+    let exe = std::env::current_exe()?;
+    replace_executable(&exe, &data)?;
+
+    info!("updated {} to {}", exe.display(), release.tag_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.2.0-beta.1"), Some((0, 2, 0)));
+        assert_eq!(parse_version("2"), Some((2, 0, 0)));
+        assert_eq!(parse_version("nightly"), None);
+
+        assert!(is_newer("v0.1.10", "0.1.2"));
+        assert!(!is_newer("v0.1.2", "0.1.2"));
+        assert!(!is_newer("nightly", "0.1.2"));
+    }
+
+    #[test]
+    fn finds_checksums() {
+        let checksums = "abc123  rbx_products-x86_64-linux\n\
+                         def456 *rbx_products-x86_64-windows.exe\n";
+
+        assert_eq!(
+            checksum_for(checksums, "rbx_products-x86_64-linux"),
+            Some("abc123")
+        );
+        assert_eq!(
+            checksum_for(checksums, "rbx_products-x86_64-windows.exe"),
+            Some("def456")
+        );
+        assert_eq!(checksum_for(checksums, "rbx_products-aarch64-macos"), None);
+    }
+}
@@ -1,20 +1,187 @@
-use log::info;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::Args;
+use clap_complete::ArgValueCompleter;
+use futures::{StreamExt, stream};
+use log::{info, warn};
+use regex::Regex;
 
 use crate::Result;
+use crate::api::THROTTLE;
+use crate::api::error::ErrorCode;
 use crate::api::model::ProductUpdateRequest;
 use crate::api::products::{
     create_dev_product, create_gamepass, fetch_all_products, update_dev_product, update_gamepass,
 };
-use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts};
+use crate::api::thumbnails::fetch_icons;
+use crate::completions::product_keys;
+use crate::sync::base::RemoteBase;
+use crate::sync::catalog::{self, CatalogChange};
+use crate::sync::diff::{self, DiffEntry, DiffOptions};
+use crate::sync::fields;
+use crate::sync::github::{self, CheckOutput, PendingRow};
+use crate::sync::limits::{CatalogUsage, max_products};
+use crate::sync::lockfile::SyncLock;
+use crate::sync::monetization::{self, SettingChange};
+use crate::sync::notify::{ChangeAction, ChangeSummary, FailureSummary, notify_all};
+use crate::sync::orphans::find_orphans;
+use crate::sync::plan::{PlannedChange, SyncPlan, remote_state_hash};
+use crate::sync::plugins;
+use crate::sync::products::{MultiProduct, Product, ProductType, VCSProducts, image_hash};
+use crate::sync::transform;
+use crate::sync::undo::{BackedUpProduct, Backup};
+use crate::sync::validate::ensure_valid;
+use crate::ui;
 use crate::ui::confirm::{ConfirmState, ConfirmViewer};
-use crate::ui::diffs::{DiffViewer, ProductDiffs};
+use crate::ui::diffs::{self, DiffChange, DiffKind, DiffViewer, ProductDiff, ProductDiffs};
+use crate::ui::failure::{FailureChoice, FailureViewer};
+use crate::ui::preview::{self, ImageProtocol};
+use crate::utils::glob_regex;
+
+#[derive(Args, Debug, Clone)]
+pub struct SyncOptions {
+    /// Campaign name made available to notification templates
+    #[arg(long)]
+    pub campaign: Option<String>,
+    /// Allow syncs that make paid products free or take them off sale
+    #[arg(long, default_value_t = false)]
+    pub allow_revenue_risk: bool,
+    /// Abort before applying anything if the sync would make more than N changes
+    #[arg(long)]
+    pub max_changes: Option<usize>,
+    /// Upper bound on concurrent requests, reduced automatically when rate limited
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
+    /// Which products to diff and push, set from `SyncFilter` by the commands that take one
+    #[arg(skip)]
+    pub filter: SyncFilter,
+    /// Report what a sync would do without writing anything, set by `sync --dry-run`
+    #[arg(skip)]
+    pub dry_run: bool,
+    /// Compare every product, including ones unchanged since the last sync, set by `sync --full`
+    #[arg(skip)]
+    pub full: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            campaign: None,
+            allow_revenue_risk: false,
+            max_changes: None,
+            concurrency: 8,
+            filter: SyncFilter::default(),
+            dry_run: false,
+            full: false,
+        }
+    }
+}
+
+/// Restricts a sync to some of the products file.
+#[derive(Args, Debug, Clone, Default)]
+pub struct SyncFilter {
+    /// Only sync these keys, comma separated. Accepts `*`/`?` globs or `/regex/`
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = parse_key_pattern,
+        add = ArgValueCompleter::new(product_keys)
+    )]
+    pub only: Vec<Regex>,
+    /// Only sync this product type
+    #[arg(long = "type")]
+    pub product_type: Option<ProductType>,
+    /// Only sync products with one of these tags, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pub tag: Vec<String>,
+}
+
+/// Turns a `--only` value into a regex matching whole keys.
+fn parse_key_pattern(s: &str) -> std::result::Result<Regex, String> {
+    match s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+        Some(regex) => Regex::new(regex),
+        None => glob_regex(s),
+    }
+    .map_err(|e| e.to_string())
+}
+
+impl SyncFilter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.product_type.is_none() && self.tag.is_empty()
+    }
+
+    pub fn matches(&self, product_type: ProductType, key: &str, product: &Product) -> bool {
+        self.product_type.is_none_or(|t| t == product_type)
+            && (self.only.is_empty() || self.only.iter().any(|re| re.is_match(key)))
+            && product.matches_tags(&self.tag)
+    }
+}
+
+/// A change outside the game passes and developer products, listed and
+/// applied after them.
+#[derive(Debug, Clone)]
+enum ExtraChange {
+    Setting(SettingChange),
+    Catalog(CatalogChange),
+}
+
+impl fmt::Display for ExtraChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtraChange::Setting(change) => change.fmt(f),
+            ExtraChange::Catalog(change) => change.fmt(f),
+        }
+    }
+}
+
+impl ExtraChange {
+    async fn apply(&self, universe_id: u64) -> Result<()> {
+        match self {
+            ExtraChange::Setting(change) => monetization::apply_change(universe_id, change).await,
+            ExtraChange::Catalog(change) => catalog::apply_change(change).await,
+        }
+    }
+
+    fn diff_entry(&self, universe_id: u64) -> DiffEntry {
+        match self {
+            ExtraChange::Setting(change) => DiffEntry::fields(
+                "metadata",
+                "monetization".to_string(),
+                universe_id,
+                &[(
+                    change.setting.key(),
+                    change.remote_value(),
+                    change.local_value(),
+                )],
+            ),
+            ExtraChange::Catalog(change) => DiffEntry::fields(
+                "catalog",
+                change.key.clone(),
+                change.item.id,
+                &change.fields(),
+            ),
+        }
+    }
+}
 
 pub struct Uploader {
     local_products: VCSProducts,
     remote_products: Vec<MultiProduct>,
+    applied: Vec<ChangeSummary>,
+    failures: Vec<FailureSummary>,
+    options: SyncOptions,
+    /// Remote state of everything this run changed, for `undo`
+    backup: Backup,
+    /// Universe prices and catalog items that differ from the products file
+    extras: Vec<ExtraChange>,
+    /// Products the last sync left matching the universe, skipped when diffing
+    lock: Option<SyncLock>,
 }
 
-fn apply_discount_prefix(product: &mut Product, prefix: Option<String>) {
+pub fn apply_discount_prefix(product: &mut Product, prefix: Option<String>) {
     let prefix = prefix.unwrap_or("💲{}% OFF💲".to_string());
 
     if product.has_discount() {
@@ -26,314 +193,1804 @@ fn apply_discount_prefix(product: &mut Product, prefix: Option<String>) {
     }
 }
 
+/// Performs the remote call for a single planned change, returning the id of
+/// the created or updated product.
+async fn execute_change(universe_id: u64, change: &PlannedChange) -> Result<u64> {
+    match change {
+        PlannedChange::Create {
+            product_type: ProductType::GamePass,
+            request,
+            ..
+        } => Ok(create_gamepass(universe_id, request).await?.game_pass_id),
+        PlannedChange::Create {
+            product_type: ProductType::DevProduct,
+            request,
+            ..
+        } => Ok(create_dev_product(universe_id, request).await?.product_id),
+        PlannedChange::Update {
+            product_type: ProductType::GamePass,
+            id,
+            request,
+            ..
+        } => {
+            update_gamepass(universe_id, *id, request).await?;
+            Ok(*id)
+        }
+        PlannedChange::Update {
+            product_type: ProductType::DevProduct,
+            id,
+            request,
+            ..
+        } => {
+            update_dev_product(universe_id, *id, request).await?;
+            Ok(*id)
+        }
+    }
+}
+
 impl Uploader {
-    fn has_empty_products(&self) -> bool {
-        let has_empty_gamepasses = self
-            .local_products
-            .gamepasses
-            .iter()
-            .any(|(_, gp)| gp.id.is_none());
-        let has_empty_devproducts = self
-            .local_products
-            .products
+    fn products_of(&self, product_type: ProductType) -> &BTreeMap<String, Product> {
+        self.local_products.products_of(product_type)
+    }
+
+    fn products_of_mut(&mut self, product_type: ProductType) -> &mut BTreeMap<String, Product> {
+        self.local_products.products_of_mut(product_type)
+    }
+
+    fn find_local(&self, product_type: ProductType, id: u64) -> Option<(&String, &Product)> {
+        self.products_of(product_type)
             .iter()
-            .any(|(_, dp)| dp.id.is_none());
+            .find(|(_, product)| product.id == Some(id))
+    }
 
-        has_empty_gamepasses || has_empty_devproducts
+    fn find_remote(&self, product_type: ProductType, id: u64) -> Option<&Product> {
+        self.remote_products.iter().find_map(|multi_product| {
+            match (multi_product, product_type) {
+                (MultiProduct::GamePass(pass), ProductType::GamePass) => Some(pass),
+                (MultiProduct::DevProduct(prod), ProductType::DevProduct) => Some(prod),
+                _ => None,
+            }
+            .filter(|product| product.id == Some(id))
+        })
     }
 
-    async fn upload_empty(&mut self, overwrite: bool) -> Result<()> {
-        if !self.has_empty_products() {
-            return Ok(());
+    fn record_failure(&mut self, change: &PlannedChange, e: &(dyn std::error::Error + 'static)) {
+        self.failures.push(FailureSummary {
+            code: ErrorCode::classify(e),
+            product_type: change.product_type(),
+            key: change.key().to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    /// Remembers the hash of an uploaded image so it isn't sent again.
+    fn record_image(
+        &mut self,
+        product_type: ProductType,
+        key: &str,
+        request: &ProductUpdateRequest,
+    ) {
+        let Some(path) = &request.image else {
+            return;
+        };
+
+        if let Some(product) = self.products_of_mut(product_type).get_mut(key) {
+            product.image_hash = image_hash(path);
         }
+    }
 
-        if !overwrite {
-            let prompt_result =
-                ConfirmViewer::show_prompt("Would you like to upload non-existant products?").await;
+    fn record_applied(
+        &mut self,
+        action: ChangeAction,
+        product_type: ProductType,
+        key: &str,
+        id: u64,
+        request: &ProductUpdateRequest,
+    ) {
+        let old_price = match action {
+            ChangeAction::Created => None,
+            ChangeAction::Updated => self
+                .find_remote(product_type, id)
+                .map(|product| product.price as u64),
+        };
 
-            if prompt_result != ConfirmState::Confirmed {
-                info!("not uploading non-existant products");
-                return Ok(());
+        let discount = self
+            .products_of(product_type)
+            .get(key)
+            .and_then(|product| product.discount);
+
+        self.applied.push(ChangeSummary {
+            action,
+            product_type,
+            key: key.to_string(),
+            name: request.name.clone(),
+            old_price,
+            new_price: request.price,
+            discount,
+        });
+    }
+
+    /// Records the current icon of every applied product, since uploads and
+    /// creates give passes a new icon asset.
+    fn record_icons(&mut self) {
+        let icons = self
+            .applied
+            .iter()
+            .filter_map(|change| {
+                let id = self.products_of(change.product_type).get(&change.key)?.id?;
+                let icon = self.find_remote(change.product_type, id)?.icon_asset_id;
+                Some((change.product_type, change.key.clone(), icon))
+            })
+            .collect::<Vec<_>>();
+
+        for (product_type, key, icon) in icons {
+            if let Some(product) = self.products_of_mut(product_type).get_mut(&key) {
+                product.icon_asset_id = icon;
             }
         }
+    }
 
-        let universe_id = self.local_products.metadata.universe_id.clone();
-        let upload_product =
-            async |universe_id: u64, product: Product, product_type: ProductType| -> Result<u64> {
-                let update_request = ProductUpdateRequest::from(&product);
-                let product_id = match product_type {
-                    ProductType::GamePass => {
-                        create_gamepass(universe_id, &update_request)
-                            .await?
-                            .game_pass_id
-                    }
+    /// Compares every applied product with the remote products, returning a
+    /// failure for each one that still doesn't match the products file.
+    fn divergences(&self) -> Vec<FailureSummary> {
+        self.applied
+            .iter()
+            .filter_map(|change| {
+                let local = self.products_of(change.product_type).get(&change.key)?;
+                let failure = |message: String| FailureSummary {
+                    code: ErrorCode::Diverged,
+                    product_type: change.product_type,
+                    key: change.key.clone(),
+                    message,
+                };
 
-                    ProductType::DevProduct => {
-                        create_dev_product(universe_id, &update_request)
-                            .await?
-                            .product_id
-                    }
+                let Some(remote) = local
+                    .id
+                    .and_then(|id| self.find_remote(change.product_type, id))
+                else {
+                    return Some(failure(
+                        "missing from the universe after syncing".to_string(),
+                    ));
                 };
 
-                info!(
-                    "uploaded {:?} '{}' with id {}",
-                    product_type, product.name, product_id
-                );
+                let diffs = local.diff(remote, Some(&self.local_products.metadata))?;
+                let changed = diffs
+                    .diffs
+                    .iter()
+                    .filter_map(|diff| match diff {
+                        DiffChange::Changed(diff) => Some(diff.describe()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
 
-                Ok(product_id)
-            };
+                Some(failure(changed.join(", ")))
+            })
+            .collect()
+    }
+
+    /// Re-fetches the universe after applying changes and checks that Roblox
+    /// now has the intended values, catching silent no-ops and moderation
+    /// rewrites.
+    async fn verify_applied(&mut self) -> Result<()> {
+        if self.applied.is_empty() {
+            return Ok(());
+        }
 
-        let mut gamepass_futures = vec![];
-        let mut devproduct_futures = vec![];
+        info!("verifying {} applied change(s)", self.applied.len());
+        self.remote_products = fetch_all_products(self.local_products.metadata.universe_id).await?;
+        self.record_icons();
 
-        info!(
-            "uploading {} products, and {} gamepasses in universe {}",
-            self.local_products.products.len(),
-            self.local_products.gamepasses.len(),
-            universe_id
+        let divergences = self.divergences();
+        if divergences.is_empty() {
+            return Ok(());
+        }
+
+        for divergence in &divergences {
+            warn!(
+                "{:?} '{}' doesn't match after syncing: {}",
+                divergence.product_type, divergence.key, divergence.message
+            );
+        }
+
+        let count = divergences.len();
+        self.failures.extend(divergences);
+
+        Err(format!(
+            "{} product(s) don't match the products file after syncing",
+            count
+        )
+        .into())
+    }
+
+    fn update_request(&self, product: &Product) -> ProductUpdateRequest {
+        let mut product = product.clone();
+
+        apply_discount_prefix(
+            &mut product,
+            self.local_products.metadata.discount_prefix.clone(),
         );
 
-        for (name, gamepass) in &self.local_products.gamepasses {
-            if gamepass.id.is_none() {
-                let universe_id = universe_id.clone();
-                let name = name.clone();
-                let mut gamepass = gamepass.clone();
+        let mut request = ProductUpdateRequest::from(&product);
+        request.price = Some(product.get_price(Some(&self.local_products.metadata)));
+        request.description = product.get_description(Some(&self.local_products.metadata));
+        request.image = product.changed_image().and(product.image.clone());
+        request
+    }
 
-                apply_discount_prefix(
-                    &mut gamepass,
-                    self.local_products.metadata.discount_prefix.clone(),
-                );
+    /// Whether a sync touches the product: it's selected by the filters and
+    /// not locked.
+    fn syncs(&self, product_type: ProductType, key: &str, product: &Product) -> bool {
+        self.options.filter.matches(product_type, key, product) && !product.is_locked()
+    }
 
-                let future = (async move {
-                    let product_id =
-                        upload_product(universe_id, gamepass, ProductType::GamePass).await;
+    /// Selected locked products that a sync would otherwise create or change.
+    fn locked_changes(&self) -> Vec<(ProductType, &String, Option<u64>)> {
+        let mut locked = vec![];
 
-                    match product_id {
-                        Ok(id) => Some((name, id)),
-                        Err(e) => {
-                            log::error!("failed to upload gamepass '{}': {}", name, e);
-                            None
-                        }
-                    }
-                })
-                .await;
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            for (key, product) in self.products_of(product_type) {
+                if !product.is_locked() || !self.options.filter.matches(product_type, key, product)
+                {
+                    continue;
+                }
+
+                let changed = match product.id {
+                    None => true,
+                    Some(id) => self.find_remote(product_type, id).is_some_and(|remote| {
+                        product
+                            .diff(remote, Some(&self.local_products.metadata))
+                            .is_some_and(|diff| {
+                                diff.diffs
+                                    .iter()
+                                    .any(|change| matches!(change, DiffChange::Changed(_)))
+                            })
+                    }),
+                };
 
-                gamepass_futures.push(future);
+                if changed {
+                    locked.push((product_type, key, product.id));
+                }
             }
         }
 
-        for (name, devproduct) in &self.local_products.products {
-            if devproduct.id.is_none() {
-                let universe_id = universe_id.clone();
-                let name = name.clone();
-                let mut devproduct = devproduct.clone();
+        locked
+    }
 
-                apply_discount_prefix(
-                    &mut devproduct,
-                    self.local_products.metadata.discount_prefix.clone(),
-                );
+    fn planned_creates(&self) -> Vec<PlannedChange> {
+        let mut creates = vec![];
 
-                let future = (async move {
-                    let product_id =
-                        upload_product(universe_id, devproduct.clone(), ProductType::DevProduct)
-                            .await;
-
-                    match product_id {
-                        Ok(id) => Some((name, id)),
-                        Err(e) => {
-                            log::error!("failed to upload dev product '{}': {}", name, e);
-                            None
-                        }
-                    }
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            let mut entries = self
+                .products_of(product_type)
+                .iter()
+                .filter(|(key, product)| {
+                    product.id.is_none() && self.syncs(product_type, key, product)
                 })
-                .await;
+                .collect::<Vec<_>>();
 
-                devproduct_futures.push(future);
-            }
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            creates.extend(
+                entries
+                    .into_iter()
+                    .map(|(key, product)| PlannedChange::Create {
+                        product_type,
+                        key: key.clone(),
+                        request: self.update_request(product),
+                    }),
+            );
         }
 
-        gamepass_futures.into_iter().for_each(|res| {
-            if let Some((name, id)) = res {
-                self.local_products
-                    .gamepasses
-                    .get_mut(name.as_str())
-                    .unwrap()
-                    .id = Some(id as u64);
-            }
-        });
+        creates
+    }
 
-        devproduct_futures.into_iter().for_each(|res| {
-            if let Some((name, id)) = res {
-                self.local_products
-                    .products
-                    .get_mut(name.as_str())
-                    .unwrap()
-                    .id = Some(id as u64);
-            }
-        });
+    fn planned_update(&self, product_type: ProductType, id: u64) -> Option<PlannedChange> {
+        self.find_local(product_type, id).map(|(key, product)| {
+            let mut request = self.update_request(product);
 
-        self.local_products.save_products().await?;
-        self.local_products.serialize_luau().await?;
+            // Uploading the image again is the only way to restore an
+            // icon that was replaced on the website.
+            if self
+                .find_remote(product_type, id)
+                .is_some_and(|remote| product.icon_changed(remote))
+            {
+                request.image = request.image.or(product.image.clone());
+            }
 
-        Ok(())
+            PlannedChange::Update {
+                product_type,
+                key: key.clone(),
+                id,
+                request,
+            }
+        })
     }
 
-    async fn upload_modified(&mut self, overwrite: bool) -> Result<()> {
-        let mut product_diffs = vec![];
-
-        let universe_id = self.local_products.metadata.universe_id.clone();
+    /// Every tracked product that differs from its remote, and whether the
+    /// remote can still be edited.
+    fn remote_diffs(&self) -> Vec<(ProductType, ProductDiffs, bool)> {
         let products = &self.remote_products;
+        let metadata = &self.local_products.metadata;
         let mut all_local_products = vec![];
 
-        all_local_products.extend(self.local_products.gamepasses.values().cloned());
-        all_local_products.extend(self.local_products.products.values().cloned());
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            all_local_products.extend(
+                self.products_of(product_type)
+                    .iter()
+                    .filter(|(key, product)| self.syncs(product_type, key, product))
+                    .map(|(_, product)| product.clone()),
+            );
+        }
 
-        product_diffs.extend(
-            all_local_products
-                .iter()
-                .filter_map(|local_product| {
-                    let id = match local_product.id {
-                        Some(id) => id,
+        let mut all_diffs = all_local_products
+            .iter()
+            .filter_map(|local_product| {
+                let id = local_product.id?;
+
+                let (product_type, remote_product) =
+                    match products.iter().find(|multi_product| match multi_product {
+                        MultiProduct::GamePass(pass) => pass.id.unwrap() == id,
+                        MultiProduct::DevProduct(prod) => prod.id.unwrap() == id,
+                    }) {
+                        Some(MultiProduct::GamePass(pass)) => (ProductType::GamePass, pass),
+                        Some(MultiProduct::DevProduct(prod)) => (ProductType::DevProduct, prod),
                         None => return None,
                     };
 
-                    let (product_type, remote_product) =
-                        match products.iter().find(|multi_product| match multi_product {
-                            MultiProduct::GamePass(pass) => pass.id.unwrap() == id,
-                            MultiProduct::DevProduct(prod) => prod.id.unwrap() == id,
-                        }) {
-                            Some(MultiProduct::GamePass(pass)) => (ProductType::GamePass, pass),
-                            Some(MultiProduct::DevProduct(prod)) => (ProductType::DevProduct, prod),
-                            None => return None,
-                        };
-
-                    local_product
-                        .diff(&remote_product, Some(&self.local_products.metadata))
-                        .map(|diff| (product_type, diff))
-                })
-                .collect::<Vec<_>>(),
-        );
+                if self.lock.as_ref().is_some_and(|lock| {
+                    lock.unchanged(product_type, local_product, remote_product, metadata)
+                }) {
+                    return None;
+                }
+
+                local_product
+                    .diff(remote_product, Some(&self.local_products.metadata))
+                    .map(|diff| (product_type, diff, !remote_product.immutable))
+            })
+            .collect::<Vec<_>>();
 
-        let mut all_diffs = vec![] as Vec<(ProductType, ProductDiffs)>;
-        all_diffs.extend(product_diffs);
         all_diffs.sort_by(|a, b| match b.0.cmp(&a.0) {
             std::cmp::Ordering::Equal => a.1.id.cmp(&b.1.id),
             other => other,
         });
 
-        if all_diffs.len() == 0 {
-            info!("no differences found between local and universe products.");
-            return Ok(());
-        }
+        all_diffs
+    }
 
-        let diffs: Vec<(ProductType, u64)>;
+    fn compute_diffs(&self) -> Vec<(ProductType, ProductDiffs)> {
+        self.remote_diffs()
+            .into_iter()
+            .filter(|(_, _, editable)| *editable)
+            .map(|(product_type, diff, _)| (product_type, diff))
+            .collect()
+    }
 
-        if !overwrite {
-            diffs = DiffViewer::confirm_diffs(all_diffs.iter().cloned().collect()).await;
+    /// Warns about passes whose icon was replaced on the website, when there's
+    /// no local image to restore it from.
+    fn warn_changed_icons(&self) {
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            for (key, product) in self.products_of(product_type) {
+                let Some(remote) = product.id.and_then(|id| self.find_remote(product_type, id))
+                else {
+                    continue;
+                };
 
-            let apply = ConfirmViewer::show_prompt("Would you like to sync products?").await;
+                if product.is_locked() {
+                    continue;
+                }
 
-            if apply == ConfirmState::Closed {
-                info!("user aborted sync.");
-                return Ok(());
+                if product.image.is_none() && product.icon_changed(remote) {
+                    warn!(
+                        "{:?} '{}' has a new icon ({}) that wasn't set by rbx-products, run `download` to record it",
+                        product_type,
+                        key,
+                        remote.icon_asset_id.unwrap_or(0)
+                    );
+                }
             }
-        } else {
-            diffs = all_diffs
-                .iter()
-                .cloned()
-                .map(|(product_type, diff)| (product_type, diff.id))
-                .collect::<Vec<_>>();
         }
+    }
 
-        if diffs.len() == 0 {
-            info!("No changes to apply.");
-            return Ok(());
+    /// Warns about products whose changes can't be synced because Roblox made
+    /// the remote product immutable.
+    fn warn_immutable(&self) {
+        for (product_type, diff, _) in self
+            .remote_diffs()
+            .into_iter()
+            .filter(|(_, _, editable)| !editable)
+        {
+            warn!(
+                "{:?} '{}' (id: {}) can't be edited on Roblox anymore, skipping its changes",
+                product_type, diff.name, diff.id
+            );
         }
+    }
 
-        info!("syncing {} product(s)", diffs.len());
+    fn log_unchanged(&self) {
+        let Some(lock) = &self.lock else {
+            return;
+        };
 
-        for (product_type, id) in diffs {
-            let mut local_product = match product_type {
-                ProductType::GamePass => self
-                    .local_products
-                    .gamepasses
+        let metadata = &self.local_products.metadata;
+        let unchanged = [ProductType::GamePass, ProductType::DevProduct]
+            .into_iter()
+            .flat_map(|product_type| {
+                self.products_of(product_type)
                     .values()
-                    .find(|gp| gp.id == Some(id)),
-                ProductType::DevProduct => self
-                    .local_products
-                    .products
-                    .values()
-                    .find(|prod| prod.id == Some(id)),
-            }
-            .unwrap()
-            .clone();
+                    .filter_map(move |product| {
+                        let remote = self.find_remote(product_type, product.id?)?;
+                        Some((product_type, product, remote))
+                    })
+            })
+            .filter(|(product_type, product, remote)| {
+                lock.unchanged(*product_type, product, remote, metadata)
+            })
+            .count();
 
-            let name = local_product.name.clone();
+        if unchanged > 0 {
+            info!(
+                "skipping {} product(s) unchanged since the last sync, pass --full to compare them",
+                unchanged
+            );
+        }
+    }
 
-            apply_discount_prefix(
-                &mut local_product,
-                self.local_products.metadata.discount_prefix.clone(),
+    fn log_locked(&self) {
+        for (product_type, key, _) in self.locked_changes() {
+            info!(
+                "{:?} '{}' is locked, leaving its remote product as is",
+                product_type, key
             );
+        }
+    }
 
-            let update_request = ProductUpdateRequest::from(&local_product);
+    /// Computes every create and update a sync would perform, without
+    /// prompting or touching the network.
+    pub fn plan(&self) -> SyncPlan {
+        let mut plan = SyncPlan::new(self.local_products.metadata.universe_id);
+        plan.remote_hash = remote_state_hash(&self.remote_products);
 
-            match product_type {
-                ProductType::GamePass => {
-                    update_gamepass(universe_id, id, &update_request).await?;
-                }
-                ProductType::DevProduct => {
-                    update_dev_product(universe_id, id, &update_request).await?;
+        plan.changes.extend(self.planned_creates());
+        plan.changes.extend(
+            self.compute_diffs()
+                .into_iter()
+                .filter_map(|(product_type, diff)| self.planned_update(product_type, diff.id)),
+        );
+
+        plan
+    }
+
+    /// Lists planned updates that would make a paid product free or take it
+    /// off sale.
+    fn revenue_risks(&self, plan: &SyncPlan) -> Vec<String> {
+        plan.changes
+            .iter()
+            .filter_map(|change| match change {
+                PlannedChange::Update {
+                    product_type,
+                    key,
+                    id,
+                    request,
+                } => {
+                    let remote = self.find_remote(*product_type, *id)?;
+                    let old_price = remote.price.max(0) as u64;
+
+                    if old_price == 0 {
+                        None
+                    } else if request.price == Some(0) {
+                        Some(format!("{}: price {} R$ -> free", key, old_price))
+                    } else if remote.active && request.is_for_sale == Some(false) {
+                        Some(format!("{}: taken off sale (was {} R$)", key, old_price))
+                    } else {
+                        None
+                    }
                 }
-            }
+                PlannedChange::Create { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Catalog usage after the planned creates for each configured cap,
+    /// counting products that exist remotely but aren't tracked.
+    fn capacity(&self, plan: &SyncPlan) -> Vec<CatalogUsage> {
+        let mut usages = Vec::new();
+
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            let Some(max) = max_products(&self.local_products.metadata, product_type) else {
+                continue;
+            };
 
-            info!("synced {:?} '{}' (id: {})", product_type, name, id);
+            let existing = self
+                .remote_products
+                .iter()
+                .filter(|multi_product| {
+                    matches!(
+                        (multi_product, product_type),
+                        (MultiProduct::GamePass(_), ProductType::GamePass)
+                            | (MultiProduct::DevProduct(_), ProductType::DevProduct)
+                    )
+                })
+                .count();
+
+            let creates = plan
+                .changes
+                .iter()
+                .filter(|change| {
+                    matches!(change, PlannedChange::Create { .. })
+                        && change.product_type() == product_type
+                })
+                .count();
+
+            usages.push(CatalogUsage {
+                product_type,
+                used: existing + creates,
+                max,
+            });
         }
 
-        info!("finished syncing all gamepasses/products");
+        usages
+    }
 
-        Ok(())
+    /// Warns when the planned creates bring the universe near or past a
+    /// configured cap. Roblox enforces its own limits, so this never blocks.
+    fn warn_capacity(&self, plan: &SyncPlan) {
+        for usage in self.capacity(plan) {
+            if usage.is_over() {
+                warn!("sync will leave {}, over the limit", usage.describe());
+            } else if usage.is_near() {
+                warn!(
+                    "sync will leave {}, approaching the limit",
+                    usage.describe()
+                );
+            }
+        }
     }
 
-    async fn create() -> Result<Self> {
-        info!("fetching local products");
-        let local_products_data = VCSProducts::get_products().await?;
+    /// Refuses risky plans unless `--allow-revenue-risk` was passed, and even
+    /// then asks for confirmation when running interactively.
+    async fn check_revenue_risk(&self, plan: &SyncPlan, interactive: bool) -> Result<bool> {
+        let risks = self.revenue_risks(plan);
 
-        info!("fetching remote products");
-        let remote_product_data =
-            fetch_all_products(local_products_data.metadata.universe_id).await?;
+        if risks.is_empty() {
+            return Ok(true);
+        }
 
-        info!(
-            "fetched {} local products, {} remote products",
-            local_products_data.gamepasses.len() + local_products_data.products.len(),
-            remote_product_data.len()
+        for risk in &risks {
+            warn!("revenue risk: {}", risk);
+        }
+
+        if !self.options.allow_revenue_risk {
+            return Err(format!(
+                "{} change(s) would make paid products free or take them off sale, pass --allow-revenue-risk to apply them",
+                risks.len()
+            )
+            .into());
+        }
+
+        if !interactive {
+            return Ok(true);
+        }
+
+        let prompt = format!(
+            "These changes put revenue at risk:\n{}\n\nApply them anyway?",
+            risks.join("\n")
         );
 
-        Ok(Self {
-            local_products: local_products_data,
-            remote_products: remote_product_data,
-        })
+        Ok(ConfirmViewer::show_prompt(prompt).await? == ConfirmState::Confirmed)
     }
 
-    pub async fn upload(overwrite: bool) -> Result<()> {
-        let mut uploader = Uploader::create().await?;
+    /// Executes a plan against the universe, writing the ids of newly created
+    /// products back into the local products.
+    pub async fn apply(&mut self, plan: &SyncPlan) -> Result<()> {
+        let universe_id = self.local_products.metadata.universe_id;
 
-        let mut run_upload = async || -> Result<()> {
-            uploader.upload_empty(overwrite).await?;
-            uploader.upload_modified(overwrite).await?;
+        if plan.universe_id != universe_id {
+            return Err(format!(
+                "plan targets universe {}, but products.toml is for universe {}",
+                plan.universe_id, universe_id
+            )
+            .into());
+        }
 
-            Ok(())
-        };
+        // Requests run concurrently behind the shared throttle, which adapts
+        // the number in flight to the rate limiting we observe.
+        THROTTLE.set_max(self.options.concurrency);
 
-        let upload_result = run_upload().await;
+        let updated = plan
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                PlannedChange::Update {
+                    product_type,
+                    key,
+                    id,
+                    ..
+                } => Some(BackedUpProduct {
+                    product_type: *product_type,
+                    key: key.clone(),
+                    product: self.find_remote(*product_type, *id)?.clone(),
+                }),
+                PlannedChange::Create { .. } => None,
+            })
+            .collect::<Vec<_>>();
 
-        uploader.local_products.save_products().await?;
-        uploader.local_products.serialize_luau().await?;
+        if !updated.is_empty() {
+            self.backup.universe_id = universe_id;
+            self.backup.updated.extend(updated);
+            self.backup
+                .save()
+                .await
+                .map_err(|e| format!("failed to write backup: {}", e))?;
+        }
 
-        if let Err(e) = upload_result {
-            info!("failed to upload modified products: {}, aborting upload", e);
+        let results = stream::iter(&plan.changes)
+            .map(|change| async move {
+                let _permit = THROTTLE.acquire().await;
+                (change, execute_change(universe_id, change).await)
+            })
+            .buffer_unordered(self.options.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut first_error = None;
+        let mut aborted = false;
+
+        for (change, mut result) in results {
+            // Ask about each failure until the user aborts, which leaves the
+            // rest to be recorded as they are.
+            while !aborted && let Err(e) = &result {
+                let product = format!("{:?} '{}'", change.product_type(), change.key());
+
+                match FailureViewer::ask(product, e.to_string()).await? {
+                    FailureChoice::Retry => {
+                        let _permit = THROTTLE.acquire().await;
+                        result = execute_change(universe_id, change).await;
+                    }
+                    FailureChoice::Skip => break,
+                    FailureChoice::Abort => aborted = true,
+                }
+            }
+
+            match (change, result) {
+                (
+                    PlannedChange::Create {
+                        product_type,
+                        key,
+                        request,
+                    },
+                    Ok(id),
+                ) => {
+                    info!(
+                        "uploaded {:?} '{}' with id {}",
+                        product_type, request.name, id
+                    );
+
+                    if let Some(product) = self.products_of_mut(*product_type).get_mut(key) {
+                        product.id = Some(id);
+                    }
+
+                    self.backup.universe_id = universe_id;
+                    self.backup.created.push(BackedUpProduct {
+                        product_type: *product_type,
+                        key: key.clone(),
+                        product: Product {
+                            id: Some(id),
+                            name: request.name.clone(),
+                            ..Default::default()
+                        },
+                    });
+
+                    self.record_image(*product_type, key, request);
+                    self.record_applied(ChangeAction::Created, *product_type, key, id, request);
+                }
+                (
+                    PlannedChange::Update {
+                        product_type,
+                        key,
+                        id,
+                        request,
+                    },
+                    Ok(_),
+                ) => {
+                    info!("synced {:?} '{}' (id: {})", product_type, key, id);
+
+                    self.record_image(*product_type, key, request);
+                    self.record_applied(ChangeAction::Updated, *product_type, key, *id, request);
+                }
+                (PlannedChange::Create { .. }, Err(e)) => {
+                    log::error!(
+                        "failed to upload {:?} '{}': {}",
+                        change.product_type(),
+                        change.key(),
+                        e
+                    );
+                    self.record_failure(change, e.as_ref());
+                    first_error.get_or_insert(e);
+                }
+                (PlannedChange::Update { .. }, Err(e)) => {
+                    log::error!(
+                        "failed to sync {:?} '{}': {}",
+                        change.product_type(),
+                        change.key(),
+                        e
+                    );
+                    self.record_failure(change, e.as_ref());
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        if aborted {
+            first_error.get_or_insert_with(|| "sync aborted after a failed request".into());
+        }
+
+        if plan.creates() > 0
+            && !self.backup.created.is_empty()
+            && let Err(e) = self.backup.save().await
+        {
+            warn!("failed to record created products in the backup: {}", e);
+        }
+
+        if !self.failures.is_empty() {
+            let mut counts = BTreeMap::new();
+            for failure in &self.failures {
+                *counts.entry(failure.code).or_insert(0) += 1;
+            }
+
+            warn!(
+                "{} change(s) failed: {}",
+                self.failures.len(),
+                counts
+                    .iter()
+                    .map(|(code, count)| format!("{} {}", count, code))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        log::debug!(
+            "finished applying with a concurrency limit of {}",
+            THROTTLE.limit()
+        );
+
+        if let Some(e) = first_error {
             return Err(e);
         }
 
         Ok(())
     }
+
+    /// The fields a planned create will set, shown as created.
+    fn creation_diffs(&self, product_type: ProductType, key: &str) -> Vec<DiffChange> {
+        let blank = Product {
+            description: Some(String::new()),
+            ..Default::default()
+        };
+
+        self.products_of(product_type)[key]
+            .diff(&blank, Some(&self.local_products.metadata))
+            .map(|diff| diff.diffs)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|change| match change {
+                DiffChange::Unchanged(diff)
+                | DiffChange::Changed(diff)
+                | DiffChange::Created(diff) => DiffChange::Created(diff),
+            })
+            .collect()
+    }
+
+    /// Remote products that no local product points at. Only listed when every
+    /// product is being synced, since filters select by local key.
+    fn untracked_remote(&self) -> Vec<(ProductType, &Product)> {
+        if !self.options.filter.is_empty() {
+            return vec![];
+        }
+
+        find_orphans(&self.local_products, &self.remote_products)
+    }
+
+    /// Everything the review screen lists: creates, then updates, then remote
+    /// products the sync leaves alone, each with the change it stands for.
+    fn review_entries(&self) -> Vec<((ProductType, ProductDiffs), Option<PlannedChange>)> {
+        let mut entries = vec![];
+
+        for change in self.planned_creates() {
+            let product_type = change.product_type();
+            let diff = ProductDiffs {
+                name: self.products_of(product_type)[change.key()].name.clone(),
+                id: 0,
+                kind: DiffKind::Created,
+                diffs: self.creation_diffs(product_type, change.key()),
+            };
+            entries.push(((product_type, diff), Some(change)));
+        }
+
+        for (product_type, diff) in self.compute_diffs() {
+            let change = self.planned_update(product_type, diff.id);
+            entries.push(((product_type, diff), change));
+        }
+
+        for (product_type, product) in self.untracked_remote() {
+            let diff = ProductDiffs {
+                name: product.name.clone(),
+                id: product.id.unwrap_or(0),
+                kind: DiffKind::Deleted,
+                diffs: vec![
+                    DiffChange::Unchanged(ProductDiff::new(
+                        &fields::TITLE,
+                        product.name.clone(),
+                        product.name.clone(),
+                    )),
+                    DiffChange::Unchanged(ProductDiff::new(
+                        &fields::PRICE,
+                        product.price as u64,
+                        product.price as u64,
+                    )),
+                    DiffChange::Unchanged(ProductDiff::new(
+                        &fields::ACTIVE,
+                        product.active,
+                        product.active,
+                    )),
+                ],
+            };
+            entries.push(((product_type, diff), None));
+        }
+
+        entries
+    }
+
+    async fn upload_changes(&mut self, overwrite: bool) -> Result<()> {
+        let (all_diffs, changes): (Vec<_>, Vec<_>) = self.review_entries().into_iter().unzip();
+
+        let products_changed = changes.iter().any(Option::is_some);
+        if !products_changed && self.extras.is_empty() {
+            info!("no differences found between local and universe products.");
+            return Ok(());
+        }
+
+        let mut selected = vec![];
+        let mut extras = self.extras.clone();
+
+        if !overwrite {
+            if !ui::can_prompt() {
+                for line in diffs::plain_lines(&all_diffs) {
+                    info!("{}", line);
+                }
+                for change in &self.extras {
+                    info!("{}", change);
+                }
+
+                info!(
+                    "{} product(s) and {} setting/catalog change(s) not synced, pass --yes to sync them without prompting",
+                    changes.iter().flatten().count(),
+                    self.extras.len()
+                );
+                return Ok(());
+            }
+
+            // Icons are drawn by the diff viewer, which a piped stdout skips.
+            let protocol = match ui::is_interactive() {
+                true => preview::protocol(),
+                false => ImageProtocol::Off,
+            };
+            let icons = match protocol {
+                ImageProtocol::Off => BTreeMap::new(),
+                _ => {
+                    let ids = all_diffs
+                        .iter()
+                        .filter(|(_, diff)| diff.kind != DiffKind::Created)
+                        .map(|(product_type, diff)| (*product_type, diff.id))
+                        .collect::<Vec<_>>();
+                    fetch_icons(&ids).await
+                }
+            };
+
+            if products_changed {
+                selected = DiffViewer::confirm_diffs(all_diffs, icons, protocol).await?;
+
+                let apply = ConfirmViewer::show_prompt("Would you like to sync products?").await?;
+                if apply == ConfirmState::Closed {
+                    info!("user aborted sync.");
+                    return Ok(());
+                }
+            }
+
+            extras = self.confirm_extras().await?;
+        } else {
+            selected = (0..changes.len()).collect();
+        }
+
+        let mut plan = SyncPlan::new(self.local_products.metadata.universe_id);
+        plan.changes = selected
+            .into_iter()
+            .filter_map(|index| changes[index].clone())
+            .collect();
+
+        if plan.is_empty() && extras.is_empty() {
+            info!("No changes to apply.");
+            return Ok(());
+        }
+
+        if !self.check_revenue_risk(&plan, !overwrite).await? {
+            info!("user aborted sync.");
+            return Ok(());
+        }
+
+        if !plan.is_empty() {
+            info!(
+                "syncing {} new and {} changed product(s)",
+                plan.creates(),
+                plan.updates()
+            );
+
+            self.apply(&plan).await?;
+        }
+
+        self.apply_extras(&extras).await?;
+
+        info!("finished syncing all gamepasses/products");
+
+        Ok(())
+    }
+
+    /// Loads the products file and runs the transforms and checks every
+    /// upload goes through.
+    async fn prepare_local() -> Result<VCSProducts> {
+        let mut local_products = VCSProducts::get_products().await?;
+        plugins::apply_transforms(&mut local_products).await?;
+        transform::apply_script(&mut local_products).await?;
+        ensure_valid(&local_products)?;
+        plugins::validate(&local_products).await?;
+
+        Ok(local_products)
+    }
+
+    async fn create() -> Result<Self> {
+        info!("fetching local products");
+        let local_products_data = Self::prepare_local().await?;
+
+        info!("fetching remote products");
+        let remote_product_data =
+            fetch_all_products(local_products_data.metadata.universe_id).await?;
+
+        info!(
+            "fetched {} local products, {} remote products",
+            local_products_data.gamepasses.len() + local_products_data.products.len(),
+            remote_product_data.len()
+        );
+
+        let mut extras = vec![];
+        if let Some(monetization) = &local_products_data.metadata.monetization {
+            info!("fetching universe settings");
+            let universe_id = local_products_data.metadata.universe_id;
+            let remote = monetization.fetch_remote(universe_id).await?;
+            extras.extend(
+                monetization
+                    .changes(&remote)
+                    .into_iter()
+                    .map(ExtraChange::Setting),
+            );
+        }
+        extras.extend(
+            catalog::pending_changes(&local_products_data.catalog)
+                .await?
+                .into_iter()
+                .map(ExtraChange::Catalog),
+        );
+
+        Ok(Self {
+            local_products: local_products_data,
+            remote_products: remote_product_data,
+            applied: vec![],
+            failures: vec![],
+            options: SyncOptions::default(),
+            backup: Backup::default(),
+            extras,
+            lock: None,
+        })
+    }
+
+    /// Records the universe as both sides of the next `download` start from.
+    async fn record_base(&self) {
+        RemoteBase::from_remote(
+            self.local_products.metadata.universe_id,
+            &self.remote_products,
+        )
+        .save(self.local_products.metadata.censor_threshold())
+        .await;
+    }
+
+    /// Records which products match the universe now. Products the lock
+    /// already vouched for carry over, and only the rest are diffed.
+    async fn record_lock(&self) {
+        let metadata = &self.local_products.metadata;
+        let mut lock = SyncLock::new(metadata.universe_id);
+
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            for product in self.products_of(product_type).values() {
+                let Some(remote) = product.id.and_then(|id| self.find_remote(product_type, id))
+                else {
+                    continue;
+                };
+
+                let unchanged = self
+                    .lock
+                    .as_ref()
+                    .is_some_and(|lock| lock.unchanged(product_type, product, remote, metadata));
+                if unchanged || product.diff(remote, Some(metadata)).is_none() {
+                    lock.insert(product_type, product, remote, metadata);
+                }
+            }
+        }
+
+        lock.save().await;
+    }
+
+    async fn notify(&self) {
+        notify_all(
+            &self.local_products.notifications,
+            &self.applied,
+            &self.failures,
+            self.options.campaign.as_deref(),
+        )
+        .await;
+    }
+
+    /// Syncs the universe with the products file, returning how many changes
+    /// were detected (whether or not they were applied).
+    pub async fn upload(overwrite: bool, options: SyncOptions) -> Result<usize> {
+        let mut uploader = Uploader::create().await?;
+        uploader.options = options;
+
+        if !uploader.options.full {
+            let universe_id = uploader.local_products.metadata.universe_id;
+            uploader.lock = SyncLock::load(universe_id).await;
+        }
+
+        if !uploader.options.filter.is_empty() {
+            let selected = [ProductType::GamePass, ProductType::DevProduct]
+                .into_iter()
+                .map(|product_type| {
+                    uploader
+                        .products_of(product_type)
+                        .iter()
+                        .filter(|(key, product)| {
+                            uploader.options.filter.matches(product_type, key, product)
+                        })
+                        .count()
+                })
+                .sum::<usize>();
+
+            if selected == 0 {
+                return Err("no products match --only/--type/--tag".into());
+            }
+            info!("only syncing {} selected product(s)", selected);
+        }
+
+        let orphans = uploader.untracked_remote().len();
+        if orphans > 0 {
+            warn!(
+                "{} remote product(s) aren't in the products file, run `orphans` to adopt or ignore them",
+                orphans
+            );
+        }
+
+        uploader.warn_immutable();
+        uploader.log_locked();
+        uploader.warn_changed_icons();
+        uploader.log_unchanged();
+
+        let plan = uploader.plan();
+        let detected = plan.changes.len() + uploader.extras.len();
+        plan.check_budget(uploader.options.max_changes)?;
+        uploader.warn_capacity(&plan);
+
+        if uploader.options.dry_run {
+            uploader.log_pending(&plan);
+            for risk in uploader.revenue_risks(&plan) {
+                warn!("{}", risk);
+            }
+            uploader.local_products.preview_generated().await?;
+
+            info!("dry run, nothing was changed");
+            return Ok(detected);
+        }
+
+        let mut run_upload = async || -> Result<()> {
+            uploader.upload_changes(overwrite).await?;
+
+            Ok(())
+        };
+
+        let mut upload_result = run_upload().await;
+        if upload_result.is_ok() {
+            upload_result = uploader.verify_applied().await;
+        }
+
+        uploader.local_products.save_products().await?;
+        uploader.local_products.serialize_luau().await?;
+        uploader.record_base().await;
+        if upload_result.is_ok() {
+            uploader.record_lock().await;
+        }
+        uploader.notify().await;
+
+        if let Err(e) = upload_result {
+            info!("failed to upload modified products: {}, aborting upload", e);
+            return Err(e);
+        }
+
+        Ok(detected)
+    }
+
+    /// Lists the changes a sync would make without applying anything,
+    /// returning how many there are.
+    pub async fn check(output: CheckOutput) -> Result<usize> {
+        let uploader = Uploader::create().await?;
+        uploader.warn_immutable();
+        uploader.log_locked();
+        uploader.warn_changed_icons();
+
+        let plan = uploader.plan();
+        uploader.warn_capacity(&plan);
+
+        match output {
+            CheckOutput::Log => uploader.log_pending(&plan),
+            CheckOutput::Github => {
+                let rows = uploader.pending_rows(&plan);
+                github::emit(&rows, &uploader.revenue_risks(&plan)).await?;
+                uploader.log_summary(&plan);
+            }
+        }
+
+        Ok(plan.changes.len() + uploader.extras.len())
+    }
+
+    /// Logs every field a plan would change, followed by a summary.
+    fn log_pending(&self, plan: &SyncPlan) {
+        for row in self.pending_rows(plan) {
+            info!(
+                "would {} {:?} '{}': {} {} -> {}",
+                row.action, row.product_type, row.key, row.field, row.before, row.after
+            );
+        }
+        for change in &self.extras {
+            info!("would change {}", change);
+        }
+
+        self.log_summary(plan);
+    }
+
+    fn log_summary(&self, plan: &SyncPlan) {
+        if plan.is_empty() && self.extras.is_empty() {
+            info!("universe is in sync with the products file");
+        } else if self.extras.is_empty() {
+            info!(
+                "{} create(s) and {} update(s) pending",
+                plan.creates(),
+                plan.updates()
+            );
+        } else {
+            info!(
+                "{} create(s), {} update(s) and {} setting/catalog change(s) pending",
+                plan.creates(),
+                plan.updates(),
+                self.extras.len()
+            );
+        }
+    }
+
+    /// Asks about each universe price and catalog change on its own, since
+    /// the diff viewer only lists products. Returns the changes to apply.
+    async fn confirm_extras(&self) -> Result<Vec<ExtraChange>> {
+        let mut confirmed = vec![];
+
+        for change in &self.extras {
+            let prompt = format!("Would you like to apply this change?\n{}", change);
+            if ConfirmViewer::show_prompt(prompt).await? == ConfirmState::Confirmed {
+                confirmed.push(change.clone());
+            }
+        }
+
+        Ok(confirmed)
+    }
+
+    /// Applies the chosen universe price and catalog changes, after the
+    /// products.
+    async fn apply_extras(&self, extras: &[ExtraChange]) -> Result<()> {
+        let universe_id = self.local_products.metadata.universe_id;
+
+        for change in extras {
+            change.apply(universe_id).await?;
+            info!("changed {}", change);
+        }
+
+        Ok(())
+    }
+
+    /// Prints a git-style diff of the products file against the universe,
+    /// returning how many products differ.
+    pub async fn diff(options: DiffOptions) -> Result<usize> {
+        let uploader = Uploader::create().await?;
+        let mut entries = vec![];
+
+        for change in uploader.planned_creates() {
+            let product_type = change.product_type();
+            let key = change.key().to_string();
+            let changes = uploader.creation_diffs(product_type, &key);
+
+            entries.push(DiffEntry::new(product_type, key, None, &changes));
+        }
+
+        for (product_type, diff) in uploader.compute_diffs() {
+            let Some((key, _)) = uploader.find_local(product_type, diff.id) else {
+                continue;
+            };
+
+            entries.push(DiffEntry::new(
+                product_type,
+                key.clone(),
+                Some(diff.id),
+                &diff.diffs,
+            ));
+        }
+
+        if options.usd {
+            let model = uploader
+                .local_products
+                .metadata
+                .revenue_model
+                .clone()
+                .unwrap_or_default();
+
+            for entry in &mut entries {
+                entry.annotate_prices(&model);
+            }
+        }
+
+        let universe_id = uploader.local_products.metadata.universe_id;
+        entries.extend(
+            uploader
+                .extras
+                .iter()
+                .map(|change| change.diff_entry(universe_id)),
+        );
+
+        let changed = entries.len();
+        for (product_type, key, id) in uploader.locked_changes() {
+            entries.push(DiffEntry::locked(product_type, key.clone(), id));
+        }
+
+        diff::print(&entries, &options);
+        Ok(changed)
+    }
+
+    /// Flattens a plan into one row per created product and changed field.
+    fn pending_rows(&self, plan: &SyncPlan) -> Vec<PendingRow> {
+        let mut rows = plan
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                PlannedChange::Create {
+                    product_type,
+                    key,
+                    request,
+                } => Some(PendingRow {
+                    action: "create",
+                    product_type: *product_type,
+                    key: key.clone(),
+                    field: "new product".to_string(),
+                    before: String::new(),
+                    after: format!("{} ({} R$)", request.name, request.price.unwrap_or(0)),
+                }),
+                PlannedChange::Update { .. } => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (product_type, diff) in self.compute_diffs() {
+            let Some((key, _)) = self.find_local(product_type, diff.id) else {
+                continue;
+            };
+
+            rows.extend(diff.diffs.iter().filter_map(|change| match change {
+                DiffChange::Changed(change) => {
+                    let (before, after) = change.values();
+                    Some(PendingRow {
+                        action: "update",
+                        product_type,
+                        key: key.clone(),
+                        field: change.field().to_string(),
+                        before,
+                        after,
+                    })
+                }
+                _ => None,
+            }));
+        }
+
+        rows
+    }
+
+    /// Creates a single local product that has no id yet, with the same
+    /// transforms and checks as a sync but without the diff review.
+    pub async fn upload_new(product_type: ProductType, key: &str) -> Result<()> {
+        let local_products = Self::prepare_local().await?;
+        let remote_products = fetch_all_products(local_products.metadata.universe_id).await?;
+
+        let mut uploader = Self {
+            local_products,
+            remote_products,
+            applied: vec![],
+            failures: vec![],
+            options: SyncOptions::default(),
+            backup: Backup::default(),
+            extras: vec![],
+            lock: None,
+        };
+
+        let mut plan = uploader.plan();
+        plan.changes.retain(|change| {
+            matches!(change, PlannedChange::Create { .. })
+                && change.product_type() == product_type
+                && change.key() == key
+        });
+
+        if plan.is_empty() {
+            return Err(format!("{:?} '{}' has no pending create", product_type, key).into());
+        }
+
+        plan.check_budget(uploader.options.max_changes)?;
+        uploader.warn_capacity(&plan);
+
+        if !uploader.check_revenue_risk(&plan, ui::can_prompt()).await? {
+            info!("user aborted create.");
+            return Ok(());
+        }
+
+        uploader.apply(&plan).await?;
+
+        uploader.local_products.save_products().await?;
+        uploader.local_products.serialize_luau().await?;
+
+        Ok(())
+    }
+
+    /// Creates every product without an id in the universe of
+    /// `local_products`, whose current products are `remote_products`, and
+    /// returns the products with the new ids written in. Failed creates are
+    /// logged and left without an id for the next sync.
+    pub async fn create_all(
+        local_products: VCSProducts,
+        remote_products: Vec<MultiProduct>,
+    ) -> Result<VCSProducts> {
+        let mut uploader = Self {
+            local_products,
+            remote_products,
+            applied: vec![],
+            failures: vec![],
+            options: SyncOptions::default(),
+            backup: Backup::default(),
+            extras: vec![],
+            lock: None,
+        };
+
+        let mut plan = SyncPlan::new(uploader.local_products.metadata.universe_id);
+        plan.changes = uploader.planned_creates();
+        uploader.warn_capacity(&plan);
+        uploader.apply(&plan).await?;
+
+        Ok(uploader.local_products)
+    }
+
+    pub async fn write_plan<P: AsRef<Path>>(path: P, ttl: Duration) -> Result<()> {
+        let uploader = Uploader::create().await?;
+        let plan = uploader.plan().with_expiry(ttl);
+        uploader.warn_capacity(&plan);
+
+        plan.save(&path).await?;
+
+        info!(
+            "wrote plan with {} create(s) and {} update(s) to {}",
+            plan.creates(),
+            plan.updates(),
+            path.as_ref().display()
+        );
+
+        Ok(())
+    }
+
+    pub async fn apply_plan<P: AsRef<Path>>(
+        path: P,
+        overwrite: bool,
+        options: SyncOptions,
+    ) -> Result<()> {
+        let plan = SyncPlan::load(&path).await?;
+        let mut uploader = Uploader::create().await?;
+        uploader.options = options;
+
+        plan.verify(&uploader.remote_products)?;
+        plan.check_budget(uploader.options.max_changes)?;
+        uploader.warn_capacity(&plan);
+
+        if !uploader
+            .check_revenue_risk(&plan, !overwrite && ui::can_prompt())
+            .await?
+        {
+            info!("user aborted apply.");
+            return Ok(());
+        }
+
+        info!(
+            "applying plan with {} create(s) and {} update(s)",
+            plan.creates(),
+            plan.updates()
+        );
+
+        let mut apply_result = uploader.apply(&plan).await;
+        if apply_result.is_ok() {
+            apply_result = uploader.verify_applied().await;
+        }
+
+        uploader.local_products.save_products().await?;
+        uploader.local_products.serialize_luau().await?;
+        uploader.record_base().await;
+        uploader.notify().await;
+
+        apply_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: u64, price: i64, active: bool) -> Product {
+        Product {
+            id: Some(id),
+            name: format!("product {}", id),
+            description: Some(String::new()),
+            price,
+            active,
+            ..Default::default()
+        }
+    }
+
+    fn uploader(local: Vec<Product>, remote: Vec<Product>) -> Uploader {
+        let mut local_products = VCSProducts::default();
+        for product in local {
+            local_products
+                .gamepasses
+                .insert(format!("pass-{}", product.id.unwrap()), product);
+        }
+
+        Uploader {
+            local_products,
+            remote_products: remote.into_iter().map(MultiProduct::GamePass).collect(),
+            applied: vec![],
+            failures: vec![],
+            options: SyncOptions::default(),
+            backup: Backup::default(),
+            extras: vec![],
+            lock: None,
+        }
+    }
+
+    #[test]
+    fn filters_restrict_planned_changes() {
+        let mut uploader = uploader(
+            vec![product(1, 80, true), product(2, 80, true)],
+            vec![product(1, 100, true), product(2, 100, true)],
+        );
+        uploader.options.filter = SyncFilter {
+            only: vec![parse_key_pattern("pass-1*").unwrap()],
+            ..Default::default()
+        };
+
+        let plan = uploader.plan();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].key(), "pass-1");
+
+        uploader.options.filter.product_type = Some(ProductType::DevProduct);
+        assert!(uploader.plan().is_empty());
+
+        assert!(
+            parse_key_pattern("/^pass-[12]$/")
+                .unwrap()
+                .is_match("pass-2")
+        );
+        assert!(!parse_key_pattern("pass-?").unwrap().is_match("pass-10"));
+    }
+
+    #[test]
+    fn plans_creates_and_updates() {
+        let mut new_pass = product(3, 40, true);
+        new_pass.id = None;
+        let mut uploader = uploader(
+            vec![product(1, 80, true), product(2, 100, true)],
+            vec![product(1, 100, true), product(2, 100, true)],
+        );
+        uploader.local_products.metadata.universe_id = 7;
+        uploader
+            .local_products
+            .gamepasses
+            .insert("new".to_string(), new_pass);
+
+        let plan = uploader.plan();
+        assert_eq!(plan.universe_id, 7);
+        assert_eq!(
+            plan.remote_hash,
+            remote_state_hash(&uploader.remote_products)
+        );
+        assert_eq!((plan.creates(), plan.updates()), (1, 1));
+
+        match &plan.changes[..] {
+            [
+                PlannedChange::Create {
+                    key: created,
+                    request: create,
+                    ..
+                },
+                PlannedChange::Update {
+                    key: updated,
+                    id,
+                    request: update,
+                    ..
+                },
+            ] => {
+                assert_eq!((created.as_str(), create.price), ("new", Some(40)));
+                assert_eq!(
+                    (updated.as_str(), *id, update.price),
+                    ("pass-1", 1, Some(80))
+                );
+            }
+            changes => panic!("unexpected changes: {:?}", changes),
+        }
+    }
+
+    #[tokio::test]
+    async fn apply_rejects_plans_for_another_universe() {
+        let mut uploader = uploader(vec![product(1, 80, true)], vec![product(1, 100, true)]);
+        uploader.local_products.metadata.universe_id = 7;
+        let mut plan = uploader.plan();
+        plan.universe_id = 8;
+
+        let error = uploader.apply(&plan).await.unwrap_err();
+        assert!(error.to_string().contains("plan targets universe 8"));
+        assert!(uploader.applied.is_empty());
+    }
+
+    #[tokio::test]
+    async fn apply_fails_when_a_create_fails() {
+        let mut uploader = uploader(vec![], vec![]);
+        let mut plan = uploader.plan();
+        plan.changes.push(PlannedChange::Create {
+            product_type: ProductType::GamePass,
+            key: "new".to_string(),
+            request: ProductUpdateRequest {
+                name: "New".to_string(),
+                image: Some("rbx-products-missing-icon.png".to_string()),
+                ..Default::default()
+            },
+        });
+
+        let error = uploader.apply(&plan).await.unwrap_err();
+        assert!(error.to_string().contains("failed to read image"));
+        assert_eq!(uploader.failures.len(), 1);
+        assert_eq!(uploader.failures[0].key, "new");
+    }
+
+    #[test]
+    fn skips_changes_to_immutable_products() {
+        let locked = Product {
+            immutable: true,
+            ..product(2, 100, true)
+        };
+        let uploader = uploader(
+            vec![product(1, 80, true), product(2, 80, true)],
+            vec![product(1, 100, true), locked],
+        );
+
+        let plan = uploader.plan();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].key(), "pass-1");
+    }
+
+    #[test]
+    fn skips_products_the_lock_vouches_for() {
+        let remote = |id, updated: &str| Product {
+            remote_updated: Some(updated.to_string()),
+            ..product(id, 100, true)
+        };
+        let mut uploader = uploader(
+            vec![product(1, 80, true), product(2, 80, true)],
+            vec![remote(1, "t1"), remote(2, "t1")],
+        );
+
+        // A lock recorded against the same remote timestamp and local content
+        // means neither side changed, so the product isn't compared again.
+        let mut lock = SyncLock::new(0);
+        let metadata = uploader.local_products.metadata.clone();
+        lock.insert(
+            ProductType::GamePass,
+            &product(2, 80, true),
+            &remote(2, "t1"),
+            &metadata,
+        );
+        uploader.lock = Some(lock);
+
+        let plan = uploader.plan();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].key(), "pass-1");
+
+        uploader.remote_products[1] = MultiProduct::GamePass(remote(2, "t2"));
+        assert_eq!(uploader.plan().changes.len(), 2);
+    }
+
+    #[test]
+    fn leaves_locked_products_alone() {
+        let locked = Product {
+            locked: Some(true),
+            ..product(2, 80, true)
+        };
+        let mut uploader = uploader(
+            vec![product(1, 80, true), locked],
+            vec![product(1, 100, true), product(2, 100, true)],
+        );
+        uploader.local_products.gamepasses.insert(
+            "draft".to_string(),
+            Product {
+                id: None,
+                locked: Some(true),
+                ..product(0, 50, true)
+            },
+        );
+
+        let plan = uploader.plan();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].key(), "pass-1");
+
+        let locked = uploader
+            .locked_changes()
+            .into_iter()
+            .map(|(_, key, _)| key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(locked, ["draft", "pass-2"]);
+    }
+
+    #[test]
+    fn review_lists_creates_updates_and_untracked_products() {
+        let mut uploader = uploader(
+            vec![product(1, 80, true)],
+            vec![product(1, 100, true), product(2, 50, true)],
+        );
+        uploader.local_products.gamepasses.insert(
+            "new".to_string(),
+            Product {
+                id: None,
+                ..product(0, 25, true)
+            },
+        );
+
+        let entries = uploader.review_entries();
+        let kinds = entries
+            .iter()
+            .map(|((_, diff), change)| (diff.kind, diff.id, change.is_some()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (DiffKind::Created, 0, true),
+                (DiffKind::Changed, 1, true),
+                (DiffKind::Deleted, 2, false)
+            ]
+        );
+        assert!(
+            entries[0]
+                .0
+                .1
+                .diffs
+                .iter()
+                .all(|change| matches!(change, DiffChange::Created(_)))
+        );
+    }
+
+    #[test]
+    fn flags_free_and_off_sale_paid_products() {
+        let uploader = uploader(
+            vec![
+                product(1, 0, true),
+                product(2, 100, false),
+                product(3, 50, true),
+                product(4, 0, false),
+            ],
+            vec![
+                product(1, 100, true),
+                product(2, 100, true),
+                product(3, 100, true),
+                product(4, 0, true),
+            ],
+        );
+
+        let mut risks = uploader.revenue_risks(&uploader.plan());
+        risks.sort();
+
+        assert_eq!(
+            risks,
+            vec![
+                "pass-1: price 100 R$ -> free".to_string(),
+                "pass-2: taken off sale (was 100 R$)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn capacity_counts_untracked_remote_products() {
+        let remote = (1..5).map(|id| product(id, 10, true)).collect::<Vec<_>>();
+        let mut uploader = uploader(vec![], remote);
+        assert!(uploader.capacity(&uploader.plan()).is_empty());
+        uploader.local_products.metadata.max_gamepasses = Some(5);
+
+        let mut new_pass = product(0, 10, true);
+        new_pass.id = None;
+        uploader
+            .local_products
+            .gamepasses
+            .insert("new".to_string(), new_pass.clone());
+        let usage = uploader.capacity(&uploader.plan());
+        assert_eq!(usage[0].used, 5);
+        assert!(!usage[0].is_over());
+
+        uploader
+            .local_products
+            .gamepasses
+            .insert("another".to_string(), new_pass);
+        let usage = uploader.capacity(&uploader.plan());
+        assert_eq!(usage[0].used, 6);
+        assert!(usage[0].is_over());
+    }
+
+    #[test]
+    fn reports_products_that_diverge_after_applying() {
+        let mut uploader = uploader(
+            vec![product(1, 80, true), product(2, 50, true)],
+            vec![product(1, 100, true), product(2, 50, true)],
+        );
+
+        for key in ["pass-1", "pass-2"] {
+            uploader.applied.push(ChangeSummary {
+                action: ChangeAction::Updated,
+                product_type: ProductType::GamePass,
+                key: key.to_string(),
+                name: String::new(),
+                old_price: None,
+                new_price: None,
+                discount: None,
+            });
+        }
+
+        let divergences = uploader.divergences();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].key, "pass-1");
+        assert_eq!(divergences[0].code, ErrorCode::Diverged);
+        assert_eq!(divergences[0].message, "price is 100, expected 80");
+    }
+
+    #[test]
+    fn pending_rows_list_changed_fields() {
+        let uploader = uploader(vec![product(1, 80, true)], vec![product(1, 100, true)]);
+
+        assert_eq!(
+            uploader.pending_rows(&uploader.plan()),
+            [PendingRow {
+                action: "update",
+                product_type: ProductType::GamePass,
+                key: "pass-1".to_string(),
+                field: "price".to_string(),
+                before: "100".to_string(),
+                after: "80".to_string(),
+            }]
+        );
+    }
 }
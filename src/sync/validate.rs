@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use log::{error, info, warn};
+
+use crate::Result;
+use crate::sync::limits::{
+    IMAGE_EXTENSIONS, MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH, MAX_PRICE, MIN_PRICE, catalog_usage,
+};
+use crate::sync::plugins;
+use crate::sync::products::{Metadata, Product, ProductType, VCSProducts, image_hash};
+use crate::sync::transform;
+use crate::sync::upload::apply_discount_prefix;
+
+/// A local product that Roblox would reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub product_type: ProductType,
+    pub key: String,
+    pub message: String,
+}
+
+/// The name as it would be uploaded, including any sale prefix.
+pub fn uploaded_title(product: &Product, discount_prefix: Option<String>) -> String {
+    let mut uploaded = product.clone();
+    apply_discount_prefix(&mut uploaded, discount_prefix);
+    uploaded.get_title()
+}
+
+fn check_product(product: &Product, metadata: &Metadata) -> Vec<String> {
+    let mut messages = vec![];
+    // What's uploaded, after discounts, rounding, plugins and transforms. A
+    // discount over 100% is reported on its own below.
+    let price = match product.discount {
+        Some(discount) if discount > 100 => product.price,
+        _ => product.get_price(Some(metadata)) as i64,
+    };
+
+    if product.price < 0 {
+        messages.push(format!("price {} R$ is negative", product.price));
+    } else if product.active && price < MIN_PRICE {
+        messages.push(format!(
+            "price must be at least {} R$ to be on sale, but {} R$ would be uploaded",
+            MIN_PRICE, price
+        ));
+    } else if price > MAX_PRICE {
+        messages.push(format!(
+            "price {} R$ exceeds the maximum of {} R$",
+            price, MAX_PRICE
+        ));
+    }
+
+    if let Some(discount) = product.discount
+        && discount > 100
+    {
+        messages.push(format!("discount {}% must be between 1 and 100", discount));
+    }
+
+    let title = uploaded_title(product, metadata.discount_prefix.clone());
+
+    if title.trim().is_empty() {
+        messages.push("name is empty".to_string());
+    } else if title.chars().count() > MAX_NAME_LENGTH {
+        messages.push(format!(
+            "name '{}' is {} characters, the limit is {}",
+            title,
+            title.chars().count(),
+            MAX_NAME_LENGTH
+        ));
+    }
+
+    if let Some(description) = product.get_description(Some(metadata))
+        && description.chars().count() > MAX_DESCRIPTION_LENGTH
+    {
+        messages.push(format!(
+            "description is {} characters, the limit is {}",
+            description.chars().count(),
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+
+    if let Some(image) = &product.image {
+        let extension = std::path::Path::new(image)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            messages.push(format!(
+                "image {} must be one of {}",
+                image,
+                IMAGE_EXTENSIONS.join(", ")
+            ));
+        } else if image_hash(image).is_none() {
+            messages.push(format!("image {} can't be read", image));
+        }
+    }
+
+    messages
+}
+
+/// Checks every local product against the marketplace limits, sorted by type
+/// and key so the output is stable.
+pub fn validate(local_products: &VCSProducts) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        let entries = match product_type {
+            ProductType::GamePass => &local_products.gamepasses,
+            ProductType::DevProduct => &local_products.products,
+        };
+
+        let mut keys = entries.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        let mut seen_names: HashMap<String, &String> = HashMap::new();
+        let mut seen_orders: HashMap<u32, &String> = HashMap::new();
+        let mut seen_ids: HashMap<u64, &String> = HashMap::new();
+
+        for key in keys {
+            let product = &entries[key];
+
+            let mut messages = check_product(product, &local_products.metadata);
+
+            if product_type == ProductType::GamePass && product.store_page_enabled.is_some() {
+                messages.push("store-page-enabled only applies to developer products".to_string());
+            }
+
+            let name = product.name.trim().to_lowercase();
+            if let Some(first) = seen_names.get(&name) {
+                messages.push(format!(
+                    "name '{}' is already used by '{}'",
+                    product.name, first
+                ));
+            } else {
+                seen_names.insert(name, key);
+            }
+
+            if let Some(id) = product.id {
+                if let Some(first) = seen_ids.get(&id) {
+                    messages.push(format!(
+                        "id {} is already used by '{}', run `duplicates` to merge them",
+                        id, first
+                    ));
+                } else {
+                    seen_ids.insert(id, key);
+                }
+            }
+
+            if let Some(order) = product.order {
+                if let Some(first) = seen_orders.get(&order) {
+                    messages.push(format!("order {} is already used by '{}'", order, first));
+                } else {
+                    seen_orders.insert(order, key);
+                }
+            }
+
+            issues.extend(messages.into_iter().map(|message| Issue {
+                product_type,
+                key: key.clone(),
+                message,
+            }));
+        }
+    }
+
+    issues
+}
+
+/// Logs any issues and fails if there are some, so nothing is uploaded that
+/// the API would only reject halfway through a sync.
+pub fn ensure_valid(local_products: &VCSProducts) -> Result<()> {
+    let issues = validate(local_products);
+
+    for usage in catalog_usage(local_products) {
+        if usage.is_over() {
+            warn!("{}, over the limit", usage.describe());
+        } else if usage.is_near() {
+            warn!("{}, approaching the limit", usage.describe());
+        }
+    }
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+
+    for issue in &issues {
+        error!(
+            "{:?} '{}': {}",
+            issue.product_type, issue.key, issue.message
+        );
+    }
+
+    Err(format!("products file has {} validation issue(s)", issues.len()).into())
+}
+
+pub async fn validate_products() -> Result<()> {
+    let mut local_products = VCSProducts::get_products().await?;
+    plugins::apply_transforms(&mut local_products).await?;
+    transform::apply_script(&mut local_products).await?;
+    ensure_valid(&local_products)?;
+    plugins::validate(&local_products).await?;
+
+    info!("all products are valid");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::products::RoundStyle;
+
+    fn product(name: &str, price: i64) -> Product {
+        Product {
+            name: name.to_string(),
+            price,
+            active: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_limit_violations_and_duplicates() {
+        let mut local_products = VCSProducts::default();
+        local_products
+            .gamepasses
+            .insert("vip".to_string(), product("VIP", 100));
+        local_products
+            .gamepasses
+            .insert("vip-2".to_string(), product("vip", 100));
+        local_products
+            .products
+            .insert("free".to_string(), product("Free", 0));
+        local_products
+            .products
+            .insert("long".to_string(), product(&"a".repeat(51), 10));
+
+        let mut sale = product("Sale", 10);
+        sale.discount = Some(120);
+        sale.order = Some(1);
+        local_products.products.insert("sale".to_string(), sale);
+
+        let mut shop = product("Shop", 10);
+        shop.order = Some(1);
+        local_products.products.insert("shop".to_string(), shop);
+
+        let issues = validate(&local_products)
+            .into_iter()
+            .map(|issue| (issue.key, issue.message))
+            .collect::<Vec<_>>();
+
+        assert_eq!(issues.len(), 5);
+        assert_eq!(issues[0].0, "vip-2");
+        assert!(issues[0].1.contains("already used by 'vip'"));
+        assert_eq!(issues[1].0, "free");
+        assert_eq!(issues[2].0, "long");
+        assert_eq!(issues[3].0, "sale");
+        assert_eq!(issues[4].0, "shop");
+        assert!(issues[4].1.contains("order 1 is already used by 'sale'"));
+    }
+
+    #[test]
+    fn reports_ids_used_twice() {
+        let mut local_products = VCSProducts::default();
+        for (key, name) in [("vip", "VIP"), ("vip-old", "Old VIP")] {
+            let mut vip = product(name, 100);
+            vip.id = Some(7);
+            local_products.gamepasses.insert(key.to_string(), vip);
+        }
+
+        let issues = validate(&local_products);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "vip-old");
+        assert!(issues[0].message.contains("id 7 is already used by 'vip'"));
+    }
+
+    #[test]
+    fn store_page_is_only_for_dev_products() {
+        let mut local_products = VCSProducts::default();
+        for entries in [&mut local_products.gamepasses, &mut local_products.products] {
+            let mut listed = product("Listed", 100);
+            listed.store_page_enabled = Some(true);
+            entries.insert("listed".to_string(), listed);
+        }
+
+        let issues = validate(&local_products);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].product_type, ProductType::GamePass);
+    }
+
+    #[test]
+    fn discount_prefix_counts_towards_name_length() {
+        let metadata = Metadata {
+            discount_prefix: Some("{}% OFF".to_string()),
+            ..Default::default()
+        };
+        let mut product = product(&"a".repeat(45), 100);
+        assert!(check_product(&product, &metadata).is_empty());
+
+        product.discount = Some(50);
+        assert_eq!(check_product(&product, &metadata).len(), 1);
+    }
+
+    #[test]
+    fn checks_the_price_that_would_be_uploaded() {
+        let metadata = Metadata::default();
+        let mut product = product("VIP", 100);
+        product.plugin_price = Some(0);
+        assert_eq!(
+            check_product(&product, &metadata),
+            ["price must be at least 1 R$ to be on sale, but 0 R$ would be uploaded"]
+        );
+
+        product.plugin_price = Some(MAX_PRICE as u64 + 1);
+        assert_eq!(check_product(&product, &metadata).len(), 1);
+    }
+
+    #[test]
+    fn checks_the_description_that_would_be_uploaded() {
+        let mut product = product("VIP", 1000);
+        product.discount = Some(95);
+        product.description = Some(format!(
+            "{}{{price}}",
+            "a".repeat(MAX_DESCRIPTION_LENGTH - 2)
+        ));
+        assert!(check_product(&product, &Metadata::default()).is_empty());
+
+        // Rounding 50 R$ to the nearest 100 R$ adds a digit.
+        let metadata = Metadata {
+            round_to: Some(100),
+            round_style: Some(RoundStyle::Nearest),
+            ..Default::default()
+        };
+        assert_eq!(product.get_price(Some(&metadata)), 100);
+        assert_eq!(check_product(&product, &metadata).len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use log::info;
+
+use crate::Result;
+use crate::api::api_token;
+use crate::api::error::{ApiError, ErrorCode};
+use crate::api::keys::{KeyScope, check_universe_access, introspect};
+use crate::sync::products::{ProductType, VCSProducts, find_products_file};
+
+/// Scope and operation pairs a key needs to download and sync products.
+const REQUIRED_SCOPES: [(&str, &str); 4] = [
+    ("game-pass", "read"),
+    ("game-pass", "write"),
+    ("developer-product", "read"),
+    ("developer-product", "write"),
+];
+
+fn missing_scopes(scopes: &[KeyScope]) -> Vec<String> {
+    REQUIRED_SCOPES
+        .iter()
+        .filter(|(name, operation)| {
+            !scopes.iter().any(|scope| {
+                scope.name == *name && scope.operations.iter().any(|op| op == operation)
+            })
+        })
+        .map(|(name, operation)| format!("{}:{}", name, operation))
+        .collect()
+}
+
+/// Checks the configured API key before anything else uses it: that it's
+/// valid, who it belongs to, that it has the product scopes, and that it can
+/// reach the universe in the products file.
+pub async fn whoami() -> Result<()> {
+    let api_key = api_token().await.ok_or("no API key is configured")?;
+
+    let key = introspect(&api_key).await.map_err(|e| {
+        if ErrorCode::classify(e.as_ref()) == ErrorCode::Auth
+            || e.downcast_ref::<ApiError>()
+                .is_some_and(|e| e.status == reqwest::StatusCode::BAD_REQUEST)
+        {
+            "the API key was rejected. Check it was copied in full, or create a new key \
+             at https://create.roblox.com/dashboard/credentials"
+                .into()
+        } else {
+            e
+        }
+    })?;
+
+    info!("API key: {}", key.name);
+    match key.authorized_user_id {
+        Some(user_id) => info!(
+            "created by: user {} (https://www.roblox.com/users/{}/profile)",
+            user_id, user_id
+        ),
+        None => info!("created by: unknown"),
+    }
+    if let Some(expires) = &key.expiration_time_utc {
+        info!("expires: {}", expires);
+    }
+
+    if key.expired {
+        return Err(
+            "the API key has expired. Create a new one, or extend its expiration \
+                    on the Creator Dashboard"
+                .into(),
+        );
+    }
+    if !key.enabled {
+        return Err("the API key is disabled. Enable it on the Creator Dashboard".into());
+    }
+
+    let missing = missing_scopes(&key.scopes);
+    if !missing.is_empty() {
+        return Err(format!(
+            "the API key is missing the {} scope(s). Edit the key on the Creator Dashboard \
+             and add them under Access Permissions",
+            missing.join(", ")
+        )
+        .into());
+    }
+    info!("scopes: ok");
+
+    if find_products_file().is_none() {
+        return Ok(());
+    }
+
+    let universe_id = VCSProducts::get_products().await?.metadata.universe_id;
+    for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+        check_universe_access(universe_id, product_type)
+            .await
+            .map_err(|e| match ErrorCode::classify(e.as_ref()) {
+                ErrorCode::Auth => format!(
+                    "the API key can't access universe {}. Add the experience to the key's \
+                     {:?} permissions, or restrict it to the right experience",
+                    universe_id, product_type
+                )
+                .into(),
+                _ => e,
+            })?;
+    }
+    info!("universe {}: ok", universe_id);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_scopes() {
+        let scopes = [
+            KeyScope {
+                name: "game-pass".to_string(),
+                operations: vec!["read".to_string(), "write".to_string()],
+            },
+            KeyScope {
+                name: "developer-product".to_string(),
+                operations: vec!["read".to_string()],
+            },
+        ];
+
+        assert_eq!(missing_scopes(&scopes), ["developer-product:write"]);
+        assert_eq!(missing_scopes(&[]).len(), 4);
+    }
+}
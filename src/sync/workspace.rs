@@ -0,0 +1,275 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use futures::future::join_all;
+use log::info;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::Result;
+use crate::sync::products::{FileFormat, find_products_file_in};
+use crate::utils::render_table;
+
+/// Lists the games of a monorepo, so `--all` can run a command for each.
+pub const WORKSPACE_FILE: &str = "rbx-products.workspace.toml";
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct WorkspaceOptions {
+    /// Run for every game listed in rbx-products.workspace.toml
+    #[arg(long)]
+    pub all: bool,
+    /// With --all, run the games at the same time instead of one by one
+    #[arg(long, requires = "all")]
+    pub parallel: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Workspace {
+    #[serde(default)]
+    pub games: Vec<Game>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Game {
+    /// Shown in logs and the summary, defaults to the path
+    pub name: Option<String>,
+    /// Directory holding the game's products file, or the file itself,
+    /// relative to the workspace file
+    pub path: PathBuf,
+}
+
+impl Game {
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+
+    /// The directory the game's commands run in.
+    fn dir(&self, root: &Path) -> PathBuf {
+        let path = root.join(&self.path);
+        match path.is_file() {
+            true => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            false => path,
+        }
+    }
+}
+
+impl Workspace {
+    pub fn parse(source: &str) -> Result<Self> {
+        let workspace: Workspace = toml::from_str(source)?;
+        if workspace.games.is_empty() {
+            return Err(format!("{} lists no games", WORKSPACE_FILE).into());
+        }
+        Ok(workspace)
+    }
+
+    pub async fn load() -> Result<Self> {
+        let source = tokio::fs::read_to_string(WORKSPACE_FILE)
+            .await
+            .map_err(|e| format!("failed to read {}: {}", WORKSPACE_FILE, e))?;
+        Self::parse(&source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Done,
+    /// `sync --ci` exited with 2
+    Changes,
+    Failed(String),
+}
+
+impl Outcome {
+    fn from_code(code: Option<i32>) -> Self {
+        match code {
+            Some(0) => Outcome::Done,
+            Some(2) => Outcome::Changes,
+            Some(code) => Outcome::Failed(format!("exit {}", code)),
+            None => Outcome::Failed("killed".to_string()),
+        }
+    }
+}
+
+struct GameRun {
+    name: String,
+    universe_id: Option<u64>,
+    outcome: Outcome,
+    elapsed: Duration,
+}
+
+/// The universe of the products file in `dir`, read without loading includes
+/// or migrating it, since that's left to the game's own run.
+fn universe_id(dir: &Path) -> Option<u64> {
+    let path = find_products_file_in(dir)?;
+    let data = std::fs::read(&path).ok()?;
+    let products: serde_json::Value = FileFormat::from_path(&path).parse(&data).ok()?;
+    products["metadata"]["universe-id"].as_u64()
+}
+
+/// Runs this executable with `args` in the game's directory. Captured output
+/// is printed in one block once the game is done, so parallel runs don't
+/// interleave.
+async fn run_game(game: &Game, root: &Path, args: &[OsString], capture: bool) -> GameRun {
+    let name = game.name();
+    let dir = game.dir(root);
+    let universe_id = universe_id(&dir);
+    let started = Instant::now();
+
+    let run = |outcome| GameRun {
+        name: name.clone(),
+        universe_id,
+        outcome,
+        elapsed: started.elapsed(),
+    };
+
+    if find_products_file_in(&dir).is_none() {
+        return run(Outcome::Failed(format!(
+            "no products file in {}",
+            dir.display()
+        )));
+    }
+
+    let executable = match std::env::current_exe() {
+        Ok(executable) => executable,
+        Err(e) => return run(Outcome::Failed(e.to_string())),
+    };
+
+    let mut command = Command::new(executable);
+    command.args(args).current_dir(&dir);
+
+    if !capture {
+        info!("==> {}", name);
+        return match command.status().await {
+            Ok(status) => run(Outcome::from_code(status.code())),
+            Err(e) => run(Outcome::Failed(e.to_string())),
+        };
+    }
+
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+
+    match output {
+        Ok(output) => {
+            eprintln!("==> {}", name);
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+            run(Outcome::from_code(output.status.code()))
+        }
+        Err(e) => run(Outcome::Failed(e.to_string())),
+    }
+}
+
+fn print_summary(runs: &[GameRun]) {
+    let rows = runs
+        .iter()
+        .map(|run| {
+            vec![
+                run.name.clone(),
+                run.universe_id.map_or("-".to_string(), |id| id.to_string()),
+                match &run.outcome {
+                    Outcome::Done => "ok".to_string(),
+                    Outcome::Changes => "changes".to_string(),
+                    Outcome::Failed(reason) => format!("failed ({})", reason),
+                },
+                format!("{:.1}s", run.elapsed.as_secs_f64()),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    eprint!(
+        "{}",
+        render_table(&["game", "universe", "result", "time"], &rows)
+    );
+}
+
+/// Reruns the current command, minus `--all` and `--parallel`, for every game
+/// in the workspace, then prints a summary. Returns whether any game reported
+/// changes, and fails if any game failed.
+pub async fn run_all(parallel: bool, yes: bool) -> Result<bool> {
+    let workspace = Workspace::load().await?;
+    let root = std::env::current_dir()?;
+
+    let mut args = std::env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--all" && arg != "--parallel")
+        .collect::<Vec<_>>();
+
+    // Parallel games can't share the terminal to ask, so they fail instead.
+    if parallel && !yes {
+        args.insert(0, "--no-input".into());
+    }
+
+    let runs = if parallel {
+        join_all(
+            workspace
+                .games
+                .iter()
+                .map(|game| run_game(game, &root, &args, true)),
+        )
+        .await
+    } else {
+        let mut runs = vec![];
+        for game in &workspace.games {
+            runs.push(run_game(game, &root, &args, false).await);
+        }
+        runs
+    };
+
+    print_summary(&runs);
+
+    let failed = runs
+        .iter()
+        .filter(|run| matches!(run.outcome, Outcome::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(format!("{} of {} game(s) failed", failed, runs.len()).into());
+    }
+
+    Ok(runs.iter().any(|run| run.outcome == Outcome::Changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_games_and_exit_codes() {
+        let workspace = Workspace::parse(
+            r#"
+            [[games]]
+            name = "Obby"
+            path = "games/obby"
+
+            [[games]]
+            path = "games/tycoon/products.yaml"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(workspace.games[0].name(), "Obby");
+        assert_eq!(workspace.games[1].name(), "games/tycoon/products.yaml");
+        assert_eq!(
+            workspace.games[0].dir(Path::new("/repo")),
+            Path::new("/repo/games/obby")
+        );
+
+        assert!(Workspace::parse("games = []").is_err());
+
+        assert_eq!(Outcome::from_code(Some(0)), Outcome::Done);
+        assert_eq!(Outcome::from_code(Some(2)), Outcome::Changes);
+        assert_eq!(
+            Outcome::from_code(Some(1)),
+            Outcome::Failed("exit 1".to_string())
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use std::cmp::Ordering;
+
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::Result;
+use crate::sync::products::ProductType;
+use crate::ui::theme;
+use crate::ui::{Terminal, with_terminal};
+
+/// A remote product as the browser lists it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseItem {
+    pub product_type: ProductType,
+    pub id: u64,
+    pub name: String,
+    pub description: String,
+    pub price: u64,
+    pub for_sale: bool,
+    /// RFC 3339 timestamp of the last change on Roblox
+    pub updated: String,
+    /// Key of the local product tracking it, if any
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Name,
+    Price,
+    Updated,
+}
+
+impl SortBy {
+    fn next(self) -> Self {
+        match self {
+            SortBy::Name => SortBy::Price,
+            SortBy::Price => SortBy::Updated,
+            SortBy::Updated => SortBy::Name,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortBy::Name => "name",
+            SortBy::Price => "price",
+            SortBy::Updated => "updated",
+        }
+    }
+
+    fn compare(self, a: &BrowseItem, b: &BrowseItem) -> Ordering {
+        match self {
+            SortBy::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortBy::Price => a.price.cmp(&b.price),
+            SortBy::Updated => a.updated.cmp(&b.updated),
+        }
+        .then(a.id.cmp(&b.id))
+    }
+}
+
+/// Lists every remote gamepass and developer product, independent of the
+/// products file, with sorting and search.
+#[derive(Debug)]
+pub struct BrowseViewer {
+    items: Vec<BrowseItem>,
+    sort: SortBy,
+    descending: bool,
+    /// Only show one product type
+    product_type: Option<ProductType>,
+    query: String,
+    searching: bool,
+    selected: usize,
+    should_quit: bool,
+}
+
+impl BrowseViewer {
+    fn new(items: Vec<BrowseItem>) -> Self {
+        Self {
+            items,
+            sort: SortBy::Name,
+            descending: false,
+            product_type: None,
+            query: String::new(),
+            searching: false,
+            selected: 0,
+            should_quit: false,
+        }
+    }
+
+    pub async fn browse(items: Vec<BrowseItem>) -> Result<()> {
+        let mut viewer = Self::new(items);
+
+        with_terminal(&mut viewer).await
+    }
+
+    fn matches(&self, item: &BrowseItem) -> bool {
+        if self
+            .product_type
+            .is_some_and(|product_type| product_type != item.product_type)
+        {
+            return false;
+        }
+
+        let query = self.query.to_lowercase();
+        query.is_empty()
+            || item.name.to_lowercase().contains(&query)
+            || item.id.to_string().contains(&query)
+            || item
+                .key
+                .as_ref()
+                .is_some_and(|key| key.to_lowercase().contains(&query))
+    }
+
+    /// The items passing the filters, in display order.
+    fn visible(&self) -> Vec<&BrowseItem> {
+        let mut visible = self
+            .items
+            .iter()
+            .filter(|item| self.matches(item))
+            .collect::<Vec<_>>();
+
+        visible.sort_by(|a, b| match self.descending {
+            true => self.sort.compare(b, a),
+            false => self.sort.compare(a, b),
+        });
+        visible
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.visible().len().saturating_sub(1));
+    }
+
+    fn row(item: &BrowseItem) -> String {
+        let product_type = match item.product_type {
+            ProductType::GamePass => "pass",
+            ProductType::DevProduct => "product",
+        };
+        // `2024-05-01T12:30:00.000Z` down to `2024-05-01 12:30`
+        let updated = item.updated.replacen('T', " ", 1);
+        let updated = updated.get(..16).unwrap_or(&updated);
+
+        format!(
+            "{:<7}  {:>10}  {:>6} R$  {:<8}  {:<16}  {}",
+            product_type,
+            item.id,
+            item.price,
+            if item.for_sale { "on sale" } else { "off sale" },
+            updated,
+            item.name
+        )
+    }
+
+    fn handle_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Esc => {
+                self.query.clear();
+                self.searching = false;
+            }
+            KeyCode::Enter => self.searching = false,
+            _ => return,
+        }
+
+        self.selected = 0;
+    }
+}
+
+impl Terminal for BrowseViewer {
+    fn render(&mut self, frame: &mut Frame) {
+        let areas = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+        let visible = self.visible();
+        let title = format!(
+            " Remote Products ({}/{}) by {} {} ",
+            visible.len(),
+            self.items.len(),
+            self.sort.label(),
+            if self.descending { "↓" } else { "↑" }
+        );
+
+        let items = visible
+            .iter()
+            .map(|item| ListItem::new(Line::from(Self::row(item))))
+            .collect::<Vec<_>>();
+
+        let list = List::new(items)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .highlight_style(theme::theme().highlight)
+            .highlight_symbol(">> ");
+
+        let mut state = ListState::default();
+        state.select((!visible.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(list, areas[0], &mut state);
+
+        let details = match visible.get(self.selected) {
+            Some(item) => {
+                let tracked = match &item.key {
+                    Some(key) => format!("tracked as '{}'", key),
+                    None => "not in the products file".to_string(),
+                };
+                format!("{}, {}\n{}", item.name, tracked, item.description)
+            }
+            None => "no products match".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(details)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title(" Details ").borders(Borders::ALL)),
+            areas[1],
+        );
+
+        let footer = if self.searching || !self.query.is_empty() {
+            format!("/{}", self.query)
+        } else {
+            "↑/↓: Select  /: Search  s: Sort  r: Reverse  t: Type  q: Quit".to_string()
+        };
+        frame.render_widget(Paragraph::new(footer).centered(), areas[2]);
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if !event.is_key_press() {
+                return;
+            }
+
+            if self.searching {
+                self.handle_search_key(key_event.code);
+                return;
+            }
+
+            match key_event.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected += 1;
+                    self.clamp_selection();
+                }
+                KeyCode::Char('/') => self.searching = true,
+                KeyCode::Char('s') => self.sort = self.sort.next(),
+                KeyCode::Char('r') => self.descending = !self.descending,
+                KeyCode::Char('t') => {
+                    self.product_type = match self.product_type {
+                        None => Some(ProductType::GamePass),
+                        Some(ProductType::GamePass) => Some(ProductType::DevProduct),
+                        Some(ProductType::DevProduct) => None,
+                    };
+                    self.clamp_selection();
+                }
+                KeyCode::Esc if !self.query.is_empty() => {
+                    self.query.clear();
+                    self.clamp_selection();
+                }
+                KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{Harness, chr, key};
+
+    fn item(
+        product_type: ProductType,
+        id: u64,
+        name: &str,
+        price: u64,
+        updated: &str,
+    ) -> BrowseItem {
+        BrowseItem {
+            product_type,
+            id,
+            name: name.to_string(),
+            description: format!("{} description", name),
+            price,
+            for_sale: true,
+            updated: updated.to_string(),
+            key: None,
+        }
+    }
+
+    fn harness() -> Harness<BrowseViewer> {
+        let mut vip = item(
+            ProductType::GamePass,
+            1,
+            "VIP",
+            100,
+            "2024-05-01T12:30:00.000Z",
+        );
+        vip.key = Some("vip".to_string());
+
+        let items = vec![
+            vip,
+            item(
+                ProductType::DevProduct,
+                2,
+                "Coins",
+                25,
+                "2024-06-01T08:00:00.000Z",
+            ),
+            item(
+                ProductType::DevProduct,
+                3,
+                "Gems",
+                50,
+                "2024-04-01T08:00:00.000Z",
+            ),
+        ];
+
+        Harness::new(BrowseViewer::new(items), 80, 10)
+    }
+
+    fn names(harness: &Harness<BrowseViewer>) -> Vec<&str> {
+        harness
+            .viewer
+            .visible()
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn renders_products_and_details() {
+        harness().assert_snapshot(&[
+            "┌ Remote Products (3/3) by name ↑ ─────────────────────────────────────────────┐",
+            "│>> product           2      25 R$  on sale   2024-06-01 08:00  Coins          │",
+            "│   product           3      50 R$  on sale   2024-04-01 08:00  Gems           │",
+            "│   pass              1     100 R$  on sale   2024-05-01 12:30  VIP            │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+            "┌ Details ─────────────────────────────────────────────────────────────────────┐",
+            "│Coins, not in the products file                                               │",
+            "│Coins description                                                             │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+            "          ↑/↓: Select  /: Search  s: Sort  r: Reverse  t: Type  q: Quit",
+        ]);
+    }
+
+    #[test]
+    fn sorts_by_price_and_updated() {
+        let mut harness = harness();
+
+        harness.send([chr('s')]);
+        assert_eq!(names(&harness), ["Coins", "Gems", "VIP"]);
+
+        harness.send([chr('r')]);
+        assert_eq!(names(&harness), ["VIP", "Gems", "Coins"]);
+
+        harness.send([chr('s')]);
+        assert_eq!(names(&harness), ["Coins", "VIP", "Gems"]);
+    }
+
+    #[test]
+    fn searches_and_filters_by_type() {
+        let mut harness = harness();
+
+        harness.send([chr('/'), chr('v'), chr('i'), key(KeyCode::Enter)]);
+        assert_eq!(names(&harness), ["VIP"]);
+
+        harness.send([key(KeyCode::Esc), chr('t'), chr('t')]);
+        assert_eq!(names(&harness), ["Coins", "Gems"]);
+        assert!(!harness.viewer.should_quit());
+
+        harness.send([chr('q')]);
+        assert!(harness.viewer.should_quit());
+    }
+}
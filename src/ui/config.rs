@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::Result;
+use crate::ui::keymap::{KeybindingsConfig, Keymap};
+use crate::ui::theme::{Theme, ThemeConfig};
+
+/// `~/.config/rbx-products/config.toml`, for per-user settings that don't
+/// belong in a project.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct UserConfig {
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| {
+        home.join(".config")
+            .join("rbx-products")
+            .join("config.toml")
+    })
+}
+
+impl UserConfig {
+    /// Loads the user config file, or an empty one if it doesn't exist.
+    pub async fn load() -> Result<Self> {
+        let Some(path) = config_path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+
+        let data = tokio::fs::read_to_string(&path).await?;
+        toml::from_str(&data).map_err(|e| format!("invalid {}: {}", path.display(), e).into())
+    }
+
+    /// Sets the key bindings and colors the terminal UIs use for the rest of
+    /// the run. `color` is off for `--no-color` and `NO_COLOR`.
+    pub fn apply(&self, color: bool) -> Result<()> {
+        let keymap = Keymap::from_config(&self.keybindings)
+            .map_err(|e| format!("invalid keybindings: {}", e))?;
+        let theme = match color {
+            true => Theme::from_config(&self.theme).map_err(|e| format!("invalid theme: {}", e))?,
+            false => Theme::plain(),
+        };
+
+        crate::ui::keymap::set_keymap(keymap);
+        crate::ui::theme::set_theme(theme);
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::Event;
 use ratatui::{
     Frame,
     layout::{Alignment, Rect},
-    style::{Color, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
-use crate::ui::{Terminal, with_terminal};
+use crate::Result;
+use crate::ui::keymap::{self, Action, Keymap, View};
+use crate::ui::theme;
+use crate::ui::{self, Terminal, prompt, with_terminal};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmState {
@@ -20,6 +22,7 @@ pub struct ConfirmViewer {
     prompt: String,
     state: ConfirmState,
     should_quit: bool,
+    keymap: Keymap,
 }
 
 impl ConfirmViewer {
@@ -28,6 +31,7 @@ impl ConfirmViewer {
             prompt,
             should_quit: false,
             state: ConfirmState::Closed,
+            keymap: keymap::keymap(),
         }
     }
 
@@ -35,12 +39,27 @@ impl ConfirmViewer {
         &self.state
     }
 
-    pub async fn show_prompt<T: Into<String>>(prompt: T) -> ConfirmState {
-        let mut backend = ratatui::init();
-        let mut viewer = Self::new(prompt.into());
+    /// Asks a yes/no question, unless `--yes` or `--no-input` answers it first.
+    pub async fn show_prompt<T: Into<String>>(prompt: T) -> Result<ConfirmState> {
+        let prompt = prompt.into();
+        if let Some(confirmed) = prompt::decide(&prompt)? {
+            return Ok(match confirmed {
+                true => ConfirmState::Confirmed,
+                false => ConfirmState::Closed,
+            });
+        }
+
+        if !ui::is_interactive() {
+            return Ok(match prompt::ask_plain(&prompt)? {
+                true => ConfirmState::Confirmed,
+                false => ConfirmState::Closed,
+            });
+        }
 
-        with_terminal(&mut viewer, &mut backend).await;
-        viewer.state().clone()
+        let mut viewer = Self::new(prompt);
+
+        with_terminal(&mut viewer).await?;
+        Ok(viewer.state().clone())
     }
 }
 
@@ -48,8 +67,10 @@ impl Terminal for ConfirmViewer {
     fn render(&mut self, frame: &mut Frame) {
         let area = frame.area();
 
-        let w = (area.width.min(60)).max(24);
-        let h = 7u16.min(area.height);
+        let prompt_lines = self.prompt.lines().count().max(1) as u16;
+
+        let w = area.width.clamp(24, 60);
+        let h = (6 + prompt_lines).min(area.height);
         let x = area.x + (area.width.saturating_sub(w)) / 2;
         let y = area.y + (area.height.saturating_sub(h)) / 2;
 
@@ -66,21 +87,23 @@ impl Terminal for ConfirmViewer {
             .title("Confirm Action")
             .borders(Borders::ALL);
 
-        let yes = Span::raw("Y").style(Style::default().fg(Color::Green));
-        let no = Span::raw("N").style(Style::default().fg(Color::Red));
-
-        let text = Text::from(vec![
-            Line::from(""),
-            Line::from(self.prompt.as_str()),
-            Line::from(""),
-            Line::from(vec![
-                Span::raw("["),
-                yes,
-                Span::raw("]  ["),
-                no,
-                Span::raw("]"),
-            ]),
-        ]);
+        let label = |action| self.keymap.label(action).to_uppercase();
+        let theme = theme::theme();
+        let yes = Span::raw(label(Action::Yes)).style(theme.added);
+        let no = Span::raw(label(Action::No)).style(theme.removed);
+
+        let mut lines = vec![Line::from("")];
+        lines.extend(self.prompt.lines().map(Line::from));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::raw("["),
+            yes,
+            Span::raw("]  ["),
+            no,
+            Span::raw("]"),
+        ]));
+
+        let text = Text::from(lines);
 
         let para = Paragraph::new(text)
             .block(block)
@@ -94,12 +117,12 @@ impl Terminal for ConfirmViewer {
                 return;
             }
 
-            match key_event.code {
-                KeyCode::Char('n') => {
+            match self.keymap.action(View::Confirm, key_event) {
+                Some(Action::No) => {
                     self.state = ConfirmState::Closed;
                     self.should_quit = true;
                 }
-                KeyCode::Char('y') => {
+                Some(Action::Yes) => {
                     self.state = ConfirmState::Confirmed;
                     self.should_quit = true;
                 }
@@ -112,3 +135,42 @@ impl Terminal for ConfirmViewer {
         self.should_quit
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{Harness, chr};
+
+    #[test]
+    fn renders_prompt() {
+        let harness = Harness::new(ConfirmViewer::new("Sync now?".to_string()), 30, 7);
+
+        harness.assert_snapshot(&[
+            "┌Confirm Action──────────────┐",
+            "│                            │",
+            "│          Sync now?         │",
+            "│                            │",
+            "│          [Y]  [N]          │",
+            "│                            │",
+            "└────────────────────────────┘",
+        ]);
+    }
+
+    #[test]
+    fn confirms_on_y() {
+        let mut harness = Harness::new(ConfirmViewer::new("Sync now?".to_string()), 30, 7);
+        harness.send([chr('x'), chr('y')]);
+
+        assert!(harness.viewer.should_quit());
+        assert_eq!(harness.viewer.state(), &ConfirmState::Confirmed);
+    }
+
+    #[test]
+    fn closes_on_n() {
+        let mut harness = Harness::new(ConfirmViewer::new("Sync now?".to_string()), 30, 7);
+        harness.send([chr('n'), chr('y')]);
+
+        assert!(harness.viewer.should_quit());
+        assert_eq!(harness.viewer.state(), &ConfirmState::Closed);
+    }
+}
@@ -1,36 +1,70 @@
-use std::ops::{Div, Mul};
+use std::collections::BTreeMap;
+use std::io::Write;
 
-use crossterm::event::{Event, KeyCode, KeyModifiers};
+use crossterm::event::Event;
 use nestify::nest;
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
-    text::{Line, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    layout::{Constraint, Layout, Margin, Rect},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        Wrap,
+    },
 };
 
 use crate::{
+    Result,
+    sync::fields::{Field, FieldValue},
     sync::products::ProductType,
-    ui::{Terminal, with_terminal},
+    ui::keymap::{self, Action, Keymap, View},
+    ui::preview::{self, ICON_HEIGHT, ICON_WIDTH, Image, ImageProtocol},
+    ui::theme::{self, Theme},
+    ui::wrap::wrapped_height,
+    ui::{self, Terminal, prompt, with_terminal},
 };
 
+/// What a sync does with a product, shown as a badge in the diff viewer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiffKind {
+    #[default]
+    Changed,
+    /// Only in the products file, so the sync creates it
+    Created,
+    /// Only in the universe. Roblox can't delete products, so the sync leaves
+    /// it alone and it can't be confirmed
+    Deleted,
+}
+
+impl DiffKind {
+    fn badge(&self) -> &'static str {
+        match self {
+            DiffKind::Changed => "Changed",
+            DiffKind::Created => "Created",
+            DiffKind::Deleted => "Deleted",
+        }
+    }
+}
+
+/// One synced field of a product, with its remote and local values.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProductDiff {
+    pub field: &'static Field,
+    pub old: FieldValue,
+    pub new: FieldValue,
+}
+
 nest! {
     #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]*
     pub struct ProductDiffs  {
         pub name: String,
+        /// Zero for products that are about to be created
         pub id: u64,
+        pub kind: DiffKind,
         pub diffs: Vec<
             pub enum DiffChange {
-                Unchanged(pub enum ProductDiff {
-                    Prefix(String, String),
-                    Title(String, String),
-                    Description(String, String),
-                    Price(u64, u64),
-                    RegionalPricing(bool, bool),
-                    Active(bool, bool),
-
-                }),
+                Unchanged(ProductDiff),
                 Changed(ProductDiff),
                 Created(ProductDiff)
             }
@@ -38,14 +72,206 @@ nest! {
     }
 }
 
+/// A line of the diff list: a product type's header or one of its diffs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Section(ProductType),
+    Diff(usize),
+}
+
 #[derive(Debug)]
 pub struct DiffViewer {
     view: Option<(ProductType, ProductDiffs)>,
     diffs: Vec<(ProductType, ProductDiffs)>,
-    confs: Vec<(ProductType, u64)>,
+    /// Indices into `diffs` of the confirmed entries
+    confs: Vec<usize>,
+    /// Product types whose diffs are hidden in the list
+    collapsed: Vec<ProductType>,
+    /// Index into `rows()`
     selected: usize,
     scroll: u16,
+    /// Furthest the open diff can scroll, from the last frame's layout
+    max_scroll: u16,
+    /// Rows of the open diff visible at once, from the last frame's layout
+    page_height: u16,
     should_quit: bool,
+    protocol: ImageProtocol,
+    /// PNG icons by product, fetched before the viewer opens
+    icons: BTreeMap<(ProductType, u64), Vec<u8>>,
+    decoded: BTreeMap<(ProductType, u64), Option<Image>>,
+    /// Where the icon of the open diff should be drawn with the kitty protocol
+    icon_area: Option<((ProductType, u64), Rect)>,
+    placed: Option<((ProductType, u64), Rect)>,
+    keymap: Keymap,
+}
+
+/// The changed fields of each product as plain text, for prompts that can't
+/// open the diff viewer.
+pub fn plain_lines(diffs: &[(ProductType, ProductDiffs)]) -> Vec<String> {
+    let mut lines = vec![];
+
+    for (product_type, product) in diffs {
+        lines.push(match product.kind {
+            DiffKind::Changed => {
+                format!("{:?} '{}' (id: {})", product_type, product.name, product.id)
+            }
+            DiffKind::Created => format!("{:?} '{}' (new)", product_type, product.name),
+            DiffKind::Deleted => format!(
+                "{:?} '{}' (id: {}) isn't in the products file and is left as is",
+                product_type, product.name, product.id
+            ),
+        });
+
+        for change in &product.diffs {
+            match change {
+                DiffChange::Changed(diff) => {
+                    let (old, new) = diff.values();
+                    lines.push(format!("  {}: {} -> {}", diff.field(), old, new));
+                }
+                DiffChange::Created(diff) => {
+                    lines.push(format!("  {}: {}", diff.field(), diff.values().1));
+                }
+                DiffChange::Unchanged(_) => {}
+            }
+        }
+    }
+
+    lines
+}
+
+/// Indices of every entry that can be confirmed, which is all but deleted ones.
+fn confirmable(diffs: &[(ProductType, ProductDiffs)]) -> Vec<usize> {
+    diffs
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, diff))| diff.kind != DiffKind::Deleted)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+impl ProductDiff {
+    pub fn new(
+        field: &'static Field,
+        old: impl Into<FieldValue>,
+        new: impl Into<FieldValue>,
+    ) -> Self {
+        Self {
+            field,
+            old: old.into(),
+            new: new.into(),
+        }
+    }
+
+    pub fn field(&self) -> &'static str {
+        self.field.name
+    }
+
+    /// The remote and local values, with text quoted.
+    pub fn values(&self) -> (String, String) {
+        (
+            (self.field.format)(&self.old),
+            (self.field.format)(&self.new),
+        )
+    }
+
+    /// Describes the difference as `field is <remote>, expected <local>`.
+    pub fn describe(&self) -> String {
+        let (old, new) = self.values();
+        format!("{} is {}, expected {}", self.field(), old, new)
+    }
+}
+
+/// Splits text into alternating runs of words and whitespace, so joining the
+/// tokens gives back the original text.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = 0;
+
+    for (i, c) in text.char_indices().skip(1) {
+        let previous = text[..i].chars().next_back().unwrap();
+        if c.is_whitespace() != previous.is_whitespace() {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// `(text, changed)` runs that join back into one side of a word diff.
+type WordRuns = Vec<(String, bool)>;
+
+/// A word-level diff of two texts, like `git diff --word-diff`.
+fn word_diff(old: &str, new: &str) -> (WordRuns, WordRuns) {
+    let old = tokens(old);
+    let new = tokens(new);
+
+    // Longest common subsequence table, built from the end so it can be
+    // walked forwards.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    fn push(runs: &mut WordRuns, token: &str, changed: bool) {
+        match runs.last_mut() {
+            Some((text, last)) if *last == changed => text.push_str(token),
+            _ => runs.push((token.to_string(), changed)),
+        }
+    }
+
+    let (mut left, mut right) = (vec![], vec![]);
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push(&mut left, old[i], false);
+            push(&mut right, new[j], false);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            push(&mut right, new[j], true);
+            j += 1;
+        } else {
+            push(&mut left, old[i], true);
+            i += 1;
+        }
+    }
+
+    (left, right)
+}
+
+/// A changed text field as a removed and an added line, with only the words
+/// that differ highlighted.
+fn word_diff_lines(
+    theme: &Theme,
+    label: &str,
+    old: &str,
+    new: &str,
+) -> (Line<'static>, Line<'static>) {
+    let (left, right) = word_diff(old, new);
+
+    let line = |sign: &str, runs: WordRuns, style: Style| {
+        let mut spans = vec![Span::raw(format!("{} {}: ", sign, label))];
+        spans.extend(runs.into_iter().map(|(text, changed)| match changed {
+            true => Span::styled(text, theme.changed_words(style)),
+            false => Span::raw(text),
+        }));
+        Line::from(spans).style(style)
+    };
+
+    (
+        line("-", left, theme.removed),
+        line("+", right, theme.added),
+    )
 }
 
 impl DiffViewer {
@@ -54,55 +280,299 @@ impl DiffViewer {
             should_quit: false,
             selected: 0,
             scroll: 0,
+            max_scroll: 0,
+            page_height: 0,
             view: None,
             diffs: vec![],
             confs: vec![],
+            collapsed: vec![],
+            protocol: ImageProtocol::Off,
+            icons: BTreeMap::new(),
+            decoded: BTreeMap::new(),
+            icon_area: None,
+            placed: None,
+            keymap: keymap::keymap(),
+        }
+    }
+
+    /// Lets the user pick which entries to apply, returning their indices in
+    /// `diffs`.
+    pub async fn confirm_diffs(
+        diffs: Vec<(ProductType, ProductDiffs)>,
+        icons: BTreeMap<(ProductType, u64), Vec<u8>>,
+        protocol: ImageProtocol,
+    ) -> Result<Vec<usize>> {
+        // `--yes` keeps every diff selected, as if the viewer was closed straight away.
+        if prompt::decide("Review the product changes?")?.is_some() {
+            return Ok(confirmable(&diffs));
+        }
+
+        // Without a terminal to draw on, list the changes and leave the
+        // decision to the confirmation that follows.
+        if !ui::is_interactive() {
+            for line in plain_lines(&diffs) {
+                eprintln!("{}", line);
+            }
+            return Ok(confirmable(&diffs));
         }
+
+        let mut viewer = Self::new().with_diffs(diffs).with_icons(icons, protocol);
+
+        with_terminal(&mut viewer).await?;
+        viewer.place_icon(None);
+        Ok(viewer.get_confs().clone())
+    }
+
+    pub fn with_icons(
+        mut self,
+        icons: BTreeMap<(ProductType, u64), Vec<u8>>,
+        protocol: ImageProtocol,
+    ) -> Self {
+        self.icons = icons;
+        self.protocol = protocol;
+        self
     }
 
-    pub async fn confirm_diffs(diffs: Vec<(ProductType, ProductDiffs)>) -> Vec<(ProductType, u64)> {
-        let mut backend = ratatui::init();
-        let mut viewer = Self::new().with_diffs(diffs);
+    /// Moves the kitty image to `target`, removing the previous one.
+    fn place_icon(&mut self, target: Option<((ProductType, u64), Rect)>) {
+        if self.placed == target {
+            return;
+        }
+
+        let mut out = String::new();
+        if self.placed.is_some() {
+            out += preview::KITTY_CLEAR;
+        }
+        if let Some((key, area)) = target
+            && let Some(png) = self.icons.get(&key)
+        {
+            out += &preview::kitty_place(png, area);
+        }
 
-        with_terminal(&mut viewer, &mut backend).await;
-        viewer.get_confs().clone()
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+        self.placed = target;
     }
 
-    pub fn get_confs(&self) -> &Vec<(ProductType, u64)> {
+    /// Splits the icon off the top of the detail pane when the product has one.
+    fn render_icon(&mut self, area: Rect, frame: &mut Frame, key: (ProductType, u64)) -> Rect {
+        if self.protocol == ImageProtocol::Off || !self.icons.contains_key(&key) {
+            return area;
+        }
+
+        let [icon_area, rest] =
+            Layout::vertical([Constraint::Length(ICON_HEIGHT + 2), Constraint::Fill(1)])
+                .areas(area);
+        let [icon_area, _] =
+            Layout::horizontal([Constraint::Length(ICON_WIDTH + 2), Constraint::Fill(1)])
+                .areas(icon_area);
+
+        let block = Block::default().title(" Icon ").borders(Borders::ALL);
+        let inner = block.inner(icon_area);
+        frame.render_widget(block, icon_area);
+
+        match self.protocol {
+            ImageProtocol::Kitty => self.icon_area = Some((key, inner)),
+            ImageProtocol::Blocks => {
+                let icons = &self.icons;
+                let image = self
+                    .decoded
+                    .entry(key)
+                    .or_insert_with(|| preview::decode_png(&icons[&key]));
+
+                if let Some(image) = image {
+                    let lines = preview::block_lines(image, inner.width, inner.height);
+                    frame.render_widget(Paragraph::new(Text::from(lines)), inner);
+                }
+            }
+            ImageProtocol::Off => {}
+        }
+
+        rest
+    }
+
+    pub fn get_confs(&self) -> &Vec<usize> {
         &self.confs
     }
 
     pub fn with_diffs(mut self, diffs: Vec<(ProductType, ProductDiffs)>) -> Self {
         self.diffs = diffs;
         self.view = None;
+        // Start on the first diff rather than its section header.
+        self.selected = usize::from(!self.diffs.is_empty());
         self
     }
 
+    /// Game passes then developer products, each under a header unless the
+    /// type has no diffs.
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+
+        for product_type in [ProductType::GamePass, ProductType::DevProduct] {
+            let indices = self
+                .section(product_type)
+                .into_iter()
+                .map(Row::Diff)
+                .collect::<Vec<_>>();
+
+            if indices.is_empty() {
+                continue;
+            }
+
+            rows.push(Row::Section(product_type));
+            if !self.collapsed.contains(&product_type) {
+                rows.extend(indices);
+            }
+        }
+
+        rows
+    }
+
+    /// Indices of the diffs of one product type.
+    fn section(&self, product_type: ProductType) -> Vec<usize> {
+        self.diffs
+            .iter()
+            .enumerate()
+            .filter(|(_, (t, _))| *t == product_type)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        self.rows().get(self.selected).copied()
+    }
+
+    /// The product type of the selected row, header or diff.
+    fn selected_section(&self) -> Option<ProductType> {
+        match self.selected_row()? {
+            Row::Section(product_type) => Some(product_type),
+            Row::Diff(index) => Some(self.diffs[index].0),
+        }
+    }
+
+    fn select_section(&mut self, product_type: ProductType) {
+        if let Some(row) = self
+            .rows()
+            .iter()
+            .position(|row| *row == Row::Section(product_type))
+        {
+            self.selected = row;
+        }
+    }
+
+    fn set_collapsed(&mut self, product_type: ProductType, collapsed: bool) {
+        self.collapsed.retain(|t| *t != product_type);
+        if collapsed {
+            self.collapsed.push(product_type);
+        }
+        self.select_section(product_type);
+    }
+
+    /// Moves to the header of the next (or previous) section, wrapping around.
+    fn jump_section(&mut self, forward: bool) {
+        let sections = self
+            .rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                Row::Section(product_type) => Some(product_type),
+                Row::Diff(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let Some(current) = self
+            .selected_section()
+            .and_then(|current| sections.iter().position(|t| *t == current))
+        else {
+            return;
+        };
+
+        let next = match forward {
+            true => (current + 1) % sections.len(),
+            false => (current + sections.len() - 1) % sections.len(),
+        };
+        self.select_section(sections[next]);
+    }
+
+    /// Confirms every confirmable diff in `indices`, or unconfirms them if
+    /// they all already are.
+    fn toggle_all(&mut self, indices: Vec<usize>) {
+        let indices = indices
+            .into_iter()
+            .filter(|index| self.diffs[*index].1.kind != DiffKind::Deleted)
+            .collect::<Vec<_>>();
+
+        if indices.iter().all(|index| self.confs.contains(index)) {
+            self.confs.retain(|index| !indices.contains(index));
+        } else {
+            for index in indices {
+                if !self.confs.contains(&index) {
+                    self.confs.push(index);
+                }
+            }
+        }
+    }
+
     fn render_list(&mut self, area: Rect, frame: &mut Frame) {
+        let theme = theme::theme();
         let tasks: Vec<ListItem> = self
-            .diffs
-            .iter()
-            .map(|pd| {
-                let confirmed = self.confs.contains(&(pd.0, pd.1.id));
-                let style = if confirmed {
-                    Style::default().fg(Color::White)
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let index = match row {
+                    Row::Diff(index) => index,
+                    Row::Section(product_type) => {
+                        let section = self.section(product_type);
+                        let confirmed = section
+                            .iter()
+                            .filter(|index| self.confs.contains(index))
+                            .count();
+
+                        let title = match product_type {
+                            ProductType::GamePass => "Game Passes",
+                            ProductType::DevProduct => "Developer Products",
+                        };
+
+                        let content = Line::from(format!(
+                            "{} {} ({}, {} confirmed)",
+                            if self.collapsed.contains(&product_type) {
+                                "▸"
+                            } else {
+                                "▾"
+                            },
+                            title,
+                            section.len(),
+                            confirmed
+                        ));
+                        return ListItem::new(content).style(theme.header);
+                    }
+                };
+
+                let pd = &self.diffs[index];
+                let confirmed = self.confs.contains(&index);
+                let style = if pd.1.kind == DiffKind::Deleted {
+                    theme.muted
+                } else if confirmed {
+                    theme.confirmed
                 } else {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(ratatui::style::Modifier::ITALIC)
+                    theme.pending
                 };
 
-                let product_type = match pd.0 {
-                    ProductType::GamePass => "GamePass",
-                    ProductType::DevProduct => "DevProduct",
+                let id = match pd.1.kind {
+                    DiffKind::Created => "new".to_string(),
+                    _ => format!("ID: {}", pd.1.id),
                 };
 
                 let content = vec![Line::from(format!(
-                    "{} {}: {} (ID: {})",
-                    if !confirmed { "*" } else { "" },
-                    product_type,
+                    "  {} [{}] {} ({})",
+                    if !confirmed && pd.1.kind != DiffKind::Deleted {
+                        "*"
+                    } else {
+                        " "
+                    },
+                    pd.1.kind.badge(),
                     pd.1.name,
-                    pd.1.id
+                    id
                 ))];
                 ListItem::new(content).style(style)
             })
@@ -114,12 +584,7 @@ impl DiffViewer {
                     .title(" Product Diff Viewer ")
                     .borders(Borders::ALL),
             )
-            .highlight_style(
-                Style::default()
-                    .bg(Color::Blue)
-                    .fg(Color::Yellow)
-                    .add_modifier(ratatui::style::Modifier::BOLD),
-            )
+            .highlight_style(theme.highlight)
             .highlight_symbol(">> ");
 
         let mut state = ratatui::widgets::ListState::default();
@@ -129,134 +594,86 @@ impl DiffViewer {
     }
 
     fn render_diff(&mut self, area: Rect, frame: &mut Frame, diff: (ProductType, ProductDiffs)) {
+        let area = self.render_icon(area, frame, (diff.0, diff.1.id));
         let chunks =
             Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
                 .split(area);
 
+        let theme = theme::theme();
         let mut left_lines = vec![];
         let mut right_lines = vec![];
 
         for change in diff.1.diffs.iter() {
             match change {
-                DiffChange::Unchanged(pd) => match pd {
-                    ProductDiff::Prefix(_, _) => {}
-                    ProductDiff::Title(old, new) => {
-                        left_lines.push(Line::from(format!("  Title: {}", old)));
-                        right_lines.push(Line::from(format!("  Title: {}", new)));
-                    }
-                    ProductDiff::Description(old, new) => {
-                        left_lines.push(Line::from(format!("  Description: {}", old)));
-                        right_lines.push(Line::from(format!("  Description: {}", new)));
-                    }
-                    ProductDiff::Price(old, new) => {
-                        left_lines.push(Line::from(format!("  Price: {}", old)));
-                        right_lines.push(Line::from(format!("  Price: {}", new)));
-                    }
-                    ProductDiff::RegionalPricing(old, new) => {
-                        left_lines.push(Line::from(format!("  Regional Pricing: {}", old)));
-                        right_lines.push(Line::from(format!("  Regional Pricing: {}", new)));
-                    }
-                    ProductDiff::Active(old, new) => {
-                        left_lines.push(Line::from(format!("  Active: {}", old)));
-                        right_lines.push(Line::from(format!("  Active: {}", new)));
-                    }
-                },
-                DiffChange::Changed(pd) => match pd {
-                    ProductDiff::Prefix(_, _) => {}
-                    ProductDiff::Title(old, new) => {
-                        left_lines.push(
-                            Line::from(format!("- Title: {}", old))
-                                .style(Style::default().fg(Color::Red)),
-                        );
-                        right_lines.push(
-                            Line::from(format!("+ Title: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Description(old, new) => {
-                        left_lines.push(
-                            Line::from(format!("- Description: {}", old))
-                                .style(Style::default().fg(Color::Red)),
-                        );
-                        right_lines.push(
-                            Line::from(format!("+ Description: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Price(old, new) => {
-                        left_lines.push(
-                            Line::from(format!("- Price: {}", old))
-                                .style(Style::default().fg(Color::Red)),
-                        );
-                        right_lines.push(
-                            Line::from(format!("+ Price: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::RegionalPricing(old, new) => {
-                        left_lines.push(
-                            Line::from(format!("- Regional Pricing: {}", old))
-                                .style(Style::default().fg(Color::Red)),
-                        );
-                        right_lines.push(
-                            Line::from(format!("+ Regional Pricing: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Active(old, new) => {
-                        left_lines.push(
-                            Line::from(format!("- Active: {}", old))
-                                .style(Style::default().fg(Color::Red)),
-                        );
-                        right_lines.push(
-                            Line::from(format!("+ Active: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                },
-                DiffChange::Created(pd) => match pd {
-                    ProductDiff::Prefix(_, _) => {}
-                    ProductDiff::Title(_, new) => {
-                        right_lines.push(
-                            Line::from(format!("+ Title: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Description(_, new) => {
-                        right_lines.push(
-                            Line::from(format!("+ Description: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Price(_, new) => {
-                        right_lines.push(
-                            Line::from(format!("+ Price: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::RegionalPricing(_, new) => {
-                        right_lines.push(
-                            Line::from(format!("+ Regional Pricing: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                    ProductDiff::Active(_, new) => {
-                        right_lines.push(
-                            Line::from(format!("+ Active: {}", new))
-                                .style(Style::default().fg(Color::Green)),
-                        );
-                    }
-                },
+                DiffChange::Unchanged(pd) => {
+                    let label = pd.field.label;
+                    left_lines.push(Line::from(format!(
+                        "  {}: {}",
+                        label,
+                        pd.field.display(&pd.old)
+                    )));
+                    right_lines.push(Line::from(format!(
+                        "  {}: {}",
+                        label,
+                        pd.field.display(&pd.new)
+                    )));
+                }
+                DiffChange::Changed(pd) if pd.field.text => {
+                    let (left, right) = word_diff_lines(
+                        &theme,
+                        pd.field.label,
+                        &pd.old.to_string(),
+                        &pd.new.to_string(),
+                    );
+                    left_lines.push(left);
+                    right_lines.push(right);
+                }
+                DiffChange::Changed(pd) => {
+                    let label = pd.field.label;
+                    left_lines.push(
+                        Line::from(format!("- {}: {}", label, pd.field.display(&pd.old)))
+                            .style(theme.removed),
+                    );
+                    right_lines.push(
+                        Line::from(format!("+ {}: {}", label, pd.field.display(&pd.new)))
+                            .style(theme.added),
+                    );
+                }
+                DiffChange::Created(pd) => {
+                    right_lines.push(
+                        Line::from(format!(
+                            "+ {}: {}",
+                            pd.field.label,
+                            pd.field.display(&pd.new)
+                        ))
+                        .style(theme.added),
+                    );
+                }
+            }
+        }
+
+        match diff.1.kind {
+            DiffKind::Changed => {}
+            DiffKind::Created => left_lines.push(Line::from("  Not in the universe yet")),
+            DiffKind::Deleted => {
+                // Roblox has no way to delete products, so there's nothing to apply.
+                right_lines.push(Line::from("  Not in the products file,"));
+                right_lines.push(Line::from("  sync leaves it as is"));
             }
         }
 
+        // Both panes scroll together, so the longer one decides how far.
+        // Heights include the borders, like the pane height.
+        let lines = wrapped_height(&left_lines, chunks[0].width)
+            .max(wrapped_height(&right_lines, chunks[1].width));
+
         let left_paragraph = Paragraph::new(Text::from(left_lines))
             .block(
                 Block::default()
                     .title(" Remote Product ")
                     .borders(Borders::ALL),
             )
-            .scroll((self.scroll, 0));
+            .wrap(Wrap { trim: false });
 
         let right_paragraph = Paragraph::new(Text::from(right_lines))
             .block(
@@ -264,57 +681,124 @@ impl DiffViewer {
                     .title(" Product Changes ")
                     .borders(Borders::ALL),
             )
-            .scroll((self.scroll, 0));
+            .wrap(Wrap { trim: false });
+
+        self.page_height = chunks[0].height.saturating_sub(2);
+        self.max_scroll = lines.saturating_sub(chunks[0].height);
+        self.scroll = self.scroll.min(self.max_scroll);
+
+        frame.render_widget(left_paragraph.scroll((self.scroll, 0)), chunks[0]);
+        frame.render_widget(right_paragraph.scroll((self.scroll, 0)), chunks[1]);
+
+        if self.max_scroll > 0 {
+            let mut state = ScrollbarState::new(self.max_scroll as usize + 1)
+                .position(self.scroll as usize)
+                .viewport_content_length(self.page_height as usize);
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                chunks[1].inner(Margin::new(0, 1)),
+                &mut state,
+            );
+        }
+    }
+
+    /// Scrolls the open diff by `delta` rows, without going past its end.
+    fn scroll_by(&mut self, delta: i32) {
+        self.scroll = (self.scroll as i32 + delta).clamp(0, self.max_scroll as i32) as u16;
+    }
+
+    /// Key hints for the footer, with the keys currently bound. Sections
+    /// can only be moved between in the list.
+    fn footer_items(&self) -> Vec<String> {
+        let label = |action| self.keymap.label(action);
+        let keys = |action| self.keymap.labels(action);
+
+        if self.view.is_some() {
+            return vec![
+                format!("{}/{}: Scroll", label(Action::Up), label(Action::Down)),
+                format!("{}: Confirm Diff", label(Action::Confirm)),
+                format!("{}: Confirm All Diffs", label(Action::ConfirmAll)),
+                format!("{}: Back", label(Action::Quit)),
+            ];
+        }
 
-        frame.render_widget(left_paragraph, chunks[0]);
-        frame.render_widget(right_paragraph, chunks[1]);
+        vec![
+            format!("{}: View Diff", label(Action::Open)),
+            format!("{}: Confirm Diff", label(Action::Confirm)),
+            format!("{}: Confirm All Diffs", label(Action::ConfirmAll)),
+            format!("{}: Next Section", keys(Action::NextSection)),
+            format!("{}: Previous Section", keys(Action::PreviousSection)),
+            format!("{}: Collapse", keys(Action::Collapse)),
+            format!("{}: Expand", keys(Action::Expand)),
+            format!("{}: Quit", label(Action::Quit)),
+        ]
+    }
+
+    /// Keys for the open diff, returning whether the key was used.
+    fn handle_view_action(&mut self, action: Action) -> bool {
+        let page = self.page_height.max(1) as i32;
+
+        match action {
+            Action::Up => self.scroll_by(-1),
+            Action::Down => self.scroll_by(1),
+            Action::PageUp => self.scroll_by(-page),
+            Action::PageDown => self.scroll_by(page),
+            Action::Top => self.scroll = 0,
+            Action::Bottom => self.scroll = self.max_scroll,
+            Action::Quit => {
+                self.view = None;
+                self.scroll = 0;
+            }
+            _ => return false,
+        }
+
+        true
     }
 }
 
+/// Packs footer hints into as few lines as fit in `width`, so narrow
+/// terminals still show every key instead of cutting the footer off.
+fn footer_lines(items: &[String], width: u16) -> Vec<Line<'static>> {
+    let mut lines: Vec<String> = vec![];
+
+    for item in items {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 2 + item.chars().count() <= width as usize => {
+                line.push_str("  ");
+                line.push_str(item);
+            }
+            _ => lines.push(item.clone()),
+        }
+    }
+
+    lines.into_iter().map(Line::from).collect()
+}
+
 impl Terminal for DiffViewer {
     fn render(&mut self, frame: &mut Frame) {
         let size = frame.area();
+        let footer = footer_lines(&self.footer_items(), size.width);
         let areas = Layout::default()
-            .constraints([Constraint::Fill(100), Constraint::Min(1)].as_ref())
+            .constraints(
+                [
+                    Constraint::Fill(100),
+                    Constraint::Length(footer.len() as u16),
+                ]
+                .as_ref(),
+            )
             .split(size);
 
         let body_area = areas[0];
         let keybind_area = areas[1];
 
-        let items = vec![
-            "Enter: View Diff".to_string(),
-            "c: Confirm Diff".to_string(),
-            "C: Confirm All Diffs".to_string(),
-            "q: Quit".to_string(),
-        ];
-
-        let keybind_areas = Layout::default()
-            .direction(ratatui::layout::Direction::Horizontal)
-            .constraints(
-                items
-                    .iter()
-                    .map(|_| {
-                        Constraint::Length(
-                            (1.0.div((items.len() - 1) as f32) as f32).mul(100.0) as u16
-                        )
-                    })
-                    .collect::<Vec<Constraint>>(),
-            )
-            .split(keybind_area);
-
+        self.icon_area = None;
         if let Some(diff) = &self.view {
             self.render_diff(body_area, frame, diff.clone());
         } else {
             self.render_list(body_area, frame);
         }
 
-        for (i, item) in items.iter().enumerate() {
-            let paragraph = Paragraph::new(item.clone())
-                .centered()
-                .block(Block::default().borders(Borders::NONE));
-
-            frame.render_widget(paragraph, keybind_areas[i]);
-        }
+        frame.render_widget(Paragraph::new(footer).centered(), keybind_area);
     }
 
     fn handle_event(&mut self, event: &Event) {
@@ -323,57 +807,80 @@ impl Terminal for DiffViewer {
                 return;
             }
 
-            match key_event.code {
-                KeyCode::Char('q') | KeyCode::Char('Q') => {
-                    if !key_event.modifiers.contains(KeyModifiers::SHIFT) {
-                        if self.view.is_some() {
-                            self.view = None;
-                            self.scroll = 0;
-                            return;
-                        }
-                    }
+            let Some(action) = self.keymap.action(View::Diffs, key_event) else {
+                return;
+            };
 
-                    self.should_quit = true;
-                }
-                KeyCode::Char('C') => {
-                    if self.confs.len() == self.diffs.len() {
+            if self.view.is_some() && self.handle_view_action(action) {
+                return;
+            }
+
+            match action {
+                Action::Quit | Action::ForceQuit => self.should_quit = true,
+                Action::ConfirmAll => {
+                    let all = confirmable(&self.diffs);
+                    if self.confs.len() == all.len() {
                         self.confs = vec![];
                     } else {
-                        self.confs = self.diffs.iter().map(|d| (d.0, d.1.id)).collect();
+                        self.confs = all;
                     }
                 }
-                KeyCode::Char('c') => {
-                    if let Some(selected_diff) = self.diffs.get(self.selected) {
-                        if self.confs.contains(&(selected_diff.0, selected_diff.1.id)) {
-                            self.confs.retain(|&(ptype, id)| {
-                                !(ptype == selected_diff.0 && id == selected_diff.1.id)
-                            });
+                Action::Confirm => match self.selected_row() {
+                    Some(Row::Section(product_type)) => {
+                        let section = self.section(product_type);
+                        self.toggle_all(section);
+                    }
+                    Some(Row::Diff(index)) if self.diffs[index].1.kind != DiffKind::Deleted => {
+                        if self.confs.contains(&index) {
+                            self.confs.retain(|&confirmed| confirmed != index);
                         } else {
-                            self.confs.push((selected_diff.0, selected_diff.1.id));
+                            self.confs.push(index);
                         }
 
                         self.view = None;
                     }
-                }
-                KeyCode::Up => {
+                    _ => {}
+                },
+                Action::Up => {
+                    let rows = self.rows().len();
                     if self.selected > 0 {
                         self.selected -= 1;
-                    } else if self.selected == 0 {
-                        self.selected = self.diffs.len() - 1;
+                    } else if rows > 0 {
+                        self.selected = rows - 1;
                     }
                 }
-                KeyCode::Down => {
-                    if self.selected + 1 < self.diffs.len() {
+                Action::Down => {
+                    if self.selected + 1 < self.rows().len() {
                         self.selected += 1;
-                    } else if self.selected + 1 == self.diffs.len() {
+                    } else {
                         self.selected = 0;
                     }
                 }
-                KeyCode::Enter => {
-                    if let Some(selected_diff) = self.diffs.get(self.selected) {
-                        self.view = Some(selected_diff.clone());
+                Action::Top => self.selected = 0,
+                Action::Bottom => self.selected = self.rows().len().saturating_sub(1),
+                Action::NextSection => self.jump_section(true),
+                Action::PreviousSection => self.jump_section(false),
+                Action::Collapse => {
+                    if let Some(product_type) = self.selected_section() {
+                        self.set_collapsed(product_type, true);
                     }
                 }
+                Action::Expand => {
+                    if let Some(product_type) = self.selected_section() {
+                        self.set_collapsed(product_type, false);
+                    }
+                }
+                Action::Open => match self.selected_row() {
+                    Some(Row::Section(product_type)) => {
+                        let collapsed = self.collapsed.contains(&product_type);
+                        self.set_collapsed(product_type, !collapsed);
+                    }
+                    Some(Row::Diff(index)) => {
+                        self.view = Some(self.diffs[index].clone());
+                        self.scroll = 0;
+                    }
+                    None => {}
+                },
                 _ => {}
             }
         }
@@ -382,4 +889,375 @@ impl Terminal for DiffViewer {
     fn should_quit(&self) -> bool {
         self.should_quit
     }
+
+    fn after_draw(&mut self) {
+        if self.protocol == ImageProtocol::Kitty {
+            self.place_icon(self.icon_area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use super::*;
+    use crate::sync::fields;
+    use crate::ui::testing::{Harness, chr, key};
+
+    fn sample_diffs() -> Vec<(ProductType, ProductDiffs)> {
+        vec![
+            (
+                ProductType::GamePass,
+                ProductDiffs {
+                    name: "VIP".to_string(),
+                    id: 1,
+                    kind: DiffKind::Changed,
+                    diffs: vec![
+                        DiffChange::Unchanged(ProductDiff::new(&fields::TITLE, "VIP", "VIP")),
+                        DiffChange::Changed(ProductDiff::new(&fields::PRICE, 100, 80)),
+                    ],
+                },
+            ),
+            (
+                ProductType::DevProduct,
+                ProductDiffs {
+                    name: "Coins".to_string(),
+                    id: 2,
+                    kind: DiffKind::Changed,
+                    diffs: vec![DiffChange::Changed(ProductDiff::new(
+                        &fields::ACTIVE,
+                        false,
+                        true,
+                    ))],
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn lists_changed_fields_as_text() {
+        assert_eq!(
+            plain_lines(&sample_diffs()),
+            vec![
+                "GamePass 'VIP' (id: 1)",
+                "  price: 100 -> 80",
+                "DevProduct 'Coins' (id: 2)",
+                "  active: false -> true",
+            ]
+        );
+    }
+
+    fn harness() -> Harness<DiffViewer> {
+        Harness::new(DiffViewer::new().with_diffs(sample_diffs()), 60, 6)
+    }
+
+    #[test]
+    fn renders_list() {
+        Harness::new(DiffViewer::new().with_diffs(sample_diffs()), 60, 9).assert_snapshot(&[
+            "┌ Product Diff Viewer ─────────────────────────────────────┐",
+            "│   ▾ Game Passes (1, 0 confirmed)                         │",
+            "│>>   * [Changed] VIP (ID: 1)                              │",
+            "│   ▾ Developer Products (1, 0 confirmed)                  │",
+            "│     * [Changed] Coins (ID: 2)                            │",
+            "└──────────────────────────────────────────────────────────┘",
+            "   Enter: View Diff  c: Confirm Diff  C: Confirm All Diffs",
+            "     Tab/]: Next Section  Shift+Tab/[: Previous Section",
+            "               ←: Collapse  →: Expand  q: Quit",
+        ]);
+    }
+
+    #[test]
+    fn renders_detail_view() {
+        let mut harness = harness();
+        harness.send([key(KeyCode::Enter)]);
+
+        harness.assert_snapshot(&[
+            "┌ Remote Product ────────────┐┌ Product Changes ───────────┐",
+            "│  Title: VIP                ││  Title: VIP                │",
+            "│- Price: 100                ││+ Price: 80                 │",
+            "│                            ││                            │",
+            "└────────────────────────────┘└────────────────────────────┘",
+            " ↑/↓: Scroll  c: Confirm Diff  C: Confirm All Diffs  q: Back",
+        ]);
+    }
+
+    #[test]
+    fn renders_regional_pricing_change() {
+        let diffs = vec![(
+            ProductType::GamePass,
+            ProductDiffs {
+                name: "VIP".to_string(),
+                id: 1,
+                kind: DiffKind::Changed,
+                diffs: vec![DiffChange::Changed(ProductDiff::new(
+                    &fields::REGIONAL_PRICING,
+                    false,
+                    true,
+                ))],
+            },
+        )];
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 60, 6);
+        harness.send([key(KeyCode::Enter)]);
+
+        harness.assert_snapshot(&[
+            "┌ Remote Product ────────────┐┌ Product Changes ───────────┐",
+            "│- Regional Pricing: false   ││+ Regional Pricing: true    │",
+            "│                            ││                            │",
+            "│                            ││                            │",
+            "└────────────────────────────┘└────────────────────────────┘",
+            " ↑/↓: Scroll  c: Confirm Diff  C: Confirm All Diffs  q: Back",
+        ]);
+    }
+
+    #[test]
+    fn icon_sits_above_the_detail_panes() {
+        let icons = BTreeMap::from([((ProductType::GamePass, 1), b"not a png".to_vec())]);
+        let viewer = DiffViewer::new()
+            .with_diffs(sample_diffs())
+            .with_icons(icons, ImageProtocol::Blocks);
+        let mut harness = Harness::new(viewer, 60, 15);
+        harness.send([key(KeyCode::Enter)]);
+
+        harness.assert_snapshot(&[
+            "┌ Icon ──────────┐                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "│                │                                          ",
+            "└────────────────┘                                          ",
+            "┌ Remote Product ────────────┐┌ Product Changes ───────────┐",
+            "│  Title: VIP                ││  Title: VIP                │",
+            "│- Price: 100                ││+ Price: 80                 │",
+            "└────────────────────────────┘└────────────────────────────┘",
+            " ↑/↓: Scroll  c: Confirm Diff  C: Confirm All Diffs  q: Back",
+        ]);
+    }
+
+    #[test]
+    fn confirms_selected_and_all() {
+        let mut harness = harness();
+        harness.send([key(KeyCode::Down), key(KeyCode::Down), chr('c')]);
+        assert_eq!(harness.viewer.get_confs(), &vec![1]);
+
+        harness.send([chr('C')]);
+        assert_eq!(harness.viewer.get_confs().len(), 2);
+
+        harness.send([chr('C')]);
+        assert!(harness.viewer.get_confs().is_empty());
+    }
+
+    #[test]
+    fn selection_wraps_around() {
+        let mut harness = harness();
+        harness.send([key(KeyCode::Up), key(KeyCode::Up), chr('c')]);
+
+        assert_eq!(harness.viewer.get_confs(), &vec![1]);
+    }
+
+    #[test]
+    fn badges_created_and_deleted_products() {
+        let mut diffs = sample_diffs();
+        diffs[0].1.kind = DiffKind::Created;
+        diffs[1].1.kind = DiffKind::Deleted;
+        diffs[1].1.diffs = vec![DiffChange::Unchanged(ProductDiff::new(
+            &fields::ACTIVE,
+            true,
+            true,
+        ))];
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 60, 9);
+
+        harness.assert_snapshot(&[
+            "┌ Product Diff Viewer ─────────────────────────────────────┐",
+            "│   ▾ Game Passes (1, 0 confirmed)                         │",
+            "│>>   * [Created] VIP (new)                                │",
+            "│   ▾ Developer Products (1, 0 confirmed)                  │",
+            "│       [Deleted] Coins (ID: 2)                            │",
+            "└──────────────────────────────────────────────────────────┘",
+            "   Enter: View Diff  c: Confirm Diff  C: Confirm All Diffs",
+            "     Tab/]: Next Section  Shift+Tab/[: Previous Section",
+            "               ←: Collapse  →: Expand  q: Quit",
+        ]);
+
+        harness.send([key(KeyCode::Down), key(KeyCode::Down), key(KeyCode::Enter)]);
+        harness.assert_snapshot(&[
+            "┌ Remote Product ────────────┐┌ Product Changes ───────────┐",
+            "│  Active: true              ││  Active: true              │",
+            "│                            ││  Not in the products file, │",
+            "│                            ││  sync leaves it as is      │",
+            "│                            ││                            │",
+            "│                            ││                            │",
+            "│                            ││                            │",
+            "└────────────────────────────┘└────────────────────────────┘",
+            " ↑/↓: Scroll  c: Confirm Diff  C: Confirm All Diffs  q: Back",
+        ]);
+    }
+
+    #[test]
+    fn deleted_products_cant_be_confirmed() {
+        let mut diffs = sample_diffs();
+        diffs[1].1.kind = DiffKind::Deleted;
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 60, 6);
+
+        harness.send([key(KeyCode::Down), key(KeyCode::Down), chr('c')]);
+        assert!(harness.viewer.get_confs().is_empty());
+
+        harness.send([chr('C')]);
+        assert_eq!(harness.viewer.get_confs(), &vec![0]);
+    }
+
+    #[test]
+    fn sections_collapse_and_confirm_together() {
+        let mut diffs = sample_diffs();
+        diffs.push((
+            ProductType::GamePass,
+            ProductDiffs {
+                name: "Admin".to_string(),
+                id: 3,
+                kind: DiffKind::Changed,
+                diffs: vec![DiffChange::Changed(ProductDiff::new(
+                    &fields::PRICE,
+                    50,
+                    60,
+                ))],
+            },
+        ));
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 60, 9);
+
+        // Confirming a header confirms its whole section.
+        harness.send([key(KeyCode::Up), chr('c')]);
+        assert_eq!(harness.viewer.get_confs(), &vec![0, 2]);
+
+        harness.send([key(KeyCode::Left)]);
+        harness.assert_snapshot(&[
+            "┌ Product Diff Viewer ─────────────────────────────────────┐",
+            "│>> ▸ Game Passes (2, 2 confirmed)                         │",
+            "│   ▾ Developer Products (1, 0 confirmed)                  │",
+            "│     * [Changed] Coins (ID: 2)                            │",
+            "│                                                          │",
+            "└──────────────────────────────────────────────────────────┘",
+            "   Enter: View Diff  c: Confirm Diff  C: Confirm All Diffs",
+            "     Tab/]: Next Section  Shift+Tab/[: Previous Section",
+            "               ←: Collapse  →: Expand  q: Quit",
+        ]);
+
+        harness.send([key(KeyCode::Tab)]);
+        assert_eq!(
+            harness.viewer.selected_row(),
+            Some(Row::Section(ProductType::DevProduct))
+        );
+
+        harness.send([key(KeyCode::BackTab), key(KeyCode::Enter)]);
+        assert!(harness.viewer.collapsed.is_empty());
+        assert_eq!(harness.viewer.rows().len(), 5);
+    }
+
+    #[test]
+    fn highlights_changed_words_only() {
+        let (left, right) = word_diff("Grants a VIP room", "Grants the VIP  lounge");
+        assert_eq!(
+            left,
+            [
+                ("Grants ".to_string(), false),
+                ("a".to_string(), true),
+                (" VIP".to_string(), false),
+                (" room".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            right,
+            [
+                ("Grants ".to_string(), false),
+                ("the".to_string(), true),
+                (" VIP".to_string(), false),
+                ("  lounge".to_string(), true),
+            ]
+        );
+
+        let (left, right) = word_diff("", "new");
+        assert!(left.is_empty());
+        assert_eq!(right, [("new".to_string(), true)]);
+    }
+
+    #[test]
+    fn wraps_long_descriptions() {
+        let diffs = vec![(
+            ProductType::GamePass,
+            ProductDiffs {
+                name: "VIP".to_string(),
+                id: 1,
+                kind: DiffKind::Changed,
+                diffs: vec![DiffChange::Changed(ProductDiff::new(
+                    &fields::DESCRIPTION,
+                    "Grants access to the VIP room",
+                    "Grants access to the VIP lounge",
+                ))],
+            },
+        )];
+
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 60, 8);
+        harness.send([key(KeyCode::Enter)]);
+        harness.assert_snapshot(&[
+            "┌ Remote Product ────────────┐┌ Product Changes ───────────┐",
+            "│- Description: Grants access││+ Description: Grants access│",
+            "│to the VIP room             ││to the VIP lounge           │",
+            "│                            ││                            │",
+            "│                            ││                            │",
+            "│                            ││                            │",
+            "└────────────────────────────┘└────────────────────────────┘",
+            " ↑/↓: Scroll  c: Confirm Diff  C: Confirm All Diffs  q: Back",
+        ]);
+    }
+
+    #[test]
+    fn scrolls_long_diffs_within_bounds() {
+        let description = (1..=12)
+            .map(|i| format!("line{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let diffs = vec![(
+            ProductType::GamePass,
+            ProductDiffs {
+                name: "VIP".to_string(),
+                id: 1,
+                kind: DiffKind::Changed,
+                diffs: vec![DiffChange::Changed(ProductDiff::new(
+                    &fields::DESCRIPTION,
+                    String::new(),
+                    description,
+                ))],
+            },
+        )];
+
+        let mut harness = Harness::new(DiffViewer::new().with_diffs(diffs), 20, 11);
+        harness.send([key(KeyCode::Enter)]);
+        let max_scroll = harness.viewer.max_scroll;
+        assert!(max_scroll > 0);
+
+        harness.send([chr('j'), key(KeyCode::Down)]);
+        assert_eq!(harness.viewer.scroll, 2.min(max_scroll));
+
+        harness.send([key(KeyCode::End), key(KeyCode::PageDown), chr('j')]);
+        assert_eq!(harness.viewer.scroll, max_scroll);
+        assert!(harness.lines()[1].ends_with('▲'));
+        assert!(harness.lines()[5].ends_with('▼'));
+
+        harness.send([key(KeyCode::PageUp), key(KeyCode::Home), chr('k')]);
+        assert_eq!(harness.viewer.scroll, 0);
+        assert!(harness.viewer.view.is_some());
+    }
+
+    #[test]
+    fn q_leaves_detail_view_before_quitting() {
+        let mut harness = harness();
+        harness.send([key(KeyCode::Enter), chr('q')]);
+        assert!(!harness.viewer.should_quit());
+
+        harness.send([chr('q')]);
+        assert!(harness.viewer.should_quit());
+    }
 }
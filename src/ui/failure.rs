@@ -0,0 +1,171 @@
+use crossterm::event::Event;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::Result;
+use crate::ui::keymap::{self, Action, Keymap, View};
+use crate::ui::prompt::{self, PromptPolicy};
+use crate::ui::theme;
+use crate::ui::wrap::wrapped_height;
+use crate::ui::{self, Terminal, with_terminal};
+
+/// What to do about a request that failed mid-sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureChoice {
+    /// Send the same request again
+    Retry,
+    /// Leave the product as it is and carry on
+    Skip,
+    /// Stop asking, and fail the sync once the rest is recorded
+    Abort,
+}
+
+/// A modal showing a failed request and the product it was for, so errors
+/// don't end up as log lines behind the terminal UI.
+#[derive(Debug)]
+pub struct FailureViewer {
+    product: String,
+    error: String,
+    choice: Option<FailureChoice>,
+    keymap: Keymap,
+}
+
+impl FailureViewer {
+    fn new(product: String, error: String) -> Self {
+        Self {
+            product,
+            error,
+            choice: None,
+            keymap: keymap::keymap(),
+        }
+    }
+
+    /// Asks what to do about a failed request. Without a terminal, or when
+    /// `--yes` or `--no-input` is set, the failure is skipped like before.
+    pub async fn ask(product: String, error: String) -> Result<FailureChoice> {
+        if !ui::is_interactive() || prompt::policy() != PromptPolicy::Ask {
+            return Ok(FailureChoice::Skip);
+        }
+
+        let mut viewer = Self::new(product, error);
+
+        with_terminal(&mut viewer).await?;
+        Ok(viewer.choice.unwrap_or(FailureChoice::Abort))
+    }
+}
+
+impl Terminal for FailureViewer {
+    fn render(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let theme = theme::theme();
+
+        let label = |action| self.keymap.label(action).to_uppercase();
+        let choices = Line::from(vec![
+            Span::raw("["),
+            Span::raw(label(Action::Retry)).style(theme.added),
+            Span::raw("] Retry  ["),
+            Span::raw(label(Action::Skip)).style(theme.pending),
+            Span::raw("] Skip  ["),
+            Span::raw(label(Action::Abort)).style(theme.removed),
+            Span::raw("] Abort"),
+        ]);
+
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(self.product.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
+            Line::from(""),
+        ];
+        lines.extend(self.error.lines().map(Line::from));
+        lines.push(Line::from(""));
+        lines.push(choices);
+
+        let w = area.width.clamp(30, 70);
+        let h = wrapped_height(&lines, w).min(area.height);
+        let para = Paragraph::new(Text::from(lines))
+            .block(
+                Block::default()
+                    .title("Request Failed")
+                    .borders(Borders::ALL)
+                    .border_style(theme.removed),
+            )
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false });
+
+        let modal = Rect {
+            x: area.x + area.width.saturating_sub(w) / 2,
+            y: area.y + area.height.saturating_sub(h) / 2,
+            width: w.min(area.width),
+            height: h,
+        };
+
+        frame.render_widget(Clear, modal);
+        frame.render_widget(para, modal);
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if !event.is_key_press() {
+                return;
+            }
+
+            self.choice = match self.keymap.action(View::Failure, key_event) {
+                Some(Action::Retry) => Some(FailureChoice::Retry),
+                Some(Action::Skip) => Some(FailureChoice::Skip),
+                Some(Action::Abort | Action::ForceQuit) => Some(FailureChoice::Abort),
+                _ => self.choice,
+            };
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.choice.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{Harness, chr};
+
+    fn harness() -> Harness<FailureViewer> {
+        let viewer = FailureViewer::new(
+            "GamePass 'vip' (id 1)".to_string(),
+            "429 Too Many Requests".to_string(),
+        );
+        Harness::new(viewer, 40, 9)
+    }
+
+    #[test]
+    fn renders_error_and_choices() {
+        harness().assert_snapshot(&[
+            "┌Request Failed────────────────────────┐",
+            "│                                      │",
+            "│         GamePass 'vip' (id 1)        │",
+            "│                                      │",
+            "│         429 Too Many Requests        │",
+            "│                                      │",
+            "│    [R] Retry  [S] Skip  [A] Abort    │",
+            "└──────────────────────────────────────┘",
+            "",
+        ]);
+    }
+
+    #[test]
+    fn picks_a_choice() {
+        let mut retried = harness();
+        retried.send([chr('x')]);
+        assert!(!retried.viewer.should_quit());
+
+        retried.send([chr('r')]);
+        assert_eq!(retried.viewer.choice, Some(FailureChoice::Retry));
+
+        let mut aborted = harness();
+        aborted.send([chr('a')]);
+        assert_eq!(aborted.viewer.choice, Some(FailureChoice::Abort));
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::Result;
+
+/// Something a key can do in the terminal UIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    /// Open the selected entry, or toggle a section header
+    Open,
+    Confirm,
+    ConfirmAll,
+    NextSection,
+    PreviousSection,
+    Collapse,
+    Expand,
+    /// Leave the open view, or quit from the list
+    Quit,
+    /// Quit from anywhere
+    ForceQuit,
+    /// Answer a confirmation with yes
+    Yes,
+    /// Answer a confirmation with no
+    No,
+    /// Send a failed request again
+    Retry,
+    /// Carry on past a failed request
+    Skip,
+    /// Stop at a failed request
+    Abort,
+}
+
+/// A terminal UI with its own set of keys. The same key can do different
+/// things in different views, but only one thing within a view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Diffs,
+    Confirm,
+    Failure,
+}
+
+impl View {
+    const ALL: [View; 3] = [View::Diffs, View::Confirm, View::Failure];
+
+    pub fn actions(self) -> &'static [Action] {
+        use Action::*;
+
+        match self {
+            View::Diffs => &[
+                Up,
+                Down,
+                PageUp,
+                PageDown,
+                Top,
+                Bottom,
+                Open,
+                Confirm,
+                ConfirmAll,
+                NextSection,
+                PreviousSection,
+                Collapse,
+                Expand,
+                Quit,
+                ForceQuit,
+            ],
+            View::Confirm => &[Yes, No],
+            View::Failure => &[Retry, Skip, Abort, ForceQuit],
+        }
+    }
+
+    fn shares(action: Action, other: Action) -> bool {
+        View::ALL.iter().any(|view| {
+            let actions = view.actions();
+            actions.contains(&action) && actions.contains(&other)
+        })
+    }
+}
+
+/// A key with the modifiers that must be held, e.g. `ctrl+d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    const fn key(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn chr(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+
+        // A trailing `+` is the plus key itself, not a separator.
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => {
+                    return Err(format!("unknown modifier '{}' in key '{}'", modifier, text).into());
+                }
+            };
+            rest = key;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key '{}'", text).into()),
+                }
+            }
+        };
+
+        Ok(Self { code, modifiers })
+    }
+
+    /// Shift is part of the character for letters and symbols, so it's only
+    /// compared for other keys.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let relevant = match event.code {
+            KeyCode::Char(_) => KeyModifiers::CONTROL | KeyModifiers::ALT,
+            _ => KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT,
+        };
+
+        self.code == event.code && (event.modifiers & relevant) == (self.modifiers & relevant)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+/// Built-in sets of bindings to start from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    #[default]
+    Default,
+    /// Adds `h`/`l` to collapse and expand, and `Ctrl+d`/`Ctrl+u` (or
+    /// `Ctrl+f`/`Ctrl+b`) to page
+    Vim,
+}
+
+/// One or more keys for an action in the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    fn parse(&self) -> Result<Vec<KeyBinding>> {
+        match self {
+            Keys::One(key) => Ok(vec![KeyBinding::parse(key)?]),
+            Keys::Many(keys) => keys.iter().map(|key| KeyBinding::parse(key)).collect(),
+        }
+    }
+}
+
+/// The `[keybindings]` section of the user config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KeybindingsConfig {
+    #[serde(default)]
+    pub preset: Preset,
+    #[serde(flatten)]
+    keys: BTreeMap<Action, Keys>,
+}
+
+/// The keys bound to each action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(Preset::Default)
+    }
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        use Action::*;
+
+        let mut bindings = BTreeMap::from([
+            (Up, vec![KeyBinding::key(KeyCode::Up), KeyBinding::chr('k')]),
+            (
+                Down,
+                vec![KeyBinding::key(KeyCode::Down), KeyBinding::chr('j')],
+            ),
+            (PageUp, vec![KeyBinding::key(KeyCode::PageUp)]),
+            (
+                PageDown,
+                vec![KeyBinding::key(KeyCode::PageDown), KeyBinding::chr(' ')],
+            ),
+            (
+                Top,
+                vec![KeyBinding::key(KeyCode::Home), KeyBinding::chr('g')],
+            ),
+            (
+                Bottom,
+                vec![KeyBinding::key(KeyCode::End), KeyBinding::chr('G')],
+            ),
+            (Open, vec![KeyBinding::key(KeyCode::Enter)]),
+            (Confirm, vec![KeyBinding::chr('c')]),
+            (ConfirmAll, vec![KeyBinding::chr('C')]),
+            (
+                NextSection,
+                vec![KeyBinding::key(KeyCode::Tab), KeyBinding::chr(']')],
+            ),
+            (
+                PreviousSection,
+                vec![KeyBinding::key(KeyCode::BackTab), KeyBinding::chr('[')],
+            ),
+            (Collapse, vec![KeyBinding::key(KeyCode::Left)]),
+            (Expand, vec![KeyBinding::key(KeyCode::Right)]),
+            (Quit, vec![KeyBinding::chr('q')]),
+            (ForceQuit, vec![KeyBinding::chr('Q')]),
+            (Yes, vec![KeyBinding::chr('y')]),
+            (No, vec![KeyBinding::chr('n')]),
+            (Retry, vec![KeyBinding::chr('r')]),
+            (Skip, vec![KeyBinding::chr('s')]),
+            (Abort, vec![KeyBinding::chr('a')]),
+        ]);
+
+        if preset == Preset::Vim {
+            let mut add = |action, keys: &[KeyBinding]| {
+                bindings.entry(action).or_default().extend_from_slice(keys);
+            };
+            add(PageUp, &[KeyBinding::ctrl('u'), KeyBinding::ctrl('b')]);
+            add(PageDown, &[KeyBinding::ctrl('d'), KeyBinding::ctrl('f')]);
+            add(Collapse, &[KeyBinding::chr('h')]);
+            add(Expand, &[KeyBinding::chr('l')]);
+            add(Quit, &[KeyBinding::key(KeyCode::Esc)]);
+        }
+
+        Self { bindings }
+    }
+
+    /// The preset with the config's bindings on top. Keys given to an action
+    /// are taken away from the other actions of the same view, and giving one
+    /// key to two actions of a view is an error.
+    pub fn from_config(config: &KeybindingsConfig) -> Result<Self> {
+        let mut keymap = Self::preset(config.preset);
+        let mut configured: Vec<(Action, Vec<KeyBinding>)> = vec![];
+
+        for (action, keys) in &config.keys {
+            let keys = keys.parse()?;
+
+            for (other, other_keys) in &configured {
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key))
+                    && View::shares(*action, *other)
+                {
+                    return Err(
+                        format!("'{}' is bound to both {:?} and {:?}", key, other, action).into(),
+                    );
+                }
+            }
+
+            for (other, bindings) in keymap.bindings.iter_mut() {
+                if View::shares(*action, *other) {
+                    bindings.retain(|binding| !keys.contains(binding));
+                }
+            }
+            keymap.bindings.insert(*action, keys.clone());
+            configured.push((*action, keys));
+        }
+
+        Ok(keymap)
+    }
+
+    /// The action a key press is bound to in `view`.
+    pub fn action(&self, view: View, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| view.actions().contains(action))
+            .find(|(_, bindings)| bindings.iter().any(|binding| binding.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Every key bound to an action, separated by slashes, for hints about
+    /// actions with more than one common key.
+    pub fn labels(&self, action: Action) -> String {
+        match self.bindings.get(&action) {
+            Some(bindings) if !bindings.is_empty() => bindings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("/"),
+            _ => "unbound".to_string(),
+        }
+    }
+
+    /// The first key bound to an action, for footers and hints.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .and_then(|bindings| bindings.first())
+            .map_or("unbound".to_string(), ToString::to_string)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref KEYMAP: RwLock<Keymap> = RwLock::new(Keymap::default());
+}
+
+pub fn set_keymap(keymap: Keymap) {
+    *KEYMAP.write().unwrap() = keymap;
+}
+
+/// The bindings the terminal UIs use for the rest of the run.
+pub fn keymap() -> Keymap {
+    KEYMAP.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        assert_eq!(KeyBinding::parse("ctrl+d").unwrap(), KeyBinding::ctrl('d'));
+        assert_eq!(
+            KeyBinding::parse("shift+tab").unwrap(),
+            KeyBinding::key(KeyCode::BackTab)
+        );
+        assert_eq!(KeyBinding::parse("+").unwrap(), KeyBinding::chr('+'));
+        assert_eq!(KeyBinding::parse("G").unwrap(), KeyBinding::chr('G'));
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("nope").is_err());
+    }
+
+    #[test]
+    fn vim_preset_and_overrides() {
+        let config: KeybindingsConfig = toml::from_str(
+            r#"
+            preset = "vim"
+            confirm = "space"
+            quit = ["x", "esc"]
+            "#,
+        )
+        .unwrap();
+        let keymap = Keymap::from_config(&config).unwrap();
+
+        let action = |code, modifiers| keymap.action(View::Diffs, &press(code, modifiers));
+        assert_eq!(
+            action(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            Some(Action::PageDown)
+        );
+        assert_eq!(action(KeyCode::Char('d'), KeyModifiers::NONE), None);
+        assert_eq!(
+            action(KeyCode::Char('l'), KeyModifiers::NONE),
+            Some(Action::Expand)
+        );
+        assert_eq!(
+            action(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(Action::Bottom)
+        );
+
+        // Space moved from paging to confirming, and `q` no longer quits.
+        assert_eq!(
+            action(KeyCode::Char(' '), KeyModifiers::NONE),
+            Some(Action::Confirm)
+        );
+        assert_eq!(action(KeyCode::Char('q'), KeyModifiers::NONE), None);
+        assert_eq!(keymap.label(Action::Confirm), "Space");
+        assert_eq!(keymap.label(Action::Quit), "x");
+        assert_eq!(keymap.label(Action::PageDown), "PgDn");
+        assert_eq!(keymap.labels(Action::Expand), "→/l");
+        assert_eq!(
+            Keymap::default().labels(Action::PreviousSection),
+            "Shift+Tab/["
+        );
+    }
+
+    #[test]
+    fn keys_are_only_taken_within_a_view() {
+        let config: KeybindingsConfig = toml::from_str(r#"confirm = "n""#).unwrap();
+        let keymap = Keymap::from_config(&config).unwrap();
+
+        let n = press(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(View::Diffs, &n), Some(Action::Confirm));
+        assert_eq!(keymap.action(View::Confirm, &n), Some(Action::No));
+        assert_eq!(keymap.label(Action::No), "n");
+
+        let config: KeybindingsConfig = toml::from_str(
+            r#"
+            confirm = "x"
+            quit = "x"
+            "#,
+        )
+        .unwrap();
+        assert!(Keymap::from_config(&config).is_err());
+
+        let config: KeybindingsConfig = toml::from_str(
+            r#"
+            confirm = "x"
+            retry = "x"
+            "#,
+        )
+        .unwrap();
+        assert!(Keymap::from_config(&config).is_ok());
+    }
+}
@@ -0,0 +1,253 @@
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::Result;
+use crate::sync::conflict::PendingMerge;
+use crate::ui::theme;
+use crate::ui::{Terminal, with_terminal};
+
+/// One conflicting field, flattened out of its product.
+#[derive(Debug, Clone)]
+struct MergeRow {
+    label: String,
+    base: Option<String>,
+    local: String,
+    remote: String,
+}
+
+/// Shows every field that both the products file and the universe changed,
+/// next to the value they started from, and lets the user pick a side.
+#[derive(Debug)]
+pub struct MergeViewer {
+    rows: Vec<MergeRow>,
+    /// Whether each row takes the remote value
+    choices: Vec<bool>,
+    selected: usize,
+    saved: bool,
+    should_quit: bool,
+}
+
+impl MergeViewer {
+    fn new(merges: &[PendingMerge]) -> Self {
+        let rows = merges
+            .iter()
+            .flat_map(|merge| {
+                merge.conflicts.iter().map(|conflict| MergeRow {
+                    label: format!(
+                        "{:?} '{}' {}",
+                        merge.product_type, merge.key, conflict.field.name
+                    ),
+                    base: conflict.base.clone(),
+                    local: conflict.local.clone(),
+                    remote: conflict.remote.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            choices: vec![false; rows.len()],
+            rows,
+            selected: 0,
+            saved: false,
+            should_quit: false,
+        }
+    }
+
+    /// Shows the merge screen, returning whether each conflict takes the
+    /// remote value, or `None` if it was cancelled.
+    pub async fn merge(merges: &[PendingMerge]) -> Result<Option<Vec<bool>>> {
+        let mut viewer = Self::new(merges);
+
+        with_terminal(&mut viewer).await?;
+
+        Ok(viewer.saved.then_some(viewer.choices))
+    }
+
+    fn pane<'a>(title: &'a str, value: &'a str, chosen: bool) -> Paragraph<'a> {
+        let (title, style) = if chosen {
+            (format!(" {} ✓ ", title), theme::theme().added)
+        } else {
+            (format!(" {} ", title), Style::default())
+        };
+
+        Paragraph::new(value).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(style),
+        )
+    }
+}
+
+impl Terminal for MergeViewer {
+    fn render(&mut self, frame: &mut Frame) {
+        let areas = Layout::vertical([
+            Constraint::Percentage(40),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+        let items = self
+            .rows
+            .iter()
+            .zip(&self.choices)
+            .map(|(row, take_remote)| {
+                let side = if *take_remote { "remote" } else { "local" };
+                ListItem::new(Line::from(format!("[{:<6}] {}", side, row.label)))
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(items)
+            .block(Block::default().title(" Conflicts ").borders(Borders::ALL))
+            .highlight_style(theme::theme().highlight)
+            .highlight_symbol(">> ");
+
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        frame.render_stateful_widget(list, areas[0], &mut state);
+
+        if let Some(row) = self.rows.get(self.selected) {
+            let take_remote = self.choices[self.selected];
+            let panes = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(areas[1]);
+
+            frame.render_widget(
+                Self::pane(
+                    "Base",
+                    row.base.as_deref().unwrap_or("(not recorded)"),
+                    false,
+                ),
+                panes[0],
+            );
+            frame.render_widget(Self::pane("Local", &row.local, !take_remote), panes[1]);
+            frame.render_widget(Self::pane("Remote", &row.remote, take_remote), panes[2]);
+        }
+
+        frame.render_widget(
+            Paragraph::new("↑/↓: Select  ←/l: Local  →/r: Remote  L/R: All  s: Save  q: Cancel")
+                .centered(),
+            areas[2],
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if !event.is_key_press() {
+                return;
+            }
+
+            match key_event.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1));
+                }
+                KeyCode::Left | KeyCode::Char('l') => {
+                    if let Some(choice) = self.choices.get_mut(self.selected) {
+                        *choice = false;
+                    }
+                }
+                KeyCode::Right | KeyCode::Char('r') => {
+                    if let Some(choice) = self.choices.get_mut(self.selected) {
+                        *choice = true;
+                    }
+                }
+                KeyCode::Char('L') => self.choices.fill(false),
+                KeyCode::Char('R') => self.choices.fill(true),
+                KeyCode::Char('s') | KeyCode::Enter => {
+                    self.saved = true;
+                    self.should_quit = true;
+                }
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.saved = false;
+                    self.should_quit = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::conflict::ConflictStrategy;
+    use crate::sync::products::{Product, ProductType};
+    use crate::ui::testing::{Harness, chr, key};
+
+    fn harness() -> Harness<MergeViewer> {
+        let local = Product {
+            id: Some(1),
+            name: "VIP".to_string(),
+            price: 100,
+            ..Default::default()
+        };
+        let remote = Product {
+            name: "VIP Room".to_string(),
+            price: 250,
+            ..local.clone()
+        };
+        let base = Product {
+            name: "Old VIP".to_string(),
+            price: 200,
+            ..local.clone()
+        };
+
+        let (_, pending) = ConflictStrategy::Interactive.merge(
+            ProductType::GamePass,
+            "vip",
+            Some(&local),
+            &remote,
+            Some(&base),
+            &None,
+        );
+
+        Harness::new(MergeViewer::new(&[pending.unwrap()]), 60, 10)
+    }
+
+    #[test]
+    fn renders_base_local_and_remote() {
+        harness().assert_snapshot(&[
+            "┌ Conflicts ───────────────────────────────────────────────┐",
+            "│>> [local ] GamePass 'vip' name                           │",
+            "│   [local ] GamePass 'vip' price                          │",
+            "└──────────────────────────────────────────────────────────┘",
+            "┌ Base ────────────┐┌ Local ✓ ─────────┐┌ Remote ──────────┐",
+            "│Old VIP           ││VIP               ││VIP Room          │",
+            "│                  ││                  ││                  │",
+            "│                  ││                  ││                  │",
+            "└──────────────────┘└──────────────────┘└──────────────────┘",
+            "↑/↓: Select  ←/l: Local  →/r: Remote  L/R: All  s: Save  q:",
+        ]);
+    }
+
+    #[test]
+    fn picks_sides_and_saves() {
+        let mut harness = harness();
+        harness.send([key(KeyCode::Down), chr('r')]);
+        assert_eq!(harness.viewer.choices, vec![false, true]);
+
+        harness.send([chr('R'), key(KeyCode::Left), chr('s')]);
+        assert!(harness.viewer.saved);
+        assert_eq!(harness.viewer.choices, vec![true, false]);
+    }
+
+    #[test]
+    fn cancels_without_saving() {
+        let mut harness = harness();
+        harness.send([chr('r'), chr('q')]);
+
+        assert!(!harness.viewer.saved);
+    }
+}
@@ -1,21 +1,121 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use crossterm::event::EventStream;
 use futures::StreamExt;
-use ratatui::{DefaultTerminal, Frame};
+use ratatui::Frame;
 
+use crate::Result;
+
+pub mod browse;
+pub mod config;
 pub mod confirm;
 pub mod diffs;
+pub mod failure;
+pub mod keymap;
+pub mod merge;
+pub mod preview;
+pub mod prompt;
+pub mod reorder;
+#[cfg(test)]
+pub mod testing;
+pub mod theme;
+pub mod wrap;
 
 const FPS: f32 = 60.0;
 
+static CI: AtomicBool = AtomicBool::new(false);
+/// Set while a terminal UI has raw mode and the alternate screen on.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Turns off terminal UIs and prompts for the rest of the run (`--ci`).
+pub fn set_ci(enabled: bool) {
+    CI.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_ci() -> bool {
+    CI.load(Ordering::Relaxed)
+}
+
+/// Whether terminal UIs and prompts can be shown. Outside CI mode this still
+/// requires a terminal, so piped runs don't hang waiting for input.
+pub fn is_interactive() -> bool {
+    !is_ci() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Whether the user can be asked to confirm something. Unlike
+/// `is_interactive`, only stdin has to be a terminal, since prompts fall back
+/// to plain text on stderr when stdout is piped.
+pub fn can_prompt() -> bool {
+    !is_ci() && std::io::stdin().is_terminal()
+}
+
 pub trait Terminal {
     fn render(&mut self, frame: &mut Frame);
     fn handle_event(&mut self, event: &crossterm::event::Event);
     fn should_quit(&self) -> bool;
+
+    /// Runs after each frame is drawn, for output ratatui can't express
+    /// such as inline images.
+    fn after_draw(&mut self) {}
+}
+
+/// Leaves raw mode and the alternate screen if a terminal UI is open. Safe
+/// to call from anywhere, any number of times.
+pub fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        ratatui::restore();
+    }
+}
+
+/// Restores the terminal before a panic is reported, and on Ctrl-C or
+/// SIGTERM, so a crash mid-sync doesn't leave the shell in raw mode.
+pub fn install_handlers() {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+
+    tokio::spawn(async {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+                return;
+            };
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {},
+                _ = terminate.recv() => {},
+            }
+        }
+        #[cfg(not(unix))]
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+
+        restore();
+        std::process::exit(130);
+    });
+}
+
+/// Runs a terminal UI until it asks to quit. The terminal is restored even
+/// when drawing or reading events fails.
+pub async fn with_terminal<T>(terminal: &mut T) -> Result<()>
+where
+    T: Terminal,
+{
+    let mut backend = ratatui::try_init()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+
+    let result = run(terminal, &mut backend).await;
+    restore();
+    result
 }
 
-pub async fn with_terminal<T>(terminal: &mut T, backend: &mut DefaultTerminal)
+async fn run<T>(terminal: &mut T, backend: &mut ratatui::DefaultTerminal) -> Result<()>
 where
     T: Terminal,
 {
@@ -25,10 +125,13 @@ where
 
     while !terminal.should_quit() {
         tokio::select! {
-            _ = interval.tick() => { backend.draw(|frame| terminal.render(frame)).unwrap(); },
-            Some(Ok(event)) = events.next() => terminal.handle_event(&event),
+            _ = interval.tick() => {
+                backend.draw(|frame| terminal.render(frame))?;
+                terminal.after_draw();
+            },
+            Some(event) = events.next() => terminal.handle_event(&event?),
         }
     }
 
-    ratatui::restore();
+    Ok(())
 }
@@ -0,0 +1,372 @@
+use std::io::Read;
+
+use base64::Engine;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+/// Columns and rows an icon takes up in the diff view. Cells are about twice
+/// as tall as they are wide, so this is roughly square.
+pub const ICON_WIDTH: u16 = 16;
+pub const ICON_HEIGHT: u16 = 8;
+
+/// How product icons are drawn in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    /// The kitty graphics protocol, also understood by WezTerm and Ghostty
+    Kitty,
+    /// Colored half-block characters, which work in any terminal with color
+    Blocks,
+    Off,
+}
+
+fn detect(var: impl Fn(&str) -> Option<String>) -> ImageProtocol {
+    match var("RBX_PRODUCTS_IMAGES").map(|value| value.to_lowercase()) {
+        Some(value) if value == "kitty" => return ImageProtocol::Kitty,
+        Some(value) if value == "blocks" => return ImageProtocol::Blocks,
+        Some(value) if value == "off" => return ImageProtocol::Off,
+        _ => {}
+    }
+
+    let kitty = var("KITTY_WINDOW_ID").is_some()
+        || var("TERM").is_some_and(|term| term.contains("kitty") || term.contains("ghostty"))
+        || var("TERM_PROGRAM").is_some_and(|program| program == "WezTerm" || program == "ghostty");
+
+    if kitty {
+        ImageProtocol::Kitty
+    } else {
+        ImageProtocol::Blocks
+    }
+}
+
+/// Picks the protocol from the terminal, unless `RBX_PRODUCTS_IMAGES` is set
+/// to `kitty`, `blocks` or `off`.
+pub fn protocol() -> ImageProtocol {
+    detect(|name| std::env::var(name).ok())
+}
+
+/// Draws a PNG at `area` with the kitty graphics protocol, leaving the cursor
+/// where it was.
+pub fn kitty_place(png: &[u8], area: Rect) -> String {
+    let data = base64::engine::general_purpose::STANDARD.encode(png);
+    let chunks = data.as_bytes().chunks(4096).collect::<Vec<_>>();
+
+    let mut out = format!("\x1b7\x1b[{};{}H", area.y + 1, area.x + 1);
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+
+        if index == 0 {
+            out += &format!(
+                "\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};",
+                area.width, area.height, more
+            );
+        } else {
+            out += &format!("\x1b_Gm={};", more);
+        }
+        out += &String::from_utf8_lossy(chunk);
+        out += "\x1b\\";
+    }
+
+    out + "\x1b8"
+}
+
+/// Removes every image placed with `kitty_place`.
+pub const KITTY_CLEAR: &str = "\x1b_Ga=d,d=A\x1b\\";
+
+/// A decoded image, as RGBA pixels row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Largest width or height decoded. Thumbnails are far smaller, so anything
+/// bigger is a broken or hostile file.
+const MAX_DIMENSION: usize = 4096;
+
+/// Decodes the non-interlaced, 8-bit PNGs the thumbnail API serves. Anything
+/// else, including chunks with a bad CRC, returns `None` and the icon is
+/// skipped.
+pub fn decode_png(data: &[u8]) -> Option<Image> {
+    let mut rest = data.strip_prefix(b"\x89PNG\r\n\x1a\n")?;
+    let (mut header, mut palette, mut alpha, mut compressed) = (None, vec![], vec![], vec![]);
+
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes(rest[..4].try_into().ok()?) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + length)?;
+        let crc = u32::from_be_bytes(rest.get(8 + length..12 + length)?.try_into().ok()?);
+        if crc32fast::hash(&rest[4..8 + length]) != crc {
+            return None;
+        }
+
+        match kind {
+            b"IHDR" => header = Some(body.to_vec()),
+            b"PLTE" => palette = body.to_vec(),
+            b"tRNS" => alpha = body.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + length..)?;
+    }
+
+    let header = header?;
+    let width = u32::from_be_bytes(header.get(0..4)?.try_into().ok()?) as usize;
+    let height = u32::from_be_bytes(header.get(4..8)?.try_into().ok()?) as usize;
+    let &[depth, color_type, _, _, interlace] = header.get(8..13)? else {
+        return None;
+    };
+
+    let channels = match color_type {
+        0 | 3 => 1,
+        4 => 2,
+        2 => 3,
+        6 => 4,
+        _ => return None,
+    };
+    if depth != 8 || interlace != 0 {
+        return None;
+    }
+    if !(1..=MAX_DIMENSION).contains(&width) || !(1..=MAX_DIMENSION).contains(&height) {
+        return None;
+    }
+
+    // Stops inflating at the size the header promises.
+    let expected = (width * channels + 1) * height;
+    let mut raw = vec![];
+    flate2::read::ZlibDecoder::new(compressed.as_slice())
+        .take(expected as u64)
+        .read_to_end(&mut raw)
+        .ok()?;
+
+    let stride = width * channels;
+    let mut previous = vec![0u8; stride];
+    let mut pixels = Vec::with_capacity(width * height);
+
+    for row in raw.chunks(stride + 1).take(height) {
+        let (filter, line) = row.split_first()?;
+        let mut line = line.to_vec();
+        if line.len() != stride {
+            return None;
+        }
+
+        for i in 0..stride {
+            let left = if i >= channels { line[i - channels] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= channels {
+                previous[i - channels]
+            } else {
+                0
+            };
+
+            line[i] = line[i].wrapping_add(match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            });
+        }
+
+        pixels.extend(line.chunks(channels).map(|px| match color_type {
+            0 => [px[0], px[0], px[0], 255],
+            4 => [px[0], px[0], px[0], px[1]],
+            2 => [px[0], px[1], px[2], 255],
+            3 => {
+                let index = px[0] as usize;
+                let rgb = palette.get(index * 3..index * 3 + 3).unwrap_or(&[0, 0, 0]);
+                [rgb[0], rgb[1], rgb[2], *alpha.get(index).unwrap_or(&255)]
+            }
+            _ => [px[0], px[1], px[2], px[3]],
+        }));
+        previous = line;
+    }
+
+    (pixels.len() == width * height).then_some(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn color(pixel: [u8; 4]) -> Color {
+    if pixel[3] < 128 {
+        Color::Reset
+    } else {
+        Color::Rgb(pixel[0], pixel[1], pixel[2])
+    }
+}
+
+/// Scales an image down to `width` by `height` cells of `▀`, with the top
+/// half of each cell in the foreground color and the bottom half behind it.
+pub fn block_lines(image: &Image, width: u16, height: u16) -> Vec<Line<'static>> {
+    if image.pixels.len() != image.width * image.height || image.pixels.is_empty() {
+        return vec![];
+    }
+
+    let sample = |x: usize, y: usize| {
+        let x = x * image.width / width as usize;
+        let y = y * image.height / (height as usize * 2);
+        image.pixels[y * image.width + x]
+    };
+
+    (0..height as usize)
+        .map(|row| {
+            Line::from(
+                (0..width as usize)
+                    .map(|col| {
+                        let style = Style::default()
+                            .fg(color(sample(col, row * 2)))
+                            .bg(color(sample(col, row * 2 + 1)));
+                        Span::styled("▀", style)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A 2x2 RGBA PNG with each row using a different filter.
+    fn png() -> Vec<u8> {
+        png_sized(2, 2)
+    }
+
+    /// The 2x2 image data under a header claiming `width` by `height`.
+    fn png_sized(width: u32, height: u32) -> Vec<u8> {
+        let chunk = |kind: &[u8], body: &[u8]| {
+            let mut out = (body.len() as u32).to_be_bytes().to_vec();
+            out.extend_from_slice(kind);
+            out.extend_from_slice(body);
+            out.extend_from_slice(&crc32fast::hash(&out[4..]).to_be_bytes());
+            out
+        };
+
+        let mut header = vec![];
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        // Row one is unfiltered, row two uses the `up` filter.
+        let raw = [
+            0, 255, 0, 0, 255, 0, 255, 0, 255, //
+            2, 0, 0, 255, 0, 0, 0, 0, 0,
+        ];
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&raw).unwrap();
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(chunk(b"IHDR", &header));
+        png.extend(chunk(b"IDAT", &encoder.finish().unwrap()));
+        png.extend(chunk(b"IEND", &[]));
+        png
+    }
+
+    #[test]
+    fn decodes_filtered_rgba() {
+        let image = decode_png(&png()).unwrap();
+
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(
+            image.pixels,
+            vec![
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [255, 0, 255, 255],
+                [0, 255, 0, 255]
+            ]
+        );
+        assert!(decode_png(b"not a png").is_none());
+    }
+
+    #[test]
+    fn rejects_bad_headers_and_checksums() {
+        assert!(decode_png(&png_sized(0, 2)).is_none());
+        assert!(decode_png(&png_sized(2, 0)).is_none());
+        assert!(decode_png(&png_sized(u32::MAX, u32::MAX)).is_none());
+
+        let mut corrupt = png();
+        let last = corrupt.len() - 13;
+        corrupt[last] ^= 1;
+        assert!(decode_png(&corrupt).is_none());
+
+        let empty = Image {
+            width: 0,
+            height: 0,
+            pixels: vec![],
+        };
+        assert!(block_lines(&empty, 4, 2).is_empty());
+    }
+
+    #[test]
+    fn renders_half_blocks() {
+        let image = decode_png(&png()).unwrap();
+        let lines = block_lines(&image, 2, 1);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(
+            lines[0].spans[0].style,
+            Style::default()
+                .fg(Color::Rgb(255, 0, 0))
+                .bg(Color::Rgb(255, 0, 255))
+        );
+    }
+
+    #[test]
+    fn detects_kitty_terminals() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert_eq!(
+            detect(env(&[("TERM", "xterm-kitty")])),
+            ImageProtocol::Kitty
+        );
+        assert_eq!(
+            detect(env(&[("TERM_PROGRAM", "WezTerm")])),
+            ImageProtocol::Kitty
+        );
+        assert_eq!(
+            detect(env(&[("TERM", "xterm-256color")])),
+            ImageProtocol::Blocks
+        );
+        assert_eq!(
+            detect(env(&[
+                ("TERM", "xterm-kitty"),
+                ("RBX_PRODUCTS_IMAGES", "off")
+            ])),
+            ImageProtocol::Off
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use log::info;
+
+use crate::Result;
+use crate::utils::prompt_line;
+
+/// How prompts are answered for the rest of the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptPolicy {
+    /// Ask in the terminal
+    Ask,
+    /// Confirm every prompt without asking (`--yes`)
+    Yes,
+    /// Fail instead of asking (`--no-input`)
+    NoInput,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(PromptPolicy::Ask as u8);
+
+pub fn set_policy(policy: PromptPolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub fn policy() -> PromptPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        1 => PromptPolicy::Yes,
+        2 => PromptPolicy::NoInput,
+        _ => PromptPolicy::Ask,
+    }
+}
+
+/// Whether the user can be asked anything at all. Text prompts check this
+/// before reading from stdin.
+pub fn allows_input() -> bool {
+    policy() != PromptPolicy::NoInput
+}
+
+/// Answers `prompt` from the policy, or returns `None` when it should be
+/// shown. Only the first line of the prompt ends up in logs and errors.
+fn answer(policy: PromptPolicy, prompt: &str) -> Result<Option<bool>> {
+    let summary = prompt.lines().next().unwrap_or_default();
+
+    match policy {
+        PromptPolicy::Ask => Ok(None),
+        PromptPolicy::Yes => {
+            info!("{} yes (--yes)", summary);
+            Ok(Some(true))
+        }
+        PromptPolicy::NoInput => {
+            Err(format!("'{}' needs an answer, but --no-input is set", summary).into())
+        }
+    }
+}
+
+/// Answers `prompt` from the current policy, or returns `None` when it should
+/// be shown.
+pub fn decide(prompt: &str) -> Result<Option<bool>> {
+    answer(policy(), prompt)
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Asks a yes/no question on stderr, for when the terminal UI can't be drawn.
+/// Anything but `y` or `yes`, including end of input, is a no.
+pub fn ask_plain(prompt: &str) -> Result<bool> {
+    Ok(is_yes(&prompt_line(&format!("{} [y/N]", prompt))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_from_the_policy() {
+        let prompt = "Would you like to sync products?\nVIP: 100 -> 80";
+
+        assert_eq!(answer(PromptPolicy::Ask, prompt).unwrap(), None);
+        assert_eq!(answer(PromptPolicy::Yes, prompt).unwrap(), Some(true));
+
+        let error = answer(PromptPolicy::NoInput, prompt)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Would you like to sync products?"));
+        assert!(!error.contains("VIP"));
+    }
+
+    #[test]
+    fn only_yes_confirms() {
+        assert!(is_yes("y"));
+        assert!(is_yes(" YES\n"));
+        assert!(!is_yes(""));
+        assert!(!is_yes("n"));
+        assert!(!is_yes("yep"));
+    }
+}
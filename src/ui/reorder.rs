@@ -0,0 +1,180 @@
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::Result;
+use crate::ui::theme;
+use crate::ui::{Terminal, with_terminal};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReorderState {
+    Saved,
+    Cancelled,
+}
+
+/// Lets the user move products up and down the in-game shop order.
+#[derive(Debug)]
+pub struct ReorderViewer {
+    /// `(key, name)` in the current order
+    items: Vec<(String, String)>,
+    selected: usize,
+    state: ReorderState,
+    should_quit: bool,
+}
+
+impl ReorderViewer {
+    fn new(items: Vec<(String, String)>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            state: ReorderState::Cancelled,
+            should_quit: false,
+        }
+    }
+
+    pub fn state(&self) -> &ReorderState {
+        &self.state
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.items.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    /// Shows the reorder screen, returning the new key order if it was saved.
+    pub async fn reorder(items: Vec<(String, String)>) -> Result<Option<Vec<String>>> {
+        let mut viewer = Self::new(items);
+
+        with_terminal(&mut viewer).await?;
+
+        Ok(match viewer.state() {
+            ReorderState::Saved => Some(viewer.keys()),
+            ReorderState::Cancelled => None,
+        })
+    }
+
+    fn move_selected(&mut self, up: bool) {
+        if up && self.selected > 0 {
+            self.items.swap(self.selected, self.selected - 1);
+            self.selected -= 1;
+        } else if !up && self.selected + 1 < self.items.len() {
+            self.items.swap(self.selected, self.selected + 1);
+            self.selected += 1;
+        }
+    }
+}
+
+impl Terminal for ReorderViewer {
+    fn render(&mut self, frame: &mut Frame) {
+        let areas =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).split(frame.area());
+
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, (key, name))| {
+                ListItem::new(Line::from(format!("{}. {} ({})", i + 1, name, key)))
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(items)
+            .block(Block::default().title(" Shop Order ").borders(Borders::ALL))
+            .highlight_style(theme::theme().highlight)
+            .highlight_symbol(">> ");
+
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+
+        frame.render_stateful_widget(list, areas[0], &mut state);
+        frame.render_widget(
+            Paragraph::new("↑/↓: Select  Shift+↑/↓ or K/J: Move  s: Save  q: Cancel").centered(),
+            areas[1],
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(key_event) = event {
+            if !event.is_key_press() {
+                return;
+            }
+
+            let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+
+            match key_event.code {
+                KeyCode::Up if shift => self.move_selected(true),
+                KeyCode::Down if shift => self.move_selected(false),
+                KeyCode::Char('K') => self.move_selected(true),
+                KeyCode::Char('J') => self.move_selected(false),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1));
+                }
+                KeyCode::Char('s') | KeyCode::Enter => {
+                    self.state = ReorderState::Saved;
+                    self.should_quit = true;
+                }
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.state = ReorderState::Cancelled;
+                    self.should_quit = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{Harness, chr, key};
+
+    fn harness() -> Harness<ReorderViewer> {
+        let items = [("vip", "VIP"), ("coins", "Coins"), ("gems", "Gems")]
+            .map(|(key, name)| (key.to_string(), name.to_string()))
+            .to_vec();
+
+        Harness::new(ReorderViewer::new(items), 60, 6)
+    }
+
+    #[test]
+    fn renders_order() {
+        harness().assert_snapshot(&[
+            "┌ Shop Order ──────────────────────────────────────────────┐",
+            "│>> 1. VIP (vip)                                           │",
+            "│   2. Coins (coins)                                       │",
+            "│   3. Gems (gems)                                         │",
+            "└──────────────────────────────────────────────────────────┘",
+            "   ↑/↓: Select  Shift+↑/↓ or K/J: Move  s: Save  q: Cancel",
+        ]);
+    }
+
+    #[test]
+    fn moves_items_and_saves() {
+        let mut harness = harness();
+        harness.send([chr('J'), chr('J'), key(KeyCode::Up), chr('K'), chr('s')]);
+
+        assert_eq!(harness.viewer.state(), &ReorderState::Saved);
+        assert_eq!(
+            harness.viewer.keys(),
+            ["gems", "coins", "vip"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn cancels_without_saving() {
+        let mut harness = harness();
+        harness.send([chr('J'), chr('q')]);
+
+        assert_eq!(harness.viewer.state(), &ReorderState::Cancelled);
+    }
+}
@@ -0,0 +1,90 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+
+use crate::ui::Terminal;
+
+/// Drives a [`Terminal`] implementation with a scripted sequence of events,
+/// rendering into an in-memory backend instead of the real tty.
+pub struct Harness<T: Terminal> {
+    pub viewer: T,
+    backend: ratatui::Terminal<TestBackend>,
+}
+
+impl<T: Terminal> Harness<T> {
+    pub fn new(viewer: T, width: u16, height: u16) -> Self {
+        let backend = ratatui::Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut harness = Self { viewer, backend };
+        harness.render();
+        harness
+    }
+
+    pub fn render(&mut self) -> &mut Self {
+        let viewer = &mut self.viewer;
+        self.backend.draw(|frame| viewer.render(frame)).unwrap();
+        self
+    }
+
+    /// Feeds events one at a time, redrawing after each, and stops as soon as
+    /// the viewer asks to quit (mirroring `with_terminal`).
+    pub fn send<I: IntoIterator<Item = Event>>(&mut self, events: I) -> &mut Self {
+        for event in events {
+            if self.viewer.should_quit() {
+                break;
+            }
+
+            self.viewer.handle_event(&event);
+            self.render();
+        }
+
+        self
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let buffer = self.backend.backend().buffer();
+        let width = buffer.area.width as usize;
+
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Compares the rendered screen against `expected`, ignoring styles and
+    /// trailing whitespace on each row.
+    #[track_caller]
+    pub fn assert_snapshot(&self, expected: &[&str]) {
+        let actual = self.lines();
+        let expected = expected
+            .iter()
+            .map(|line| line.trim_end().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            actual,
+            expected,
+            "\nrendered screen:\n{}\n",
+            actual.join("\n")
+        );
+    }
+}
+
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+pub fn chr(c: char) -> Event {
+    let modifiers = if c.is_ascii_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+
+    Event::Key(KeyEvent::new(KeyCode::Char(c), modifiers))
+}
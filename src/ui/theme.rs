@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::Result;
+
+/// Parts of the terminal UIs whose style can be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Element {
+    /// New values in diffs, and "yes"
+    Added,
+    /// Old values in diffs, and "no"
+    Removed,
+    /// The selected row of a list
+    Highlight,
+    /// Section headers
+    Header,
+    /// Entries still waiting to be confirmed
+    Pending,
+    Confirmed,
+    /// Entries that can't be acted on
+    Muted,
+}
+
+/// Built-in color sets to start from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// The `[theme]` section of the user config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: ThemePreset,
+    #[serde(flatten)]
+    styles: BTreeMap<Element, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub added: Style,
+    pub removed: Style,
+    pub highlight: Style,
+    pub header: Style,
+    pub pending: Style,
+    pub confirmed: Style,
+    pub muted: Style,
+    /// Off for `--no-color` and `NO_COLOR`, where only attributes are used
+    pub color: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(ThemePreset::Dark)
+    }
+}
+
+/// Parses styles like `black on light-cyan bold`: an optional foreground, an
+/// optional `on <background>`, and any modifiers.
+pub fn parse_style(text: &str) -> Result<Style> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+
+    while let Some(word) = words.next() {
+        let modifier = match word.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed-out" => Modifier::CROSSED_OUT,
+            "on" => {
+                let color = words
+                    .next()
+                    .ok_or_else(|| format!("missing background color in '{}'", text))?;
+                style = style.bg(parse_color(color)?);
+                continue;
+            }
+            _ => {
+                style = style.fg(parse_color(word)?);
+                continue;
+            }
+        };
+
+        style = style.add_modifier(modifier);
+    }
+
+    Ok(style)
+}
+
+fn parse_color(text: &str) -> Result<Color> {
+    Color::from_str(text).map_err(|_| format!("unknown color '{}'", text).into())
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Dark => Self {
+                added: Style::default().fg(Color::Green),
+                removed: Style::default().fg(Color::Red),
+                highlight: Style::default()
+                    .bg(Color::Blue)
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                header: Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+                pending: Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::ITALIC),
+                confirmed: Style::default().fg(Color::White),
+                muted: Style::default().fg(Color::DarkGray),
+                color: true,
+            },
+            // Yellow and white text vanish on light backgrounds.
+            ThemePreset::Light => Self {
+                added: Style::default().fg(Color::Green),
+                removed: Style::default().fg(Color::Red),
+                highlight: Style::default()
+                    .bg(Color::Blue)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                header: Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+                pending: Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::ITALIC),
+                confirmed: Style::default().fg(Color::Black),
+                muted: Style::default().fg(Color::Gray),
+                color: true,
+            },
+        }
+    }
+
+    /// Attribute-only styling for terminals without color.
+    pub fn plain() -> Self {
+        Self {
+            added: Style::default().add_modifier(Modifier::BOLD),
+            removed: Style::default(),
+            highlight: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            header: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            pending: Style::default().add_modifier(Modifier::ITALIC),
+            confirmed: Style::default(),
+            muted: Style::default().add_modifier(Modifier::DIM),
+            color: false,
+        }
+    }
+
+    /// The preset with the config's styles on top.
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = Self::preset(config.preset);
+
+        for (element, text) in &config.styles {
+            let name = format!("{:?}", element).to_lowercase();
+            let style = parse_style(text).map_err(|e| format!("{}: {}", name, e))?;
+            *theme.style_mut(*element) = style;
+        }
+
+        Ok(theme)
+    }
+
+    fn style_mut(&mut self, element: Element) -> &mut Style {
+        match element {
+            Element::Added => &mut self.added,
+            Element::Removed => &mut self.removed,
+            Element::Highlight => &mut self.highlight,
+            Element::Header => &mut self.header,
+            Element::Pending => &mut self.pending,
+            Element::Confirmed => &mut self.confirmed,
+            Element::Muted => &mut self.muted,
+        }
+    }
+
+    /// Changed words inside an added or removed line.
+    pub fn changed_words(&self, line: Style) -> Style {
+        line.add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref THEME: RwLock<Theme> = RwLock::new(Theme::default());
+}
+
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap() = theme;
+}
+
+/// The styles the terminal UIs use for the rest of the run.
+pub fn theme() -> Theme {
+    THEME.read().unwrap().clone()
+}
+
+/// Whether plain text output like `diff` may use colors at all.
+pub fn color_enabled() -> bool {
+    THEME.read().unwrap().color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_styles() {
+        assert_eq!(
+            parse_style("black on light-cyan bold").unwrap(),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            parse_style("#ff8800 italic").unwrap(),
+            Style::default()
+                .fg(Color::Rgb(255, 136, 0))
+                .add_modifier(Modifier::ITALIC)
+        );
+        assert!(parse_style("on").is_err());
+        assert!(parse_style("chartreuse-ish").is_err());
+    }
+
+    #[test]
+    fn overrides_preset_styles() {
+        let config: ThemeConfig = toml::from_str(
+            r#"
+            preset = "light"
+            highlight = "reversed"
+            "#,
+        )
+        .unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+
+        assert_eq!(
+            theme.highlight,
+            Style::default().add_modifier(Modifier::REVERSED)
+        );
+        assert_eq!(theme.pending, Theme::preset(ThemePreset::Light).pending);
+
+        let config: ThemeConfig = toml::from_str(r#"added = "nope""#).unwrap();
+        assert_eq!(
+            Theme::from_config(&config).unwrap_err().to_string(),
+            "added: unknown color 'nope'"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use ratatui::text::Line;
+use unicode_width::UnicodeWidthChar;
+
+/// Rows `lines` take in a bordered `Paragraph` of `width` columns with
+/// `Wrap { trim: false }`, borders included.
+pub fn wrapped_height(lines: &[Line], width: u16) -> u16 {
+    let inner = width.saturating_sub(2);
+    let rows = lines
+        .iter()
+        .map(|line| wrapped_rows(line, inner))
+        .sum::<usize>();
+
+    (rows as u16).saturating_add(2)
+}
+
+/// Rows one line wraps to, breaking between words the way ratatui's word
+/// wrapper does when it doesn't trim whitespace.
+fn wrapped_rows(line: &Line, width: u16) -> usize {
+    if width == 0 {
+        return 0;
+    }
+
+    let mut rows = 0;
+    let mut line_width = 0;
+    let mut line_len = 0;
+    let mut word_width = 0;
+    let mut word_len = 0;
+    let mut whitespace = VecDeque::new();
+    let mut whitespace_width = 0;
+    let mut non_whitespace_previous = false;
+
+    for c in line.spans.iter().flat_map(|span| span.content.chars()) {
+        let is_whitespace = c.is_whitespace();
+        let symbol_width = c.width().unwrap_or(0) as u16;
+
+        // Symbols wider than a row are skipped.
+        if symbol_width > width {
+            continue;
+        }
+
+        let word_found = non_whitespace_previous && is_whitespace;
+        let overflow = line_len == 0 && word_width + whitespace_width + symbol_width > width;
+        if word_found || overflow {
+            line_width += whitespace_width + word_width;
+            line_len += whitespace.len() + word_len;
+            whitespace.clear();
+            whitespace_width = 0;
+            word_width = 0;
+            word_len = 0;
+        }
+
+        let line_full = line_width >= width;
+        let word_overflow = symbol_width > 0 && line_width + whitespace_width + word_width >= width;
+        if line_full || word_overflow {
+            let mut remaining = width.saturating_sub(line_width);
+            rows += 1;
+            line_width = 0;
+            line_len = 0;
+
+            // Whitespace that still fits stays at the end of the full row.
+            while let Some(&space) = whitespace.front() {
+                if space > remaining {
+                    break;
+                }
+                whitespace_width -= space;
+                remaining -= space;
+                whitespace.pop_front();
+            }
+
+            if is_whitespace && whitespace.is_empty() {
+                continue;
+            }
+        }
+
+        if is_whitespace {
+            whitespace_width += symbol_width;
+            whitespace.push_back(symbol_width);
+        } else {
+            word_width += symbol_width;
+            word_len += 1;
+        }
+
+        non_whitespace_previous = !is_whitespace;
+    }
+
+    if line_len + whitespace.len() + word_len > 0 {
+        rows += 1;
+    }
+
+    rows.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::text::Text;
+    use ratatui::widgets::{Paragraph, Widget, Wrap};
+
+    use super::*;
+
+    #[test]
+    fn wraps_between_words() {
+        let lines = [
+            Line::from("- Description: Grants access to the VIP room"),
+            Line::from(""),
+            Line::from("line1 line2 line3 line4 line5 line6"),
+        ];
+
+        assert_eq!(wrapped_height(&lines[..1], 30), 4);
+        assert_eq!(wrapped_height(&lines[1..2], 30), 3);
+        assert_eq!(wrapped_height(&lines, 10), 16);
+        assert_eq!(wrapped_height(&[Line::from("a".repeat(20))], 10), 5);
+    }
+
+    #[test]
+    fn matches_the_rendered_paragraph() {
+        let lines = vec![
+            Line::from("Grants access to the VIP lounge and a golden badge"),
+            Line::from("x".repeat(25)),
+            Line::from("  indented  twice"),
+            Line::from("a  b   c    d"),
+        ];
+        let height = wrapped_height(&lines, 14);
+
+        // A marker after the text shows where ratatui's wrapping ended.
+        let mut marked = lines.clone();
+        marked.push(Line::from("#"));
+        let area = Rect::new(0, 0, 12, 30);
+        let mut buffer = Buffer::empty(area);
+        Paragraph::new(Text::from(marked))
+            .wrap(Wrap { trim: false })
+            .render(area, &mut buffer);
+        let marker = (0..area.height)
+            .position(|y| buffer[(0, y)].symbol() == "#")
+            .unwrap();
+
+        assert_eq!(height as usize, marker + 2);
+    }
+}
@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -5,7 +7,7 @@ use serde::{Deserialize, Serialize};
 lazy_static! {
 
     static ref WS: Regex = Regex::new(r#"\s+"#).unwrap();
-    static ref DEFAULT_FILTERS: Vec<Regex> = vec![
+    static ref DEFAULT_FILTERS: Vec<NameFilter> = [
         // Remove our discount prefix from the name before canonicalizing, since it doesn't affect the actual product
         r#"💲.*?% OFF💲"#,
         // Remove everything within brackets, including the brackets themselves
@@ -14,83 +16,259 @@ lazy_static! {
         r#"[^a-zA-Z0-9!?,.\-\s]"#,
     ]
     .iter()
-    .map(|s| Regex::new(s).unwrap())
+    .map(|s| NameFilter::from(Regex::new(s).unwrap()))
     .collect::<Vec<_>>();
+    pub static ref UNICODE_FILTERS: Vec<NameFilter> = [
+        r#"💲.*?% OFF💲"#,
+        r#"\[.*?\]"#,
+        // Like the default, but letters and digits of any script are kept
+        r#"[^\p{L}\p{N}!?,.\-\s]"#,
+    ]
+    .iter()
+    .map(|s| NameFilter::from(Regex::new(s).unwrap()))
+    .collect::<Vec<_>>();
+}
+
+/// Converts a `*`/`?` glob into a regex matching the whole string.
+pub fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!(
+        "^{}$",
+        regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".")
+    ))
+}
+
+/// What happens to non-ASCII characters when a name is turned into a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnicodeKeys {
+    /// Drop them, so `Épée` becomes `pe`
+    #[default]
+    Strip,
+    /// Spell them in ASCII, so `Épée` becomes `epee` and `東京` `dong-jing`
+    Transliterate,
+    /// Keep letters and digits of every script
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyCase {
+    #[default]
+    Lower,
+    Upper,
+    /// Keep the name's own casing
+    Preserve,
+}
+
+/// How product names are turned into keys, set under `[metadata.slug]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SlugOptions {
+    pub unicode: UnicodeKeys,
+    /// Replaces whitespace between words, `-` by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    pub case: KeyCase,
 }
 
-pub fn format_name<T: Into<String>>(name: T) -> String {
-    let mut name = name.into().to_lowercase();
-    name = name
-        .replace(
-            |c: char| !c.is_ascii_alphanumeric() && !c.is_whitespace(),
-            "",
+impl SlugOptions {
+    pub fn key(&self, name: &str) -> String {
+        let name = match self.unicode {
+            UnicodeKeys::Transliterate => deunicode::deunicode(name),
+            _ => name.to_string(),
+        };
+        let name = match self.case {
+            KeyCase::Lower => name.to_lowercase(),
+            KeyCase::Upper => name.to_uppercase(),
+            KeyCase::Preserve => name,
+        };
+
+        let kept = name
+            .chars()
+            .filter(|c| {
+                c.is_ascii_alphanumeric()
+                    || c.is_whitespace()
+                    || (self.unicode == UnicodeKeys::Keep && c.is_alphanumeric())
+            })
+            .collect::<String>();
+
+        kept.trim().replace(
+            char::is_whitespace,
+            self.separator.as_deref().unwrap_or("-"),
         )
-        .trim()
-        .to_string();
-    name = name.replace(|c: char| c.is_whitespace(), "-");
-    name
+    }
 }
 
-pub fn is_censored<T: Into<String>>(s: T) -> bool {
-    let name = s.into();
-    name.chars().all(|c| c == '#' || c.is_whitespace())
+/// Share of the non-whitespace characters that are `#`, which is how
+/// Roblox's text filter masks words.
+pub fn censored_share(s: &str) -> f64 {
+    let (masked, total) = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold((0, 0), |(masked, total), c| {
+            (masked + usize::from(c == '#'), total + 1)
+        });
+
+    if total == 0 {
+        0.0
+    } else {
+        masked as f64 / total as f64
+    }
 }
 
-pub fn canonical_name<T: Into<String>>(s: T, filters: &Option<Vec<Regex>>) -> String {
+/// Whether at least `threshold` of the text is masked. Fully masked text
+/// always counts.
+pub fn is_censored(s: &str, threshold: f64) -> bool {
+    let share = censored_share(s);
+    share > 0.0 && (share >= threshold || share == 1.0)
+}
+
+pub fn canonical_name<T: Into<String>>(s: T, filters: &Option<Vec<NameFilter>>) -> String {
     let mut out = s.into();
 
     let temp = Vec::new();
-    let mut name_filters: &Vec<Regex> = filters.as_ref().unwrap_or(&temp);
+    let mut name_filters: &Vec<NameFilter> = filters.as_ref().unwrap_or(&temp);
 
-    if name_filters.len() == 0 {
+    if name_filters.is_empty() {
         name_filters = &DEFAULT_FILTERS;
     }
 
     for filter in name_filters {
-        out = filter.replace_all(&out, " ").to_string();
+        out = filter.apply(&out);
     }
 
     out = WS.replace_all(&out, " ").trim().to_string();
     out
 }
 
-pub fn deserialize_regex_vec<'de, D>(deserializer: D) -> Result<Option<Vec<Regex>>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let opt = Option::<Vec<String>>::deserialize(deserializer)?;
+pub fn prompt_line(label: &str) -> std::io::Result<String> {
+    let mut stderr = std::io::stderr();
+    write!(stderr, "{}: ", label)?;
+    stderr.flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// A `name-filters` entry. Matches of `pattern` are replaced with `replace`,
+/// which can use capture groups like `$1`, or with a space by default.
+#[derive(Debug, Clone)]
+pub struct NameFilter {
+    pub pattern: Regex,
+    pub replace: Option<String>,
+}
 
-    match opt {
-        Some(strings) => {
-            let regexes = strings
-                .into_iter()
-                .map(|s| {
-                    Regex::new(&s)
-                        .map_err(|e| serde::de::Error::custom(format!("Invalid regex `{s}`: {e}")))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawNameFilter {
+    Pattern(String),
+    Template { pattern: String, replace: String },
+}
 
-            Ok(Some(regexes))
+impl NameFilter {
+    pub fn apply(&self, name: &str) -> String {
+        self.pattern
+            .replace_all(name, self.replace.as_deref().unwrap_or(" "))
+            .to_string()
+    }
+}
+
+impl From<Regex> for NameFilter {
+    fn from(pattern: Regex) -> Self {
+        Self {
+            pattern,
+            replace: None,
         }
-        None => Ok(None),
     }
 }
 
-pub fn serialize_regex_vec<S>(
-    regexes: &Option<Vec<Regex>>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    match regexes {
-        Some(regexes) => {
-            let strings = regexes
-                .iter()
-                .map(|r| r.as_str().to_string())
-                .collect::<Vec<_>>();
-            strings.serialize(serializer)
+impl<'de> Deserialize<'de> for NameFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (pattern, replace) = match RawNameFilter::deserialize(deserializer)? {
+            RawNameFilter::Pattern(pattern) => (pattern, None),
+            RawNameFilter::Template { pattern, replace } => (pattern, Some(replace)),
+        };
+
+        let regex = Regex::new(&pattern)
+            .map_err(|e| serde::de::Error::custom(format!("Invalid regex `{pattern}`: {e}")))?;
+
+        Ok(Self {
+            pattern: regex,
+            replace,
+        })
+    }
+}
+
+impl Serialize for NameFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let pattern = self.pattern.as_str().to_string();
+
+        match &self.replace {
+            Some(replace) => RawNameFilter::Template {
+                pattern,
+                replace: replace.clone(),
+            },
+            None => RawNameFilter::Pattern(pattern),
         }
-        None => serializer.serialize_none(),
+        .serialize(serializer)
     }
 }
+
+/// Formats rows as a plain-text table with left-aligned, padded columns.
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = headers
+        .iter()
+        .map(|h| h.chars().count())
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some(width) = widths.get_mut(i) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let mut out = format_row(headers.to_vec()) + "\n" + separator.as_str() + "\n";
+
+    for row in rows {
+        out += format_row(row.iter().map(String::as_str).collect()).as_str();
+        out += "\n";
+    }
+
+    out
+}
+
+pub fn render_csv(headers: &[&str], rows: &[Vec<String>]) -> crate::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(headers)?;
+
+    for row in rows {
+        writer.write_record(row)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}